@@ -1,5 +1,7 @@
 //! Command-line options and subcommands.
 
+use std::path::PathBuf;
+
 use git_version::git_version;
 
 use crate::{
@@ -7,7 +9,11 @@ use crate::{
     pprint_ast::PprintAstCommand, query::QueryCommand, typecheck::TypecheckCommand,
 };
 
-use nickel_lang_core::error::report::ErrorFormat;
+use nickel_lang_core::error::{
+    catalog::{self, CatalogError},
+    report::ErrorFormat,
+    warning::{WarningConfig, WarningLevel, WarningSelector},
+};
 
 #[cfg(feature = "repl")]
 use crate::repl::ReplCommand;
@@ -54,12 +60,87 @@ pub struct GlobalOptions {
     #[arg(long, global = true, value_enum, default_value_t)]
     pub error_format: ErrorFormat,
 
+    /// If the program fails because a contract is broken, write a machine-readable report of the
+    /// blame error as JSON to the given file, in addition to reporting the error as usual. The
+    /// report includes the path of the offending field, the broken contract, an excerpt of the
+    /// offending value and their locations.
+    #[arg(long, global = true, value_name = "FILE")]
+    pub blame_report: Option<PathBuf>,
+
+    /// Report the warnings with the given code, such as `N0401`, or all warnings with `warnings`.
+    /// This is the default.
+    #[arg(long, short = 'W', global = true, value_name = "CODE")]
+    pub warn: Vec<WarningSelector>,
+
+    /// Silence the warnings with the given code, such as `N0401`, or all warnings with
+    /// `warnings`.
+    #[arg(long, short = 'A', global = true, value_name = "CODE")]
+    pub allow: Vec<WarningSelector>,
+
+    /// Turn the warnings with the given code, such as `N0401`, or all warnings with `warnings`,
+    /// into errors. For example, use `-D warnings` to fail on any warning.
+    ///
+    /// The levels set for specific codes take precedence over the ones set with `warnings`. If the
+    /// same code is given several levels, `--deny` takes precedence over `--allow`, which takes
+    /// precedence over `--warn`.
+    #[arg(long, short = 'D', global = true, value_name = "CODE")]
+    pub deny: Vec<WarningSelector>,
+
+    /// The language of the error messages, such as `fr` or `fr_FR`. Defaults to the value of the
+    /// `NICKEL_LANG` environment variable, or to English.
+    ///
+    /// Translations are read from a file `<LANG>.toml` in one of the directories listed in the
+    /// `NICKEL_CATALOG_PATH` environment variable, separated by `:`. Untranslated messages are
+    /// shown in English.
+    #[arg(long, global = true, value_name = "LANG")]
+    pub lang: Option<String>,
+
     #[cfg(feature = "metrics")]
     /// Print all recorded metrics at the very end of the program
     #[arg(long, global = true, default_value_t = false)]
     pub metrics: bool,
 }
 
+impl GlobalOptions {
+    /// Build the configuration of warning levels from the `--warn`, `--allow` and `--deny` flags.
+    pub fn warning_config(&self) -> WarningConfig {
+        let mut config = WarningConfig::default();
+        let flags = [
+            (&self.warn, WarningLevel::Warn),
+            (&self.allow, WarningLevel::Allow),
+            (&self.deny, WarningLevel::Deny),
+        ];
+
+        // `warnings` must be handled first, so that it doesn't override specific codes.
+        for only_all in [true, false] {
+            for (selectors, level) in flags.iter() {
+                for selector in selectors.iter() {
+                    if (*selector == WarningSelector::All) == only_all {
+                        config.set(*selector, *level);
+                    }
+                }
+            }
+        }
+
+        config
+    }
+
+    /// Select the language of the error messages from `--lang` or the `NICKEL_LANG` environment
+    /// variable.
+    pub fn select_lang(&self) -> Result<(), CatalogError> {
+        let Some(lang) = self
+            .lang
+            .clone()
+            .or_else(|| std::env::var("NICKEL_LANG").ok())
+        else {
+            return Ok(());
+        };
+
+        let search_path = std::env::var("NICKEL_CATALOG_PATH").unwrap_or_default();
+        catalog::select_locale(&lang, search_path.split(':').filter(|dir| !dir.is_empty()))
+    }
+}
+
 /// Available subcommands.
 #[derive(clap::Subcommand, Debug)]
 pub enum Command {
@@ -7,6 +7,7 @@ use std::{
 use nickel_lang_core::{
     error::{Error, IOError},
     eval::cache::CacheImpl,
+    pretty::PrettyOptions,
     program::Program,
 };
 
@@ -56,6 +57,9 @@ pub struct DocCommand {
     #[arg(long, value_enum, default_value_t)]
     pub format: crate::doc::DocFormat,
 
+    #[command(flatten)]
+    pub pretty: PrettyOptions,
+
     #[command(flatten)]
     pub input: InputOptions<ExtractFieldOnly>,
 }
@@ -65,11 +69,12 @@ const DEFAULT_OUT_DIR: &str = ".nickel/doc/";
 impl DocCommand {
     pub fn run(self, global: GlobalOptions) -> CliResult<()> {
         let mut program = self.input.prepare(&global)?;
-        self.export_doc(&mut program).report_with_program(program)
+        self.export_doc(&mut program)
+            .report_with_program(program, &global)
     }
 
     fn export_doc(self, program: &mut Program<CacheImpl>) -> Result<(), Error> {
-        let doc = program.extract_doc()?;
+        let doc = program.extract_doc(&self.pretty)?;
 
         let (mut out, out_path): (Box<dyn std::io::Write>, Option<Cow<'_, str>>) = if self.stdout {
             (Box::new(std::io::stdout()), None)
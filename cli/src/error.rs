@@ -1,7 +1,10 @@
 //! Error handling for the CLI.
 
+use std::path::Path;
+
 use nickel_lang_core::{
     error::{
+        catalog::CatalogError,
         report::{ColorOpt, ErrorFormat},
         Diagnostic, FileId, Files, IntoDiagnostics, ParseError,
     },
@@ -9,6 +12,8 @@ use nickel_lang_core::{
     program::{FieldOverride, FieldPath, Program},
 };
 
+use crate::cli::GlobalOptions;
+
 /// Data about an unknown field error.
 pub struct UnknownFieldData {
     /// The field that was unknown.
@@ -39,6 +44,14 @@ pub enum Error {
     Io {
         error: std::io::Error,
     },
+    /// Some warnings have been turned into errors by `--deny`. They have already been reported.
+    DeniedWarnings {
+        count: usize,
+    },
+    /// The language of the messages couldn't be selected.
+    Catalog {
+        error: CatalogError,
+    },
     #[cfg(feature = "repl")]
     Repl {
         error: nickel_lang_core::repl::InitError,
@@ -193,6 +206,12 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<CatalogError> for Error {
+    fn from(error: CatalogError) -> Self {
+        Error::Catalog { error }
+    }
+}
+
 #[cfg(feature = "format")]
 impl From<crate::format::FormatError> for Error {
     fn from(error: crate::format::FormatError) -> Self {
@@ -208,16 +227,48 @@ impl From<nickel_lang_core::repl::InitError> for Error {
 }
 
 pub trait ResultErrorExt<T> {
-    fn report_with_program(self, program: Program<CBNCache>) -> CliResult<T>;
+    /// Report the warnings of `program`, and attach `program` to the error, if any, so that the
+    /// error can be reported later. Denied warnings make the result an error.
+    fn report_with_program(
+        self,
+        program: Program<CBNCache>,
+        global: &GlobalOptions,
+    ) -> CliResult<T>;
 }
 
 impl<T> ResultErrorExt<T> for Result<T, nickel_lang_core::error::Error> {
-    fn report_with_program(self, program: Program<CBNCache>) -> CliResult<T> {
-        self.map_err(|error| Error::Program { program, error })
+    fn report_with_program(
+        self,
+        mut program: Program<CBNCache>,
+        global: &GlobalOptions,
+    ) -> CliResult<T> {
+        let denied = program.report_warnings(global.error_format);
+
+        match self {
+            Err(error) => Err(Error::Program { program, error }),
+            Ok(_) if denied > 0 => Err(Error::DeniedWarnings { count: denied }),
+            Ok(value) => Ok(value),
+        }
     }
 }
 
 impl Error {
+    /// Write a machine-readable report of this error as JSON to the file at `path`, if it's a
+    /// contract blame error. Otherwise, do nothing.
+    pub fn write_blame_report(&self, path: &Path) -> std::io::Result<()> {
+        let Error::Program { program, error } = self else {
+            return Ok(());
+        };
+
+        match program.blame_report(error) {
+            Some(report) => {
+                let file = std::fs::File::create(path)?;
+                serde_json::to_writer_pretty(file, &report).map_err(std::io::Error::from)
+            }
+            None => Ok(()),
+        }
+    }
+
     /// Report this error on the standard error stream.
     pub fn report(self, format: ErrorFormat, color: ColorOpt) {
         // Report a standalone error which doesn't actually refer to any source code.
@@ -243,6 +294,19 @@ impl Error {
             Error::Io { error } => {
                 report_standalone("IO error", Some(error.to_string()));
             }
+            Error::DeniedWarnings { count } => {
+                let plural = if count == 1 { "" } else { "s" };
+                report_standalone(
+                    &format!("aborting due to {count} denied warning{plural}"),
+                    None,
+                );
+            }
+            Error::Catalog { error } => {
+                report_standalone(
+                    "failed to select the language of messages",
+                    Some(error.to_string()),
+                );
+            }
             #[cfg(feature = "repl")]
             Error::Repl { error } => {
                 use nickel_lang_core::repl::InitError;
@@ -1,3 +1,5 @@
+use nickel_lang_core::pretty::{to_string_with, PrettyOptions};
+
 use crate::{
     cli::GlobalOptions,
     customize::CustomizeMode,
@@ -7,6 +9,9 @@ use crate::{
 
 #[derive(clap::Parser, Debug)]
 pub struct EvalCommand {
+    #[command(flatten)]
+    pub pretty: PrettyOptions,
+
     #[command(flatten)]
     pub input: InputOptions<CustomizeMode>,
 }
@@ -17,7 +22,7 @@ impl EvalCommand {
 
         program
             .eval_full()
-            .map(|t| println!("{t}"))
-            .report_with_program(program)
+            .map(|t| println!("{}", to_string_with(&t, &self.pretty)))
+            .report_with_program(program, &global)
     }
 }
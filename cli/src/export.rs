@@ -1,10 +1,15 @@
-use std::{fs, io::Write, path::PathBuf};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use nickel_lang_core::{
-    error::{Error, IOError},
+    error::{Error, ExportError, ExportErrorData, IOError},
     eval::cache::lazy::CBNCache,
     program::Program,
-    serialize::{self, ExportFormat},
+    serialize::{self, EnvOptions, EnvQuoting, ExportFormat, InexactNumbers, NickelPointerElem},
+    term::{RichTerm, Term},
 };
 
 use crate::{
@@ -19,10 +24,41 @@ pub struct ExportCommand {
     #[arg(long, short, value_enum, default_value_t)]
     pub format: ExportFormat,
 
+    /// What to do with numbers which can't be serialized exactly, such as `1/3` or integers which
+    /// don't fit in 64 bits: serialize the nearest 64 bits float, fail, or serialize a string
+    /// holding the exact value
+    #[arg(long, value_enum, default_value_t)]
+    pub inexact_numbers: InexactNumbers,
+
+    /// For the env format, the separator put between the fields of a nested record to build the
+    /// name of a variable
+    #[arg(long, default_value = "_")]
+    pub env_separator: String,
+
+    /// For the env format, when to quote values
+    #[arg(long, value_enum, default_value_t)]
+    pub env_quoting: EnvQuoting,
+
+    /// Serialize the fields of all the records in alphabetical order, including the records whose
+    /// fields have been explicitly reordered, for example by `std.record.sort_fields_by`
+    #[arg(long)]
+    pub sort_keys: bool,
+
+    /// Write the output incrementally, evaluating each value only when it's about to be written,
+    /// so that very large outputs don't need to be held in memory. Only supported by the JSON
+    /// format
+    #[arg(long)]
+    pub stream: bool,
+
     /// Output file. Standard output by default
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
+    /// Write each field of the top-level record to its own file in this directory, named after
+    /// the field with the extension of the format, instead of writing a single output
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["output", "stream"])]
+    pub split_by_key: Option<PathBuf>,
+
     #[command(flatten)]
     pub input: InputOptions<CustomizeMode>,
 }
@@ -31,33 +67,123 @@ impl ExportCommand {
     pub fn run(self, global: GlobalOptions) -> CliResult<()> {
         let mut program = self.input.prepare(&global)?;
 
-        self.export(&mut program).report_with_program(program)
+        self.export(&mut program)
+            .report_with_program(program, &global)
     }
 
     fn export(self, program: &mut Program<CBNCache>) -> Result<(), Error> {
+        if self.stream {
+            return self.export_streaming(program);
+        }
+
         let rt = program.eval_full_for_export()?;
 
-        // We only add a trailing newline for JSON exports. Both YAML and TOML
-        // exporters already append a trailing newline by default.
-        let trailing_newline = self.format == ExportFormat::Json;
+        if let Some(dir) = &self.split_by_key {
+            return self.export_split(rt, dir);
+        }
 
-        serialize::validate(self.format, &rt)?;
+        let rt = self.prepare_value(rt)?;
 
-        if let Some(file) = self.output {
+        if let Some(file) = &self.output {
             let mut file = fs::File::create(file).map_err(IOError::from)?;
-            serialize::to_writer(&mut file, self.format, &rt)?;
+            self.write(&mut file, &rt)
+        } else {
+            self.write(&mut std::io::stdout().lock(), &rt)
+        }
+    }
 
-            if trailing_newline {
-                writeln!(file).map_err(IOError::from)?;
-            }
+    /// Validate a fully evaluated value and put it in the shape required by the options, before
+    /// serializing it.
+    fn prepare_value(&self, rt: RichTerm) -> Result<RichTerm, Error> {
+        serialize::validate(self.format, &rt)?;
+        let rt = serialize::handle_inexact_numbers(self.inexact_numbers, rt)?;
+
+        if self.sort_keys {
+            Ok(serialize::sort_keys(rt))
         } else {
-            serialize::to_writer(std::io::stdout(), self.format, &rt)?;
+            Ok(rt)
+        }
+    }
 
-            if trailing_newline {
-                println!();
+    /// Serialize a prepared value to `writer`.
+    fn write(&self, writer: &mut dyn Write, rt: &RichTerm) -> Result<(), Error> {
+        match self.format {
+            ExportFormat::Env => {
+                let env_options = EnvOptions {
+                    separator: self.env_separator.clone(),
+                    quoting: self.env_quoting,
+                };
+
+                serialize::to_env_writer(&mut *writer, rt, &env_options)?
             }
+            format => serialize::to_writer(&mut *writer, format, rt)?,
         }
 
+        // We only add a trailing newline for JSON exports. Both YAML and TOML
+        // exporters already append a trailing newline by default.
+        if self.format == ExportFormat::Json {
+            writeln!(writer).map_err(IOError::from)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write each field of a record to its own file in `dir`, named after the field.
+    fn export_split(&self, rt: RichTerm, dir: &Path) -> Result<(), Error> {
+        let Term::Record(record) = rt.as_ref() else {
+            return Err(ExportError::from(ExportErrorData::Other(String::from(
+                "--split-by-key requires the program to evaluate to a record",
+            )))
+            .into());
+        };
+
+        fs::create_dir_all(dir).map_err(IOError::from)?;
+
+        for binding in record.iter_serializable() {
+            let (id, value) = binding.map_err(|err| err.into_eval_err(rt.pos, rt.pos))?;
+            let name = id.label();
+
+            if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+                return Err(ExportError::from(ExportErrorData::Other(format!(
+                    "the field `{name}` can't be used as a file name"
+                )))
+                .into());
+            }
+
+            let value = self.prepare_value(value.clone()).map_err(|err| match err {
+                Error::ExportError(mut err) => {
+                    err.path.0.insert(0, NickelPointerElem::Field(id));
+                    Error::ExportError(err)
+                }
+                err => err,
+            })?;
+
+            let path = dir.join(format!("{name}.{}", self.format.extension()));
+            let mut file = fs::File::create(path).map_err(IOError::from)?;
+            self.write(&mut file, &value)?;
+        }
+
+        Ok(())
+    }
+
+    fn export_streaming(self, program: &mut Program<CBNCache>) -> Result<(), Error> {
+        if self.format != ExportFormat::Json {
+            return Err(ExportError::from(ExportErrorData::Other(format!(
+                "streaming export isn't supported by the {} format, only by json",
+                self.format
+            )))
+            .into());
+        }
+
+        let mut writer: Box<dyn Write> = match self.output {
+            Some(file) => Box::new(fs::File::create(file).map_err(IOError::from)?),
+            None => Box::new(std::io::stdout().lock()),
+        };
+
+        program.export_json_streaming(&mut writer, self.inexact_numbers, self.sort_keys)?;
+
+        writeln!(writer).map_err(IOError::from)?;
+
         Ok(())
     }
 }
@@ -1,4 +1,7 @@
-use std::path::PathBuf;
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
 
 use nickel_lang_core::{eval::cache::lazy::CBNCache, program::Program};
 
@@ -23,10 +26,83 @@ pub struct InputOptions<Customize: clap::Args> {
     #[arg(long, short = 'I', global = true)]
     pub import_path: Vec<PathBuf>,
 
+    /// Extends the standard library with the modules of a directory.
+    ///
+    /// Each file `<name>.ncl` of the directory is made available as `std.<name>`, replacing
+    /// the builtin module of the same name, if any.
+    #[arg(long, global = true, value_name = "DIR")]
+    pub stdlib: Option<PathBuf>,
+
+    /// Enables the generation of random UUIDs by `std.uuid.v4`, seeded by the given number.
+    ///
+    /// Random UUIDs are disabled by default, so that evaluation is reproducible. The same seed
+    /// always yields the same UUIDs, which is useful for reproducible runs: use `--allow-random`
+    /// to get fresh ones on each run instead.
+    #[arg(long, global = true, value_name = "SEED")]
+    pub uuid_seed: Option<u64>,
+
+    /// Enables the generation of random UUIDs by `std.uuid.v4`, seeded from the entropy of the
+    /// operating system.
+    ///
+    /// Each run yields different UUIDs. Use `--uuid-seed` instead for reproducible runs.
+    #[arg(long, global = true, conflicts_with = "uuid_seed")]
+    pub allow_random: bool,
+
+    /// Allows the program to read the given environment variables through `std.env.get`.
+    ///
+    /// Reading any other variable returns `'None`, as if it wasn't set.
+    #[arg(long, global = true, value_name = "VARS", value_delimiter = ',')]
+    pub allow_env: Vec<String>,
+
+    /// Allows the program to read files and list directories under the given path through
+    /// `std.fs`. Can be repeated.
+    ///
+    /// Reading any other path fails. Symbolic links are resolved before checking the path.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub allow_read: Vec<PathBuf>,
+
+    /// Evaluates the program in sandbox mode.
+    ///
+    /// A sandboxed program can only import files located under the sandbox roots, and can't read
+    /// environment variables nor files through the standard library, whatever `--allow-env` and
+    /// `--allow-read` say.
+    #[arg(long, global = true)]
+    pub sandbox: bool,
+
+    /// Allows a sandboxed program to import files located under the given directory. Can be
+    /// repeated.
+    ///
+    /// Defaults to the directories of the input files, or to the current directory when reading
+    /// from the standard input.
+    #[arg(long, global = true, value_name = "DIR", requires = "sandbox")]
+    pub sandbox_root: Vec<PathBuf>,
+
     #[command(flatten)]
     pub customize_mode: Customize,
 }
 
+impl<C: clap::Args> InputOptions<C> {
+    /// The directories under which a sandboxed program can import files: the ones given with
+    /// `--sandbox-root`, or by default the directories of the input files.
+    fn sandbox_roots(&self) -> Vec<PathBuf> {
+        if !self.sandbox_root.is_empty() {
+            return self.sandbox_root.clone();
+        }
+
+        if self.files.is_empty() {
+            return vec![PathBuf::from(".")];
+        }
+
+        self.files
+            .iter()
+            .map(|file| match file.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_owned(),
+                _ => PathBuf::from("."),
+            })
+            .collect()
+    }
+}
+
 pub trait Prepare {
     fn prepare(&self, global: &GlobalOptions) -> CliResult<Program<CBNCache>>;
 }
@@ -40,6 +116,7 @@ impl<C: clap::Args + Customize> Prepare for InputOptions<C> {
         }?;
 
         program.color_opt = global.color.into();
+        program.warning_config = global.warning_config();
 
         program.add_import_paths(self.import_path.iter());
 
@@ -47,6 +124,23 @@ impl<C: clap::Args + Customize> Prepare for InputOptions<C> {
             program.add_import_paths(nickel_path.split(':'));
         }
 
+        if let Some(dir) = &self.stdlib {
+            program.set_stdlib(read_stdlib_dir(dir)?);
+        }
+
+        if let Some(seed) = self.uuid_seed {
+            program.set_uuid_seed(seed);
+        } else if self.allow_random {
+            program.allow_random_uuids();
+        }
+
+        program.allow_env(self.allow_env.iter().cloned());
+        program.allow_read(self.allow_read.iter().cloned());
+
+        if self.sandbox {
+            program.enable_sandbox(self.sandbox_roots());
+        }
+
         #[cfg(debug_assertions)]
         if self.nostdlib {
             program.set_skip_stdlib();
@@ -55,3 +149,22 @@ impl<C: clap::Args + Customize> Prepare for InputOptions<C> {
         self.customize_mode.customize(program)
     }
 }
+
+/// Read the Nickel files of a custom stdlib directory, as pairs of a module name and a source.
+fn read_stdlib_dir(dir: &Path) -> std::io::Result<Vec<(String, String)>> {
+    let mut modules = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.extension().and_then(OsStr::to_str) == Some("ncl") {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                modules.push((name.to_owned(), std::fs::read_to_string(&path)?));
+            }
+        }
+    }
+
+    // The order of directory entries is platform-dependent.
+    modules.sort();
+    Ok(modules)
+}
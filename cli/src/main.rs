@@ -32,9 +32,15 @@ fn main() -> ExitCode {
 
     let error_format = opts.global.error_format;
     let color = opts.global.color;
+    let blame_report = opts.global.blame_report.clone();
     #[cfg(feature = "metrics")]
     let report_metrics = opts.global.metrics;
 
+    if let Err(error) = opts.global.select_lang() {
+        error::Error::from(error).report(error_format, color.into());
+        return ExitCode::FAILURE;
+    }
+
     let result = match opts.command {
         Command::Eval(eval) => eval.run(opts.global),
         Command::PprintAst(pprint_ast) => pprint_ast.run(opts.global),
@@ -63,6 +69,12 @@ fn main() -> ExitCode {
         // user's point of view.
         Ok(()) | Err(error::Error::CustomizeInfoPrinted) => ExitCode::SUCCESS,
         Err(error) => {
+            if let Some(path) = blame_report {
+                if let Err(io_error) = error.write_blame_report(&path) {
+                    error::Error::from(io_error).report(error_format, color.into());
+                }
+            }
+
             error.report(error_format, color.into());
             ExitCode::FAILURE
         }
@@ -1,3 +1,5 @@
+use nickel_lang_core::pretty::PrettyOptions;
+
 use crate::{
     cli::GlobalOptions,
     customize::NoCustomizeMode,
@@ -11,6 +13,9 @@ pub struct PprintAstCommand {
     #[arg(long)]
     pub transform: bool,
 
+    #[command(flatten)]
+    pub pretty: PrettyOptions,
+
     #[command(flatten)]
     pub inputs: InputOptions<NoCustomizeMode>,
 }
@@ -19,7 +24,7 @@ impl PprintAstCommand {
     pub fn run(self, global: GlobalOptions) -> CliResult<()> {
         let mut program = self.inputs.prepare(&global)?;
         program
-            .pprint_ast(&mut std::io::stdout(), self.transform)
-            .report_with_program(program)
+            .pprint_ast(&mut std::io::stdout(), self.transform, &self.pretty)
+            .report_with_program(program, &global)
     }
 }
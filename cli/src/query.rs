@@ -65,7 +65,7 @@ impl QueryCommand {
                 )
                 .unwrap()
             })
-            .report_with_program(program)?;
+            .report_with_program(program, &global)?;
 
         if !found {
             eprintln!("No metadata found for this field.")
@@ -14,6 +14,6 @@ pub struct TypecheckCommand {
 impl TypecheckCommand {
     pub fn run(self, global: GlobalOptions) -> CliResult<()> {
         let mut program = self.inputs.prepare(&global)?;
-        program.typecheck().report_with_program(program)
+        program.typecheck().report_with_program(program, &global)
     }
 }
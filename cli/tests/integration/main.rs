@@ -73,3 +73,38 @@ fn automatic_color_on_non_tty() {
         );
     }
 }
+
+#[test]
+fn export_split_by_key() {
+    let nickel_bin = env!("CARGO_BIN_EXE_nickel");
+    let output = tempdir()
+        .expect("should be able to make a temporary directory")
+        .into_path()
+        .join("manifests");
+    let mut nickel = Command::new(nickel_bin)
+        .args(["export", "--format", "yaml", "--split-by-key"])
+        .arg(&output)
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Nickel should be runnable");
+    let Some(mut stdin) = nickel.stdin.take() else {
+        panic!("couldn't retrieve stdin handle to Nickel")
+    };
+    stdin
+        .write_all(b"{app = {replicas = 2}, db = {replicas = 1}, secret | not_exported = 0}")
+        .expect("writing into Nickel stdin should work");
+    drop(stdin);
+
+    let status = nickel.wait().expect("Nickel should exit successfully");
+    assert!(status.success());
+
+    let mut files: Vec<_> = std::fs::read_dir(&output)
+        .expect("the output directory should exist")
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["app.yaml", "db.yaml"]);
+
+    let app = std::fs::read_to_string(output.join("app.yaml")).unwrap();
+    assert_eq!(app.trim(), "replicas: 2");
+}
@@ -1,18 +1,20 @@
 //! Source cache.
 
-use crate::error::{Error, ImportError, ParseError, ParseErrors, TypecheckError};
+use crate::error::{warning::Warning, Error, ImportError, ParseError, ParseErrors, TypecheckError};
 use crate::eval::cache::Cache as EvalCache;
 use crate::eval::Closure;
+use crate::identifier::LocIdent;
 #[cfg(feature = "nix-experimental")]
 use crate::nix_ffi;
 use crate::parser::{lexer::Lexer, ErrorTolerantParser};
 use crate::position::TermPos;
 use crate::program::FieldPath;
+use crate::serialize;
 use crate::stdlib::{self as nickel_stdlib, StdlibModule};
 use crate::term::array::Array;
 use crate::term::record::{Field, RecordData};
-use crate::term::{RichTerm, SharedTerm, Term};
-use crate::transform::import_resolution;
+use crate::term::{IndexMap, RichTerm, SharedTerm, Term};
+use crate::transform::{import_resolution, Transformation};
 use crate::typ::UnboundTypeVariableError;
 use crate::typecheck::{self, type_check, Wildcards};
 use crate::{eval, parser, transform};
@@ -24,10 +26,12 @@ use std::collections::hash_map;
 use std::collections::{HashMap, HashSet};
 use std::ffi::{OsStr, OsString};
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::result::Result;
+use std::sync::Arc;
 use std::time::SystemTime;
 use void::Void;
 
@@ -39,6 +43,7 @@ pub enum InputFormat {
     Json,
     Yaml,
     Toml,
+    Csv,
     #[cfg(feature = "nix-experimental")]
     Nix,
     Raw,
@@ -52,6 +57,7 @@ impl InputFormat {
             Some("json") => Some(InputFormat::Json),
             Some("yaml") | Some("yml") => Some(InputFormat::Yaml),
             Some("toml") => Some(InputFormat::Toml),
+            Some("csv") => Some(InputFormat::Csv),
             #[cfg(feature = "nix-experimental")]
             Some("nix") => Some(InputFormat::Nix),
             Some("txt") => Some(InputFormat::Raw),
@@ -96,17 +102,58 @@ pub struct Cache {
     terms: HashMap<FileId, TermEntry>,
     /// The list of ids corresponding to the stdlib modules
     stdlib_ids: Option<HashMap<StdlibModule, FileId>>,
+    /// Custom modules extending or overriding the `std` module, as pairs of a name and a source.
+    /// See [Self::set_stdlib_extensions].
+    stdlib_extensions: Vec<(String, String)>,
+    /// The limits on the size of the term cache.
+    eviction_policy: EvictionPolicy,
+    /// A logical clock, incremented each time a file is loaded or retrieved from the cache.
+    clock: u64,
+    /// The time at which each file was last loaded or retrieved from the cache, used to evict the
+    /// least recently used entries first.
+    last_used: HashMap<FileId, u64>,
+    /// The ids of the files evicted from the cache, indexed by their normalized path. Their
+    /// source is dropped upon eviction, and the id is reused if the file is loaded again, so that
+    /// evicting and reloading a file doesn't accumulate copies of its source.
+    evicted_ids: HashMap<PathBuf, FileId>,
     /// The inferred type of wildcards for each `FileId`.
     wildcards: HashMap<FileId, Wildcards>,
     /// Whether processing should try to continue even in case of errors. Needed by the NLS.
     error_tolerance: ErrorTolerance,
     import_paths: Vec<PathBuf>,
+    /// The warnings emitted while processing the entries, which haven't been retrieved yet. See
+    /// [Self::take_warnings].
+    warnings: Vec<Warning>,
+    /// The custom transformations applied to the entries, except for the stdlib. See
+    /// [Self::add_transformation].
+    transformations: Vec<Arc<dyn Transformation>>,
+    /// The custom source of imported files, looked up before the filesystem. See
+    /// [Self::set_import_source].
+    import_source: Option<Arc<dyn ImportSource>>,
+    /// The directories under which files can be imported, if the cache is sandboxed. See
+    /// [Self::set_sandbox_roots].
+    sandbox_roots: Option<Vec<PathBuf>>,
 
     #[cfg(debug_assertions)]
     /// Skip loading the stdlib, used for debugging purpose
     pub skip_stdlib: bool,
 }
 
+/// Limits on the size of the term cache, for long-running processes which would otherwise
+/// accumulate every file they ever loaded. No limit is enforced by default.
+///
+/// When a limit is exceeded, the least recently used entries are evicted, but only among the
+/// entries which can be transparently restored: fully processed entries of files read from the
+/// filesystem, which no other entry imports. The cache can thus end up above the limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvictionPolicy {
+    /// The maximum number of entries in the term cache.
+    pub max_entries: Option<usize>,
+    /// The maximum total size, in bytes, of the sources of the entries in the term cache. This is
+    /// an approximation of the memory used by the corresponding terms.
+    pub max_bytes: Option<usize>,
+}
+
 /// The error tolerance mode used by the parser. The NLS needs to try to
 /// continue even in case of errors.
 #[derive(Debug, Clone)]
@@ -152,12 +199,24 @@ pub struct TermEntry {
 /// the REPL, the standard library, or the language server).
 ///
 /// Inputs read from the filesystem get auto-refreshed: if we try to access them again and
-/// the on-disk file has changed, we read it again. Inputs read from in-memory buffers
+/// the on-disk file has changed, we read it again. A file is considered changed only if its
+/// content did: timestamps are unreliable (they are updated by version control checkouts or build
+/// sandboxes, for example), so they are only used as a cheap first check before comparing the hash
+/// of the content. Inputs read from in-memory buffers
 /// are not auto-refreshed. If an in-memory buffer has a path that also exists in the
 /// filesystem, we will not even check that file to see if it has changed.
+///
+/// The sources generated by glob imports are refreshed as well, when files are added to or removed
+/// from the directory of the pattern.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone)]
 enum SourceKind {
-    Filesystem(SystemTime),
+    /// A file read from the filesystem, with its *modified at* timestamp and the hash of its
+    /// content at the time it was read.
+    Filesystem(SystemTime, u64),
+    /// The source generated by a glob import, with the *modified at* timestamp of the directory of
+    /// the pattern and the hash of the source at the time it was generated. See
+    /// [Cache::get_or_add_glob].
+    Glob(SystemTime, u64),
     Memory,
 }
 
@@ -166,9 +225,9 @@ enum SourceKind {
 /// A source can be either a snippet input by the user, in which case it is only identified by its
 /// name in the name-id table, and a unique `FileId`. On the other hand, different versions of the
 /// same file can coexist during the same session of the REPL. For this reason, an entry of the
-/// name-id table of a file also stores the *modified at* timestamp, such that if a file is
-/// imported or loaded again and has been modified in between, the entry is invalidated, the
-/// content is loaded again and a new `FileId` is generated.
+/// name-id table of a file also stores the *modified at* timestamp and the hash of the content,
+/// such that if a file is imported or loaded again and its content has been modified in between,
+/// the entry is invalidated, the content is loaded again and a new `FileId` is generated.
 ///
 /// Note that in that case, invalidation just means that the `FileId` of a previous version is not
 /// accessible anymore in the name-id table. However, terms that contain non evaluated imports or
@@ -283,6 +342,9 @@ pub enum SourcePath {
     CliFieldAssignment,
     Override(FieldPath),
     Generated(String),
+    /// A custom module extending the standard library, made available as `std.<name>`. See
+    /// [Cache::set_stdlib_extensions].
+    StdExtension(String),
 }
 
 impl<'a> TryFrom<&'a SourcePath> for &'a OsStr {
@@ -312,6 +374,7 @@ impl From<SourcePath> for OsString {
             SourcePath::CliFieldAssignment => "<cli-assignment>".into(),
             SourcePath::Override(path) => format!("<override {path}>",).into(),
             SourcePath::Generated(description) => format!("<generated {}>", description).into(),
+            SourcePath::StdExtension(name) => format!("<stdlib extension std.{name}>").into(),
         }
     }
 }
@@ -334,6 +397,24 @@ pub enum SourceState {
     /// The source is stale because it came from a file on disk that has since been updated.
     /// The data is the timestamp of the new version of the file.
     Stale(SystemTime),
+    /// The file on disk has a new timestamp, but its content didn't change. The cached source and
+    /// everything derived from it can be reused as is. The data is the id of the source and the
+    /// new timestamp of the file.
+    Touched(FileId, SystemTime),
+}
+
+/// A change of a file on disk, detected by [Cache::poll_changes].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileChange {
+    /// The normalized path of the file.
+    pub path: PathBuf,
+    /// The id of the version of the file that was cached before the change.
+    pub old_id: FileId,
+    /// The id of the new version of the file, or `None` if the file couldn't be read anymore
+    /// (because it was deleted, for example).
+    pub new_id: Option<FileId>,
+    /// The entries that (transitively) import the file, which have been invalidated.
+    pub invalidated: HashSet<FileId>,
 }
 
 impl Cache {
@@ -347,8 +428,17 @@ impl Cache {
             imports: HashMap::new(),
             rev_imports: HashMap::new(),
             stdlib_ids: None,
+            stdlib_extensions: Vec::new(),
+            eviction_policy: EvictionPolicy::default(),
+            clock: 0,
+            last_used: HashMap::new(),
+            evicted_ids: HashMap::new(),
             error_tolerance,
             import_paths: Vec::new(),
+            warnings: Vec::new(),
+            transformations: Vec::new(),
+            import_source: None,
+            sandbox_roots: None,
 
             #[cfg(debug_assertions)]
             skip_stdlib: false,
@@ -366,19 +456,121 @@ impl Cache {
     /// timestamp as a parameter.
     fn add_file_(&mut self, path: PathBuf, timestamp: SystemTime) -> io::Result<FileId> {
         let contents = std::fs::read_to_string(&path)?;
-        let file_id = self.files.add(&path, contents);
+        let hash = content_hash(&contents);
+        let file_id = match self.evicted_ids.remove(&path) {
+            Some(file_id) => {
+                self.files.update(file_id, contents);
+                file_id
+            }
+            None => self.files.add(&path, contents),
+        };
         self.file_paths
             .insert(file_id, SourcePath::Path(path.clone()));
         self.file_ids.insert(
             SourcePath::Path(path),
             NameIdEntry {
                 id: file_id,
-                source: SourceKind::Filesystem(timestamp),
+                source: SourceKind::Filesystem(timestamp, hash),
             },
         );
+        self.mark_used(file_id);
+        self.evict();
         Ok(file_id)
     }
 
+    /// Record that a file has just been loaded or retrieved from the cache.
+    fn mark_used(&mut self, file_id: FileId) {
+        self.clock += 1;
+        self.last_used.insert(file_id, self.clock);
+    }
+
+    /// Set the limits on the size of the term cache. The limits are enforced each time a new file
+    /// is loaded from the filesystem, or explicitly by calling [Self::evict].
+    ///
+    /// The source of an evicted entry is dropped as well. The entry is loaded and processed again
+    /// from the filesystem the next time it's imported, under the same `FileId`: callers holding
+    /// the `FileId` of a file which isn't imported by any other entry shouldn't set a limit, as
+    /// the source is missing until the file is loaded again.
+    pub fn set_eviction_policy(&mut self, policy: EvictionPolicy) {
+        self.eviction_policy = policy;
+    }
+
+    /// Can this entry be evicted from the cache and transparently restored later? Only fully
+    /// processed entries of files read from the filesystem, which no other entry imports, can.
+    fn is_evictable(&self, file_id: FileId, entry: &TermEntry) -> bool {
+        let from_filesystem = match self.file_paths.get(&file_id) {
+            Some(source_path @ SourcePath::Path(_)) => matches!(
+                self.file_ids.get(source_path),
+                Some(NameIdEntry { id, source: SourceKind::Filesystem(..) }) if *id == file_id
+            ),
+            _ => false,
+        };
+
+        from_filesystem
+            && entry.state == EntryState::Transformed
+            && self.get_rev_imports(file_id).next().is_none()
+    }
+
+    /// Evict the least recently used entries of the term cache until it fits the limits of the
+    /// eviction policy, or until no entry can be evicted anymore. Return the ids of the evicted
+    /// entries.
+    pub fn evict(&mut self) -> Vec<FileId> {
+        let EvictionPolicy {
+            max_entries,
+            max_bytes,
+        } = self.eviction_policy;
+
+        if max_entries.is_none() && max_bytes.is_none() {
+            return Vec::new();
+        }
+
+        let mut bytes: usize = self
+            .terms
+            .keys()
+            .map(|file_id| self.files.source(*file_id).len())
+            .sum();
+        let mut evicted = Vec::new();
+
+        while max_entries.map_or(false, |max| self.terms.len() > max)
+            || max_bytes.map_or(false, |max| bytes > max)
+        {
+            let lru = self
+                .terms
+                .iter()
+                .filter(|(file_id, entry)| self.is_evictable(**file_id, entry))
+                .min_by_key(|(file_id, _)| self.last_used.get(*file_id).copied().unwrap_or(0))
+                .map(|(file_id, _)| *file_id);
+
+            let Some(file_id) = lru else {
+                break;
+            };
+
+            self.terms.remove(&file_id);
+            self.wildcards.remove(&file_id);
+            self.last_used.remove(&file_id);
+
+            for import in self.imports.remove(&file_id).into_iter().flatten() {
+                if let Some(rev_imports) = self.rev_imports.get_mut(&import) {
+                    rev_imports.remove(&file_id);
+                }
+            }
+
+            if let Some(source_path) = self.file_paths.get(&file_id) {
+                self.file_ids.remove(source_path);
+
+                if let SourcePath::Path(path) = source_path {
+                    self.evicted_ids.insert(path.clone(), file_id);
+                }
+            }
+
+            bytes -= self.files.source(file_id).len();
+            self.files.update(file_id, String::new());
+            evicted.push(file_id);
+        }
+
+        evicted
+    }
+
     /// Load a file from the filesystem and add it to the name-id table.
     ///
     /// Uses the normalized path and the *modified at* timestamp as the name-id table entry.
@@ -396,14 +588,180 @@ impl Cache {
     pub fn get_or_add_file(&mut self, path: impl Into<OsString>) -> io::Result<CacheOp<FileId>> {
         let path = path.into();
         let normalized = normalize_path(&path)?;
-        match self.id_or_new_timestamp_of(path.as_ref())? {
-            SourceState::UpToDate(id) => Ok(CacheOp::Cached(id)),
+        // Files read from the filesystem are indexed by their normalized path, but in-memory
+        // sources are indexed by the path they were added with. We thus look the raw path up
+        // first, and fall back to the normalized one (which can differ e.g. by `..` components).
+        let lookup = if self
+            .file_ids
+            .contains_key(&SourcePath::Path(PathBuf::from(&path)))
+        {
+            PathBuf::from(&path)
+        } else {
+            normalized.clone()
+        };
+
+        match self.id_or_new_timestamp_of(&lookup)? {
+            SourceState::UpToDate(id) => {
+                self.mark_used(id);
+                Ok(CacheOp::Cached(id))
+            }
+            SourceState::Touched(id, timestamp) => {
+                self.touch(normalized, timestamp);
+                self.mark_used(id);
+                Ok(CacheOp::Cached(id))
+            }
             SourceState::Stale(timestamp) => {
                 self.add_file_(normalized, timestamp).map(CacheOp::Done)
             }
         }
     }
 
+    /// Expand a glob import such as `import "configs/*.ncl"` into a generated Nickel source: a
+    /// record with one field per matching file, named after the stem of the file and defined as an
+    /// import of this file. The matched files are thus imported and cached as usual.
+    ///
+    /// Only the file name of the pattern can contain wildcards (see [wildcard_match]). The
+    /// directory of the pattern is looked up in `possible_parents`, in order. The generated source
+    /// is cached under the path of the pattern, so that importing the same pattern again doesn't
+    /// list the directory again, unless the directory has been modified since.
+    fn get_or_add_glob(
+        &mut self,
+        pattern: &Path,
+        possible_parents: &[PathBuf],
+        pos: &TermPos,
+    ) -> Result<(CacheOp<FileId>, PathBuf), ImportError> {
+        let import_err =
+            |msg: String| ImportError::IOError(pattern.to_string_lossy().into_owned(), msg, *pos);
+
+        let (Some(dir), Some(file_pattern)) = (
+            pattern.parent(),
+            pattern.file_name().and_then(OsStr::to_str),
+        ) else {
+            return Err(import_err(String::from("invalid glob pattern")));
+        };
+
+        let dir = possible_parents
+            .iter()
+            .map(|parent| parent.join(dir))
+            .find(|dir| dir.is_dir())
+            .ok_or_else(|| {
+                let parents = possible_parents
+                    .iter()
+                    .map(|p| p.to_string_lossy())
+                    .collect::<Vec<_>>();
+                import_err(format!(
+                    "could not find the directory of the glob import (looked in [{}])",
+                    parents.join(", ")
+                ))
+            })?;
+        let dir = normalize_path(dir).map_err(|err| import_err(err.to_string()))?;
+
+        if !self.in_sandbox(&dir) {
+            return Err(ImportError::SandboxViolation(
+                pattern.to_string_lossy().into_owned(),
+                *pos,
+            ));
+        }
+
+        let path_buf = dir.join(file_pattern);
+
+        if let Some(id) = self.fresh_id_of(&SourcePath::Path(path_buf.clone())) {
+            return Ok((CacheOp::Cached(id), path_buf));
+        }
+
+        let id = self.add_glob(path_buf.clone()).map_err(import_err)?;
+
+        Ok((CacheOp::Done(id), path_buf))
+    }
+
+    /// Generate the source of the glob import `pattern`, which must be normalized, and add it to
+    /// the name-id table under the path of the pattern. See [Self::get_or_add_glob].
+    fn add_glob(&mut self, pattern: PathBuf) -> Result<FileId, String> {
+        let dir = pattern.parent().unwrap_or(Path::new(""));
+        let timestamp = timestamp(dir).map_err(|err| err.to_string())?;
+        let source = glob_source(&pattern)?;
+        let hash = content_hash(&source);
+
+        let file_id = self.files.add(&pattern, source);
+        self.file_paths
+            .insert(file_id, SourcePath::Path(pattern.clone()));
+        self.file_ids.insert(
+            SourcePath::Path(pattern),
+            NameIdEntry {
+                id: file_id,
+                source: SourceKind::Glob(timestamp, hash),
+            },
+        );
+        Ok(file_id)
+    }
+
+    /// Record the new timestamp of a file whose content didn't change, so that we don't have to
+    /// hash the content again on the next access.
+    fn touch(&mut self, path: PathBuf, timestamp: SystemTime) {
+        if let Some(NameIdEntry {
+            source: SourceKind::Filesystem(ts, _) | SourceKind::Glob(ts, _),
+            ..
+        }) = self.file_ids.get_mut(&SourcePath::Path(path))
+        {
+            *ts = timestamp;
+        }
+    }
+
+    /// Check the files loaded from the filesystem for changes. The new version of each changed
+    /// file is loaded, and the entries importing it (transitively) are invalidated through
+    /// [Self::invalidate_dependents]. The sources generated by glob imports are checked as well,
+    /// and generated again if files matching the pattern have been added or removed.
+    ///
+    /// This doesn't spawn any background watcher: it's up to the caller to decide when to poll,
+    /// for example before each evaluation in a watch mode. Each call reads the timestamp of every
+    /// cached file, so frequent callers such as the language server should rate-limit it. Files
+    /// loaded from in-memory buffers are never considered changed.
+    pub fn poll_changes(&mut self) -> Vec<FileChange> {
+        let mut files: Vec<(PathBuf, FileId, bool)> = self
+            .file_ids
+            .iter()
+            .filter_map(|(source_path, entry)| match (source_path, entry.source) {
+                (SourcePath::Path(path), SourceKind::Filesystem(..)) => {
+                    Some((path.clone(), entry.id, false))
+                }
+                (SourcePath::Path(path), SourceKind::Glob(..)) => {
+                    Some((path.clone(), entry.id, true))
+                }
+                _ => None,
+            })
+            .collect();
+        // Report changes in a deterministic order.
+        files.sort();
+
+        let mut changes = Vec::new();
+
+        for (path, old_id, is_glob) in files {
+            let new_id = match self.id_or_new_timestamp_of(&path) {
+                Ok(SourceState::UpToDate(_)) => continue,
+                Ok(SourceState::Touched(_, timestamp)) => {
+                    self.touch(path, timestamp);
+                    continue;
+                }
+                Ok(SourceState::Stale(_)) if is_glob => self.add_glob(path.clone()).ok(),
+                Ok(SourceState::Stale(timestamp)) => self.add_file_(path.clone(), timestamp).ok(),
+                Err(_) => None,
+            };
+
+            if new_id.is_none() {
+                self.file_ids.remove(&SourcePath::Path(path.clone()));
+            }
+
+            changes.push(FileChange {
+                path,
+                old_id,
+                new_id,
+                invalidated: self.invalidate_dependents(old_id),
+            });
+        }
+
+        changes
+    }
+
     /// Load a source and add it to the name-id table.
     ///
     /// Do not check if a source with the same name already exists: if it is the
@@ -446,7 +804,7 @@ impl Cache {
     /// Used to store intermediate short-lived generated snippets that needs to have a
     /// corresponding `FileId`, such as when querying or reporting errors.
     pub fn replace_string(&mut self, source_name: SourcePath, s: String) -> FileId {
-        if let Some(file_id) = self.id_of(&source_name) {
+        if let Some(file_id) = self.fresh_id_of(&source_name) {
             self.files.update(file_id, s);
             self.terms.remove(&file_id);
             file_id
@@ -464,6 +822,25 @@ impl Cache {
         }
     }
 
+    /// Shadow the file at `path` with an in-memory content, which is used instead of the file on
+    /// the filesystem from now on. If the file has already been loaded, its content is replaced,
+    /// its cached term is deleted and its `FileId` is kept.
+    pub fn add_overlay(&mut self, path: impl Into<PathBuf>, content: String) -> io::Result<FileId> {
+        let source_path = SourcePath::Path(normalize_path(path)?);
+        let id = self.replace_string(source_path.clone(), content);
+
+        // A file previously read from the filesystem would be read again if it changed on disk.
+        self.file_ids.insert(
+            source_path,
+            NameIdEntry {
+                id,
+                source: SourceKind::Memory,
+            },
+        );
+
+        Ok(id)
+    }
+
     /// Parse a source and populate the corresponding entry in the cache, or do
     /// nothing if the entry has already been parsed. Support multiple formats.
     /// This function is always error tolerant, independently from `self.error_tolerant`.
@@ -476,6 +853,11 @@ impl Cache {
             Ok(CacheOp::Cached(parse_errs.clone()))
         } else {
             let (term, parse_errs) = self.parse_nocache_multi(file_id, format)?;
+
+            if format == InputFormat::Nickel {
+                self.lint(file_id, &term);
+            }
+
             self.terms.insert(
                 file_id,
                 TermEntry {
@@ -508,6 +890,130 @@ impl Cache {
         }
     }
 
+    /// Parse a source and populate the corresponding entry in the cache, or do nothing if the
+    /// entry has already been parsed, and return the parsed term together with the errors that
+    /// were recovered from. Support multiple formats.
+    ///
+    /// Contrary to [Self::parse], this is always error tolerant, independently from
+    /// `self.error_tolerance`: the returned term may be partial, with the parts that couldn't be
+    /// parsed replaced by [Term::ParseError] nodes. This lets callers report all the syntax errors
+    /// of a source at once, while still getting the partial term. An error is only returned if
+    /// nothing could be parsed at all.
+    pub fn parse_recover(
+        &mut self,
+        file_id: FileId,
+        format: InputFormat,
+    ) -> Result<(RichTerm, ParseErrors), ParseError> {
+        let parse_errs = self.parse_lax(file_id, format)?.inner();
+        let term = self
+            .get_owned(file_id)
+            .expect("cache::parse_recover(): the entry was just parsed");
+
+        Ok((term, parse_errs))
+    }
+
+    /// Populate the entry of a source with an already parsed term, replacing any previous entry.
+    /// The entry is put in the [EntryState::Parsed] state, as if the source had been parsed by
+    /// [Self::parse].
+    ///
+    /// This is used by the language server, which avoids reparsing the parts of a source that
+    /// didn't change since the last parse.
+    pub fn insert_parsed(&mut self, file_id: FileId, term: RichTerm, parse_errs: ParseErrors) {
+        self.lint(file_id, &term);
+        self.terms.insert(
+            file_id,
+            TermEntry {
+                term,
+                state: EntryState::Parsed,
+                parse_errs,
+            },
+        );
+    }
+
+    /// Check a freshly parsed term for code which is most likely wrong, and record the warnings.
+    /// The standard library isn't checked, as users can't act on its warnings.
+    fn lint(&mut self, file_id: FileId, term: &RichTerm) {
+        if !matches!(self.file_paths.get(&file_id), Some(SourcePath::Std(_))) {
+            self.warnings.extend(crate::lint::lint(term));
+        }
+    }
+
+    /// Record a warning, to be retrieved later by [Self::take_warnings].
+    pub fn warn(&mut self, warning: Warning) {
+        self.warnings.push(warning);
+    }
+
+    /// Return the warnings emitted since the last call to this method, and clear them.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Register a custom transformation, applied after the ones already registered. Entries which
+    /// have already been transformed aren't affected.
+    pub fn add_transformation(&mut self, transformation: Arc<dyn Transformation>) {
+        self.transformations.push(transformation);
+    }
+
+    /// Set the custom source of imported files, which is looked up before the filesystem. See
+    /// [ImportSource].
+    pub fn set_import_source(&mut self, import_source: Arc<dyn ImportSource>) {
+        self.import_source = Some(import_source);
+    }
+
+    /// Sandbox the imports: only the files located under one of `roots` can be imported, and
+    /// importing any other file fails with [ImportError::SandboxViolation]. The sources added
+    /// directly to the cache, such as the main program, aren't restricted.
+    pub fn set_sandbox_roots(&mut self, roots: Vec<PathBuf>) {
+        self.sandbox_roots = Some(roots);
+    }
+
+    /// Check that `path` is located under one of the sandbox roots, if the imports are sandboxed.
+    /// Existing paths are canonicalized, so that symbolic links can't be used to escape the roots.
+    fn in_sandbox(&self, path: &Path) -> bool {
+        let Some(roots) = &self.sandbox_roots else {
+            return true;
+        };
+
+        let resolve = |path: &Path| path.canonicalize().or_else(|_| normalize_path(path));
+        let Ok(path) = resolve(path) else {
+            return false;
+        };
+
+        roots
+            .iter()
+            .filter_map(|root| resolve(root).ok())
+            .any(|root| path.starts_with(root))
+    }
+
+    /// Try to load the file at `path` from the custom [ImportSource], if any. Files loaded this way
+    /// are stored as in-memory sources, indexed by their normalized path, and are thus only
+    /// requested once.
+    fn get_or_add_from_import_source(
+        &mut self,
+        path: &Path,
+    ) -> io::Result<Option<CacheOp<FileId>>> {
+        let Some(import_source) = self.import_source.clone() else {
+            return Ok(None);
+        };
+
+        let normalized = normalize_path(path)?;
+        let source_path = SourcePath::Path(normalized.clone());
+
+        if let Some(NameIdEntry {
+            id,
+            source: SourceKind::Memory,
+        }) = self.file_ids.get(&source_path)
+        {
+            let id = *id;
+            self.mark_used(id);
+            return Ok(Some(CacheOp::Cached(id)));
+        }
+
+        Ok(import_source
+            .read(&normalized)?
+            .map(|content| CacheOp::Done(self.add_string(source_path, content))))
+    }
+
     /// Parse a source without querying nor populating the cache.
     pub fn parse_nocache(&self, file_id: FileId) -> Result<(RichTerm, ParseErrors), ParseError> {
         self.parse_nocache_multi(file_id, InputFormat::default())
@@ -575,9 +1081,22 @@ impl Cache {
                     ))
                 }
             }
-            InputFormat::Toml => toml::from_str(self.files.source(file_id))
-                .map(|t| (attach_pos(t), ParseErrors::default()))
-                .map_err(|err| (ParseError::from_toml(err, file_id))),
+            InputFormat::Toml => {
+                // We go through `toml::Value` instead of deserializing a term directly, so that
+                // dates and times are converted to strings instead of the internal representation
+                // used by the toml crate.
+                let value: toml::Value = toml::from_str(self.files.source(file_id))
+                    .map_err(|err| ParseError::from_toml(err, file_id))?;
+
+                serialize::from_toml_value(value)
+                    .map(|t| (attach_pos(t), ParseErrors::default()))
+                    .map_err(|msg| ParseError::ExternalFormatError(String::from("toml"), msg, None))
+            }
+            InputFormat::Csv => {
+                serialize::from_csv(self.files.source(file_id), &Default::default())
+                    .map(|t| (attach_pos(t), ParseErrors::default()))
+                    .map_err(|msg| ParseError::ExternalFormatError(String::from("csv"), msg, None))
+            }
             #[cfg(feature = "nix-experimental")]
             InputFormat::Nix => {
                 let json = nix_ffi::eval_to_json(self.files.source(file_id))
@@ -639,7 +1158,13 @@ impl Cache {
             Some(state) if state >= EntryState::Transformed => Ok(CacheOp::Cached(())),
             Some(state) if state >= EntryState::Parsed => {
                 if state < EntryState::Transforming {
-                    let cached_term = self.terms.remove(&file_id).unwrap();
+                    let mut cached_term = self.terms.remove(&file_id).unwrap();
+
+                    if !self.is_stdlib_module(file_id) {
+                        cached_term.term =
+                            transform::apply_custom(cached_term.term, &self.transformations);
+                    }
+
                     let term =
                         transform::transform(cached_term.term, self.wildcards.get(&file_id))?;
                     self.terms.insert(
@@ -936,6 +1461,7 @@ impl Cache {
         } = import_resolution::strict::resolve_imports(term, self)?;
 
         let wildcards = type_check(&term, initial_ctxt.clone(), self)?;
+        let term = transform::apply_custom(term, &self.transformations);
         let term = transform::transform(term, Some(&wildcards))
             .map_err(|err| Error::ParseErrors(err.into()))?;
         Ok((term, pending))
@@ -950,10 +1476,30 @@ impl Cache {
     ///
     /// Note that files added via [Self::add_file] are indexed by their full normalized path (cf
     /// [normalize_path]).
+    ///
+    /// If a file has a new timestamp but the same content, its content is hashed again on each
+    /// call, as the new timestamp can't be recorded here. This is rare, as [Self::poll_changes]
+    /// and the other accesses through a mutable reference record it.
     pub fn id_of(&self, name: &SourcePath) -> Option<FileId> {
+        match name {
+            SourcePath::Path(p) => match self.id_or_new_timestamp_of(p).ok()? {
+                SourceState::UpToDate(id) | SourceState::Touched(id, _) => Some(id),
+                SourceState::Stale(_) => None,
+            },
+            name => Some(self.file_ids.get(name)?.id),
+        }
+    }
+
+    /// Same as [Self::id_of], but record the new timestamp of files which have been touched, so
+    /// that their content isn't hashed again on the next access.
+    fn fresh_id_of(&mut self, name: &SourcePath) -> Option<FileId> {
         match name {
             SourcePath::Path(p) => match self.id_or_new_timestamp_of(p).ok()? {
                 SourceState::UpToDate(id) => Some(id),
+                SourceState::Touched(id, timestamp) => {
+                    self.touch(p.clone(), timestamp);
+                    Some(id)
+                }
                 SourceState::Stale(_) => None,
             },
             name => Some(self.file_ids.get(name)?.id),
@@ -967,17 +1513,35 @@ impl Cache {
     /// trying to read it (which most likely means there was no such file).
     ///
     /// The main point of this awkward signature is to minimize I/O operations: if we accessed
-    /// the timestamp, keep it around.
+    /// the timestamp, keep it around. The content of the file is only read when the timestamp
+    /// changed, to check if the content changed as well.
     fn id_or_new_timestamp_of(&self, name: &Path) -> io::Result<SourceState> {
         match self.file_ids.get(&SourcePath::Path(name.to_owned())) {
             None => Ok(SourceState::Stale(timestamp(name)?)),
             Some(NameIdEntry {
                 id,
-                source: SourceKind::Filesystem(ts),
+                source: SourceKind::Filesystem(ts, hash),
             }) => {
                 let new_timestamp = timestamp(name)?;
                 if ts == &new_timestamp {
                     Ok(SourceState::UpToDate(*id))
+                } else if content_hash(&fs::read_to_string(name)?) == *hash {
+                    Ok(SourceState::Touched(*id, new_timestamp))
+                } else {
+                    Ok(SourceState::Stale(new_timestamp))
+                }
+            }
+            // The directory of a glob pattern is modified whenever a file is added, removed or
+            // renamed, but the matching files might not have changed.
+            Some(NameIdEntry {
+                id,
+                source: SourceKind::Glob(ts, hash),
+            }) => {
+                let new_timestamp = timestamp(name.parent().unwrap_or(Path::new("")))?;
+                if ts == &new_timestamp {
+                    Ok(SourceState::UpToDate(*id))
+                } else if glob_source(name).map(|source| content_hash(&source)) == Ok(*hash) {
+                    Ok(SourceState::Touched(*id, new_timestamp))
                 } else {
                     Ok(SourceState::Stale(new_timestamp))
                 }
@@ -1069,11 +1633,25 @@ impl Cache {
 
     /// Returns the set of files that transitively depend on this file.
     pub fn get_rev_imports_transitive(&self, file: FileId) -> HashSet<FileId> {
+        Self::transitive_closure(file, |f| self.get_rev_imports(f))
+    }
+
+    /// Returns the set of files that this file transitively depends on.
+    pub fn get_imports_transitive(&self, file: FileId) -> HashSet<FileId> {
+        Self::transitive_closure(file, |f| self.get_imports(f))
+    }
+
+    /// Returns the set of files reachable from `file` in the graph whose edges are given by
+    /// `next`. `file` itself is only included if it belongs to a cycle.
+    fn transitive_closure<I>(file: FileId, next: impl Fn(FileId) -> I) -> HashSet<FileId>
+    where
+        I: Iterator<Item = FileId>,
+    {
         let mut ret = HashSet::new();
         let mut stack = vec![file];
 
         while let Some(file) = stack.pop() {
-            for f in self.get_rev_imports(file) {
+            for f in next(file) {
                 if ret.insert(f) {
                     stack.push(f);
                 }
@@ -1083,6 +1661,27 @@ impl Cache {
         ret
     }
 
+    /// Invalidate the entries which (transitively) import `file`, typically because `file` has
+    /// changed. Their sources are kept, but their terms are removed from the term cache, so that
+    /// they are parsed again and import the current version of their dependencies the next time
+    /// they are needed. Return the ids of the invalidated entries.
+    pub fn invalidate_dependents(&mut self, file: FileId) -> HashSet<FileId> {
+        let dependents = self.get_rev_imports_transitive(file);
+
+        for dependent in &dependents {
+            self.terms.remove(dependent);
+            self.wildcards.remove(dependent);
+
+            for import in self.imports.remove(dependent).into_iter().flatten() {
+                if let Some(rev_imports) = self.rev_imports.get_mut(&import) {
+                    rev_imports.remove(dependent);
+                }
+            }
+        }
+
+        dependents
+    }
+
     /// Retrieve the FileIds for all the stdlib modules
     pub fn get_all_stdlib_modules_file_id(&self) -> Option<Vec<FileId>> {
         let ids = self.stdlib_ids.as_ref()?;
@@ -1109,10 +1708,67 @@ impl Cache {
         for (_, file_id) in file_ids.iter() {
             self.parse(*file_id, InputFormat::Nickel)?;
         }
+
+        let extensions: Vec<(LocIdent, FileId)> = std::mem::take(&mut self.stdlib_extensions)
+            .into_iter()
+            .map(|(name, source)| {
+                let file_id = self.add_string(SourcePath::StdExtension(name.clone()), source);
+                (LocIdent::from(name.as_str()), file_id)
+            })
+            .collect();
+
+        for (_, file_id) in extensions.iter() {
+            self.parse(*file_id, InputFormat::Nickel)?;
+        }
+
+        self.extend_std(file_ids[&StdlibModule::Std], &extensions);
         self.stdlib_ids.replace(file_ids);
         Ok(CacheOp::Done(()))
     }
 
+    /// Extend or override the `std` module with custom modules: each module `(name, source)` is
+    /// made available as `std.<name>`, replacing the builtin module of the same name, if any.
+    ///
+    /// The extensions are added when the standard library is loaded, so this must be called
+    /// before [Self::load_stdlib] (or any method loading the standard library) to have an effect.
+    /// Extensions can refer to the rest of the standard library through `std`, but they can't
+    /// import other files.
+    pub fn set_stdlib_extensions<N, S>(&mut self, modules: impl IntoIterator<Item = (N, S)>)
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        self.stdlib_extensions = modules
+            .into_iter()
+            .map(|(name, source)| (name.into(), source.into()))
+            .collect();
+    }
+
+    /// Add the parsed stdlib extensions as fields of the parsed `std` module.
+    fn extend_std(&mut self, std_id: FileId, extensions: &[(LocIdent, FileId)]) {
+        if extensions.is_empty() {
+            return;
+        }
+
+        let modules: Vec<(LocIdent, RichTerm)> = extensions
+            .iter()
+            .filter_map(|(name, file_id)| Some((*name, self.get_owned(*file_id)?)))
+            .collect();
+
+        let Some(TermEntry { term, .. }) = self.terms.get_mut(&std_id) else {
+            return;
+        };
+
+        match SharedTerm::make_mut(&mut term.term) {
+            Term::RecRecord(data, ..) => {
+                for (name, module) in modules {
+                    data.fields.insert(name, Field::from(module));
+                }
+            }
+            _ => panic!("cache::extend_std(): the std module is not a record"),
+        }
+    }
+
     /// Typecheck the standard library. Currently only used in the test suite.
     pub fn typecheck_stdlib(&mut self) -> Result<CacheOp<()>, CacheError<TypecheckError>> {
         // We have a small bootstraping problem: to typecheck the initial environment, we already
@@ -1259,6 +1915,26 @@ impl Cache {
     }
 }
 
+/// A custom source of imported files, for applications embedding Nickel which serve imports from
+/// somewhere else than the filesystem, such as memory, a database or bundled assets. See
+/// [Cache::set_import_source].
+///
+/// Imports are still identified by paths. Each location where an import is looked up is first
+/// requested from the custom source, and then from the filesystem. In particular, the relative
+/// imports of a file served by a custom source are resolved relatively to its path, as usual. Glob
+/// imports only list the filesystem.
+pub trait ImportSource: Send + Sync {
+    /// Return the content of the file at `path`, which is absolute and normalized, or `None` if
+    /// this source doesn't provide it, in which case it's read from the filesystem.
+    fn read(&self, path: &Path) -> io::Result<Option<String>>;
+}
+
+impl std::fmt::Debug for dyn ImportSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ImportSource")
+    }
+}
+
 /// Abstract the access to imported files and the import cache. Used by the evaluator, the
 /// typechecker and at the [import resolution](crate::transform::import_resolution) phase.
 ///
@@ -1313,15 +1989,50 @@ impl ImportResolver for Cache {
             .chain(self.import_paths.iter().cloned())
             .collect();
 
-        // Try to import from all possibilities, taking the first one that succeeds.
-        let (id_op, path_buf) = possible_parents
-            .iter()
-            .find_map(|parent| {
-                let mut path_buf = parent.clone();
-                path_buf.push(path);
-                self.get_or_add_file(&path_buf).ok().map(|x| (x, path_buf))
-            })
-            .ok_or_else(|| {
+        let (id_op, path_buf, format) = if is_glob_pattern(path) {
+            let (id_op, path_buf) =
+                self.get_or_add_glob(Path::new(path), &possible_parents, pos)?;
+            (id_op, path_buf, InputFormat::Nickel)
+        } else {
+            let mut found = None;
+            // Whether an existing file has been skipped because it's outside of the sandbox.
+            let mut denied = false;
+
+            // Try to import from all possibilities, taking the first one that succeeds. At each
+            // location, the custom import source takes precedence over the filesystem.
+            for parent in possible_parents.iter() {
+                let path_buf = parent.join(path);
+
+                if !self.in_sandbox(&path_buf) {
+                    denied = denied || path_buf.exists();
+                    continue;
+                }
+
+                let id_op = self
+                    .get_or_add_from_import_source(&path_buf)
+                    .map_err(|err| {
+                        ImportError::IOError(
+                            path.to_string_lossy().into_owned(),
+                            err.to_string(),
+                            *pos,
+                        )
+                    })?
+                    .or_else(|| self.get_or_add_file(&path_buf).ok());
+
+                if let Some(id_op) = id_op {
+                    found = Some((id_op, path_buf));
+                    break;
+                }
+            }
+
+            if found.is_none() && denied {
+                return Err(ImportError::SandboxViolation(
+                    path.to_string_lossy().into_owned(),
+                    *pos,
+                ));
+            }
+
+            let (id_op, path_buf) = found.ok_or_else(|| {
                 let parents = possible_parents
                     .iter()
                     .map(|p| p.to_string_lossy())
@@ -1333,7 +2044,10 @@ impl ImportResolver for Cache {
                 )
             })?;
 
-        let format = InputFormat::from_path(&path_buf).unwrap_or_default();
+            let format = InputFormat::from_path(&path_buf).unwrap_or_default();
+            (id_op, path_buf, format)
+        };
+
         let (result, file_id) = match id_op {
             CacheOp::Cached(id) => (ResolvedTerm::FromCache, id),
             CacheOp::Done(id) => (ResolvedTerm::FromFile { path: path_buf }, id),
@@ -1366,6 +2080,90 @@ impl ImportResolver for Cache {
     }
 }
 
+/// Whether an import path is a glob pattern, that is if its file name contains wildcards.
+fn is_glob_pattern(path: &OsStr) -> bool {
+    Path::new(path)
+        .file_name()
+        .and_then(OsStr::to_str)
+        .is_some_and(|name| name.contains(['*', '?']))
+}
+
+/// Match a file name against a pattern, where `*` matches any sequence of characters and `?`
+/// matches any single character. As in shells, wildcards don't match a leading `.`: hidden files
+/// are only matched by patterns starting with `.`.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Generate the source of a glob import, given the normalized path of the pattern: a record with
+/// one field per file of the directory matching the pattern, named after the stem of the file and
+/// defined as an import of this file. Return an error message if the directory can't be listed or
+/// if several matching files have the same stem.
+fn glob_source(pattern: &Path) -> Result<String, String> {
+    let (Some(dir), Some(file_pattern)) = (
+        pattern.parent(),
+        pattern.file_name().and_then(OsStr::to_str),
+    ) else {
+        return Err(String::from("invalid glob pattern"));
+    };
+
+    let mut matches = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(|err| err.to_string())? {
+        let path = entry.map_err(|err| err.to_string())?.path();
+
+        let matching = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .is_some_and(|name| wildcard_match(file_pattern, name));
+
+        if matching && path.is_file() {
+            matches.push(path);
+        }
+    }
+
+    matches.sort();
+
+    let mut fields = IndexMap::new();
+
+    for file in matches {
+        let stem = file
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+
+        let import = RichTerm::from(Term::Import(file.into_os_string()));
+
+        if fields
+            .insert(LocIdent::from(stem.as_str()), import)
+            .is_some()
+        {
+            return Err(format!(
+                "several matching files have the same name `{stem}`"
+            ));
+        }
+    }
+
+    let record = RichTerm::from(Term::Record(RecordData::with_field_values(fields)));
+    Ok(format!("{record}"))
+}
+
 /// Normalize the path of a file for unique identification in the cache.
 ///
 /// The returned path will be an absolute path.
@@ -1421,6 +2219,13 @@ pub fn timestamp(path: impl AsRef<OsStr>) -> io::Result<SystemTime> {
     fs::metadata(path.as_ref())?.modified()
 }
 
+/// Return a hash of the content of a source, used to detect if a file has actually changed.
+fn content_hash(contents: &str) -> u64 {
+    let mut hasher = hash_map::DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
 /// Provide mockup import resolvers for testing purpose.
 pub mod resolvers {
     use super::*;
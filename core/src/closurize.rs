@@ -225,6 +225,7 @@ impl Closurize for ArrayAttrs {
             // `ArrayAttrs::closurize_as_btype` set the flag explicitly to avoid surprises.
             closurized: self.closurized,
             pending_contracts,
+            tuple: self.tuple,
         }
     }
 }
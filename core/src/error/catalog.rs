@@ -0,0 +1,260 @@
+//! Message catalogs, used to translate the user-facing messages of diagnostics.
+//!
+//! Each message is identified by a key, such as `missing-field`, and is written as a template
+//! where `{name}` is replaced by the argument `name`. The English templates are written inline
+//! where the diagnostics are built (see [tr]), so that English doesn't need a catalog and is
+//! always available. Other languages are provided by catalogs: TOML files mapping keys to
+//! templates, named after their locale, such as `fr.toml`:
+//!
+//! ```toml
+//! missing-field = "champ `{field}` manquant"
+//! ```
+//!
+//! As in English, the names mentioned by a translated message must be kept between backquotes: the
+//! language server relies on it to extract them, for example to suggest fixes for an unbound
+//! identifier.
+//!
+//! A catalog doesn't need to be complete: a message missing from the selected catalog falls back
+//! to English. The catalog is selected once for the whole process, with [select_locale] or
+//! [set_catalog].
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+/// The locale of the messages written inline, which doesn't need a catalog.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// The catalog used to translate messages. `None` stands for English.
+static CATALOG: RwLock<Option<Catalog>> = RwLock::new(None);
+
+/// A set of translated message templates for a locale.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Catalog {
+    locale: String,
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Create a catalog for `locale` from a list of keys and templates.
+    pub fn new(
+        locale: impl Into<String>,
+        messages: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        Catalog {
+            locale: locale.into(),
+            messages: messages.into_iter().collect(),
+        }
+    }
+
+    /// Parse a catalog for `locale` from the content of a TOML file, which must be a table of
+    /// strings.
+    pub fn from_toml(locale: impl Into<String>, source: &str) -> Result<Self, String> {
+        let messages: HashMap<String, String> =
+            toml::from_str(source).map_err(|err| err.to_string())?;
+
+        Ok(Catalog {
+            locale: locale.into(),
+            messages,
+        })
+    }
+
+    /// Read a catalog for `locale` from a TOML file.
+    pub fn from_file(locale: impl Into<String>, path: &Path) -> Result<Self, CatalogError> {
+        let source = std::fs::read_to_string(path).map_err(|err| CatalogError::Invalid {
+            path: path.to_owned(),
+            msg: err.to_string(),
+        })?;
+
+        Catalog::from_toml(locale, &source).map_err(|msg| CatalogError::Invalid {
+            path: path.to_owned(),
+            msg,
+        })
+    }
+
+    /// The locale of this catalog.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Return the template of the message `key`, if it's translated by this catalog.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+}
+
+/// An error occurring when selecting a locale.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CatalogError {
+    /// No catalog was found for the locale.
+    NotFound { locale: String },
+    /// A catalog file couldn't be read or parsed.
+    Invalid { path: PathBuf, msg: String },
+}
+
+impl Display for CatalogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogError::NotFound { locale } => {
+                write!(f, "no message catalog found for the locale `{locale}`")
+            }
+            CatalogError::Invalid { path, msg } => {
+                write!(f, "invalid message catalog `{}`: {msg}", path.display())
+            }
+        }
+    }
+}
+
+/// Set the catalog used to translate messages. `None` restores English.
+pub fn set_catalog(catalog: Option<Catalog>) {
+    *CATALOG.write().unwrap() = catalog;
+}
+
+/// Return the locale of the messages currently produced.
+pub fn current_locale() -> String {
+    CATALOG
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(|catalog| catalog.locale.clone())
+        .unwrap_or_else(|| String::from(DEFAULT_LOCALE))
+}
+
+/// Return the locales to try, from the most to the least specific, for a locale given in the
+/// POSIX format (`fr_FR.UTF-8`) or as a language tag (`fr-FR`): here, `fr_FR` then `fr`.
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let locale = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('-', "_");
+
+    let mut candidates = vec![locale.clone()];
+
+    if let Some((language, _)) = locale.split_once('_') {
+        candidates.push(language.to_owned());
+    }
+
+    candidates
+}
+
+/// Select the catalog for `locale`, looking for a file `<locale>.toml` in each directory of
+/// `search_path`, in order. A region-specific locale falls back to the catalog of its language
+/// (`fr_FR` to `fr`). English, as well as the `C` and `POSIX` locales, don't need a catalog.
+pub fn select_locale<P: AsRef<Path>>(
+    locale: &str,
+    search_path: impl IntoIterator<Item = P>,
+) -> Result<(), CatalogError> {
+    let search_path: Vec<P> = search_path.into_iter().collect();
+
+    for candidate in locale_candidates(locale) {
+        if candidate == DEFAULT_LOCALE || candidate == "C" || candidate == "POSIX" {
+            set_catalog(None);
+            return Ok(());
+        }
+
+        for dir in search_path.iter() {
+            let path = dir.as_ref().join(format!("{candidate}.toml"));
+
+            if path.is_file() {
+                set_catalog(Some(Catalog::from_file(candidate, &path)?));
+                return Ok(());
+            }
+        }
+    }
+
+    Err(CatalogError::NotFound {
+        locale: locale.to_owned(),
+    })
+}
+
+/// Replace each `{name}` in `template` by the argument `name`. Other braces are left untouched.
+fn render(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let arg = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            args.iter()
+                .find(|(arg_name, _)| *arg_name == name)
+                .map(|(_, value)| (end, value))
+        });
+
+        match arg {
+            Some((end, value)) => {
+                result.push_str(&value.to_string());
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Translate the message `key` with the current catalog, falling back to the English template
+/// `default`, and fill in the arguments. Use [tr] instead of calling this function directly.
+pub fn translate(key: &str, default: &str, args: &[(&str, &dyn Display)]) -> String {
+    let catalog = CATALOG.read().unwrap();
+    let template = catalog
+        .as_ref()
+        .and_then(|catalog| catalog.get(key))
+        .unwrap_or(default);
+
+    render(template, args)
+}
+
+/// Build a translatable message from a key, an English template and named arguments:
+///
+/// ```ignore
+/// tr!("missing-field", "missing field `{field}`", field = id)
+/// ```
+macro_rules! tr {
+    ($key:literal, $default:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::error::catalog::translate(
+            $key,
+            $default,
+            &[$((stringify!($name), &$value as &dyn std::fmt::Display)),*],
+        )
+    };
+}
+
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_templates() {
+        assert_eq!(
+            render("missing field `{field}`", &[("field", &"foo")]),
+            "missing field `foo`"
+        );
+        assert_eq!(
+            render("{a} and {b} or {c}", &[("a", &1), ("b", &"two")]),
+            "1 and two or {c}"
+        );
+        assert_eq!(
+            render("unmatched '}' and '{'", &[]),
+            "unmatched '}' and '{'"
+        );
+    }
+
+    #[test]
+    fn locale_fallbacks() {
+        assert_eq!(locale_candidates("fr_FR.UTF-8"), vec!["fr_FR", "fr"]);
+        assert_eq!(locale_candidates("pt-BR"), vec!["pt_BR", "pt"]);
+        assert_eq!(locale_candidates("de"), vec!["de"]);
+    }
+}
@@ -3,7 +3,7 @@
 //! Define error types for different phases of the execution, together with functions to generate a
 //! [codespan](https://crates.io/crates/codespan-reporting) diagnostic from them.
 pub use codespan::{FileId, Files};
-pub use codespan_reporting::diagnostic::{Diagnostic, Label, LabelStyle};
+pub use codespan_reporting::diagnostic::{Diagnostic, Label, LabelStyle, Severity};
 
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream, WriteColor};
 use lalrpop_util::ErrorRecovery;
@@ -31,8 +31,57 @@ use crate::{
     typ::{EnumRow, RecordRow, Type, TypeF, VarKindDiscriminant},
 };
 
+pub mod catalog;
 pub mod report;
 pub mod suggest;
+pub mod warning;
+
+use catalog::tr;
+
+/// A stable code identifying a kind of error, rendered as `N` followed by four digits, such as
+/// `N0201`. Codes are grouped by error type:
+///
+/// - `N0001` to `N0099`: [ParseError]
+/// - `N0101` to `N0199`: [TypecheckError]
+/// - `N0201` to `N0299`: [EvalError]
+/// - `N0301` to `N0399`: [ImportError]
+/// - `N0401` to `N0499`: [warning::Warning]
+/// - `N0501` to `N0599`: diagnostics reported only by the language server
+///
+/// Codes are never reused nor reassigned: new variants get new codes, and the code of a removed
+/// variant is retired.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErrorCode(pub u16);
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "N{:04}", self.0)
+    }
+}
+
+impl std::str::FromStr for ErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix(['N', 'n'])
+            .filter(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse().ok())
+            .map(ErrorCode)
+            .ok_or_else(|| format!("invalid code `{s}`, expected a code such as `N0401`"))
+    }
+}
+
+/// Set the code of the main diagnostic of an error, which is the first one, unless it already has
+/// one (which happens when an error wraps another one).
+fn with_code(mut diagnostics: Vec<Diagnostic<FileId>>, code: ErrorCode) -> Vec<Diagnostic<FileId>> {
+    if let Some(diagnostic) = diagnostics.first_mut() {
+        if diagnostic.code.is_none() {
+            diagnostic.code = Some(code.to_string());
+        }
+    }
+
+    diagnostics
+}
 
 /// A general error occurring during either parsing or evaluation.
 #[derive(Debug, Clone, PartialEq)]
@@ -46,6 +95,20 @@ pub enum Error {
     ReplError(ReplError),
 }
 
+impl Error {
+    /// Return the code of this error, if it has one. For a list of parse errors, this is the code
+    /// of the first one.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::EvalError(err) => Some(err.code()),
+            Error::TypecheckError(err) => Some(err.code()),
+            Error::ParseErrors(errs) => errs.errors.first().map(ParseError::code),
+            Error::ImportError(err) => Some(err.code()),
+            Error::ExportError(_) | Error::IOError(_) | Error::ReplError(_) => None,
+        }
+    }
+}
+
 /// An error occurring during evaluation.
 #[derive(Debug, Clone, PartialEq)]
 pub enum EvalError {
@@ -173,12 +236,87 @@ pub enum EvalError {
         /// Evaluated expression
         value: RichTerm,
     },
+    /// Evaluation used more resources than allowed by the host. See
+    /// [crate::eval::VirtualMachine::set_fuel],
+    /// [crate::eval::VirtualMachine::set_max_allocations] and
+    /// [crate::eval::VirtualMachine::set_timeout].
+    ResourceExhausted {
+        /// The exhausted resource.
+        resource: EvalResource,
+        /// The limit set by the host.
+        limit: usize,
+        /// The position of the term being evaluated when the limit was reached.
+        pos: TermPos,
+    },
+    /// Evaluation has been cancelled by the host. See
+    /// [crate::eval::VirtualMachine::set_cancellation_token].
+    Cancelled(/* position of the term being evaluated */ TermPos),
+    /// A primitive operation depending on the environment of the host was called in sandbox mode.
+    SandboxViolation {
+        /// The primitive operation which isn't allowed.
+        primop: String,
+        /// The position of the primitive operation application.
+        pos_op: TermPos,
+    },
     /// An unexpected internal error.
     InternalError(String, TermPos),
     /// Errors occurring rarely enough to not deserve a dedicated variant.
     Other(String, TermPos),
 }
 
+impl EvalError {
+    /// Return the stable code of this error. See [ErrorCode].
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode(match self {
+            EvalError::BlameError { .. } => 201,
+            EvalError::MissingFieldDef { .. } => 202,
+            EvalError::TypeError(..) => 203,
+            EvalError::UnaryPrimopTypeError { .. } => 204,
+            EvalError::NAryPrimopTypeError { .. } => 205,
+            EvalError::ParseError(_) => 206,
+            EvalError::NotAFunc(..) => 207,
+            EvalError::FieldMissing { .. } => 208,
+            EvalError::NotEnoughArgs(..) => 209,
+            EvalError::MergeIncompatibleArgs { .. } => 210,
+            EvalError::UnboundIdentifier(..) => 211,
+            EvalError::InfiniteRecursion(..) => 212,
+            EvalError::SerializationError(_) => 213,
+            EvalError::DeserializationError(..) => 214,
+            EvalError::IllegalPolymorphicTailAccess { .. } => 215,
+            EvalError::EqError { .. } => 216,
+            EvalError::NonExhaustiveEnumMatch { .. } => 217,
+            EvalError::NonExhaustiveMatch { .. } => 218,
+            EvalError::QueryNonRecord { .. } => 219,
+            EvalError::InternalError(..) => 220,
+            EvalError::Other(..) => 221,
+            EvalError::SandboxViolation { .. } => 222,
+            EvalError::ResourceExhausted { .. } => 223,
+            EvalError::Cancelled(_) => 224,
+        })
+    }
+}
+
+/// A resource whose use by evaluation can be limited.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EvalResource {
+    /// The number of evaluation steps.
+    Fuel,
+    /// The number of elements allocated in the evaluation cache.
+    Allocations,
+    /// The wall-clock time, in milliseconds.
+    Time,
+}
+
+impl std::fmt::Display for EvalResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalResource::Fuel => write!(f, "evaluation steps"),
+            EvalResource::Allocations => write!(f, "allocations"),
+            EvalResource::Time => write!(f, "milliseconds"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum IllegalPolymorphicTailAction {
     FieldAccess { field: String },
@@ -393,6 +531,30 @@ pub enum TypecheckError {
     },
 }
 
+impl TypecheckError {
+    /// Return the stable code of this error. See [ErrorCode].
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode(match self {
+            TypecheckError::UnboundIdentifier { .. } => 101,
+            TypecheckError::MissingRow { .. } => 102,
+            TypecheckError::MissingDynTail { .. } => 103,
+            TypecheckError::ExtraRow { .. } => 104,
+            TypecheckError::ExtraDynTail { .. } => 105,
+            TypecheckError::ForallParametricityViolation { .. } => 106,
+            TypecheckError::UnboundTypeVariable(_) => 107,
+            TypecheckError::TypeMismatch { .. } => 108,
+            TypecheckError::RecordRowMismatch { .. } => 109,
+            TypecheckError::EnumRowMismatch { .. } => 110,
+            TypecheckError::RecordRowConflict { .. } => 111,
+            TypecheckError::EnumRowConflict { .. } => 112,
+            TypecheckError::ArrowTypeMismatch { .. } => 113,
+            TypecheckError::IncomparableFlatTypes { .. } => 114,
+            TypecheckError::FlatTypeInTermPosition { .. } => 115,
+            TypecheckError::VarLevelMismatch { .. } => 116,
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct ParseErrors {
     pub errors: Vec<ParseError>,
@@ -546,6 +708,13 @@ pub enum ParseError {
         /// The previous instance of the duplicated identifier.
         prev_ident: LocIdent,
     },
+    /// A duplicate binding was encountered in a recursive let-binding group.
+    DuplicateIdentInLetRec {
+        /// The duplicate identifier.
+        ident: LocIdent,
+        /// The previous instance of the duplicated identifier.
+        prev_ident: LocIdent,
+    },
     /// There was an attempt to use a feature that hasn't been enabled.
     DisabledFeature { feature: String, span: RawSpan },
     /// A term was used as a contract in type position, but this term has no chance to make any
@@ -555,6 +724,32 @@ pub enum ParseError {
     InvalidContract(RawSpan),
 }
 
+impl ParseError {
+    /// Return the stable code of this error. See [ErrorCode].
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode(match self {
+            ParseError::UnexpectedEOF(..) => 1,
+            ParseError::UnexpectedToken(..) => 2,
+            ParseError::ExtraToken(_) => 3,
+            ParseError::UnmatchedCloseBrace(_) => 4,
+            ParseError::InvalidEscapeSequence(_) => 5,
+            ParseError::InvalidAsciiEscapeCode(_) => 6,
+            ParseError::StringDelimiterMismatch { .. } => 7,
+            ParseError::ExternalFormatError(..) => 8,
+            ParseError::UnboundTypeVariables(_) => 9,
+            ParseError::InvalidRecordType { .. } => 10,
+            ParseError::RecursiveLetPattern(_) => 11,
+            ParseError::TypeVariableKindMismatch { .. } => 12,
+            ParseError::TypedFieldWithoutDefinition { .. } => 13,
+            ParseError::InterpolationInStaticPath { .. } => 14,
+            ParseError::DuplicateIdentInRecordPattern { .. } => 15,
+            ParseError::DisabledFeature { .. } => 16,
+            ParseError::InvalidContract(_) => 17,
+            ParseError::DuplicateIdentInLetRec { .. } => 18,
+        })
+    }
+}
+
 /// An error occurring during the resolution of an import.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ImportError {
@@ -569,6 +764,22 @@ pub enum ImportError {
         /* error */ ParseErrors,
         /* import position */ TermPos,
     ),
+    /// A file outside of the sandbox roots was imported in sandbox mode.
+    SandboxViolation(
+        /* imported file */ String,
+        /* import position */ TermPos,
+    ),
+}
+
+impl ImportError {
+    /// Return the stable code of this error. See [ErrorCode].
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode(match self {
+            ImportError::IOError(..) => 301,
+            ImportError::ParseErrors(..) => 302,
+            ImportError::SandboxViolation(..) => 303,
+        })
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -589,6 +800,8 @@ pub enum ExportErrorData {
     NotAString(RichTerm),
     /// A term contains constructs that cannot be serialized.
     NonSerializable(RichTerm),
+    /// A term can't be flattened into environment variables by the `env` format.
+    NotFlattenable(RichTerm),
     /// No exportable documentation was found when requested.
     NoDocumentation(RichTerm),
     /// A number was too large (in absolute value) to be serialized as `f64`
@@ -596,6 +809,12 @@ pub enum ExportErrorData {
         term: RichTerm,
         value: Number,
     },
+    /// A number couldn't be serialized exactly, and approximating it wasn't allowed. See
+    /// [crate::serialize::InexactNumbers].
+    InexactNumber {
+        term: RichTerm,
+        value: Number,
+    },
     Other(String),
 }
 
@@ -771,6 +990,9 @@ impl ParseError {
                 InternalParseError::DuplicateIdentInRecordPattern { ident, prev_ident } => {
                     ParseError::DuplicateIdentInRecordPattern { ident, prev_ident }
                 }
+                InternalParseError::DuplicateIdentInLetRec { ident, prev_ident } => {
+                    ParseError::DuplicateIdentInLetRec { ident, prev_ident }
+                }
                 InternalParseError::DisabledFeature { feature, span } => {
                     ParseError::DisabledFeature { feature, span }
                 }
@@ -1047,6 +1269,18 @@ impl IntoDiagnostics<FileId> for EvalError {
         self,
         files: &mut Files<String>,
         stdlib_ids: Option<&Vec<FileId>>,
+    ) -> Vec<Diagnostic<FileId>> {
+        let code = self.code();
+        with_code(self.uncoded_diagnostics(files, stdlib_ids), code)
+    }
+}
+
+impl EvalError {
+    /// Convert this error to diagnostics, without setting their code.
+    fn uncoded_diagnostics(
+        self,
+        files: &mut Files<String>,
+        stdlib_ids: Option<&Vec<FileId>>,
     ) -> Vec<Diagnostic<FileId>> {
         match self {
             EvalError::BlameError {
@@ -1082,7 +1316,11 @@ impl IntoDiagnostics<FileId> for EvalError {
                 }
 
                 let mut diags = vec![Diagnostic::error()
-                    .with_message(format!("missing definition for `{id}`",))
+                    .with_message(tr!(
+                        "missing-definition",
+                        "missing definition for `{id}`",
+                        id = id
+                    ))
                     .with_labels(labels)];
 
                 // Is it really useful to include the label if we show the position of the ident?
@@ -1129,13 +1367,13 @@ impl IntoDiagnostics<FileId> for EvalError {
                 };
 
                 vec![Diagnostic::error()
-                    .with_message("dynamic type error")
+                    .with_message(tr!("dynamic-type-error", "dynamic type error"))
                     .with_labels(labels)
                     .with_notes(vec![msg])]
             }
             EvalError::ParseError(parse_error) => parse_error.into_diagnostics(files, stdlib_ids),
             EvalError::NotAFunc(t, arg, pos_opt) => vec![Diagnostic::error()
-                .with_message("not a function")
+                .with_message(tr!("not-a-function", "not a function"))
                 .with_labels(vec![
                     primary_term(&t, files)
                         .with_message("this term is applied, but it is not a function"),
@@ -1174,7 +1412,11 @@ impl IntoDiagnostics<FileId> for EvalError {
                 suggest::add_suggestion(&mut notes, &field_names, &name);
 
                 vec![Diagnostic::error()
-                    .with_message(format!("missing field `{field}`"))
+                    .with_message(tr!(
+                        "missing-field",
+                        "missing field `{field}`",
+                        field = field
+                    ))
                     .with_labels(labels)
                     .with_notes(notes)]
             }
@@ -1193,7 +1435,7 @@ impl IntoDiagnostics<FileId> for EvalError {
                 }
 
                 vec![Diagnostic::error()
-                    .with_message("not enough arguments")
+                    .with_message(tr!("not-enough-arguments", "not enough arguments"))
                     .with_labels(labels)
                     .with_notes(notes)]
             }
@@ -1301,13 +1543,24 @@ impl IntoDiagnostics<FileId> for EvalError {
                     }
                 }
 
+                if let Some(diff) = merge_diff(&left_arg, &right_arg) {
+                    notes.push(format!(
+                        "Difference between the two values \
+                        (`-` for the first one, `+` for the second one):\n{diff}"
+                    ));
+                }
+
                 vec![Diagnostic::error()
-                    .with_message("non mergeable terms")
+                    .with_message(tr!("non-mergeable-terms", "non mergeable terms"))
                     .with_labels(labels)
                     .with_notes(notes)]
             }
             EvalError::UnboundIdentifier(ident, span_opt) => vec![Diagnostic::error()
-                .with_message(format!("unbound identifier `{ident}`"))
+                .with_message(tr!(
+                    "unbound-identifier",
+                    "unbound identifier `{ident}`",
+                    ident = ident
+                ))
                 .with_labels(vec![primary_alt(
                     span_opt.into_opt(),
                     ident.to_string(),
@@ -1321,7 +1574,7 @@ impl IntoDiagnostics<FileId> for EvalError {
                     .unwrap_or_default();
 
                 vec![Diagnostic::error()
-                    .with_message("infinite recursion")
+                    .with_message(tr!("infinite-recursion", "infinite recursion"))
                     .with_labels(labels)]
             }
             EvalError::Other(msg, span_opt) => {
@@ -1339,7 +1592,7 @@ impl IntoDiagnostics<FileId> for EvalError {
                     .unwrap_or_default();
 
                 vec![Diagnostic::error()
-                    .with_message(format!("internal error: {msg}"))
+                    .with_message(tr!("internal-error", "internal error: {msg}", msg = msg))
                     .with_labels(labels)
                     .with_notes(vec![String::from(INTERNAL_ERROR_MSG)])]
             }
@@ -1351,7 +1604,12 @@ impl IntoDiagnostics<FileId> for EvalError {
                     .unwrap_or_default();
 
                 vec![Diagnostic::error()
-                    .with_message(format!("{format} parse error: {msg}"))
+                    .with_message(tr!(
+                        "deserialization-error",
+                        "{format} parse error: {msg}",
+                        format = format,
+                        msg = msg
+                    ))
                     .with_labels(labels)]
             }
             EvalError::EqError { eq_pos, term: t } => {
@@ -1374,7 +1632,10 @@ impl IntoDiagnostics<FileId> for EvalError {
                 };
 
                 vec![Diagnostic::error()
-                    .with_message("cannot compare values for equality")
+                    .with_message(tr!(
+                        "incomparable-values",
+                        "cannot compare values for equality"
+                    ))
                     .with_labels(labels)]
             }
             EvalError::NonExhaustiveEnumMatch {
@@ -1403,7 +1664,7 @@ impl IntoDiagnostics<FileId> for EvalError {
                 );
 
                 vec![Diagnostic::error()
-                    .with_message("unmatched pattern")
+                    .with_message(tr!("unmatched-pattern", "unmatched pattern"))
                     .with_labels(labels)
                     .with_notes(vec![
                         format!("This match expression isn't exhaustive, matching only the following pattern(s): `{tag_list}`"),
@@ -1423,7 +1684,7 @@ impl IntoDiagnostics<FileId> for EvalError {
                 );
 
                 vec![Diagnostic::error()
-                    .with_message("unmatched pattern")
+                    .with_message(tr!("unmatched-pattern", "unmatched pattern"))
                     .with_labels(labels)]
             }
             EvalError::IllegalPolymorphicTailAccess {
@@ -1484,14 +1745,97 @@ impl IntoDiagnostics<FileId> for EvalError {
                 };
 
                 vec![Diagnostic::error()
-                    .with_message("tried to query field of a non-record")
+                    .with_message(tr!(
+                        "query-non-record",
+                        "tried to query field of a non-record"
+                    ))
                     .with_labels(vec![label])]
             }
+            EvalError::ResourceExhausted {
+                resource,
+                limit,
+                pos,
+            } => {
+                let labels = pos
+                    .as_opt_ref()
+                    .map(|span| vec![primary(span).with_message("while evaluating this")])
+                    .unwrap_or_default();
+
+                vec![Diagnostic::error()
+                    .with_message(tr!(
+                        "resource-exhausted",
+                        "evaluation exceeded its limit of {limit} {resource}",
+                        limit = limit,
+                        resource = resource
+                    ))
+                    .with_labels(labels)
+                    .with_notes(vec![String::from(
+                        "The program may not terminate, or may need a higher limit.",
+                    )])]
+            }
+            EvalError::Cancelled(pos) => {
+                let labels = pos
+                    .as_opt_ref()
+                    .map(|span| vec![primary(span).with_message("while evaluating this")])
+                    .unwrap_or_default();
+
+                vec![Diagnostic::error()
+                    .with_message(tr!("eval-cancelled", "evaluation has been cancelled"))
+                    .with_labels(labels)]
+            }
+            EvalError::SandboxViolation { primop, pos_op } => {
+                let labels = pos_op
+                    .as_opt_ref()
+                    .map(|span| vec![primary(span).with_message("called here")])
+                    .unwrap_or_default();
+
+                vec![Diagnostic::error()
+                    .with_message(tr!(
+                        "sandbox-primop",
+                        "`{primop}` isn't allowed in sandbox mode",
+                        primop = primop
+                    ))
+                    .with_labels(labels)
+                    .with_notes(vec![String::from(
+                        "A sandboxed program can't depend on the environment of the host, such as \
+                        environment variables or files.",
+                    )])]
+            }
         }
     }
 }
 
 /// Common functionality for formatting blame errors.
+/// The maximum number of lines of the values diffed by [merge_diff].
+const MERGE_DIFF_MAX_LINES: usize = 200;
+
+/// Render the line-based diff of two values which couldn't be merged, or return `None` if they
+/// can't be meaningfully diffed. Only constants are diffed: other values, such as functions or the
+/// arguments of enum variants, aren't evaluated when the merge fails.
+fn merge_diff(left: &RichTerm, right: &RichTerm) -> Option<String> {
+    use crate::eval::structural_diff::diff_lines;
+
+    let is_diffable = |t: &RichTerm| {
+        matches!(
+            t.as_ref(),
+            Term::Null | Term::Bool(_) | Term::Num(_) | Term::Str(_) | Term::Enum(_)
+        )
+    };
+
+    if !is_diffable(left) || !is_diffable(right) {
+        return None;
+    }
+
+    let left = left.to_string();
+    let right = right.to_string();
+
+    if left.lines().count() > MERGE_DIFF_MAX_LINES || right.lines().count() > MERGE_DIFF_MAX_LINES {
+        return None;
+    }
+
+    Some(diff_lines(&left, &right).join("\n"))
+}
+
 mod blame_error {
     use codespan::{FileId, Files};
     use codespan_reporting::diagnostic::{Diagnostic, Label};
@@ -1518,18 +1862,30 @@ mod blame_error {
             // a positive blame
             assert_eq!(l.polarity, Polarity::Positive);
             match l.field_name {
-                Some(ident) => format!("contract broken by the value of `{ident}`"),
-                None => "contract broken by a value".to_owned(),
+                Some(ident) => tr!(
+                    "blame-value-of",
+                    "contract broken by the value of `{ident}`",
+                    ident = ident
+                ),
+                None => tr!("blame-value", "contract broken by a value"),
             }
         } else if l.polarity == Polarity::Positive {
             match l.field_name {
-                Some(ident) => format!("contract broken by the function `{ident}`"),
-                None => "contract broken by a function".to_owned(),
+                Some(ident) => tr!(
+                    "blame-function-of",
+                    "contract broken by the function `{ident}`",
+                    ident = ident
+                ),
+                None => tr!("blame-function", "contract broken by a function"),
             }
         } else {
             match l.field_name {
-                Some(ident) => format!("contract broken by the caller of `{ident}`"),
-                None => "contract broken by the caller".to_owned(),
+                Some(ident) => tr!(
+                    "blame-caller-of",
+                    "contract broken by the caller of `{ident}`",
+                    ident = ident
+                ),
+                None => tr!("blame-caller", "contract broken by the caller"),
             }
         }
     }
@@ -1843,6 +2199,18 @@ is None but last_arrow_elem is Some"
 
 impl IntoDiagnostics<FileId> for ParseError {
     fn into_diagnostics(
+        self,
+        files: &mut Files<String>,
+        stdlib_ids: Option<&Vec<FileId>>,
+    ) -> Vec<Diagnostic<FileId>> {
+        let code = self.code();
+        with_code(self.uncoded_diagnostics(files, stdlib_ids), code)
+    }
+}
+
+impl ParseError {
+    /// Convert this error to diagnostics, without setting their code.
+    fn uncoded_diagnostics(
         self,
         files: &mut Files<String>,
         _stdlib_ids: Option<&Vec<FileId>>,
@@ -1851,9 +2219,10 @@ impl IntoDiagnostics<FileId> for ParseError {
             ParseError::UnexpectedEOF(file_id, _expected) => {
                 let end = files.source_span(file_id).end();
                 Diagnostic::error()
-                    .with_message(format!(
-                        "unexpected end of file when parsing {}",
-                        files.name(file_id).to_string_lossy()
+                    .with_message(tr!(
+                        "unexpected-eof",
+                        "unexpected end of file when parsing {file}",
+                        file = files.name(file_id).to_string_lossy()
                     ))
                     .with_labels(vec![primary(&RawSpan {
                         start: end,
@@ -1862,25 +2231,25 @@ impl IntoDiagnostics<FileId> for ParseError {
                     })])
             }
             ParseError::UnexpectedToken(span, _expected) => Diagnostic::error()
-                .with_message("unexpected token")
+                .with_message(tr!("unexpected-token", "unexpected token"))
                 .with_labels(vec![primary(&span)]),
             ParseError::ExtraToken(span) => Diagnostic::error()
-                .with_message("superfluous unexpected token")
+                .with_message(tr!("extra-token", "superfluous unexpected token"))
                 .with_labels(vec![primary(&span)]),
             ParseError::UnmatchedCloseBrace(span) => Diagnostic::error()
-                .with_message("unmatched closing brace \'}\'")
+                .with_message(tr!("unmatched-close-brace", "unmatched closing brace '}'"))
                 .with_labels(vec![primary(&span)]),
             ParseError::InvalidEscapeSequence(span) => Diagnostic::error()
-                .with_message("invalid escape sequence")
+                .with_message(tr!("invalid-escape-sequence", "invalid escape sequence"))
                 .with_labels(vec![primary(&span)]),
             ParseError::InvalidAsciiEscapeCode(span) => Diagnostic::error()
-                .with_message("invalid ascii escape code")
+                .with_message(tr!("invalid-ascii-escape-code", "invalid ascii escape code"))
                 .with_labels(vec![primary(&span)]),
             ParseError::StringDelimiterMismatch {
                 opening_delimiter,
                 closing_delimiter,
             } => Diagnostic::error()
-                .with_message("string closing delimiter has too many `%`")
+                .with_message(tr!("string-delimiter-mismatch", "string closing delimiter has too many `%`"))
                 .with_labels(vec![
                     primary(&closing_delimiter).with_message("the closing delimiter"),
                     secondary(&opening_delimiter).with_message("the opening delimiter"),
@@ -1898,13 +2267,14 @@ impl IntoDiagnostics<FileId> for ParseError {
                     .unwrap_or_default();
 
                 Diagnostic::error()
-                    .with_message(format!("{format} parse error: {msg}"))
+                    .with_message(tr!("deserialization-error", "{format} parse error: {msg}", format = format, msg = msg))
                     .with_labels(labels)
             }
             ParseError::UnboundTypeVariables(idents) => Diagnostic::error()
-                .with_message(format!(
-                    "unbound type variable(s): {}",
-                    idents
+                .with_message(tr!(
+                    "unbound-type-variables",
+                    "unbound type variable(s): {idents}",
+                    idents = idents
                         .iter()
                         .map(|x| format!("`{x}`"))
                         .collect::<Vec<_>>()
@@ -1949,12 +2319,12 @@ impl IntoDiagnostics<FileId> for ParseError {
                     );
                 };
                 Diagnostic::error()
-                    .with_message("invalid record literal")
+                    .with_message(tr!("invalid-record-literal", "invalid record literal"))
                     .with_labels(labels)
                     .with_notes(notes)
             }
             ParseError::RecursiveLetPattern(span) => Diagnostic::error()
-                .with_message("recursive destructuring is not supported")
+                .with_message(tr!("recursive-let-pattern", "recursive destructuring is not supported"))
                 .with_labels(vec![primary(&span)])
                 .with_notes(vec![
                     "A destructuring let-binding can't be recursive. Try removing the `rec` \
@@ -1965,8 +2335,10 @@ impl IntoDiagnostics<FileId> for ParseError {
                         .into(),
                 ]),
             ParseError::TypeVariableKindMismatch { ty_var, span } => Diagnostic::error()
-                .with_message(format!(
-                    "the type variable `{ty_var}` is used in conflicting ways"
+                .with_message(tr!(
+                    "type-variable-kind-mismatch",
+                    "the type variable `{ty_var}` is used in conflicting ways",
+                    ty_var = ty_var
                 ))
                 .with_labels(vec![primary(&span)])
                 .with_notes(vec![
@@ -1981,7 +2353,7 @@ impl IntoDiagnostics<FileId> for ParseError {
                 field_span,
                 annot_span,
             } => Diagnostic::error()
-                .with_message("statically typed field without a definition")
+                .with_message(tr!("typed-field-without-definition", "statically typed field without a definition"))
                 .with_labels(vec![
                     primary(&field_span).with_message("this field doesn't have a definition"),
                     secondary(&annot_span).with_message("but it has a type annotation"),
@@ -2003,7 +2375,7 @@ impl IntoDiagnostics<FileId> for ParseError {
                 input: _,
                 path_elem_span,
             } => Diagnostic::error()
-                .with_message("string interpolation is forbidden within a query")
+                .with_message(tr!("interpolation-in-query", "string interpolation is forbidden within a query"))
                 .with_labels(vec![primary(&path_elem_span)])
                 .with_notes(vec![
                     "Field paths don't support string interpolation when querying \
@@ -2012,16 +2384,27 @@ impl IntoDiagnostics<FileId> for ParseError {
                     "Only identifiers and simple string literals are allowed.".into(),
                 ]),
             ParseError::DuplicateIdentInRecordPattern { ident, prev_ident } => Diagnostic::error()
-                .with_message(format!(
-                    "duplicated binding `{}` in record pattern",
-                    ident.label()
+                .with_message(tr!(
+                    "duplicate-ident-in-record-pattern",
+                    "duplicated binding `{ident}` in record pattern",
+                    ident = ident.label()
+                ))
+                .with_labels(vec![
+                    secondary(&prev_ident.pos.unwrap()).with_message("previous binding here"),
+                    primary(&ident.pos.unwrap()).with_message("duplicated binding here"),
+                ]),
+            ParseError::DuplicateIdentInLetRec { ident, prev_ident } => Diagnostic::error()
+                .with_message(tr!(
+                    "duplicate-ident-in-let-rec",
+                    "duplicated binding `{ident}` in recursive let-binding",
+                    ident = ident.label()
                 ))
                 .with_labels(vec![
                     secondary(&prev_ident.pos.unwrap()).with_message("previous binding here"),
                     primary(&ident.pos.unwrap()).with_message("duplicated binding here"),
                 ]),
             ParseError::DisabledFeature { feature, span } => Diagnostic::error()
-                .with_message("interpreter compiled without required features")
+                .with_message(tr!("disabled-feature", "interpreter compiled without required features"))
                 .with_labels(vec![primary(&span).with_message(format!(
                     "this syntax is only supported with the `{}` feature enabled",
                     feature
@@ -2031,7 +2414,7 @@ impl IntoDiagnostics<FileId> for ParseError {
                     feature
                 )]),
             ParseError::InvalidContract(span) => Diagnostic::error()
-                .with_message("invalid contract expression")
+                .with_message(tr!("invalid-contract", "invalid contract expression"))
                 .with_labels(vec![primary(&span).with_message("this can't be used as a contract")])
                 .with_notes(vec![
                     "This expression is used as a contract as part of an annotation or a type expression."
@@ -2049,6 +2432,18 @@ impl IntoDiagnostics<FileId> for TypecheckError {
         self,
         files: &mut Files<String>,
         stdlib_ids: Option<&Vec<FileId>>,
+    ) -> Vec<Diagnostic<FileId>> {
+        let code = self.code();
+        with_code(self.uncoded_diagnostics(files, stdlib_ids), code)
+    }
+}
+
+impl TypecheckError {
+    /// Convert this error to diagnostics, without setting their code.
+    fn uncoded_diagnostics(
+        self,
+        files: &mut Files<String>,
+        stdlib_ids: Option<&Vec<FileId>>,
     ) -> Vec<Diagnostic<FileId>> {
         fn mk_expr_label(span_opt: &TermPos) -> Vec<Label<FileId>> {
             span_opt
@@ -2069,7 +2464,7 @@ impl IntoDiagnostics<FileId> for TypecheckError {
             TypecheckError::UnboundIdentifier { id, pos } =>
             // Use the same diagnostic as `EvalError::UnboundIdentifier` for consistency.
             {
-                EvalError::UnboundIdentifier(id, pos).into_diagnostics(files, stdlib_ids)
+                EvalError::UnboundIdentifier(id, pos).uncoded_diagnostics(files, stdlib_ids)
             }
             TypecheckError::MissingRow {
                 id,
@@ -2077,7 +2472,11 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 inferred,
                 pos,
             } => vec![Diagnostic::error()
-                .with_message(format!("type error: missing row `{id}`"))
+                .with_message(tr!(
+                    "missing-row",
+                    "type error: missing row `{id}`",
+                    id = id
+                ))
                 .with_labels(mk_expr_label(&pos))
                 .with_notes(vec![
                     format!(
@@ -2094,7 +2493,10 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 inferred,
                 pos,
             } => vec![Diagnostic::error()
-                .with_message(String::from("type error: missing dynamic tail `; Dyn`"))
+                .with_message(tr!(
+                    "missing-dyn-tail",
+                    "type error: missing dynamic tail `; Dyn`"
+                ))
                 .with_labels(mk_expr_label(&pos))
                 .with_notes(vec![
                     format!(
@@ -2112,7 +2514,7 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 inferred,
                 pos,
             } => vec![Diagnostic::error()
-                .with_message(format!("type error: extra row `{id}`"))
+                .with_message(tr!("extra-row", "type error: extra row `{id}`", id = id))
                 .with_labels(mk_expr_label(&pos))
                 .with_notes(vec![
                     format!(
@@ -2129,7 +2531,10 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 inferred,
                 pos,
             } => vec![Diagnostic::error()
-                .with_message(String::from("type error: extra dynamic tail `; Dyn`"))
+                .with_message(tr!(
+                    "extra-dyn-tail",
+                    "type error: extra dynamic tail `; Dyn`"
+                ))
                 .with_labels(mk_expr_label(&pos))
                 .with_notes(vec![
                     format!(
@@ -2142,7 +2547,11 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                     ),
                 ])],
             TypecheckError::UnboundTypeVariable(ident) => vec![Diagnostic::error()
-                .with_message(format!("unbound type variable `{ident}`"))
+                .with_message(tr!(
+                    "unbound-type-variable",
+                    "unbound type variable `{ident}`",
+                    ident = ident
+                ))
                 .with_labels(vec![primary_alt(
                     ident.pos.into_opt(),
                     ident.to_string(),
@@ -2171,7 +2580,7 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 };
 
                 vec![Diagnostic::error()
-                    .with_message("incompatible types")
+                    .with_message(tr!("type-mismatch", "incompatible types"))
                     .with_labels(mk_expr_label(&pos))
                     .with_notes(vec![
                         format!("{}{}", mk_expected_msg(&expected), addendum(&expected),),
@@ -2238,7 +2647,10 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 };
 
                 let mut diags = vec![Diagnostic::error()
-                    .with_message("incompatible record rows declaration")
+                    .with_message(tr!(
+                        "record-row-mismatch",
+                        "incompatible record rows declaration"
+                    ))
                     .with_labels(mk_expr_label(&pos))
                     .with_notes(vec![
                         note1,
@@ -2249,12 +2661,16 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 // We generate a diagnostic for the underlying error, but append a prefix to the
                 // error message to make it clear that this is not a separate error but a more
                 // precise description of why the unification of a row failed.
-                diags.extend((*err).into_diagnostics(files, stdlib_ids).into_iter().map(
-                    |mut diag| {
-                        diag.message = format!("while typing field `{}`: {}", field, diag.message);
-                        diag
-                    },
-                ));
+                diags.extend(
+                    (*err)
+                        .uncoded_diagnostics(files, stdlib_ids)
+                        .into_iter()
+                        .map(|mut diag| {
+                            diag.message =
+                                format!("while typing field `{}`: {}", field, diag.message);
+                            diag
+                        }),
+                );
                 diags
             }
             TypecheckError::EnumRowMismatch {
@@ -2293,7 +2709,10 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 };
 
                 let mut diags = vec![Diagnostic::error()
-                    .with_message("incompatible enum rows declaration")
+                    .with_message(tr!(
+                        "enum-row-mismatch",
+                        "incompatible enum rows declaration"
+                    ))
                     .with_labels(mk_expr_label(&pos))
                     .with_notes(vec![
                         note1,
@@ -2305,13 +2724,16 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 // the error message to make it clear that this is not a separate error but a more
                 // precise description of why the unification of a row failed.
                 if let Some(err) = cause {
-                    diags.extend((*err).into_diagnostics(files, stdlib_ids).into_iter().map(
-                        |mut diag| {
-                            diag.message =
-                                format!("while typing enum row `{id}`: {}", diag.message);
-                            diag
-                        },
-                    ));
+                    diags.extend(
+                        (*err)
+                            .uncoded_diagnostics(files, stdlib_ids)
+                            .into_iter()
+                            .map(|mut diag| {
+                                diag.message =
+                                    format!("while typing enum row `{id}`: {}", diag.message);
+                                diag
+                            }),
+                    );
                 }
 
                 diags
@@ -2326,7 +2748,10 @@ impl IntoDiagnostics<FileId> for TypecheckError {
 
                 diags.push(
                     Diagnostic::error()
-                        .with_message("multiple record row declarations")
+                        .with_message(tr!(
+                            "record-row-conflict",
+                            "multiple record row declarations"
+                        ))
                         .with_labels(mk_expr_label(&pos))
                         .with_notes(vec![
                             format!("Found an expression with the row `{row}`"),
@@ -2362,7 +2787,7 @@ impl IntoDiagnostics<FileId> for TypecheckError {
 
                 diags.push(
                     Diagnostic::error()
-                        .with_message("multiple enum row declarations")
+                        .with_message(tr!("enum-row-conflict", "multiple enum row declarations"))
                         .with_labels(mk_expr_label(&pos))
                         .with_notes(vec![
                             format!("Found an expression with the row `{row}`"),
@@ -2410,7 +2835,7 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 labels.extend(mk_expr_label(&pos));
 
                 let mut diags = vec![Diagnostic::error()
-                    .with_message("function types mismatch")
+                    .with_message(tr!("arrow-type-mismatch", "function types mismatch"))
                     .with_labels(labels)
                     .with_notes(vec![
                         mk_expected_msg(&expected),
@@ -2426,7 +2851,7 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                     // information, so we just ignore it.
                     TypecheckError::TypeMismatch { .. } => (),
                     err => {
-                        diags.extend(err.into_diagnostics(files, stdlib_ids).into_iter().map(
+                        diags.extend(err.uncoded_diagnostics(files, stdlib_ids).into_iter().map(
                             |mut diag| {
                                 diag.message =
                                     format!("while matching function types: {}", diag.message);
@@ -2444,7 +2869,10 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 pos,
             } => {
                 vec![Diagnostic::error()
-                    .with_message("internal error: can't compare unconverted flat types")
+                    .with_message(tr!(
+                        "incomparable-flat-types",
+                        "internal error: can't compare unconverted flat types"
+                    ))
                     .with_labels(mk_expr_label(&pos))
                     .with_notes(vec![
                         format!("{} (contract)", mk_expected_msg(&expected.to_string()),),
@@ -2464,9 +2892,13 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                     VarKindDiscriminant::RecordRows => "record tail",
                 };
                 vec![Diagnostic::error()
-                    .with_message(format!(
+                    .with_message(tr!(
+                        "forbidden-field-in-polymorphic-tail",
                         "values of type `{violating_type}` are not guaranteed to be compatible \
-                        with polymorphic {tail_kind} `{tail}`"
+                        with polymorphic {tail_kind} `{tail}`",
+                        violating_type = violating_type,
+                        tail_kind = tail_kind,
+                        tail = tail
                     ))
                     .with_labels(mk_expr_label(&pos))
                     .with_notes(vec![
@@ -2476,9 +2908,10 @@ impl IntoDiagnostics<FileId> for TypecheckError {
             }
             TypecheckError::FlatTypeInTermPosition { flat, pos } => {
                 vec![Diagnostic::error()
-                    .with_message(
+                    .with_message(tr!(
+                        "flat-type-in-term-position",
                         "types containing user-defined contracts cannot be converted into contracts"
-                    )
+                    ))
                     .with_labels(
                         pos.into_opt()
                             .map(|span| {
@@ -2502,7 +2935,10 @@ impl IntoDiagnostics<FileId> for TypecheckError {
                 }
 
                 vec![Diagnostic::error()
-                    .with_message("invalid polymorphic generalization".to_string())
+                    .with_message(tr!(
+                        "var-level-mismatch",
+                        "invalid polymorphic generalization"
+                    ))
                     .with_labels(labels)
                     .with_notes(vec![
                         "While the type of this expression is still undetermined, it appears \
@@ -2525,6 +2961,18 @@ impl IntoDiagnostics<FileId> for ImportError {
         self,
         files: &mut Files<String>,
         stdlib_ids: Option<&Vec<FileId>>,
+    ) -> Vec<Diagnostic<FileId>> {
+        let code = self.code();
+        with_code(self.uncoded_diagnostics(files, stdlib_ids), code)
+    }
+}
+
+impl ImportError {
+    /// Convert this error to diagnostics, without setting their code.
+    fn uncoded_diagnostics(
+        self,
+        files: &mut Files<String>,
+        stdlib_ids: Option<&Vec<FileId>>,
     ) -> Vec<Diagnostic<FileId>> {
         match self {
             ImportError::IOError(path, error, span_opt) => {
@@ -2534,7 +2982,12 @@ impl IntoDiagnostics<FileId> for ImportError {
                     .unwrap_or_default();
 
                 vec![Diagnostic::error()
-                    .with_message(format!("import of {path} failed: {error}"))
+                    .with_message(tr!(
+                        "import-failed",
+                        "import of {path} failed: {error}",
+                        path = path,
+                        error = error
+                    ))
                     .with_labels(labels)]
             }
             ImportError::ParseErrors(error, span_opt) => {
@@ -2552,6 +3005,23 @@ impl IntoDiagnostics<FileId> for ImportError {
 
                 diagnostic
             }
+            ImportError::SandboxViolation(path, span_opt) => {
+                let labels = span_opt
+                    .as_opt_ref()
+                    .map(|span| vec![primary(span).with_message("imported here")])
+                    .unwrap_or_default();
+
+                vec![Diagnostic::error()
+                    .with_message(tr!(
+                        "sandbox-import",
+                        "import of {path} isn't allowed in sandbox mode",
+                        path = path
+                    ))
+                    .with_labels(labels)
+                    .with_notes(vec![String::from(
+                        "A sandboxed program can only import files located under the sandbox roots.",
+                    )])]
+            }
         }
     }
 }
@@ -2601,6 +3071,23 @@ impl IntoDiagnostics<FileId> for ExportError {
                     .with_labels(vec![primary_term(&rt, files)])
                     .with_notes(notes)]
             }
+            ExportErrorData::NotFlattenable(rt) => {
+                notes.push(
+                    "The env format expects a record whose fields are strings, numbers, \
+                    booleans, enum tags, or records of such values, which are flattened."
+                        .into(),
+                );
+
+                vec![Diagnostic::error()
+                    .with_message(format!(
+                        "can't flatten a value of type {} into environment variables",
+                        rt.as_ref()
+                            .type_of()
+                            .unwrap_or_else(|| String::from("<unevaluated>"))
+                    ))
+                    .with_labels(vec![primary_term(&rt, files)])
+                    .with_notes(notes)]
+            }
             ExportErrorData::NoDocumentation(rt) => {
                 notes.push("documentation can only be collected from a record.".to_owned());
 
@@ -2624,6 +3111,18 @@ impl IntoDiagnostics<FileId> for ExportError {
                     .with_labels(vec![primary_term(&term, files)])
                     .with_notes(notes)]
             }
+            ExportErrorData::InexactNumber { term, value } => {
+                notes.push(
+                    "Only integers fitting in 64 bits and numbers which are exactly written as \
+                    64 bits floats can be serialized without loss of precision"
+                        .into(),
+                );
+
+                vec![Diagnostic::error()
+                    .with_message(format!("The number {value} can't be serialized exactly."))
+                    .with_labels(vec![primary_term(&term, files)])
+                    .with_notes(notes)]
+            }
             ExportErrorData::Other(msg) => {
                 notes.push(msg);
 
@@ -109,3 +109,108 @@ pub fn report_with<E: IntoDiagnostics<FileId>>(
         Err(err) => panic!("error::report_with(): could not print an error on stderr: {err}"),
     };
 }
+
+/// The location of the start of a span, as a file name together with a one-based line and column
+/// number.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceLocation {
+    fn from_span(files: &Files<String>, span: &RawSpan) -> Option<Self> {
+        let location = files.location(span.src_id, span.start).ok()?;
+
+        Some(SourceLocation {
+            file: files.name(span.src_id).to_string_lossy().into_owned(),
+            line: location.line.to_usize() + 1,
+            column: location.column.to_usize() + 1,
+        })
+    }
+}
+
+/// A machine-readable report of a contract blame error.
+///
+/// Contrary to diagnostics, whose serialization reflects the way they are rendered, a blame report
+/// exposes the data of the blame error itself, so that tools such as CI systems can tell exactly
+/// which value broke which contract.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct BlameReport {
+    /// The error code, which is always the one of [EvalError::BlameError].
+    pub code: String,
+    /// The main error message, such as ``contract broken by the value of `port` ``.
+    pub message: String,
+    /// The path to the offending value: the field the contract is attached to, if any, followed by
+    /// the fields leading to the offending value inside the type of the contract.
+    pub field_path: Vec<String>,
+    /// The contract which has been broken, pretty-printed.
+    pub contract: String,
+    /// The location where the contract has been applied.
+    pub contract_location: Option<SourceLocation>,
+    /// An excerpt of the offending value, if it has been evaluated.
+    pub value: Option<String>,
+    /// The location of the offending value.
+    pub value_location: Option<SourceLocation>,
+    /// `positive` if the value is to blame, or `negative` if its context is to blame, such as the
+    /// caller of a function providing an argument of the wrong type.
+    pub polarity: label::Polarity,
+    /// The custom diagnostics set by the contracts, in the order in which they have been stacked
+    /// up. Empty diagnostics are skipped.
+    pub diagnostics: Vec<label::ContractDiagnostic>,
+}
+
+impl BlameReport {
+    /// The maximum width of the excerpt of the offending value.
+    const VALUE_EXCERPT_WIDTH: usize = 80;
+
+    /// Build the report of a blame error, or return `None` if `error` isn't a blame error.
+    pub fn from_error(error: &Error, files: &Files<String>) -> Option<Self> {
+        let Error::EvalError(
+            eval_error @ EvalError::BlameError {
+                evaluated_arg,
+                label,
+                ..
+            },
+        ) = error
+        else {
+            return None;
+        };
+
+        let field_path = label
+            .field_name
+            .iter()
+            .map(LocIdent::to_string)
+            .chain(label.path.iter().filter_map(|elem| match elem {
+                ty_path::Elem::Field(id) => Some(id.to_string()),
+                _ => None,
+            }))
+            .collect();
+
+        let value_span = label.arg_pos.into_opt().or_else(|| {
+            evaluated_arg
+                .as_ref()
+                .and_then(|value| value.pos.into_opt())
+        });
+
+        Some(BlameReport {
+            code: eval_error.code().to_string(),
+            message: blame_error::title(label),
+            field_path,
+            contract: label.typ.to_string(),
+            contract_location: SourceLocation::from_span(files, &label.span),
+            value: evaluated_arg
+                .as_ref()
+                .map(|value| value.pretty_print_cap(Self::VALUE_EXCERPT_WIDTH)),
+            value_location: value_span.and_then(|span| SourceLocation::from_span(files, &span)),
+            polarity: label.polarity,
+            diagnostics: label
+                .diagnostics
+                .iter()
+                .filter(|diag| !diag.is_empty())
+                .cloned()
+                .collect(),
+        })
+    }
+}
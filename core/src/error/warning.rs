@@ -0,0 +1,259 @@
+//! Warnings, which are non-fatal diagnostics.
+//!
+//! Contrary to errors, warnings don't interrupt the execution: they are collected on the side by
+//! the different phases (see [crate::cache::Cache::warn] and
+//! [crate::eval::VirtualMachine::warn]) and reported at the end. Each warning has an
+//! [ErrorCode], and the level of each code can be tuned through a [WarningConfig]: a warning can
+//! be silenced (allowed), reported (warned) or turned into an error (denied).
+use std::{collections::HashMap, str::FromStr};
+
+use super::*;
+use crate::{identifier::LocIdent, term::record::Deprecation};
+
+/// A warning, emitted for code which is valid but most likely wrong.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// A branch of a match expression can never be selected, because a previous branch matches
+    /// any value.
+    UnreachableMatchBranch {
+        /// The position of the pattern of the unreachable branch.
+        pos: TermPos,
+        /// The position of the pattern matching any value.
+        catch_all_pos: TermPos,
+    },
+    /// A deprecated field has been accessed.
+    DeprecatedField {
+        /// The accessed field.
+        field: LocIdent,
+        /// The deprecation of the field.
+        deprecation: Deprecation,
+        /// The position of the access.
+        pos: TermPos,
+    },
+    /// A branch of a match expression can never be selected, because a previous branch without
+    /// guard matches all the values it matches.
+    RedundantMatchBranch {
+        /// The position of the pattern of the redundant branch.
+        pos: TermPos,
+        /// The position of the pattern of the previous branch.
+        shadowed_by: TermPos,
+    },
+    /// Some enum tags handled by a match expression without catch-all branch are only matched by
+    /// branches with a guard, so the match fails on these tags when the guards are false.
+    UncoveredEnumTags {
+        /// The tags only matched by guarded branches.
+        tags: Vec<LocIdent>,
+        /// The position of the match expression.
+        pos: TermPos,
+    },
+}
+
+impl Warning {
+    /// Return the code of this warning.
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode(match self {
+            Warning::UnreachableMatchBranch { .. } => 401,
+            Warning::DeprecatedField { .. } => 402,
+            Warning::RedundantMatchBranch { .. } => 403,
+            Warning::UncoveredEnumTags { .. } => 404,
+        })
+    }
+
+    /// Return the position the warning is about.
+    pub fn pos(&self) -> TermPos {
+        match self {
+            Warning::UnreachableMatchBranch { pos, .. }
+            | Warning::DeprecatedField { pos, .. }
+            | Warning::RedundantMatchBranch { pos, .. }
+            | Warning::UncoveredEnumTags { pos, .. } => *pos,
+        }
+    }
+}
+
+impl IntoDiagnostics<FileId> for Warning {
+    fn into_diagnostics(
+        self,
+        _files: &mut Files<String>,
+        _stdlib_ids: Option<&Vec<FileId>>,
+    ) -> Vec<Diagnostic<FileId>> {
+        let code = self.code();
+
+        let diagnostic = match self {
+            Warning::UnreachableMatchBranch { pos, catch_all_pos } => {
+                let mut labels = Vec::new();
+
+                if let Some(span) = pos.into_opt() {
+                    labels.push(primary(&span).with_message("this branch is never selected"));
+                }
+
+                if let Some(span) = catch_all_pos.into_opt() {
+                    labels.push(
+                        secondary(&span).with_message("because this pattern matches any value"),
+                    );
+                }
+
+                Diagnostic::warning()
+                    .with_message(tr!("unreachable-match-branch", "unreachable match branch"))
+                    .with_labels(labels)
+            }
+            Warning::DeprecatedField {
+                field,
+                deprecation,
+                pos,
+            } => {
+                let labels = pos
+                    .into_opt()
+                    .map(|span| vec![primary(&span).with_message("accessed here")])
+                    .unwrap_or_default();
+
+                let mut notes = Vec::new();
+
+                if let Some(message) = deprecation.message {
+                    notes.push(message);
+                }
+
+                if let Some(replacement) = deprecation.replacement {
+                    notes.push(format!("Use `{replacement}` instead."));
+                }
+
+                Diagnostic::warning()
+                    .with_message(tr!(
+                        "deprecated-field",
+                        "use of deprecated field `{field}`",
+                        field = field
+                    ))
+                    .with_labels(labels)
+                    .with_notes(notes)
+            }
+            Warning::RedundantMatchBranch { pos, shadowed_by } => {
+                let mut labels = Vec::new();
+
+                if let Some(span) = pos.into_opt() {
+                    labels.push(primary(&span).with_message("this branch is never selected"));
+                }
+
+                if let Some(span) = shadowed_by.into_opt() {
+                    labels.push(
+                        secondary(&span)
+                            .with_message("because this pattern matches all the same values"),
+                    );
+                }
+
+                Diagnostic::warning()
+                    .with_message(tr!("redundant-match-branch", "redundant match branch"))
+                    .with_labels(labels)
+            }
+            Warning::UncoveredEnumTags { tags, pos } => {
+                let labels = pos
+                    .into_opt()
+                    .map(|span| vec![primary(&span).with_message("this match may fail")])
+                    .unwrap_or_default();
+
+                let tags = tags
+                    .iter()
+                    .map(|tag| format!("'{tag}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                Diagnostic::warning()
+                    .with_message(tr!(
+                        "uncovered-enum-tags",
+                        "match may not cover {tags}",
+                        tags = tags
+                    ))
+                    .with_labels(labels)
+                    .with_notes(vec![String::from(
+                        "All the branches matching these tags have a guard. Add a branch without \
+                        guard or a catch-all branch `_ => ...` to handle the case where the guards \
+                        are false.",
+                    )])
+            }
+        };
+
+        with_code(vec![diagnostic], code)
+    }
+}
+
+/// A warning which has been turned into an error by a [WarningConfig].
+pub struct DeniedWarning(pub Warning);
+
+impl IntoDiagnostics<FileId> for DeniedWarning {
+    fn into_diagnostics(
+        self,
+        files: &mut Files<String>,
+        stdlib_ids: Option<&Vec<FileId>>,
+    ) -> Vec<Diagnostic<FileId>> {
+        let mut diagnostics = self.0.into_diagnostics(files, stdlib_ids);
+
+        if let Some(diagnostic) = diagnostics.first_mut() {
+            diagnostic.severity = Severity::Error;
+            diagnostic
+                .notes
+                .push("This warning has been turned into an error.".to_owned());
+        }
+
+        diagnostics
+    }
+}
+
+/// The level of a warning.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WarningLevel {
+    /// The warning is silenced.
+    Allow,
+    /// The warning is reported.
+    #[default]
+    Warn,
+    /// The warning is reported as an error, and makes the execution fail.
+    Deny,
+}
+
+/// A set of warnings whose level can be set at once.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WarningSelector {
+    /// All the warnings, written `warnings`.
+    All,
+    /// The warnings with a specific code.
+    Code(ErrorCode),
+}
+
+impl FromStr for WarningSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "warnings" {
+            Ok(WarningSelector::All)
+        } else {
+            s.parse()
+                .map(WarningSelector::Code)
+                .map_err(|err| format!("{err}, or `warnings` to select all warnings"))
+        }
+    }
+}
+
+/// The levels of warnings. Warnings are reported, unless specified otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WarningConfig {
+    /// The level of the warnings which don't have a specific level.
+    default: WarningLevel,
+    /// The levels set for specific codes.
+    levels: HashMap<ErrorCode, WarningLevel>,
+}
+
+impl WarningConfig {
+    /// Set the level of the warnings selected by `selector`. Setting the level of all warnings
+    /// doesn't override the levels previously set for specific codes.
+    pub fn set(&mut self, selector: WarningSelector, level: WarningLevel) {
+        match selector {
+            WarningSelector::All => self.default = level,
+            WarningSelector::Code(code) => {
+                self.levels.insert(code, level);
+            }
+        }
+    }
+
+    /// Return the level of the warnings with the given code.
+    pub fn level(&self, code: ErrorCode) -> WarningLevel {
+        self.levels.get(&code).copied().unwrap_or(self.default)
+    }
+}
@@ -196,6 +196,10 @@ impl Cache for IncCache {
         }
     }
 
+    fn allocated(&self) -> usize {
+        self.store.len()
+    }
+
     fn get(&self, idx: CacheIndex) -> Closure {
         let node = self.store.get(idx).unwrap();
 
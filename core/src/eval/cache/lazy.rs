@@ -633,8 +633,22 @@ impl ThunkUpdateFrame {
 }
 
 /// Placeholder [Cache] for the call-by-need evaluation strategy.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct CBNCache {}
+#[derive(Clone, Debug)]
+pub struct CBNCache {
+    /// The number of thunks allocated so far. Thunks are reference counted and freed on their
+    /// own, so this is an upper bound of the number of live thunks.
+    allocated: usize,
+}
+
+// The cache doesn't hold any data, so all caches are equal. The allocation counter is only a
+// statistic and doesn't take part in the comparison.
+impl PartialEq for CBNCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for CBNCache {}
 
 impl Cache for CBNCache {
     type UpdateIndex = ThunkUpdateFrame;
@@ -662,6 +676,8 @@ impl Cache for CBNCache {
     }
 
     fn add(&mut self, clos: Closure, bty: BindingType) -> CacheIndex {
+        self.allocated += 1;
+
         match bty {
             BindingType::Normal => Thunk::new(clos),
             BindingType::Revertible(deps) => Thunk::new_rev(clos, deps),
@@ -681,7 +697,11 @@ impl Cache for CBNCache {
     }
 
     fn new() -> Self {
-        CBNCache {}
+        CBNCache { allocated: 0 }
+    }
+
+    fn allocated(&self) -> usize {
+        self.allocated
     }
 
     fn reset_index_state(&mut self, idx: &mut Self::UpdateIndex) {
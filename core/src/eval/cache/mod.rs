@@ -56,6 +56,9 @@ pub trait Cache: Clone {
     /// Initializes a new [Cache].
     fn new() -> Self;
 
+    /// Returns the number of elements added to the [Cache] since its creation.
+    fn allocated(&self) -> usize;
+
     /// Resets the state of the element at index `idx` to `Suspended`
     fn reset_index_state(&mut self, idx: &mut Self::UpdateIndex);
 
@@ -0,0 +1,32 @@
+//! Cooperative cancellation of evaluation.
+//!
+//! Evaluation can't be interrupted from the outside without killing the whole process. Instead,
+//! the host gives a [CancellationToken] to the virtual machine (see
+//! [super::VirtualMachine::set_cancellation_token]), keeps a clone of it, and cancels it from
+//! another thread, for example when the file being evaluated has been edited in the meantime. The
+//! virtual machine checks the token periodically, and stops with
+//! [crate::error::EvalError::Cancelled] once it has been cancelled.
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A shared flag telling an evaluation to stop. Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the evaluations using this token to stop. Cancelling is definitive: a cancelled token
+    /// can't be reused.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
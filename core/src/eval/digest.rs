@@ -0,0 +1,143 @@
+//! Hashing support for the `std.hash` family of primitive operations.
+//!
+//! Digests are computed on the UTF-8 bytes of Nickel strings, and rendered back as strings, either
+//! in lowercase hexadecimal or in standard base64 with padding.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use md5::digest::Digest;
+
+/// A hashing algorithm, as selected by an enum tag on the Nickel side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl Algorithm {
+    /// The enum type of the algorithms, as displayed in error messages.
+    pub const ENUM_TYPE: &'static str = "[| 'Md5, 'Sha1, 'Sha256, 'Sha512, 'Blake3 |]";
+
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "Md5" => Some(Algorithm::Md5),
+            "Sha1" => Some(Algorithm::Sha1),
+            "Sha256" => Some(Algorithm::Sha256),
+            "Sha512" => Some(Algorithm::Sha512),
+            "Blake3" => Some(Algorithm::Blake3),
+            _ => None,
+        }
+    }
+
+    /// The size in bytes of the blocks processed by the hash function, which is the size of the
+    /// padded key in HMAC.
+    fn block_size(self) -> usize {
+        match self {
+            Algorithm::Sha512 => 128,
+            Algorithm::Md5 | Algorithm::Sha1 | Algorithm::Sha256 | Algorithm::Blake3 => 64,
+        }
+    }
+
+    /// Hash the concatenation of `chunks`.
+    fn hash_chunks(self, chunks: &[&[u8]]) -> Vec<u8> {
+        fn with<D: Digest>(chunks: &[&[u8]]) -> Vec<u8> {
+            let mut hasher = D::new();
+
+            for chunk in chunks {
+                hasher.update(chunk);
+            }
+
+            hasher.finalize().to_vec()
+        }
+
+        match self {
+            Algorithm::Md5 => with::<md5::Md5>(chunks),
+            Algorithm::Sha1 => with::<sha1::Sha1>(chunks),
+            Algorithm::Sha256 => with::<sha2::Sha256>(chunks),
+            Algorithm::Sha512 => with::<sha2::Sha512>(chunks),
+            Algorithm::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+
+                for chunk in chunks {
+                    hasher.update(chunk);
+                }
+
+                hasher.finalize().as_bytes().to_vec()
+            }
+        }
+    }
+
+    /// Hash `data`.
+    pub fn hash(self, data: &[u8]) -> Vec<u8> {
+        self.hash_chunks(&[data])
+    }
+
+    /// Compute the HMAC (RFC 2104) of `data` with the secret `key`.
+    pub fn hmac(self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        let block_size = self.block_size();
+
+        // Keys longer than a block are hashed first. The key is then padded with zeros.
+        let mut padded_key = if key.len() > block_size {
+            self.hash(key)
+        } else {
+            key.to_vec()
+        };
+        padded_key.resize(block_size, 0);
+
+        let inner_key: Vec<u8> = padded_key.iter().map(|byte| byte ^ 0x36).collect();
+        let outer_key: Vec<u8> = padded_key.iter().map(|byte| byte ^ 0x5c).collect();
+
+        let inner = self.hash_chunks(&[&inner_key, data]);
+        self.hash_chunks(&[&outer_key, &inner])
+    }
+}
+
+/// The encoding of a digest as a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Hex,
+    Base64,
+}
+
+impl Encoding {
+    /// The enum type of the encodings, as displayed in error messages.
+    pub const ENUM_TYPE: &'static str = "[| 'Hex, 'Base64 |]";
+
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "Hex" => Some(Encoding::Hex),
+            "Base64" => Some(Encoding::Base64),
+            _ => None,
+        }
+    }
+
+    pub fn encode(self, digest: &[u8]) -> String {
+        match self {
+            Encoding::Hex => digest.iter().map(|byte| format!("{byte:02x}")).collect(),
+            Encoding::Base64 => STANDARD.encode(digest),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_test_vectors() {
+        // Test cases 1 and 6 of RFC 4231, the latter using a key longer than a block.
+        assert_eq!(
+            Encoding::Hex.encode(&Algorithm::Sha256.hmac(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            Encoding::Hex.encode(&Algorithm::Sha512.hmac(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352\
+            6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"
+        );
+    }
+}
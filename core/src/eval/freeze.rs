@@ -0,0 +1,55 @@
+//! The contracts recorded by `std.contract.freeze`.
+//!
+//! Freezing a record or an array records the contracts known to hold for it, so that applying one
+//! of them again is a no-op. Frozen values are identified by their term, through a weak reference:
+//! checking whether a value is frozen is a lookup, whatever the size of the value. Any operation
+//! building a new value from a frozen one, such as merging, inserting or removing a field, or
+//! slicing an array, allocates a new term, which isn't frozen. The new value is thus checked
+//! again, even if the operation doesn't actually break the contract.
+use std::collections::HashMap;
+
+use crate::term::{RichTerm, SharedTerm, WeakTerm};
+
+/// The minimum number of entries before dropped terms are removed from [FrozenTerms].
+const MIN_PRUNE_THRESHOLD: usize = 64;
+
+/// The contracts known to hold for frozen terms.
+#[derive(Clone, Debug)]
+pub struct FrozenTerms {
+    /// The frozen terms, indexed by their address, together with their validated contracts.
+    entries: HashMap<usize, (WeakTerm, Vec<RichTerm>)>,
+    /// The number of entries above which dropped terms are removed.
+    prune_threshold: usize,
+}
+
+impl Default for FrozenTerms {
+    fn default() -> Self {
+        FrozenTerms {
+            entries: HashMap::new(),
+            prune_threshold: MIN_PRUNE_THRESHOLD,
+        }
+    }
+}
+
+impl FrozenTerms {
+    /// Return the contracts known to hold for `term`, if any.
+    pub fn get(&self, term: &SharedTerm) -> &[RichTerm] {
+        self.entries
+            .get(&SharedTerm::addr(term))
+            .map(|(_, contracts)| contracts.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Record `contracts` as the contracts known to hold for `term`, replacing the previous ones.
+    pub fn insert(&mut self, term: &SharedTerm, contracts: Vec<RichTerm>) {
+        let weak = SharedTerm::downgrade(term);
+        self.entries.insert(weak.addr(), (weak, contracts));
+
+        // Dropped terms are removed once in a while. Doubling the threshold each time keeps the
+        // cost of pruning constant per insertion on average.
+        if self.entries.len() > self.prune_threshold {
+            self.entries.retain(|_, (weak, _)| !weak.is_dropped());
+            self.prune_threshold = MIN_PRUNE_THRESHOLD.max(2 * self.entries.len());
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! File system support for the `std.fs` primitive operations.
+//!
+//! Reading files makes evaluation depend on the host, so it's disabled by default: the host must
+//! explicitly allow the program to read under a list of path prefixes, see
+//! [super::VirtualMachine::allow_read]. Relative paths are resolved against the current working
+//! directory. The functions of this module return an error message on failure, which is then
+//! wrapped in an [crate::error::EvalError] by the caller.
+use std::path::{Path, PathBuf};
+
+/// Resolve `path` and check that it's under one of the `allowed` prefixes. Both are
+/// canonicalized beforehand, so that symbolic links or `..` components can't be used to escape
+/// the allowed prefixes.
+///
+/// A path which can't be canonicalized, for example because it doesn't exist, fails with the same
+/// error as a path outside of the allowed prefixes. Otherwise, the error would tell whether a file
+/// exists anywhere on the host.
+fn check_allowed(allowed: &[PathBuf], path: &str) -> Result<PathBuf, String> {
+    let not_allowed = || format!("reading `{path}` isn't allowed");
+
+    if allowed.is_empty() {
+        return Err(not_allowed());
+    }
+
+    let canonical = Path::new(path).canonicalize().map_err(|_| not_allowed())?;
+
+    if allowed
+        .iter()
+        .filter_map(|prefix| prefix.canonicalize().ok())
+        .any(|prefix| canonical.starts_with(prefix))
+    {
+        Ok(canonical)
+    } else {
+        Err(not_allowed())
+    }
+}
+
+/// Read the content of a text file.
+pub fn read_text(allowed: &[PathBuf], path: &str) -> Result<String, String> {
+    let canonical = check_allowed(allowed, path)?;
+
+    std::fs::read_to_string(canonical).map_err(|err| format!("couldn't read `{path}` ({err})"))
+}
+
+/// List the names of the entries of a directory, sorted alphabetically.
+pub fn read_dir(allowed: &[PathBuf], path: &str) -> Result<Vec<String>, String> {
+    let canonical = check_allowed(allowed, path)?;
+    let io_err = |err: std::io::Error| format!("couldn't read directory `{path}` ({err})");
+
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(canonical).map_err(io_err)? {
+        let name = entry.map_err(io_err)?.file_name();
+        let name = name
+            .into_string()
+            .map_err(|name| format!("`{name:?}` in `{path}` isn't a valid UTF-8 file name"))?;
+
+        names.push(name);
+    }
+
+    // The order of directory entries is platform-dependent.
+    names.sort();
+    Ok(names)
+}
@@ -32,7 +32,7 @@ use crate::error::{EvalError, IllegalPolymorphicTailAction};
 use crate::label::{Label, MergeLabel};
 use crate::position::TermPos;
 use crate::term::{
-    record::{self, Field, FieldDeps, FieldMetadata, RecordAttrs, RecordData},
+    record::{self, Deprecation, Field, FieldDeps, FieldMetadata, RecordAttrs, RecordData},
     BinaryOp, EnumVariantAttrs, IndexMap, RichTerm, Term, TypeAnnotation,
 };
 
@@ -430,6 +430,7 @@ fn merge_fields<'a, C: Cache, I: DoubleEndedIterator<Item = &'a LocIdent> + Clon
             opt: metadata1.opt && metadata2.opt,
             not_exported: metadata1.not_exported || metadata2.not_exported,
             priority,
+            deprecated: Deprecation::combine(metadata1.deprecated, metadata2.deprecated),
         },
         value,
         pending_contracts,
@@ -80,7 +80,7 @@ use crate::{
     cache::{Cache as ImportCache, Envs, ImportResolver},
     closurize::{closurize_rec_record, Closurize},
     environment::Environment as GenericEnvironment,
-    error::{Error, EvalError},
+    error::{warning::Warning, Error, EvalError, EvalResource},
     identifier::LocIdent,
     match_sharedterm,
     position::TermPos,
@@ -88,21 +88,37 @@ use crate::{
     term::{
         array::ArrayAttrs,
         make as mk_term,
-        pattern::compile::Compile,
+        pattern::compile::MatchCompilation,
         record::{Field, RecordData},
         BinaryOp, BindingType, LetAttrs, MatchBranch, MatchData, RecordOpKind, RichTerm,
         RuntimeContract, StrChunk, Term, UnaryOp,
     },
 };
 
-use std::io::Write;
+use std::{
+    collections::HashSet,
+    io::Write,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 pub mod cache;
 pub mod callstack;
+pub mod cancel;
+pub mod digest;
 pub mod fixpoint;
+pub mod freeze;
+pub mod fs;
 pub mod merge;
 pub mod operation;
+pub mod regex_cache;
+pub mod semver;
 pub mod stack;
+pub mod structural_diff;
+pub mod structural_key;
+pub mod time;
+pub mod url;
+pub mod uuid;
 
 use callstack::*;
 use codespan::FileId;
@@ -110,6 +126,11 @@ use operation::OperationCont;
 use stack::{Stack, StrAccData};
 
 use self::cache::{Cache, CacheIndex};
+use self::cancel::CancellationToken;
+
+/// The number of evaluation steps between two checks of the cancellation token and of the
+/// timeout. Checking at every step would slow down evaluation for no good reason.
+const INTERRUPT_CHECK_INTERVAL: usize = 1024;
 
 impl AsRef<Vec<StackElem>> for CallStack {
     fn as_ref(&self) -> &Vec<StackElem> {
@@ -131,6 +152,35 @@ pub struct VirtualMachine<R: ImportResolver, C: Cache> {
     initial_env: Environment,
     // The stream for writing trace output.
     trace: Box<dyn Write>,
+    // The number of evaluation steps left before giving up, if evaluation is bounded.
+    fuel: Option<usize>,
+    // The fuel initially given to the machine, reported when it's exhausted.
+    fuel_limit: usize,
+    // The maximum number of allocations performed since the limit was set, if any, and the value
+    // of the allocation counter of the cache at that point.
+    allocation_limit: Option<(usize, usize)>,
+    // The token telling the machine to stop evaluating, if any.
+    cancellation_token: Option<CancellationToken>,
+    // The time limit of evaluation and the instant at which it expires, if any.
+    deadline: Option<(Duration, Instant)>,
+    // The number of evaluation steps performed, used to check for interruptions periodically.
+    steps: usize,
+    // The recently compiled regexes of the regex primitive operations.
+    regex_cache: regex_cache::RegexCache,
+    // The contracts recorded by `std.contract.freeze`.
+    frozen_terms: freeze::FrozenTerms,
+    // The strategy used to compile match expressions.
+    match_compilation: MatchCompilation,
+    // The generator of random UUIDs, if the host enabled them by providing a seed.
+    uuid_generator: Option<uuid::UuidGenerator>,
+    // The environment variables which the program is allowed to read.
+    allowed_env: HashSet<String>,
+    // The path prefixes under which the program is allowed to read files.
+    allowed_read: Vec<PathBuf>,
+    // Whether the primitive operations depending on the environment of the host are disabled.
+    sandboxed: bool,
+    // The warnings emitted during evaluation, which haven't been retrieved yet.
+    warnings: Vec<Warning>,
 }
 
 impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
@@ -142,6 +192,20 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
             cache: Cache::new(),
             initial_env: Environment::new(),
             trace: Box::new(trace),
+            fuel: None,
+            fuel_limit: 0,
+            allocation_limit: None,
+            cancellation_token: None,
+            deadline: None,
+            steps: 0,
+            regex_cache: Default::default(),
+            frozen_terms: Default::default(),
+            match_compilation: Default::default(),
+            uuid_generator: None,
+            allowed_env: HashSet::new(),
+            allowed_read: Vec::new(),
+            sandboxed: false,
+            warnings: Vec::new(),
         }
     }
 
@@ -153,6 +217,20 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
             cache,
             trace: Box::new(trace),
             initial_env: Environment::new(),
+            fuel: None,
+            fuel_limit: 0,
+            allocation_limit: None,
+            cancellation_token: None,
+            deadline: None,
+            steps: 0,
+            regex_cache: Default::default(),
+            frozen_terms: Default::default(),
+            match_compilation: Default::default(),
+            uuid_generator: None,
+            allowed_env: HashSet::new(),
+            allowed_read: Vec::new(),
+            sandboxed: false,
+            warnings: Vec::new(),
         }
     }
 
@@ -163,6 +241,20 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
         self.stack.reset(&mut self.cache);
     }
 
+    /// Record a warning, to be retrieved later by [Self::take_warnings]. A warning identical to
+    /// one already recorded is ignored, as the same code may be evaluated many times.
+    pub fn warn(&mut self, warning: Warning) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    /// Return the warnings emitted during evaluation since the last call to this method, and
+    /// clear them.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
     pub fn import_resolver(&self) -> &R {
         &self.import_resolver
     }
@@ -231,6 +323,106 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
         self
     }
 
+    /// Bound the number of evaluation steps the machine may perform. Once the fuel is exhausted,
+    /// evaluation stops with an error. This is useful to evaluate untrusted or unfinished programs
+    /// which might not terminate, for example in the language server.
+    ///
+    /// Return the new virtual machine with the given fuel.
+    pub fn with_fuel(mut self, fuel: usize) -> Self {
+        self.set_fuel(fuel);
+        self
+    }
+
+    /// Bound the number of evaluation steps the machine may perform. Once the fuel is exhausted,
+    /// evaluation stops with [EvalError::ResourceExhausted]. See [Self::with_fuel].
+    pub fn set_fuel(&mut self, fuel: usize) {
+        self.fuel = Some(fuel);
+        self.fuel_limit = fuel;
+    }
+
+    /// Bound the number of elements the machine may allocate in the evaluation cache from now on,
+    /// which roughly bounds the memory used by evaluation. Once the limit is reached, evaluation
+    /// stops with [EvalError::ResourceExhausted]. Elements allocated before the call, for
+    /// example by a previous [Self::prepare_eval], aren't counted.
+    pub fn set_max_allocations(&mut self, max: usize) {
+        self.allocation_limit = Some((max, self.cache.allocated()));
+    }
+
+    /// Stop evaluation once `token` has been cancelled, with [EvalError::Cancelled]. The token is
+    /// checked periodically, so evaluation may go on for a few more steps after the cancellation.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation_token = Some(token);
+    }
+
+    /// Stop evaluation with [EvalError::ResourceExhausted] if it's still running once `timeout`
+    /// has elapsed, starting from now.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.deadline = Some((timeout, Instant::now() + timeout));
+    }
+
+    /// Check whether evaluation has been cancelled or has timed out. `pos` is the position of the
+    /// term being evaluated, which is reported in the error.
+    fn check_interrupts(&self, pos: TermPos) -> Result<(), EvalError> {
+        if self
+            .cancellation_token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(EvalError::Cancelled(pos));
+        }
+
+        match self.deadline {
+            Some((timeout, deadline)) if Instant::now() >= deadline => {
+                Err(EvalError::ResourceExhausted {
+                    resource: EvalResource::Time,
+                    limit: usize::try_from(timeout.as_millis()).unwrap_or(usize::MAX),
+                    pos,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Set the strategy used to compile match expressions. This is mostly useful to compare the
+    /// strategies. See [MatchCompilation].
+    pub fn set_match_compilation(&mut self, strategy: MatchCompilation) {
+        self.match_compilation = strategy;
+    }
+
+    /// Enable the generation of random UUIDs by `std.uuid.v4`, which is disabled by default since
+    /// it makes evaluation impure. The UUIDs are generated from `seed`, so that evaluating the same
+    /// program with the same seed always yields the same UUIDs.
+    pub fn set_uuid_seed(&mut self, seed: u64) {
+        self.uuid_generator = Some(uuid::UuidGenerator::new(seed));
+    }
+
+    /// Enable the generation of random UUIDs by `std.uuid.v4`, seeded from the entropy of the
+    /// operating system. As opposed to [Self::set_uuid_seed], each evaluation yields different
+    /// UUIDs.
+    pub fn allow_random_uuids(&mut self) {
+        self.uuid_generator = Some(uuid::UuidGenerator::from_entropy());
+    }
+
+    /// Allow the program to read the given environment variables through `std.env.get`. Reading
+    /// any other variable returns `'None`, as if it wasn't set.
+    pub fn allow_env<S: Into<String>>(&mut self, vars: impl IntoIterator<Item = S>) {
+        self.allowed_env.extend(vars.into_iter().map(Into::into));
+    }
+
+    /// Allow the program to read files and list directories under the given path prefixes through
+    /// `std.fs`, which fails on any other path.
+    pub fn allow_read<P: Into<PathBuf>>(&mut self, prefixes: impl IntoIterator<Item = P>) {
+        self.allowed_read
+            .extend(prefixes.into_iter().map(Into::into));
+    }
+
+    /// Disable the primitive operations depending on the environment of the host, that is reading
+    /// environment variables and files. Calling them fails with [EvalError::SandboxViolation],
+    /// whatever has been allowed through [Self::allow_env] or [Self::allow_read].
+    pub fn enable_sandbox(&mut self) {
+        self.sandboxed = true;
+    }
+
     fn eval_deep_closure_impl(
         &mut self,
         mut closure: Closure,
@@ -487,6 +679,32 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
     ///  - the evaluated term with its final environment
     pub fn eval_closure(&mut self, mut clos: Closure) -> Result<Closure, EvalError> {
         loop {
+            if let Some(fuel) = self.fuel.as_mut() {
+                if *fuel == 0 {
+                    return Err(EvalError::ResourceExhausted {
+                        resource: EvalResource::Fuel,
+                        limit: self.fuel_limit,
+                        pos: clos.body.pos,
+                    });
+                }
+                *fuel -= 1;
+            }
+
+            self.steps = self.steps.wrapping_add(1);
+            if self.steps % INTERRUPT_CHECK_INTERVAL == 0 {
+                self.check_interrupts(clos.body.pos)?;
+            }
+
+            if let Some((max, start)) = self.allocation_limit {
+                if self.cache.allocated() - start > max {
+                    return Err(EvalError::ResourceExhausted {
+                        resource: EvalResource::Allocations,
+                        limit: max,
+                        pos: clos.body.pos,
+                    });
+                }
+            }
+
             let Closure {
                 body:
                     RichTerm {
@@ -818,6 +1036,7 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                                 ArrayAttrs {
                                     closurized: true,
                                     pending_contracts: closurized_ctrs,
+                                    tuple: attrs.tuple,
                                 },
                             ),
                             pos,
@@ -889,7 +1108,11 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                 Term::Match(data) if !has_cont_on_stack => {
                     if let Some((arg, pos_app)) = self.stack.pop_arg(&self.cache) {
                         Closure {
-                            body: data.compile(arg.body.closurize(&mut self.cache, arg.env), pos),
+                            body: data.compile_with(
+                                arg.body.closurize(&mut self.cache, arg.env),
+                                pos,
+                                self.match_compilation,
+                            ),
                             env,
                         }
                     } else {
@@ -7,9 +7,13 @@
 //! On the other hand, the functions `process_unary_operation` and `process_binary_operation`
 //! receive evaluated operands and implement the actual semantics of operators.
 use super::{
+    cache::CacheIndex,
+    digest, fs,
     merge::{self, MergeMode},
+    semver,
     stack::StrAccData,
-    subst, Cache, Closure, Environment, ImportResolver, VirtualMachine,
+    structural_diff, structural_key, subst, time, url, uuid, Cache, Closure, Environment,
+    ImportResolver, VirtualMachine,
 };
 
 #[cfg(feature = "nix-experimental")]
@@ -17,18 +21,19 @@ use crate::nix_ffi;
 
 use crate::{
     closurize::Closurize,
-    error::{EvalError, IllegalPolymorphicTailAction},
+    error::{warning::Warning, EvalError, IllegalPolymorphicTailAction},
     identifier::LocIdent,
     label::{ty_path, Polarity, TypeVarData},
     match_sharedterm, mk_app, mk_fun, mk_opn, mk_record,
     parser::utils::parse_number_sci,
     position::TermPos,
     serialize,
-    serialize::ExportFormat,
+    serialize::{CsvOptions, ExportFormat},
     stdlib::internals,
     term::{
         array::{Array, ArrayAttrs, OutOfBoundError},
         make as mk_term,
+        pattern::compile::value_heads,
         record::{self, Field, FieldMetadata, RecordData},
         string::NickelString,
         *,
@@ -46,14 +51,21 @@ use malachite::{
     Integer,
 };
 
-use md5::digest::Digest;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use simple_counter::*;
 use unicode_segmentation::UnicodeSegmentation;
 
-use std::{convert::TryFrom, iter::Extend, rc::Rc};
+use std::{cmp::Ordering, convert::TryFrom, iter::Extend, rc::Rc};
 
 generate_counter!(FreshVariableCounter, usize);
 
+/// The maximum number of bits by which `%bit_shift%` can shift an integer.
+const MAX_BIT_SHIFT: i64 = 1 << 16;
+
+/// The maximum number of decimal digits, in absolute value, to which `%number_round%` can round a
+/// number.
+const MAX_ROUND_DIGITS: i64 = 1 << 12;
+
 /// Result of the equality of two terms.
 ///
 /// The equality of two terms can either be computed directly for base types (`Number`, `String`,
@@ -363,6 +375,40 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                     Err(mk_type_error!("match", "Enum", 2))
                 }
             }
+            UnaryOp::HeadMatch() => {
+                let (cases_closure, ..) = self
+                    .stack
+                    .pop_arg(&self.cache)
+                    .expect("missing arg for match");
+                let (default, ..) = self
+                    .stack
+                    .pop_arg(&self.cache)
+                    .expect("missing default case for match");
+
+                let Closure {
+                    body:
+                        RichTerm {
+                            term: cases_term, ..
+                        },
+                    env: cases_env,
+                } = cases_closure;
+
+                let mut cases = match cases_term.into_owned() {
+                    Term::Record(r) => r.fields,
+                    _ => panic!("invalid argument for %match%"),
+                };
+
+                Ok(value_heads(&t)
+                    .into_iter()
+                    .find_map(|head| cases.remove(&LocIdent::from(head)))
+                    .map(|field| Closure {
+                        // As for `TagsOnlyMatch`, the cases are generated by the interpreter and
+                        // always have a definition.
+                        body: field.value.expect("%match% cases must have a definition"),
+                        env: cases_env,
+                    })
+                    .unwrap_or(default))
+            }
             UnaryOp::ChangePolarity() => match_sharedterm!(match (t) {
                 Term::Lbl(l) => {
                     let mut l = l;
@@ -440,6 +486,7 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                         .map_err(|err| err.into_eval_err(pos, pos_op))?
                     {
                         Some(value) => {
+                            self.warn_if_deprecated(record, id, pos_op);
                             self.call_stack.enter_field(id, pos, value.pos, pos_op);
                             Ok(Closure { body: value, env })
                         }
@@ -507,6 +554,22 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                 }
                 _ => Err(mk_type_error!("values", "Record")),
             }),
+            UnaryOp::RecordSortFields() => match_sharedterm!(match (t) {
+                Term::Record(record) => {
+                    // due to a limitation of `match_sharedterm`: see the macro's documentation
+                    let mut record = record;
+                    record
+                        .fields
+                        .sort_by(|id1, _, id2, _| id1.label().cmp(id2.label()));
+                    record.attrs = record.attrs.ordered();
+
+                    Ok(Closure {
+                        body: RichTerm::new(Term::Record(record), pos_op_inh),
+                        env,
+                    })
+                }
+                _ => Err(mk_type_error!("record_sort_fields", "Record")),
+            }),
             UnaryOp::ArrayMap() => {
                 let (f, ..) = self
                     .stack
@@ -892,7 +955,9 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
             }
             UnaryOp::StrIsMatch() => {
                 if let Term::Str(s) = &*t {
-                    let re = regex::Regex::new(s)
+                    let re = self
+                        .regex_cache
+                        .get_or_compile(s)
                         .map_err(|err| EvalError::Other(err.to_string(), pos_op))?;
 
                     let param = LocIdent::fresh();
@@ -914,7 +979,9 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
             }
             UnaryOp::StrFind() => {
                 if let Term::Str(s) = &*t {
-                    let re = regex::Regex::new(s)
+                    let re = self
+                        .regex_cache
+                        .get_or_compile(s)
                         .map_err(|err| EvalError::Other(err.to_string(), pos_op))?;
 
                     let param = LocIdent::fresh();
@@ -936,7 +1003,9 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
             }
             UnaryOp::StrFindAll() => {
                 if let Term::Str(s) = &*t {
-                    let re = regex::Regex::new(s)
+                    let re = self
+                        .regex_cache
+                        .get_or_compile(s)
                         .map_err(|err| EvalError::Other(err.to_string(), pos_op))?;
 
                     let param = LocIdent::fresh();
@@ -956,6 +1025,231 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                     Err(mk_type_error!("str_find_all", "String", 1))
                 }
             }
+            UnaryOp::StrSplitKeep() => {
+                if let Term::Str(s) = &*t {
+                    let re = self
+                        .regex_cache
+                        .get_or_compile(s)
+                        .map_err(|err| EvalError::Other(err.to_string(), pos_op))?;
+
+                    let param = LocIdent::fresh();
+                    let splitter = Term::Fun(
+                        param,
+                        RichTerm::new(
+                            Term::Op1(
+                                UnaryOp::StrSplitKeepCompiled(re.into()),
+                                RichTerm::new(Term::Var(param), pos_op_inh),
+                            ),
+                            pos_op_inh,
+                        ),
+                    );
+
+                    Ok(Closure::atomic_closure(RichTerm::new(splitter, pos_op_inh)))
+                } else {
+                    Err(mk_type_error!("str_split_keep", "String", 1))
+                }
+            }
+            UnaryOp::UrlParse() => {
+                if let Term::Str(s) = &*t {
+                    let record = url::parse(s)
+                        .map_err(|msg| EvalError::Other(format!("url_parse: {msg}"), pos_op))?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Record(record),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("url_parse", "String"))
+                }
+            }
+            UnaryOp::UrlEncode() => {
+                if let Term::Str(s) = &*t {
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Str(url::encode(s).into()),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("url_encode", "String"))
+                }
+            }
+            UnaryOp::SemverParse() => {
+                if let Term::Str(s) = &*t {
+                    let record = semver::parse(s)
+                        .map_err(|msg| EvalError::Other(format!("semver_parse: {msg}"), pos_op))?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Record(record),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("semver_parse", "String"))
+                }
+            }
+            UnaryOp::UrlDecode() => {
+                if let Term::Str(s) = &*t {
+                    let decoded = url::decode(s)
+                        .map_err(|msg| EvalError::Other(format!("url_decode: {msg}"), pos_op))?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Str(decoded.into()),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("url_decode", "String"))
+                }
+            }
+            UnaryOp::Base64Encode() => {
+                if let Term::Str(s) = &*t {
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Str(STANDARD.encode(s.as_bytes()).into()),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("base64_encode", "String"))
+                }
+            }
+            UnaryOp::Base64Decode() => {
+                if let Term::Str(s) = &*t {
+                    let bytes = STANDARD.decode(s.as_bytes()).map_err(|err| {
+                        EvalError::Other(
+                            format!("base64_decode: invalid base64 string `{s}` ({err})"),
+                            pos_op,
+                        )
+                    })?;
+                    let decoded = String::from_utf8(bytes).map_err(|_| {
+                        EvalError::Other(
+                            format!("base64_decode: `{s}` doesn't decode to a valid UTF-8 string"),
+                            pos_op,
+                        )
+                    })?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Str(decoded.into()),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("base64_decode", "String"))
+                }
+            }
+            UnaryOp::StructuralKey() => {
+                // The key is computed on the fully substituted value, as for serialization.
+                let rt = subst(
+                    &self.cache,
+                    RichTerm { term: t, pos },
+                    &Environment::new(),
+                    &env,
+                );
+                let key = structural_key::key(&rt)
+                    .map_err(|msg| EvalError::Other(format!("structural_key: {msg}"), pos_op))?;
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Str(key.into()),
+                    pos_op_inh,
+                )))
+            }
+            UnaryOp::UuidV4() => {
+                let generator = self.uuid_generator.as_mut().ok_or_else(|| {
+                    EvalError::Other(
+                        String::from(
+                            "uuid_v4: generating random UUIDs is disabled. \
+                            Enable it with `--allow-random` on the command line, or with \
+                            `--uuid-seed <SEED>` for reproducible UUIDs",
+                        ),
+                        pos_op,
+                    )
+                })?;
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Str(generator.v4().into()),
+                    pos_op_inh,
+                )))
+            }
+            UnaryOp::UuidParse() => {
+                if let Term::Str(s) = &*t {
+                    let uuid = uuid::parse(s)
+                        .map_err(|msg| EvalError::Other(format!("uuid_parse: {msg}"), pos_op))?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Str(uuid.into()),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("uuid_parse", "String"))
+                }
+            }
+            UnaryOp::EnvGet() => {
+                if self.sandboxed {
+                    return Err(EvalError::SandboxViolation {
+                        primop: String::from("env_get"),
+                        pos_op,
+                    });
+                }
+
+                if let Term::Str(name) = &*t {
+                    // Variables which aren't allowed are indistinguishable from unset ones.
+                    let value = if self.allowed_env.contains(name.as_str()) {
+                        std::env::var(name.as_str()).ok()
+                    } else {
+                        None
+                    };
+
+                    let result = match value {
+                        Some(value) => Term::EnumVariant {
+                            tag: LocIdent::from("Some"),
+                            arg: RichTerm::from(Term::Str(value.into())),
+                            attrs: EnumVariantAttrs::new(),
+                        },
+                        None => Term::Enum(LocIdent::from("None")),
+                    };
+
+                    Ok(Closure::atomic_closure(RichTerm::new(result, pos_op_inh)))
+                } else {
+                    Err(mk_type_error!("env_get", "String"))
+                }
+            }
+            UnaryOp::FsReadText() => {
+                if self.sandboxed {
+                    return Err(EvalError::SandboxViolation {
+                        primop: String::from("fs_read_text"),
+                        pos_op,
+                    });
+                }
+
+                if let Term::Str(path) = &*t {
+                    let content = fs::read_text(&self.allowed_read, path)
+                        .map_err(|msg| EvalError::Other(format!("fs_read_text: {msg}"), pos_op))?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Str(content.into()),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("fs_read_text", "String"))
+                }
+            }
+            UnaryOp::FsReadDir() => {
+                if self.sandboxed {
+                    return Err(EvalError::SandboxViolation {
+                        primop: String::from("fs_read_dir"),
+                        pos_op,
+                    });
+                }
+
+                if let Term::Str(path) = &*t {
+                    let names = fs::read_dir(&self.allowed_read, path)
+                        .map_err(|msg| EvalError::Other(format!("fs_read_dir: {msg}"), pos_op))?;
+                    let names = names
+                        .into_iter()
+                        .map(|name| RichTerm::from(Term::Str(name.into())));
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Array(Array::from_iter(names), ArrayAttrs::new().closurized()),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!("fs_read_dir", "String"))
+                }
+            }
             UnaryOp::StrIsMatchCompiled(regex) => {
                 if let Term::Str(s) = &*t {
                     Ok(Closure::atomic_closure(RichTerm::new(
@@ -982,12 +1276,17 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                                     Array::default(),
                                     ArrayAttrs::default()
                                 ))
+                            ),
+                            (
+                                "named_groups",
+                                RichTerm::from(Term::Record(RecordData::empty()))
                             )
                         ),
                         Some(RegexFindResult {
                             matched: mtch,
                             index,
                             groups,
+                            named_groups,
                         }) => mk_record!(
                             ("matched", RichTerm::from(Term::Str(mtch))),
                             ("index", RichTerm::from(Term::Num(index))),
@@ -999,7 +1298,8 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                                     ),
                                     ArrayAttrs::new().closurized()
                                 ))
-                            )
+                            ),
+                            ("named_groups", named_groups_record(named_groups))
                         ),
                     };
                     Ok(Closure::atomic_closure(result))
@@ -1025,7 +1325,8 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                                         ),
                                         ArrayAttrs::new().closurized()
                                     ))
-                                )
+                                ),
+                                ("named_groups", named_groups_record(found.named_groups))
                             )
                         })),
                         ArrayAttrs::default(),
@@ -1039,6 +1340,19 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                     ))
                 }
             }
+            UnaryOp::StrSplitKeepCompiled(regex) => {
+                if let Term::Str(s) = &*t {
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Array(s.split_keep_regex(&regex), ArrayAttrs::new().closurized()),
+                        pos_op_inh,
+                    )))
+                } else {
+                    Err(mk_type_error!(
+                        "a compiled regular expression split",
+                        "String"
+                    ))
+                }
+            }
             UnaryOp::Force {
                 ignore_not_exported,
             } => {
@@ -1284,6 +1598,262 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
         }
     }
 
+    /// Emit a warning if the field `id` of `record` is deprecated. `pos` is the position of the
+    /// access.
+    fn warn_if_deprecated(&mut self, record: &RecordData, id: LocIdent, pos: TermPos) {
+        if let Some(deprecation) = record
+            .fields
+            .get(&id)
+            .and_then(|field| field.metadata.deprecated.clone())
+        {
+            self.warn(Warning::DeprecatedField {
+                field: id,
+                deprecation,
+                pos,
+            });
+        }
+    }
+
+    /// Evaluate a closure to a weak head normal form from within a primitive operation, for
+    /// primops which need to call back into Nickel code. The closure is evaluated on a fresh
+    /// stack, so that it doesn't consume the continuations of the operation being processed.
+    fn eval_nested(&mut self, closure: Closure) -> Result<Closure, EvalError> {
+        let stack = std::mem::take(&mut self.stack);
+        let result = self.eval_closure(closure);
+        self.stack = stack;
+        result
+    }
+
+    /// Check if the value stored at `idx` is a record or an array which has been frozen by
+    /// `std.contract.freeze` with a contract equal to `contract`. The value isn't evaluated: we only
+    /// follow the variables and the closures which usually stand between a contract application
+    /// and the frozen value, up to a fixed number of indirections.
+    fn is_validated(&self, idx: CacheIndex, contract: &RichTerm, env: &Environment) -> bool {
+        const MAX_INDIRECTIONS: usize = 8;
+
+        let is_validated_by = |validated: &[RichTerm]| {
+            validated
+                .iter()
+                .any(|ctr| contract_eq(0, ctr, &Environment::new(), contract, env))
+        };
+
+        let mut closure = self.cache.get(idx);
+
+        for _ in 0..MAX_INDIRECTIONS {
+            let next = match closure.body.as_ref() {
+                Term::Record(_) | Term::Array(..) => {
+                    return is_validated_by(self.frozen_terms.get(&closure.body.term))
+                }
+                Term::Closure(idx) => idx.clone(),
+                Term::Var(id) => match closure.env.get(&id.ident()) {
+                    Some(idx) => idx.clone(),
+                    None => return false,
+                },
+                _ => return false,
+            };
+
+            closure = self.cache.get(next);
+        }
+
+        false
+    }
+
+    /// Return the fields of a record, with their pending contracts applied and their values
+    /// closurized, for the deep record operations. Empty optional fields are skipped.
+    fn deep_record_fields(
+        &mut self,
+        record: RecordData,
+        env: &Environment,
+        pos_record: TermPos,
+        pos_op: TermPos,
+    ) -> Result<IndexMap<LocIdent, Field>, EvalError> {
+        if let Some(record::SealedTail { label, .. }) = record.sealed_tail {
+            return Err(EvalError::IllegalPolymorphicTailAccess {
+                action: IllegalPolymorphicTailAction::Map,
+                evaluated_arg: label.get_evaluated_arg(&self.cache),
+                label,
+                call_stack: std::mem::take(&mut self.call_stack),
+            });
+        }
+
+        record
+            .fields
+            .into_iter()
+            .filter(|(_, field)| !field.is_empty_optional())
+            .map_values_closurize(&mut self.cache, env, |_, value| value)
+            .map_err(|missing_field_err| missing_field_err.into_eval_err(pos_record, pos_op))
+    }
+
+    /// Evaluate the (closurized) value of a field to a weak head normal form, and return its
+    /// fields if it's a record. This is how the deep record operations tell the inner nodes of a
+    /// tree of nested records from its leaves.
+    fn eval_deep_record_node(
+        &mut self,
+        value: &RichTerm,
+        pos_op: TermPos,
+    ) -> Result<Option<IndexMap<LocIdent, Field>>, EvalError> {
+        let Closure {
+            body: RichTerm { term, pos },
+            env,
+        } = self.eval_nested(Closure::atomic_closure(value.clone()))?;
+
+        match term.into_owned() {
+            Term::Record(record) => self.deep_record_fields(record, &env, pos, pos_op).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Worker for [BinaryOp::RecordDeepMap]. The function is applied lazily, but each field
+    /// value is evaluated to a weak head normal form to tell the inner nodes from the leaves.
+    fn record_deep_map(
+        &mut self,
+        f: &RichTerm,
+        path: &mut Vec<LocIdent>,
+        fields: IndexMap<LocIdent, Field>,
+        pos_op: TermPos,
+    ) -> Result<IndexMap<LocIdent, Field>, EvalError> {
+        let mut result = IndexMap::with_capacity(fields.len());
+
+        for (id, field) in fields {
+            // unwrap(): `deep_record_fields` only returns fields with a value
+            let value = field.value.clone().unwrap();
+            path.push(id);
+
+            let value = match self.eval_deep_record_node(&value, pos_op)? {
+                Some(fields) => RichTerm::new(
+                    Term::Record(RecordData {
+                        fields: self.record_deep_map(f, path, fields, pos_op)?,
+                        ..Default::default()
+                    }),
+                    value.pos,
+                ),
+                None => {
+                    mk_app!(f.clone(), path_to_term(path), value).with_pos(pos_op.into_inherited())
+                }
+            };
+
+            path.pop();
+            result.insert(
+                id,
+                Field {
+                    value: Some(value),
+                    ..field
+                },
+            );
+        }
+
+        Ok(result)
+    }
+
+    /// Worker for [BinaryOp::RecordDeepFilter]. Sub-records are kept, even if all their leaves
+    /// have been filtered out.
+    fn record_deep_filter(
+        &mut self,
+        pred: &RichTerm,
+        path: &mut Vec<LocIdent>,
+        fields: IndexMap<LocIdent, Field>,
+        pos_op: TermPos,
+    ) -> Result<IndexMap<LocIdent, Field>, EvalError> {
+        let mut result = IndexMap::with_capacity(fields.len());
+
+        for (id, field) in fields {
+            // unwrap(): `deep_record_fields` only returns fields with a value
+            let value = field.value.clone().unwrap();
+            path.push(id);
+
+            let value = match self.eval_deep_record_node(&value, pos_op)? {
+                Some(fields) => Some(RichTerm::new(
+                    Term::Record(RecordData {
+                        fields: self.record_deep_filter(pred, path, fields, pos_op)?,
+                        ..Default::default()
+                    }),
+                    value.pos,
+                )),
+                None => {
+                    let keep = self.eval_nested(Closure::atomic_closure(mk_app!(
+                        pred.clone(),
+                        path_to_term(path),
+                        value.clone()
+                    )))?;
+
+                    match keep.body.as_ref() {
+                        Term::Bool(true) => Some(value),
+                        Term::Bool(false) => None,
+                        _ => {
+                            return Err(EvalError::Other(
+                                format!(
+                                    "record_deep_filter: the predicate must return a Bool, got {}",
+                                    keep.body
+                                ),
+                                pos_op,
+                            ))
+                        }
+                    }
+                }
+            };
+
+            path.pop();
+
+            if let Some(value) = value {
+                result.insert(
+                    id,
+                    Field {
+                        value: Some(value),
+                        ..field
+                    },
+                );
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Worker for [NAryOp::RecordDeepMergeWith]. The fields of the left record come first, in
+    /// order, followed by the fields which are only defined in the right one.
+    fn record_deep_merge_with(
+        &mut self,
+        f: &RichTerm,
+        path: &mut Vec<LocIdent>,
+        left: IndexMap<LocIdent, Field>,
+        mut right: IndexMap<LocIdent, Field>,
+        pos_op: TermPos,
+    ) -> Result<IndexMap<LocIdent, Field>, EvalError> {
+        let mut result = IndexMap::with_capacity(left.len() + right.len());
+
+        for (id, left_field) in left {
+            let Some(right_field) = right.shift_remove(&id) else {
+                result.insert(id, left_field);
+                continue;
+            };
+
+            // unwrap(): `deep_record_fields` only returns fields with a value
+            let left_value = left_field.value.unwrap();
+            let right_value = right_field.value.unwrap();
+            path.push(id);
+
+            let value = match (
+                self.eval_deep_record_node(&left_value, pos_op)?,
+                self.eval_deep_record_node(&right_value, pos_op)?,
+            ) {
+                (Some(left), Some(right)) => RichTerm::new(
+                    Term::Record(RecordData {
+                        fields: self.record_deep_merge_with(f, path, left, right, pos_op)?,
+                        ..Default::default()
+                    }),
+                    pos_op.into_inherited(),
+                ),
+                _ => mk_app!(f.clone(), path_to_term(path), left_value, right_value)
+                    .with_pos(pos_op.into_inherited()),
+            };
+
+            path.pop();
+            result.insert(id, Field::from(value));
+        }
+
+        result.extend(right);
+        Ok(result)
+    }
+
     /// Evaluate a binary operation.
     ///
     /// Both arguments are expected to be evaluated (in WHNF). `pos_op` corresponds to the whole
@@ -1473,7 +2043,55 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                     Err(mk_type_error!("pow", "Number", 1, t1, pos1))
                 }
             }
-            BinaryOp::StrConcat() => {
+            BinaryOp::BitAnd() | BinaryOp::BitOr() | BinaryOp::BitXor() | BinaryOp::BitShift() => {
+                let op_name = b_op.to_string();
+
+                let Term::Num(ref n1) = *t1 else {
+                    return Err(mk_type_error!(op_name, "Number", 1, t1, pos1));
+                };
+
+                let Term::Num(ref n2) = *t2 else {
+                    return Err(mk_type_error!(op_name, "Number", 2, t2, pos2));
+                };
+
+                let to_integer = |n: &Number, pos| {
+                    Integer::try_from(n).map_err(|_| {
+                        EvalError::Other(format!("{op_name}: expected an integer, got {n}"), pos)
+                    })
+                };
+
+                let i1 = to_integer(n1, pos1)?;
+
+                let result = match b_op {
+                    BinaryOp::BitAnd() => i1 & to_integer(n2, pos2)?,
+                    BinaryOp::BitOr() => i1 | to_integer(n2, pos2)?,
+                    BinaryOp::BitXor() => i1 ^ to_integer(n2, pos2)?,
+                    _ => {
+                        // Shifting to the left by a huge amount would exhaust the memory, so we
+                        // bound the amount. This is way more than enough for bit manipulations.
+                        let amount = i64::try_from(n2)
+                            .ok()
+                            .filter(|amount| amount.abs() <= MAX_BIT_SHIFT)
+                            .ok_or_else(|| {
+                                EvalError::Other(
+                                    format!(
+                                        "{op_name}: expected an integer between \
+                                        -{MAX_BIT_SHIFT} and {MAX_BIT_SHIFT}, got {n2}"
+                                    ),
+                                    pos2,
+                                )
+                            })?;
+
+                        i1 << amount
+                    }
+                };
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Num(Number::from(result)),
+                    pos_op_inh,
+                )))
+            }
+            BinaryOp::StrConcat() => {
                 if let Term::Str(s1) = &*t1 {
                     if let Term::Str(s2) = &*t2 {
                         let ss: [&str; 2] = [s1, s2];
@@ -1497,13 +2115,31 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                     })?;
                     let mut l = l.clone();
                     l.arg_pos = self.cache.get_then(idx.clone(), |c| c.body.pos);
-                    l.arg_idx = Some(idx);
+                    l.arg_idx = Some(idx.clone());
 
                     self.stack.push_arg(
                         Closure::atomic_closure(RichTerm::new(Term::Lbl(l), pos2.into_inherited())),
                         pos2.into_inherited(),
                     );
 
+                    let contract = RichTerm {
+                        term: t1,
+                        pos: pos1,
+                    };
+
+                    // If the value has been frozen with the same contract, there's no need to
+                    // check it again: the contract becomes the identity (ignoring the label).
+                    if self.is_validated(idx, &contract, &env1) {
+                        return Ok(Closure::atomic_closure(
+                            mk_fun!("l", "x", mk_term::var("x")).with_pos(pos1.into_inherited()),
+                        ));
+                    }
+
+                    let RichTerm {
+                        term: t1,
+                        pos: pos1,
+                    } = contract;
+
                     match *t1 {
                         Term::Fun(..) | Term::Match { .. } => Ok(Closure {
                             body: RichTerm {
@@ -1544,6 +2180,38 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                     Err(mk_type_error!("apply_contract", "Label", 2, t2, pos2))
                 }
             }
+            BinaryOp::ContractFreeze() => {
+                let contract = RichTerm {
+                    term: t1,
+                    pos: pos1,
+                }
+                .closurize(&mut self.cache, env1);
+
+                // Other values are cheap to check again: there's nothing to record.
+                if matches!(&*t2, Term::Record(_) | Term::Array(..)) {
+                    // Add the contract to the contracts already validated for this term, unless an
+                    // equal contract already is.
+                    let empty = Environment::new();
+                    let mut validated = self.frozen_terms.get(&t2).to_vec();
+
+                    if !validated
+                        .iter()
+                        .any(|ctr| contract_eq(0, ctr, &empty, &contract, &empty))
+                    {
+                        validated.push(contract);
+                    }
+
+                    self.frozen_terms.insert(&t2, validated);
+                }
+
+                Ok(Closure {
+                    body: RichTerm {
+                        term: t2,
+                        pos: pos2,
+                    },
+                    env: env2,
+                })
+            }
             BinaryOp::Unseal() => {
                 if let Term::SealingKey(s1) = &*t1 {
                     // Return a function that either behaves like the identity or
@@ -1698,6 +2366,7 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                                 |missing_field_err| missing_field_err.into_eval_err(pos2, pos_op),
                             )? {
                                 Some(value) => {
+                                    self.warn_if_deprecated(record, ident, pos_op);
                                     self.call_stack.enter_field(ident, pos2, value.pos, pos_op);
                                     Ok(Closure {
                                         body: value,
@@ -1997,6 +2666,7 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                         let attrs = ArrayAttrs {
                             closurized: true,
                             pending_contracts: ctrs_common,
+                            tuple: false,
                         };
 
                         Ok(Closure {
@@ -2015,6 +2685,253 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                     Err(mk_type_error!("(@)", "Array", 1, t1, pos1))
                 }
             }),
+            BinaryOp::ArraySortBy() => match_sharedterm!(match (t2) {
+                Term::Array(ts, attrs) => {
+                    let cmp = RichTerm {
+                        term: t1,
+                        pos: pos1,
+                    }
+                    .closurize(&mut self.cache, env1);
+
+                    // The elements are closurized with their pending contracts, so that they're
+                    // evaluated at most once even if they take part in several comparisons.
+                    let ts: Vec<_> = ts
+                        .into_iter()
+                        .map(|t| {
+                            RuntimeContract::apply_all(
+                                t,
+                                attrs.pending_contracts.iter().cloned(),
+                                pos2.into_inherited(),
+                            )
+                            .closurize(&mut self.cache, env2.clone())
+                        })
+                        .collect();
+
+                    let sorted = merge_sort(ts, &mut |t1, t2| {
+                        let result = self.eval_nested(Closure::atomic_closure(mk_app!(
+                            cmp.clone(),
+                            t1.clone(),
+                            t2.clone()
+                        )))?;
+
+                        match result.body.as_ref() {
+                            Term::Enum(tag)
+                                if tag.label() == "Lesser" || tag.label() == "Equal" =>
+                            {
+                                Ok(false)
+                            }
+                            Term::Enum(tag) if tag.label() == "Greater" => Ok(true),
+                            _ => Err(EvalError::Other(
+                                format!(
+                                    "array_sort_by: the comparison function must return \
+                                    'Lesser, 'Equal or 'Greater, got {}",
+                                    result.body
+                                ),
+                                pos1,
+                            )),
+                        }
+                    })?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Array(
+                            sorted.into_iter().collect(),
+                            attrs.contracts_cleared().closurized(),
+                        ),
+                        pos_op_inh,
+                    )))
+                }
+                _ => Err(mk_type_error!("array_sort_by", "Array", 2, t2, pos2)),
+            }),
+            BinaryOp::ArrayChunks() | BinaryOp::ArrayWindows() => {
+                let op_name = b_op.to_string();
+
+                let Term::Num(ref n) = *t1 else {
+                    return Err(mk_type_error!(op_name, "Number", 1, t1, pos1));
+                };
+
+                let Some(size) = usize::try_from(n).ok().filter(|size| *size > 0) else {
+                    return Err(EvalError::Other(
+                        format!("{op_name}: expected a positive integer, got {n}"),
+                        pos1,
+                    ));
+                };
+
+                match_sharedterm!(match (t2) {
+                    Term::Array(array, attrs) => {
+                        let len = array.len();
+
+                        // The sub-arrays are slices sharing the backing array of the original one,
+                        // together with its attributes and its environment. In particular,
+                        // pending contracts are preserved.
+                        let sub_array = |start: usize, end: usize| {
+                            let mut sub_array = array.clone();
+                            let sliced = sub_array.slice(start, end);
+                            debug_assert!(sliced.is_ok());
+
+                            RichTerm::new(
+                                Term::Array(sub_array, attrs.clone()),
+                                pos2.into_inherited(),
+                            )
+                        };
+
+                        let sub_arrays: Array = if let BinaryOp::ArrayChunks() = b_op {
+                            (0..len)
+                                .step_by(size)
+                                .map(|start| sub_array(start, usize::min(start + size, len)))
+                                .collect()
+                        } else {
+                            (0..(len + 1).saturating_sub(size))
+                                .map(|start| sub_array(start, start + size))
+                                .collect()
+                        };
+
+                        Ok(Closure {
+                            body: RichTerm::new(
+                                Term::Array(sub_arrays, ArrayAttrs::default()),
+                                pos_op_inh,
+                            ),
+                            env: env2,
+                        })
+                    }
+                    _ => Err(mk_type_error!(op_name, "Array", 2, t2, pos2)),
+                })
+            }
+            BinaryOp::RecordDeepMap() | BinaryOp::RecordDeepFilter() => {
+                match_sharedterm!(match (t2) {
+                    Term::Record(record) => {
+                        let f = RichTerm {
+                            term: t1,
+                            pos: pos1,
+                        }
+                        .closurize(&mut self.cache, env1);
+                        let fields = self.deep_record_fields(record, &env2, pos2, pos_op)?;
+
+                        let fields = if let BinaryOp::RecordDeepMap() = b_op {
+                            self.record_deep_map(&f, &mut Vec::new(), fields, pos_op)?
+                        } else {
+                            self.record_deep_filter(&f, &mut Vec::new(), fields, pos_op)?
+                        };
+
+                        Ok(Closure::atomic_closure(RichTerm::new(
+                            Term::Record(RecordData {
+                                fields,
+                                ..Default::default()
+                            }),
+                            pos_op_inh,
+                        )))
+                    }
+                    _ => Err(mk_type_error!(b_op.to_string(), "Record", 2, t2, pos2)),
+                })
+            }
+            BinaryOp::RecordSortFieldsBy() => match_sharedterm!(match (t2) {
+                Term::Record(record) => {
+                    let cmp = RichTerm {
+                        term: t1,
+                        pos: pos1,
+                    }
+                    .closurize(&mut self.cache, env1);
+
+                    // due to a limitation of `match_sharedterm`: see the macro's documentation
+                    let mut record = record;
+                    let fields: Vec<_> = std::mem::take(&mut record.fields).into_iter().collect();
+
+                    let sorted = merge_sort(fields, &mut |(id1, _), (id2, _)| {
+                        let result = self.eval_nested(Closure::atomic_closure(mk_app!(
+                            cmp.clone(),
+                            mk_term::string(id1.label()),
+                            mk_term::string(id2.label())
+                        )))?;
+
+                        match result.body.as_ref() {
+                            Term::Enum(tag)
+                                if tag.label() == "Lesser" || tag.label() == "Equal" =>
+                            {
+                                Ok(false)
+                            }
+                            Term::Enum(tag) if tag.label() == "Greater" => Ok(true),
+                            _ => Err(EvalError::Other(
+                                format!(
+                                    "record_sort_fields_by: the comparison function must return \
+                                    'Lesser, 'Equal or 'Greater, got {}",
+                                    result.body
+                                ),
+                                pos1,
+                            )),
+                        }
+                    })?;
+
+                    record.fields = sorted.into_iter().collect();
+                    record.attrs = record.attrs.ordered();
+
+                    Ok(Closure {
+                        body: RichTerm::new(Term::Record(record), pos_op_inh),
+                        env: env2,
+                    })
+                }
+                _ => Err(mk_type_error!(
+                    "record_sort_fields_by",
+                    "Record",
+                    2,
+                    t2,
+                    pos2
+                )),
+            }),
+            BinaryOp::RecordMoveFirst() => {
+                let Term::Array(names, attrs) = &*t1 else {
+                    return Err(mk_type_error!("record_move_first", "Array", 1, t1, pos1));
+                };
+
+                let mut first = Vec::with_capacity(names.len());
+
+                for name in names.iter() {
+                    let name = RuntimeContract::apply_all(
+                        name.clone(),
+                        attrs.pending_contracts.iter().cloned(),
+                        pos1.into_inherited(),
+                    );
+                    let name = self.eval_nested(Closure {
+                        body: name,
+                        env: env1.clone(),
+                    })?;
+
+                    let Term::Str(name) = name.body.as_ref() else {
+                        return Err(EvalError::Other(
+                            format!(
+                                "record_move_first: expected an array of strings, \
+                                got an element {}",
+                                name.body
+                            ),
+                            pos1,
+                        ));
+                    };
+
+                    first.push(LocIdent::from(name.as_str()));
+                }
+
+                match_sharedterm!(match (t2) {
+                    Term::Record(record) => {
+                        // due to a limitation of `match_sharedterm`: see the macro's documentation
+                        let mut record = record;
+                        let mut fields = IndexMap::with_capacity(record.fields.len());
+
+                        for id in first {
+                            if let Some(field) = record.fields.shift_remove(&id) {
+                                fields.insert(id, field);
+                            }
+                        }
+
+                        fields.extend(std::mem::take(&mut record.fields));
+                        record.fields = fields;
+                        record.attrs = record.attrs.ordered();
+
+                        Ok(Closure {
+                            body: RichTerm::new(Term::Record(record), pos_op_inh),
+                            env: env2,
+                        })
+                    }
+                    _ => Err(mk_type_error!("record_move_first", "Record", 2, t2, pos2)),
+                })
+            }
             BinaryOp::ArrayElemAt() => match (&*t1, &*t2) {
                 (Term::Array(ts, attrs), Term::Num(n)) => {
                     let Ok(n_as_usize) = usize::try_from(n) else {
@@ -2071,58 +2988,72 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                 &mut self.call_stack,
             ),
             BinaryOp::Hash() => {
-                let mk_err_fst = |t1| {
-                    Err(mk_type_error!(
+                let algorithm = match &*t1 {
+                    Term::Enum(id) => digest::Algorithm::from_tag(id.label()),
+                    _ => None,
+                };
+
+                let Some(algorithm) = algorithm else {
+                    return Err(mk_type_error!(
                         "hash",
-                        "[| 'Md5, 'Sha1, 'Sha256, 'Sha512 |]",
+                        digest::Algorithm::ENUM_TYPE,
                         1,
                         t1,
                         pos1
-                    ))
+                    ));
                 };
 
-                if let Term::Enum(id) = &*t1 {
-                    if let Term::Str(s) = &*t2 {
-                        let result = match id.as_ref() {
-                            "Md5" => {
-                                let mut hasher = md5::Md5::new();
-                                hasher.update(s.as_ref());
-                                format!("{:x}", hasher.finalize())
-                            }
-                            "Sha1" => {
-                                let mut hasher = sha1::Sha1::new();
-                                hasher.update(s.as_ref());
-                                format!("{:x}", hasher.finalize())
-                            }
-                            "Sha256" => {
-                                let mut hasher = sha2::Sha256::new();
-                                hasher.update(s.as_ref());
-                                format!("{:x}", hasher.finalize())
-                            }
-                            "Sha512" => {
-                                let mut hasher = sha2::Sha512::new();
-                                hasher.update(s.as_ref());
-                                format!("{:x}", hasher.finalize())
-                            }
-                            _ => return mk_err_fst(t1),
-                        };
+                let Term::Str(s) = &*t2 else {
+                    return Err(mk_type_error!("hash", "String", 2, t2, pos2));
+                };
 
-                        Ok(Closure::atomic_closure(RichTerm::new(
-                            Term::Str(result.into()),
-                            pos_op_inh,
-                        )))
-                    } else {
-                        Err(mk_type_error!("hash", "String", 2, t2, pos2))
-                    }
-                } else {
-                    mk_err_fst(t1)
-                }
+                let result = digest::Encoding::Hex.encode(&algorithm.hash(s.as_bytes()));
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Str(result.into()),
+                    pos_op_inh,
+                )))
+            }
+            BinaryOp::StructuralDiff() => {
+                // As for `StructuralKey`, the values are compared once fully substituted.
+                let expected = subst(
+                    &self.cache,
+                    RichTerm {
+                        term: t1,
+                        pos: pos1,
+                    },
+                    &Environment::new(),
+                    &env1,
+                );
+                let actual = subst(
+                    &self.cache,
+                    RichTerm {
+                        term: t2,
+                        pos: pos2,
+                    },
+                    &Environment::new(),
+                    &env2,
+                );
+
+                let diffs = structural_diff::diff(&expected, &actual)
+                    .map_err(|msg| EvalError::Other(format!("structural_diff: {msg}"), pos_op))?;
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Array(
+                        diffs
+                            .into_iter()
+                            .map(|diff| RichTerm::from(Term::Str(diff.into())))
+                            .collect(),
+                        ArrayAttrs::new().closurized(),
+                    ),
+                    pos_op_inh,
+                )))
             }
             BinaryOp::Serialize() => {
                 let mk_err_fst = |t1| {
                     Err(mk_type_error!(
                         "serialize",
-                        "[| 'Json, 'Yaml, 'Toml |]",
+                        "[| 'Json, 'Yaml, 'YamlDocuments, 'Toml |]",
                         1,
                         t1,
                         pos1
@@ -2142,16 +3073,23 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                         &env2,
                     );
 
-                    let format = match id.to_string().as_str() {
-                        "Json" => ExportFormat::Json,
-                        "Yaml" => ExportFormat::Yaml,
-                        "Toml" => ExportFormat::Toml,
-                        _ => return mk_err_fst(t1),
+                    let result = match id.to_string().as_str() {
+                        "YamlDocuments" => serialize::to_yaml_documents(&rt2)?,
+                        tag => {
+                            let format = match tag {
+                                "Json" => ExportFormat::Json,
+                                "Yaml" => ExportFormat::Yaml,
+                                "Toml" => ExportFormat::Toml,
+                                _ => return mk_err_fst(t1),
+                            };
+
+                            serialize::validate(format, &rt2)?;
+                            serialize::to_string(format, &rt2)?
+                        }
                     };
 
-                    serialize::validate(format, &rt2)?;
                     Ok(Closure::atomic_closure(RichTerm::new(
-                        Term::Str(serialize::to_string(format, &rt2)?.into()),
+                        Term::Str(result.into()),
                         pos_op_inh,
                     )))
                 } else {
@@ -2162,7 +3100,7 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                 let mk_err_fst = |t1| {
                     Err(mk_type_error!(
                         "deserialize",
-                        "[| 'Json, 'Yaml, 'Toml |]",
+                        "[| 'Json, 'Yaml, 'YamlDocuments, 'Toml, 'Csv |]",
                         1,
                         t1,
                         pos1
@@ -2171,31 +3109,22 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
 
                 if let Term::Enum(id) = &*t1 {
                     if let Term::Str(s) = &*t2 {
-                        let rt: RichTerm = match id.as_ref() {
-                            "Json" => serde_json::from_str(s).map_err(|err| {
-                                EvalError::DeserializationError(
-                                    String::from("json"),
-                                    format!("{err}"),
-                                    pos_op,
-                                )
-                            })?,
-                            "Yaml" => serde_yaml::from_str(s).map_err(|err| {
-                                EvalError::DeserializationError(
-                                    String::from("yaml"),
-                                    format!("{err}"),
-                                    pos_op,
-                                )
-                            })?,
-                            "Toml" => toml::from_str(s).map_err(|err| {
-                                EvalError::DeserializationError(
-                                    String::from("toml"),
-                                    format!("{err}"),
-                                    pos_op,
-                                )
-                            })?,
+                        let (format, result) = match id.as_ref() {
+                            "Json" => (
+                                "json",
+                                serde_json::from_str(s).map_err(|err| format!("{err}")),
+                            ),
+                            "Yaml" => ("yaml", serialize::from_yaml(s)),
+                            "YamlDocuments" => ("yaml", serialize::from_yaml_documents(s)),
+                            "Toml" => ("toml", serialize::from_toml(s)),
+                            "Csv" => ("csv", serialize::from_csv(s, &CsvOptions::default())),
                             _ => return mk_err_fst(t1),
                         };
 
+                        let rt: RichTerm = result.map_err(|msg| {
+                            EvalError::DeserializationError(String::from(format), msg, pos_op)
+                        })?;
+
                         Ok(Closure::atomic_closure(rt.with_pos(pos_op_inh)))
                     } else {
                         Err(mk_type_error!("deserialize", "String", 2, t2, pos2))
@@ -2226,6 +3155,77 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                 (Term::Str(_), _) => Err(mk_type_error!("str_contains", "String", 2, t2, pos2)),
                 (_, _) => Err(mk_type_error!("str_contains", "String", 1, t1, pos1)),
             },
+            BinaryOp::TimeParse() | BinaryOp::TimeFormat() | BinaryOp::TimeDiff() => {
+                let op_name = b_op.to_string();
+
+                let (Term::Str(s1), Term::Str(s2)) = (&*t1, &*t2) else {
+                    return if let Term::Str(_) = &*t1 {
+                        Err(mk_type_error!(op_name, "String", 2, t2, pos2))
+                    } else {
+                        Err(mk_type_error!(op_name, "String", 1, t1, pos1))
+                    };
+                };
+
+                let result = match b_op {
+                    BinaryOp::TimeParse() => time::parse(s1, s2).map(|s| Term::Str(s.into())),
+                    BinaryOp::TimeFormat() => time::format(s1, s2).map(|s| Term::Str(s.into())),
+                    _ => time::diff(s1, s2).map(Term::Num),
+                }
+                .map_err(|msg| EvalError::Other(format!("{op_name}: {msg}"), pos_op))?;
+
+                Ok(Closure::atomic_closure(RichTerm::new(result, pos_op_inh)))
+            }
+            BinaryOp::TimeAdd() => match (&*t1, &*t2) {
+                (Term::Num(seconds), Term::Str(timestamp)) => {
+                    let result = time::add(seconds, timestamp)
+                        .map_err(|msg| EvalError::Other(format!("time_add: {msg}"), pos_op))?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Str(result.into()),
+                        pos_op_inh,
+                    )))
+                }
+                (Term::Num(_), _) => Err(mk_type_error!("time_add", "String", 2, t2, pos2)),
+                (_, _) => Err(mk_type_error!("time_add", "Number", 1, t1, pos1)),
+            },
+            BinaryOp::UuidV5() => match (&*t1, &*t2) {
+                (Term::Str(namespace), Term::Str(name)) => {
+                    let uuid = uuid::v5(namespace, name)
+                        .map_err(|msg| EvalError::Other(format!("uuid_v5: {msg}"), pos_op))?;
+
+                    Ok(Closure::atomic_closure(RichTerm::new(
+                        Term::Str(uuid.into()),
+                        pos_op_inh,
+                    )))
+                }
+                (Term::Str(_), _) => Err(mk_type_error!("uuid_v5", "String", 2, t2, pos2)),
+                (_, _) => Err(mk_type_error!("uuid_v5", "String", 1, t1, pos1)),
+            },
+            BinaryOp::SemverCompare() | BinaryOp::SemverSatisfies() => {
+                let op_name = b_op.to_string();
+
+                let (Term::Str(s1), Term::Str(s2)) = (&*t1, &*t2) else {
+                    return if let Term::Str(_) = &*t1 {
+                        Err(mk_type_error!(op_name, "String", 2, t2, pos2))
+                    } else {
+                        Err(mk_type_error!(op_name, "String", 1, t1, pos1))
+                    };
+                };
+
+                let result = match b_op {
+                    BinaryOp::SemverCompare() => semver::compare(s1, s2).map(|ordering| {
+                        Term::Enum(LocIdent::from(match ordering {
+                            Ordering::Less => "Lesser",
+                            Ordering::Equal => "Equal",
+                            Ordering::Greater => "Greater",
+                        }))
+                    }),
+                    _ => semver::satisfies(s1, s2).map(Term::Bool),
+                }
+                .map_err(|msg| EvalError::Other(format!("{op_name}: {msg}"), pos_op))?;
+
+                Ok(Closure::atomic_closure(RichTerm::new(result, pos_op_inh)))
+            }
             BinaryOp::ArrayLazyAppCtr() => {
                 let (ctr, _) = self.stack.pop_arg(&self.cache).ok_or_else(|| {
                     EvalError::NotEnoughArgs(3, String::from("array_lazy_app_ctr"), pos_op)
@@ -2512,7 +3512,9 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                         let result = if let NAryOp::StrReplace() = n_op {
                             s.replace(from.as_str(), to.as_str())
                         } else {
-                            let re = regex::Regex::new(from)
+                            let re = self
+                                .regex_cache
+                                .get_or_compile(from)
                                 .map_err(|err| EvalError::Other(err.to_string(), pos_op))?;
 
                             s.replace_regex(&CompiledRegex(re), to)
@@ -2951,6 +3953,405 @@ impl<R: ImportResolver, C: Cache> VirtualMachine<R, C> {
                     pos2.into_inherited(),
                 )))
             }
+            NAryOp::RecordDeepMergeWith() => {
+                let mut args = args.into_iter();
+                let (Closure { body: f, env: env1 }, _) = args.next().unwrap();
+                let mut records = Vec::with_capacity(2);
+
+                for arg_number in [2, 3] {
+                    let (
+                        Closure {
+                            body: RichTerm { term, pos },
+                            env,
+                        },
+                        arg_pos,
+                    ) = args.next().unwrap();
+
+                    match term.into_owned() {
+                        Term::Record(record) => {
+                            records.push(self.deep_record_fields(record, &env, pos, pos_op)?)
+                        }
+                        term => {
+                            return Err(EvalError::NAryPrimopTypeError {
+                                primop: String::from("record_deep_merge_with"),
+                                expected: String::from("Record"),
+                                arg_number,
+                                arg_pos,
+                                arg_evaluated: RichTerm::new(term, pos),
+                            })
+                        }
+                    }
+                }
+                debug_assert!(args.next().is_none());
+
+                let f = f.closurize(&mut self.cache, env1);
+                // unwrap(): we pushed exactly two records above
+                let right = records.pop().unwrap();
+                let left = records.pop().unwrap();
+                let fields =
+                    self.record_deep_merge_with(&f, &mut Vec::new(), left, right, pos_op)?;
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Record(RecordData {
+                        fields,
+                        ..Default::default()
+                    }),
+                    pos_op_inh,
+                )))
+            }
+            NAryOp::ArrayZipWith() => {
+                let mut args = args.into_iter();
+                let (Closure { body: f, env: env1 }, _) = args.next().unwrap();
+                let mut arrays = Vec::with_capacity(2);
+
+                for arg_number in [2, 3] {
+                    let (
+                        Closure {
+                            body: RichTerm { term, pos },
+                            env,
+                        },
+                        arg_pos,
+                    ) = args.next().unwrap();
+
+                    match term.into_owned() {
+                        Term::Array(array, attrs) => arrays.push((array, attrs, pos, env)),
+                        term => {
+                            return Err(EvalError::NAryPrimopTypeError {
+                                primop: String::from("array_zip_with"),
+                                expected: String::from("Array"),
+                                arg_number,
+                                arg_pos,
+                                arg_evaluated: RichTerm::new(term, pos),
+                            })
+                        }
+                    }
+                }
+                debug_assert!(args.next().is_none());
+
+                let f_as_var = f.closurize(&mut self.cache, env1);
+                let len = arrays
+                    .iter()
+                    .map(|(array, ..)| array.len())
+                    .min()
+                    .unwrap_or_default();
+
+                // As for `ArrayMap`, the elements are closurized to preserve laziness.
+                let mut elems = arrays.into_iter().map(|(array, attrs, pos, env)| {
+                    array
+                        .into_iter()
+                        .take(len)
+                        .map(|t| {
+                            RuntimeContract::apply_all(
+                                t,
+                                attrs.pending_contracts.iter().cloned(),
+                                pos.into_inherited(),
+                            )
+                            .closurize(&mut self.cache, env.clone())
+                        })
+                        .collect::<Vec<_>>()
+                });
+                // unwrap(): we pushed exactly two arrays above
+                let xs = elems.next().unwrap();
+                let ys = elems.next().unwrap();
+
+                let ts = xs
+                    .into_iter()
+                    .zip(ys)
+                    .map(|(x, y)| {
+                        RichTerm::new(Term::App(mk_app!(f_as_var.clone(), x), y), pos_op_inh)
+                            .closurize(&mut self.cache, Environment::new())
+                    })
+                    .collect();
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Array(ts, ArrayAttrs::new().closurized()),
+                    pos_op_inh,
+                )))
+            }
+            NAryOp::CsvParse() => {
+                let args: Vec<_> = args
+                    .into_iter()
+                    .map(|(clos, arg_pos)| (clos.body, arg_pos))
+                    .collect();
+                debug_assert_eq!(args.len(), 4);
+
+                let type_error = |arg_number: usize, expected: &str| {
+                    let (arg_evaluated, arg_pos) = args[arg_number - 1].clone();
+
+                    EvalError::NAryPrimopTypeError {
+                        primop: String::from("csv_parse"),
+                        expected: String::from(expected),
+                        arg_number,
+                        arg_pos,
+                        arg_evaluated,
+                    }
+                };
+
+                let ascii_char = |arg_number: usize| {
+                    let arg = &args[arg_number - 1].0;
+
+                    match arg.as_ref() {
+                        Term::Str(s) if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+                        Term::Str(s) => Err(EvalError::Other(
+                            format!("csv_parse: expected a single ASCII character, got \"{s}\""),
+                            arg.pos,
+                        )),
+                        _ => Err(type_error(arg_number, "String")),
+                    }
+                };
+
+                let delimiter = ascii_char(1)?;
+                let quote = ascii_char(2)?;
+
+                let Term::Bool(infer_types) = *args[2].0.as_ref() else {
+                    return Err(type_error(3, "Bool"));
+                };
+
+                let Term::Str(input) = args[3].0.as_ref() else {
+                    return Err(type_error(4, "String"));
+                };
+
+                let options = CsvOptions {
+                    delimiter,
+                    quote,
+                    infer_types,
+                };
+
+                let rt = serialize::from_csv(input, &options).map_err(|msg| {
+                    EvalError::DeserializationError(String::from("csv"), msg, pos_op)
+                })?;
+
+                Ok(Closure::atomic_closure(rt.with_pos(pos_op_inh)))
+            }
+            NAryOp::NumberRound() => {
+                let args: Vec<_> = args
+                    .into_iter()
+                    .map(|(clos, arg_pos)| (clos.body, arg_pos))
+                    .collect();
+                debug_assert_eq!(args.len(), 3);
+
+                let type_error = |arg_number: usize, expected: &str| {
+                    let (arg_evaluated, arg_pos) = args[arg_number - 1].clone();
+
+                    EvalError::NAryPrimopTypeError {
+                        primop: String::from("number_round"),
+                        expected: String::from(expected),
+                        arg_number,
+                        arg_pos,
+                        arg_evaluated,
+                    }
+                };
+
+                let modes = "[| 'Floor, 'Ceil, 'Truncate, 'HalfUp, 'HalfEven |]";
+
+                let Term::Enum(mode) = args[0].0.as_ref() else {
+                    return Err(type_error(1, modes));
+                };
+
+                let Term::Num(digits) = args[1].0.as_ref() else {
+                    return Err(type_error(2, "Number"));
+                };
+
+                let Term::Num(n) = args[2].0.as_ref() else {
+                    return Err(type_error(3, "Number"));
+                };
+
+                let digits = i64::try_from(digits)
+                    .ok()
+                    .filter(|digits| digits.abs() <= MAX_ROUND_DIGITS)
+                    .ok_or_else(|| {
+                        EvalError::Other(
+                            format!(
+                                "number_round: expected an integer between \
+                                -{MAX_ROUND_DIGITS} and {MAX_ROUND_DIGITS}, got {digits}"
+                            ),
+                            args[1].0.pos,
+                        )
+                    })?;
+
+                // We round `n * 10^digits` to an integer, and scale the result back.
+                let scale = Number::from(10).pow(digits);
+                let scaled = n * &scale;
+                let round = |x: Number, mode| Number::from(Integer::rounding_from(x, mode).0);
+
+                let rounded = match mode.label() {
+                    "Floor" => round(scaled, RoundingMode::Floor),
+                    "Ceil" => round(scaled, RoundingMode::Ceiling),
+                    "Truncate" => round(scaled, RoundingMode::Down),
+                    "HalfEven" => round(scaled, RoundingMode::Nearest),
+                    // Ties are rounded away from zero.
+                    "HalfUp" => {
+                        let half = Number::from_signeds(1, 2);
+
+                        if scaled >= Number::ZERO {
+                            round(scaled + half, RoundingMode::Floor)
+                        } else {
+                            round(scaled - half, RoundingMode::Ceiling)
+                        }
+                    }
+                    _ => return Err(type_error(1, modes)),
+                };
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Num(rounded / scale),
+                    pos_op_inh,
+                )))
+            }
+            NAryOp::HashWith() | NAryOp::Hmac() => {
+                let primop = n_op.to_string();
+                let args: Vec<_> = args
+                    .into_iter()
+                    .map(|(clos, arg_pos)| (clos.body, arg_pos))
+                    .collect();
+                debug_assert_eq!(args.len(), n_op.arity());
+
+                let type_error = |arg_number: usize, expected: &str| {
+                    let (arg_evaluated, arg_pos) = args[arg_number - 1].clone();
+
+                    EvalError::NAryPrimopTypeError {
+                        primop: primop.clone(),
+                        expected: String::from(expected),
+                        arg_number,
+                        arg_pos,
+                        arg_evaluated,
+                    }
+                };
+
+                let tag = |arg_number: usize| match args[arg_number - 1].0.as_ref() {
+                    Term::Enum(id) => Some(id.label()),
+                    _ => None,
+                };
+
+                let algorithm = tag(1)
+                    .and_then(digest::Algorithm::from_tag)
+                    .ok_or_else(|| type_error(1, digest::Algorithm::ENUM_TYPE))?;
+                let encoding = tag(2)
+                    .and_then(digest::Encoding::from_tag)
+                    .ok_or_else(|| type_error(2, digest::Encoding::ENUM_TYPE))?;
+
+                let string = |arg_number: usize| match args[arg_number - 1].0.as_ref() {
+                    Term::Str(s) => Ok(s.as_bytes()),
+                    _ => Err(type_error(arg_number, "String")),
+                };
+
+                let result = if let NAryOp::HashWith() = n_op {
+                    algorithm.hash(string(3)?)
+                } else {
+                    algorithm.hmac(string(3)?, string(4)?)
+                };
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Str(encoding.encode(&result).into()),
+                    pos_op_inh,
+                )))
+            }
+            NAryOp::SequenceTake() => {
+                let mut args = args.into_iter();
+                let (
+                    Closure {
+                        body:
+                            RichTerm {
+                                term: t1,
+                                pos: pos1,
+                            },
+                        ..
+                    },
+                    fst_pos,
+                ) = args.next().unwrap();
+                let (
+                    Closure {
+                        body: step,
+                        env: env2,
+                    },
+                    _,
+                ) = args.next().unwrap();
+                let (
+                    Closure {
+                        body: state,
+                        env: env3,
+                    },
+                    _,
+                ) = args.next().unwrap();
+                debug_assert!(args.next().is_none());
+
+                let Term::Num(ref n) = *t1 else {
+                    return Err(EvalError::NAryPrimopTypeError {
+                        primop: String::from("sequence_take"),
+                        expected: String::from("Number"),
+                        arg_number: 1,
+                        arg_pos: fst_pos,
+                        arg_evaluated: RichTerm {
+                            term: t1,
+                            pos: pos1,
+                        },
+                    });
+                };
+
+                let Ok(count) = usize::try_from(n) else {
+                    return Err(EvalError::Other(
+                        format!("sequence_take: expected a natural number, got {n}"),
+                        pos1,
+                    ));
+                };
+
+                let step = step.closurize(&mut self.cache, env2);
+                let mut state = state.closurize(&mut self.cache, env3);
+                let mut values = Vec::new();
+
+                let invalid_step = |result: &RichTerm| {
+                    EvalError::Other(
+                        format!(
+                            "sequence_take: the step function must return \
+                            'Some {{ value, state }} or 'None, got {result}"
+                        ),
+                        pos_op,
+                    )
+                };
+
+                // The step function is only called as long as more elements are needed, so that
+                // infinite sequences can be consumed as well.
+                while values.len() < count {
+                    let Closure { body: result, env } = self.eval_nested(
+                        Closure::atomic_closure(mk_app!(step.clone(), state.clone())),
+                    )?;
+
+                    let arg = match result.as_ref() {
+                        Term::Enum(tag) if tag.label() == "None" => break,
+                        Term::EnumVariant { tag, arg, .. } if tag.label() == "Some" => arg.clone(),
+                        _ => return Err(invalid_step(&result)),
+                    };
+
+                    let Closure {
+                        body: RichTerm { term, pos },
+                        env,
+                    } = self.eval_nested(Closure { body: arg, env })?;
+
+                    let Term::Record(record) = term.into_owned() else {
+                        return Err(invalid_step(&result));
+                    };
+
+                    let mut fields = self.deep_record_fields(record, &env, pos, pos_op)?;
+                    let mut take_field = |name: &str| {
+                        fields
+                            .remove(&LocIdent::from(name))
+                            .and_then(|field| field.value)
+                    };
+
+                    let (Some(value), Some(next_state)) =
+                        (take_field("value"), take_field("state"))
+                    else {
+                        return Err(invalid_step(&result));
+                    };
+
+                    values.push(value);
+                    state = next_state;
+                }
+
+                Ok(Closure::atomic_closure(RichTerm::new(
+                    Term::Array(values.into_iter().collect(), ArrayAttrs::new().closurized()),
+                    pos_op_inh,
+                )))
+            }
             NAryOp::ArraySlice() => {
                 let mut args = args.into_iter();
 
@@ -3214,6 +4615,59 @@ impl RecPriority {
     }
 }
 
+/// Represent the named capture groups of a regex match as a Nickel record mapping group names to
+/// the captured strings.
+fn named_groups_record(named_groups: Vec<(String, NickelString)>) -> RichTerm {
+    RichTerm::from(Term::Record(RecordData::with_field_values(
+        named_groups
+            .into_iter()
+            .map(|(name, s)| (LocIdent::from(name), RichTerm::from(Term::Str(s)))),
+    )))
+}
+
+/// Represent the path of a field in a tree of nested records as a Nickel array of strings.
+fn path_to_term(path: &[LocIdent]) -> RichTerm {
+    RichTerm::from(Term::Array(
+        path.iter().map(|id| mk_term::string(id.label())).collect(),
+        ArrayAttrs::new(),
+    ))
+}
+
+/// Stable merge sort with a fallible comparison function `is_greater`, used by
+/// [BinaryOp::ArraySortBy]. We can't use the sorting functions of the standard library, which
+/// can't propagate errors and are allowed to panic when the comparison isn't a total order, which
+/// a user-provided comparison function has no reason to be.
+fn merge_sort<T, E>(
+    mut items: Vec<T>,
+    is_greater: &mut impl FnMut(&T, &T) -> Result<bool, E>,
+) -> Result<Vec<T>, E> {
+    if items.len() <= 1 {
+        return Ok(items);
+    }
+
+    let right = items.split_off(items.len() / 2);
+    let left = merge_sort(items, is_greater)?;
+    let right = merge_sort(right, is_greater)?;
+
+    let mut result = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // Taking from the left unless it's strictly greater is what makes the sort stable.
+        let next = if is_greater(l, r)? {
+            right.next()
+        } else {
+            left.next()
+        };
+        result.extend(next);
+    }
+
+    result.extend(left);
+    result.extend(right);
+    Ok(result)
+}
+
 /// Compute the equality of two terms, represented as closures.
 ///
 /// # Parameters
@@ -0,0 +1,93 @@
+//! A cache of compiled regular expressions for the regex primitive operations.
+//!
+//! Primitive operations such as `%str_is_match%` compile their pattern at each call. Sharing the
+//! partially applied function compiles the pattern only once, but this is easy to miss, and isn't
+//! possible when the pattern is computed dynamically. The virtual machine thus keeps the most
+//! recently used compiled regexes, indexed by their pattern. Compiled regexes are reference
+//! counted, so retrieving one from the cache is cheap.
+use std::collections::HashMap;
+
+/// The number of compiled regexes kept by [RegexCache::default].
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// A least recently used cache of compiled regexes.
+#[derive(Clone, Debug)]
+pub struct RegexCache {
+    /// The maximum number of regexes kept.
+    capacity: usize,
+    /// A logical clock, incremented at each lookup.
+    clock: u64,
+    /// The compiled regexes, together with the time of their last lookup.
+    entries: HashMap<String, (regex::Regex, u64)>,
+}
+
+impl RegexCache {
+    pub fn new(capacity: usize) -> Self {
+        RegexCache {
+            capacity,
+            clock: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Return the compiled regex for `pattern`, compiling it if it isn't in the cache yet. When
+    /// the cache is full, the least recently used regex is evicted to make room for the new one.
+    pub fn get_or_compile(&mut self, pattern: &str) -> Result<regex::Regex, regex::Error> {
+        self.clock += 1;
+
+        if let Some((re, last_used)) = self.entries.get_mut(pattern) {
+            *last_used = self.clock;
+            return Ok(re.clone());
+        }
+
+        let re = regex::Regex::new(pattern)?;
+
+        if self.capacity == 0 {
+            return Ok(re);
+        }
+
+        if self.entries.len() >= self.capacity {
+            let lru = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(pattern, _)| pattern.clone());
+
+            if let Some(lru) = lru {
+                self.entries.remove(&lru);
+            }
+        }
+
+        self.entries
+            .insert(pattern.to_owned(), (re.clone(), self.clock));
+        Ok(re)
+    }
+}
+
+impl Default for RegexCache {
+    fn default() -> Self {
+        RegexCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = RegexCache::new(2);
+
+        cache.get_or_compile("a+").unwrap();
+        cache.get_or_compile("b+").unwrap();
+        // Looking `a+` up makes `b+` the least recently used regex.
+        cache.get_or_compile("a+").unwrap();
+        cache.get_or_compile("c+").unwrap();
+
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.entries.contains_key("a+"));
+        assert!(cache.entries.contains_key("c+"));
+        assert!(cache.get_or_compile("(").is_err());
+        assert_eq!(cache.entries.len(), 2);
+    }
+}
@@ -0,0 +1,398 @@
+//! Semantic versioning support for the `std.semver` primitive operations.
+//!
+//! Versions are represented on the Nickel side as strings following the [Semantic Versioning
+//! 2.0.0](https://semver.org) specification, such as `1.2.3-rc.1+build.5`. Version requirements
+//! follow the syntax and the semantics of Cargo: a comma-separated list of comparators such as
+//! `>=1.2, <1.5`, where a bare version like `1.2.3` is a caret requirement (`^1.2.3`). The
+//! functions of this module return an error message on invalid input, which is then wrapped in an
+//! [crate::error::EvalError] by the caller.
+use std::{cmp::Ordering, fmt};
+
+use crate::{
+    identifier::LocIdent,
+    term::{array::ArrayAttrs, record::RecordData, Number, RichTerm, Term},
+};
+
+/// A dot-separated identifier of a pre-release. Numeric identifiers have a lower precedence than
+/// alphanumeric ones, which is the order of the variants.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    Numeric(u64),
+    AlphaNumeric(String),
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identifier::Numeric(n) => write!(f, "{n}"),
+            Identifier::AlphaNumeric(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// A parsed version. Build metadata is kept around, but doesn't take part in the comparison.
+#[derive(Clone, Debug)]
+struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: Vec<Identifier>,
+    build: Vec<String>,
+}
+
+/// Compare two pre-releases. A version without pre-release has a higher precedence than the same
+/// version with a pre-release, so the empty pre-release is the greatest one.
+fn cmp_pre(pre1: &[Identifier], pre2: &[Identifier]) -> Ordering {
+    match (pre1.is_empty(), pre2.is_empty()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => pre1.cmp(pre2),
+    }
+}
+
+impl Version {
+    fn cmp_precedence(&self, other: &Version) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| cmp_pre(&self.pre, &other.pre))
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-'
+}
+
+/// Parse a numeric component, which must not have leading zeros.
+fn parse_numeric(s: &str) -> Option<u64> {
+    let has_leading_zero = s.len() > 1 && s.starts_with('0');
+
+    if s.is_empty() || has_leading_zero || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    s.parse().ok()
+}
+
+fn parse_pre(s: &str) -> Option<Vec<Identifier>> {
+    s.split('.')
+        .map(|ident| {
+            if ident.is_empty() || !ident.chars().all(is_identifier_char) {
+                None
+            } else if ident.bytes().all(|b| b.is_ascii_digit()) {
+                parse_numeric(ident).map(Identifier::Numeric)
+            } else {
+                Some(Identifier::AlphaNumeric(ident.to_owned()))
+            }
+        })
+        .collect()
+}
+
+fn parse_build(s: &str) -> Option<Vec<String>> {
+    s.split('.')
+        .map(|ident| {
+            (!ident.is_empty() && ident.chars().all(is_identifier_char)).then(|| ident.to_owned())
+        })
+        .collect()
+}
+
+/// Split the optional pre-release and build metadata off a version.
+fn split_suffixes(s: &str) -> (&str, Option<&str>, Option<&str>) {
+    let (rest, build) = match s.split_once('+') {
+        Some((rest, build)) => (rest, Some(build)),
+        None => (s, None),
+    };
+
+    match rest.split_once('-') {
+        Some((core, pre)) => (core, Some(pre), build),
+        None => (rest, None, build),
+    }
+}
+
+fn parse_version(input: &str) -> Result<Version, String> {
+    let invalid = || format!("invalid version `{input}`");
+
+    let (core, pre, build) = split_suffixes(input);
+    let mut parts = core.split('.').map(parse_numeric);
+
+    let (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+
+    Ok(Version {
+        major,
+        minor,
+        patch,
+        pre: pre
+            .map_or(Some(Vec::new()), parse_pre)
+            .ok_or_else(invalid)?,
+        build: build
+            .map_or(Some(Vec::new()), parse_build)
+            .ok_or_else(invalid)?,
+    })
+}
+
+/// The operator of a comparator. A wildcard comparator such as `1.2.*` matches all the versions
+/// which agree on the specified components.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+    Wildcard,
+}
+
+/// A single comparator of a version requirement. The minor and patch components may be omitted,
+/// as in `>=1.2`.
+#[derive(Clone, Debug)]
+struct Comparator {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<Identifier>,
+}
+
+fn parse_comparator(input: &str) -> Result<Comparator, String> {
+    let invalid = || format!("invalid version requirement `{input}`");
+
+    let (op, rest) = [
+        (">=", Op::GreaterEq),
+        ("<=", Op::LessEq),
+        (">", Op::Greater),
+        ("<", Op::Less),
+        ("=", Op::Exact),
+        ("~", Op::Tilde),
+        ("^", Op::Caret),
+    ]
+    .into_iter()
+    .find_map(|(prefix, op)| input.strip_prefix(prefix).map(|rest| (op, rest)))
+    .unwrap_or((Op::Caret, input));
+
+    // Build metadata is irrelevant for comparisons.
+    let (core, pre, _) = split_suffixes(rest.trim_start());
+    let mut components = Vec::with_capacity(3);
+    let mut wildcard = false;
+
+    for part in core.split('.') {
+        match part {
+            "*" | "x" | "X" => wildcard = true,
+            _ if wildcard => return Err(invalid()),
+            _ => components.push(parse_numeric(part).ok_or_else(invalid)?),
+        }
+    }
+
+    let op = match op {
+        _ if !wildcard => op,
+        Op::Caret | Op::Exact => Op::Wildcard,
+        _ => return Err(invalid()),
+    };
+
+    let (major, minor, patch) = match components[..] {
+        [major] => (major, None, None),
+        [major, minor] => (major, Some(minor), None),
+        [major, minor, patch] => (major, Some(minor), Some(patch)),
+        _ => return Err(invalid()),
+    };
+
+    // A pre-release only makes sense on a complete version.
+    let pre = match pre {
+        Some(pre) if patch.is_some() => parse_pre(pre).ok_or_else(invalid)?,
+        Some(_) => return Err(invalid()),
+        None => Vec::new(),
+    };
+
+    Ok(Comparator {
+        op,
+        major,
+        minor,
+        patch,
+        pre,
+    })
+}
+
+/// Parse a version requirement. `*` alone is the empty list of comparators, which matches any
+/// version without pre-release.
+fn parse_requirement(input: &str) -> Result<Vec<Comparator>, String> {
+    if matches!(input.trim(), "*" | "x" | "X") {
+        return Ok(Vec::new());
+    }
+
+    input
+        .split(',')
+        .map(|comparator| parse_comparator(comparator.trim()))
+        .collect()
+}
+
+fn matches_exact(cmp: &Comparator, ver: &Version) -> bool {
+    ver.major == cmp.major
+        && (cmp.minor.is_none() || cmp.minor == Some(ver.minor))
+        && (cmp.patch.is_none() || cmp.patch == Some(ver.patch))
+        && ver.pre == cmp.pre
+}
+
+fn matches_greater(cmp: &Comparator, ver: &Version) -> bool {
+    if ver.major != cmp.major {
+        return ver.major > cmp.major;
+    }
+
+    match cmp.minor {
+        None => return false,
+        Some(minor) if ver.minor != minor => return ver.minor > minor,
+        Some(_) => (),
+    }
+
+    match cmp.patch {
+        None => return false,
+        Some(patch) if ver.patch != patch => return ver.patch > patch,
+        Some(_) => (),
+    }
+
+    cmp_pre(&ver.pre, &cmp.pre) == Ordering::Greater
+}
+
+fn matches_less(cmp: &Comparator, ver: &Version) -> bool {
+    if ver.major != cmp.major {
+        return ver.major < cmp.major;
+    }
+
+    match cmp.minor {
+        None => return false,
+        Some(minor) if ver.minor != minor => return ver.minor < minor,
+        Some(_) => (),
+    }
+
+    match cmp.patch {
+        None => return false,
+        Some(patch) if ver.patch != patch => return ver.patch < patch,
+        Some(_) => (),
+    }
+
+    cmp_pre(&ver.pre, &cmp.pre) == Ordering::Less
+}
+
+fn matches_tilde(cmp: &Comparator, ver: &Version) -> bool {
+    if ver.major != cmp.major || cmp.minor.is_some_and(|minor| ver.minor != minor) {
+        return false;
+    }
+
+    match cmp.patch {
+        Some(patch) if ver.patch != patch => ver.patch > patch,
+        _ => cmp_pre(&ver.pre, &cmp.pre) != Ordering::Less,
+    }
+}
+
+fn matches_caret(cmp: &Comparator, ver: &Version) -> bool {
+    if ver.major != cmp.major {
+        return false;
+    }
+
+    let Some(minor) = cmp.minor else {
+        return true;
+    };
+
+    let Some(patch) = cmp.patch else {
+        return if cmp.major > 0 {
+            ver.minor >= minor
+        } else {
+            ver.minor == minor
+        };
+    };
+
+    if cmp.major > 0 {
+        if ver.minor != minor {
+            return ver.minor > minor;
+        } else if ver.patch != patch {
+            return ver.patch > patch;
+        }
+    } else if minor > 0 {
+        if ver.minor != minor {
+            return false;
+        } else if ver.patch != patch {
+            return ver.patch > patch;
+        }
+    } else if ver.minor != minor || ver.patch != patch {
+        return false;
+    }
+
+    cmp_pre(&ver.pre, &cmp.pre) != Ordering::Less
+}
+
+fn matches(cmp: &Comparator, ver: &Version) -> bool {
+    match cmp.op {
+        Op::Exact | Op::Wildcard => matches_exact(cmp, ver),
+        Op::Greater => matches_greater(cmp, ver),
+        Op::GreaterEq => matches_exact(cmp, ver) || matches_greater(cmp, ver),
+        Op::Less => matches_less(cmp, ver),
+        Op::LessEq => matches_exact(cmp, ver) || matches_less(cmp, ver),
+        Op::Tilde => matches_tilde(cmp, ver),
+        Op::Caret => matches_caret(cmp, ver),
+    }
+}
+
+/// A pre-release version only satisfies a requirement if one of the comparators explicitly opts
+/// in, by mentioning a pre-release of the same major, minor and patch version.
+fn pre_is_compatible(cmp: &Comparator, ver: &Version) -> bool {
+    cmp.major == ver.major
+        && cmp.minor == Some(ver.minor)
+        && cmp.patch == Some(ver.patch)
+        && !cmp.pre.is_empty()
+}
+
+/// Parse a version into a record with the fields `major`, `minor`, `patch`, `prerelease` and
+/// `build`. The last two are arrays of the dot-separated identifiers, which are empty if the
+/// version doesn't have a pre-release or build metadata.
+pub fn parse(input: &str) -> Result<RecordData, String> {
+    let version = parse_version(input)?;
+
+    let num_field = |n: u64| RichTerm::from(Term::Num(Number::from(n)));
+    let array_field = |idents: Vec<String>| {
+        RichTerm::from(Term::Array(
+            idents
+                .into_iter()
+                .map(|ident| RichTerm::from(Term::Str(ident.into())))
+                .collect(),
+            ArrayAttrs::new().closurized(),
+        ))
+    };
+
+    let fields = [
+        ("major", num_field(version.major)),
+        ("minor", num_field(version.minor)),
+        ("patch", num_field(version.patch)),
+        (
+            "prerelease",
+            array_field(version.pre.iter().map(Identifier::to_string).collect()),
+        ),
+        ("build", array_field(version.build)),
+    ];
+
+    Ok(RecordData::with_field_values(
+        fields
+            .into_iter()
+            .map(|(id, value)| (LocIdent::from(id), value)),
+    ))
+}
+
+/// Compare two versions by precedence. Build metadata is ignored.
+pub fn compare(version1: &str, version2: &str) -> Result<Ordering, String> {
+    Ok(parse_version(version1)?.cmp_precedence(&parse_version(version2)?))
+}
+
+/// Check if a version satisfies a requirement.
+pub fn satisfies(requirement: &str, version: &str) -> Result<bool, String> {
+    let comparators = parse_requirement(requirement)?;
+    let version = parse_version(version)?;
+
+    Ok(comparators.iter().all(|cmp| matches(cmp, &version))
+        && (version.pre.is_empty()
+            || comparators
+                .iter()
+                .any(|cmp| pre_is_compatible(cmp, &version))))
+}
@@ -0,0 +1,224 @@
+//! Structural comparison of values for the `std.test` primitive operations.
+//!
+//! Instead of just telling if two values are equal, [diff] lists the differences between them as
+//! human-readable lines, one per field path (or array index) which has been added, removed or
+//! changed. Two values have no difference if and only if they are equal for `==`: in particular,
+//! the order of record fields and empty optional fields are ignored.
+//!
+//! The values must have been fully evaluated and substituted beforehand. Values which can't be
+//! compared for equality, such as functions, make [diff] return an error message, which is then
+//! wrapped in an [crate::error::EvalError] by the caller.
+//!
+//! This module also provides [diff_lines], a line-based diff of pretty-printed values used when
+//! reporting merge conflicts.
+use std::collections::BTreeMap;
+
+use crate::{
+    identifier::Ident,
+    serialize::{NickelPointer, NickelPointerElem},
+    term::{record::RecordData, RichTerm, Term},
+};
+
+/// Render a path to a subvalue, such as `servers[0].port`.
+fn display_path(path: &NickelPointer) -> String {
+    if path.0.is_empty() {
+        String::from("<root>")
+    } else {
+        path.to_string()
+    }
+}
+
+/// Compute the differences between the `expected` and the `actual` value. The result is empty if
+/// the values are equal.
+pub fn diff(expected: &RichTerm, actual: &RichTerm) -> Result<Vec<String>, String> {
+    let mut diffs = Vec::new();
+    diff_at(&mut NickelPointer::new(), expected, actual, &mut diffs)?;
+    Ok(diffs)
+}
+
+/// Return the defined fields of a record, sorted by name. Empty optional fields are skipped.
+fn defined_fields(record: &RecordData) -> Result<BTreeMap<&str, &RichTerm>, String> {
+    let mut fields = BTreeMap::new();
+
+    for (id, field) in record.fields.iter() {
+        match field.value {
+            Some(ref value) => {
+                fields.insert(id.label(), value);
+            }
+            None if field.metadata.opt => (),
+            None => return Err(format!("missing definition for field `{id}`")),
+        }
+    }
+
+    Ok(fields)
+}
+
+/// Check that a value can be compared for equality.
+fn check_comparable(t: &Term) -> Result<(), String> {
+    match t {
+        Term::Null
+        | Term::Bool(_)
+        | Term::Num(_)
+        | Term::Str(_)
+        | Term::Enum(_)
+        | Term::EnumVariant { .. }
+        | Term::Record(_)
+        | Term::Array(..) => Ok(()),
+        t => Err(format!(
+            "can't compare values of type {}",
+            t.type_of().unwrap_or_else(|| String::from("<unevaluated>"))
+        )),
+    }
+}
+
+fn diff_at(
+    path: &mut NickelPointer,
+    expected: &RichTerm,
+    actual: &RichTerm,
+    out: &mut Vec<String>,
+) -> Result<(), String> {
+    match (expected.as_ref(), actual.as_ref()) {
+        (Term::Record(record1), Term::Record(record2)) => {
+            let fields1 = defined_fields(record1)?;
+            let fields2 = defined_fields(record2)?;
+
+            for (&label, &value1) in fields1.iter() {
+                path.0.push(NickelPointerElem::Field(Ident::from(label)));
+
+                match fields2.get(label) {
+                    Some(&value2) => diff_at(path, value1, value2, out)?,
+                    None => out.push(format!("removed `{}`: {value1}", display_path(path))),
+                }
+
+                path.0.pop();
+            }
+
+            for (&label, &value2) in fields2.iter() {
+                if !fields1.contains_key(label) {
+                    path.0.push(NickelPointerElem::Field(Ident::from(label)));
+                    out.push(format!("added `{}`: {value2}", display_path(path)));
+                    path.0.pop();
+                }
+            }
+        }
+        (Term::Array(ts1, _), Term::Array(ts2, _)) => {
+            for idx in 0..usize::max(ts1.len(), ts2.len()) {
+                path.0.push(NickelPointerElem::Index(idx));
+
+                match (ts1.get(idx), ts2.get(idx)) {
+                    (Some(t1), Some(t2)) => diff_at(path, t1, t2, out)?,
+                    (Some(t1), None) => out.push(format!("removed `{}`: {t1}", display_path(path))),
+                    (None, Some(t2)) => out.push(format!("added `{}`: {t2}", display_path(path))),
+                    (None, None) => unreachable!("the index is smaller than one of the lengths"),
+                }
+
+                path.0.pop();
+            }
+        }
+        (
+            Term::EnumVariant {
+                tag: tag1,
+                arg: arg1,
+                ..
+            },
+            Term::EnumVariant {
+                tag: tag2,
+                arg: arg2,
+                ..
+            },
+        ) if tag1 == tag2 => diff_at(path, arg1, arg2, out)?,
+        (t1, t2) => {
+            check_comparable(t1)?;
+            check_comparable(t2)?;
+
+            let equal = match (t1, t2) {
+                (Term::Null, Term::Null) => true,
+                (Term::Bool(b1), Term::Bool(b2)) => b1 == b2,
+                (Term::Num(n1), Term::Num(n2)) => n1 == n2,
+                (Term::Str(s1), Term::Str(s2)) => s1 == s2,
+                (Term::Enum(tag1), Term::Enum(tag2)) => tag1 == tag2,
+                _ => false,
+            };
+
+            if !equal {
+                out.push(format!(
+                    "changed `{}`: expected {expected}, got {actual}",
+                    display_path(path)
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The number of unchanged lines kept around each change by [diff_lines].
+const DIFF_CONTEXT: usize = 2;
+
+/// Compute a line-based diff between two texts, in the style of the unified diff format: each line
+/// of the result starts with `- ` if it only appears in `old`, `+ ` if it only appears in `new`, or
+/// two spaces if it appears in both. Unchanged lines which are more than [DIFF_CONTEXT] lines away
+/// from a change are replaced by a single `  …` line.
+///
+/// The diff is computed from a longest common subsequence, which is quadratic in the number of
+/// lines: the caller is responsible for not diffing very long texts.
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // `lcs[i][j]` is the length of the longest common subsequence of `old[i..]` and `new[j..]`.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                usize::max(lcs[i + 1][j], lcs[i][j + 1])
+            };
+        }
+    }
+
+    // The lines of the diff, together with a flag telling if they're unchanged.
+    let mut lines = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((true, format!("  {}", old[i])));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push((false, format!("- {}", old[i])));
+            i += 1;
+        } else {
+            lines.push((false, format!("+ {}", new[j])));
+            j += 1;
+        }
+    }
+
+    let changes: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, (unchanged, _))| (!unchanged).then_some(idx))
+        .collect();
+    let near_change = |idx: usize| {
+        changes
+            .iter()
+            .any(|&change| idx.abs_diff(change) <= DIFF_CONTEXT)
+    };
+
+    let mut result = Vec::new();
+    let mut elided = false;
+
+    for (idx, (unchanged, line)) in lines.into_iter().enumerate() {
+        if !unchanged || near_change(idx) {
+            result.push(line);
+            elided = false;
+        } else if !elided {
+            result.push(String::from("  …"));
+            elided = true;
+        }
+    }
+
+    result
+}
@@ -0,0 +1,91 @@
+//! Structural hashing of values for the `std.set` and `std.hashmap` primitive operations.
+//!
+//! Sets and hash maps are represented on the Nickel side as records indexed by the structural key
+//! of their elements. Two values have the same key if and only if they are structurally equal
+//! (up to the collision resistance of the underlying hash function): in particular, the key
+//! distinguishes values of different types, such as `1` and `"1"`, and ignores the order of record
+//! fields.
+//!
+//! The value must have been fully evaluated and substituted beforehand. Values which can't be
+//! compared for equality, such as functions, don't have a structural key: [key] returns an error
+//! message, which is then wrapped in an [crate::error::EvalError] by the caller.
+use std::fmt::Write;
+
+use sha2::Digest;
+
+use crate::term::{RichTerm, Term};
+
+/// Compute the structural key of a value, as the hex-encoded SHA-256 digest of its canonical
+/// encoding.
+pub fn key(rt: &RichTerm) -> Result<String, String> {
+    let mut encoded = String::new();
+    encode(rt, &mut encoded)?;
+
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(encoded);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Write a length-prefixed string, so that the encoding of a sequence of values is unambiguous.
+fn encode_str(s: &str, out: &mut String) {
+    // Writing to a string can't fail.
+    let _ = write!(out, "{}:{s}", s.len());
+}
+
+/// Write the canonical encoding of a value. Each kind of value starts with a distinct tag.
+fn encode(rt: &RichTerm, out: &mut String) -> Result<(), String> {
+    match rt.as_ref() {
+        Term::Null => out.push('n'),
+        Term::Bool(b) => out.push_str(if *b { "t" } else { "f" }),
+        Term::Num(n) => {
+            out.push('d');
+            encode_str(&n.to_string(), out);
+        }
+        Term::Str(s) => {
+            out.push('s');
+            encode_str(s, out);
+        }
+        Term::Enum(tag) => {
+            out.push('e');
+            encode_str(tag.label(), out);
+        }
+        Term::EnumVariant { tag, arg, .. } => {
+            out.push('v');
+            encode_str(tag.label(), out);
+            encode(arg, out)?;
+        }
+        Term::Record(record) => {
+            let mut fields = Vec::with_capacity(record.fields.len());
+
+            for (id, field) in record.fields.iter() {
+                match field.value {
+                    Some(ref value) => fields.push((id.label(), value)),
+                    None if field.metadata.opt => (),
+                    None => return Err(format!("missing definition for field `{id}`")),
+                }
+            }
+
+            fields.sort_by_key(|(label, _)| *label);
+
+            let _ = write!(out, "r{};", fields.len());
+            for (label, value) in fields {
+                encode_str(label, out);
+                encode(value, out)?;
+            }
+        }
+        Term::Array(ts, _) => {
+            let _ = write!(out, "a{};", ts.len());
+            for t in ts.iter() {
+                encode(t, out)?;
+            }
+        }
+        t => {
+            return Err(format!(
+                "can't compute the structural key of a value of type {}",
+                t.type_of().unwrap_or_else(|| String::from("<unevaluated>"))
+            ))
+        }
+    }
+
+    Ok(())
+}
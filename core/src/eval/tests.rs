@@ -9,6 +9,7 @@ use crate::term::Number;
 use crate::term::{BinaryOp, StrChunk, UnaryOp};
 use crate::transform::import_resolution::strict::resolve_imports;
 use crate::{mk_app, mk_fun};
+use assert_matches::assert_matches;
 use codespan::Files;
 
 /// Evaluate a term without import support.
@@ -72,6 +73,149 @@ fn simple_app() {
     assert_eq!(Ok(Term::Num(Number::from(5))), eval_no_import(t));
 }
 
+#[test]
+fn fuel_bounds_evaluation() {
+    let looping = parse("let rec f = fun x => f x in f 0").unwrap();
+    let result = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink())
+        .with_fuel(1000)
+        .eval(looping);
+    assert_matches!(
+        result,
+        Err(EvalError::ResourceExhausted {
+            resource: EvalResource::Fuel,
+            limit: 1000,
+            ..
+        })
+    );
+
+    let t = mk_app!(mk_term::id(), mk_term::integer(5));
+    let result = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink())
+        .with_fuel(1000)
+        .eval(t)
+        .map(Term::from);
+    assert_eq!(Ok(Term::Num(Number::from(5))), result);
+}
+
+#[test]
+fn allocations_bound_evaluation() {
+    let looping = parse("let rec f = fun x => f (x + 1) in f 0").unwrap();
+    let mut vm = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink());
+    vm.set_max_allocations(1000);
+    assert_matches!(
+        vm.eval(looping),
+        Err(EvalError::ResourceExhausted {
+            resource: EvalResource::Allocations,
+            limit: 1000,
+            ..
+        })
+    );
+
+    let t = parse("let x = 1 in let y = 2 in x + y").unwrap();
+    let mut vm = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink());
+    vm.set_max_allocations(1000);
+    assert_eq!(vm.eval(t).map(Term::from), Ok(Term::Num(Number::from(3))));
+}
+
+#[test]
+fn random_uuids_are_opt_in() {
+    let t = parse("%uuid_v4% null").unwrap();
+    let mut vm = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink());
+    assert_matches!(vm.eval(t.clone()), Err(EvalError::Other(..)));
+
+    vm.allow_random_uuids();
+    assert_matches!(vm.eval(t).map(Term::from), Ok(Term::Str(uuid)) if uuid.len() == 36);
+}
+
+#[test]
+fn cancellation_stops_evaluation() {
+    let looping = parse("let rec f = fun x => f x in f 0").unwrap();
+    let token = cancel::CancellationToken::new();
+    let mut vm = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink());
+    vm.set_cancellation_token(token.clone());
+    token.cancel();
+    assert_matches!(vm.eval(looping), Err(EvalError::Cancelled(_)));
+}
+
+#[test]
+fn timeout_stops_evaluation() {
+    let looping = parse("let rec f = fun x => f x in f 0").unwrap();
+    let mut vm = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink());
+    vm.set_timeout(std::time::Duration::from_millis(50));
+    assert_matches!(
+        vm.eval(looping),
+        Err(EvalError::ResourceExhausted {
+            resource: EvalResource::Time,
+            limit: 50,
+            ..
+        })
+    );
+}
+
+#[test]
+fn match_compilation_strategies_agree() {
+    use crate::term::pattern::compile::MatchCompilation;
+
+    let mk_match = |value: &str| {
+        parse(&format!(
+            "{value} |> match {{ 'Foo x if x > 1 => 1, {{ a }} => 2, x => 3, 'Foo x => 4, \
+            'Bar => 5, true => 6 }}"
+        ))
+        .unwrap()
+    };
+
+    for value in ["'Foo 2", "'Foo 0", "{ a = 1 }", "'Bar", "true", "null"] {
+        let results: Vec<_> = [MatchCompilation::Sequential, MatchCompilation::DecisionTree]
+            .into_iter()
+            .map(|strategy| {
+                let mut vm = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink());
+                vm.set_match_compilation(strategy);
+                vm.eval(mk_match(value)).map(Term::from)
+            })
+            .collect();
+
+        assert_eq!(results[0], results[1], "strategies disagree on {value}");
+    }
+}
+
+#[test]
+fn record_match_compilation_strategies_agree() {
+    use crate::term::pattern::compile::MatchCompilation;
+
+    let mk_match = |value: &str| {
+        parse(&format!(
+            "{value} |> match {{ {{ a, b }} if a == 0 => 1, {{ a }} => 2, {{ b, .. }} => 3, \
+            {{ a, b }} => 4, {{ c ? 0, d }} => c + 5, {{ c, a }} => 6, {{}} => 7, x => 8 }}"
+        ))
+        .unwrap()
+    };
+
+    for value in [
+        "{ a = 0, b = 1 }",
+        "{ a = 1, b = 1 }",
+        "{ a = 1 }",
+        "{ a = 1, c | optional }",
+        "{ b = 1, c = 1 }",
+        "{ d = 1 }",
+        "{ c = 2, d = 1 }",
+        "{ a = 1, c = 1 }",
+        "{ \"a,b\" = 1 }",
+        "{}",
+        "{ e = 1 }",
+        "'Foo",
+    ] {
+        let results: Vec<_> = [MatchCompilation::Sequential, MatchCompilation::DecisionTree]
+            .into_iter()
+            .map(|strategy| {
+                let mut vm = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink());
+                vm.set_match_compilation(strategy);
+                vm.eval(mk_match(value)).map(Term::from)
+            })
+            .collect();
+
+        assert_eq!(results[0], results[1], "strategies disagree on {value}");
+    }
+}
+
 #[test]
 fn simple_let() {
     let t = mk_term::let_in("x", mk_term::integer(5), mk_term::var("x"));
@@ -347,3 +491,37 @@ fn substitution() {
         .to_string()
     );
 }
+
+#[test]
+fn diff_lines_elides_unchanged_lines() {
+    use structural_diff::diff_lines;
+
+    assert_eq!(diff_lines("1", "2"), vec!["- 1", "+ 2"]);
+
+    let old = "a\nb\nc\nd\ne\nf\ng\nh";
+    let new = "a\nb\nc\nd\nE\nf\ng\nh\ni";
+    assert_eq!(
+        diff_lines(old, new),
+        vec!["  …", "  c", "  d", "- e", "+ E", "  f", "  g", "  h", "+ i"]
+    );
+}
+
+#[test]
+fn deprecated_field_access_warns() {
+    use crate::error::warning::Warning;
+
+    let mut vm = VirtualMachine::<_, CacheImpl>::new(DummyResolver {}, std::io::sink());
+    let t = parse(
+        "let r = {foo | deprecated \"old\" | replaced_by \"bar\" = 1, bar = 2} in r.foo + r.foo + r.bar",
+    )
+    .unwrap();
+
+    assert_eq!(vm.eval(t).map(Term::from), Ok(Term::Num(Number::from(3))));
+    assert_matches!(
+        vm.take_warnings().as_slice(),
+        [Warning::DeprecatedField { field, deprecation, .. }]
+            if field.label() == "foo"
+                && deprecation.message.as_deref() == Some("old")
+                && deprecation.replacement.as_deref() == Some("bar")
+    );
+}
@@ -0,0 +1,101 @@
+//! Date and time support for the `std.time` primitive operations.
+//!
+//! Timestamps are represented on the Nickel side as RFC 3339 strings (e.g.
+//! `2024-03-01T12:00:00Z`), which is how they usually appear in configurations anyway, and
+//! durations as a number of seconds. The functions of this module return an error message on
+//! invalid input, which is then wrapped in an [crate::error::EvalError] by the caller.
+use std::fmt::Write;
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat, Utc,
+};
+use malachite::{num::conversion::traits::RoundingFrom, rounding_modes::RoundingMode, Integer};
+
+use crate::term::Number;
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Render a timestamp in the canonical RFC 3339 representation used by the stdlib.
+fn to_rfc3339(datetime: DateTime<FixedOffset>) -> String {
+    datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Parse a timestamp in the RFC 3339 representation used by the stdlib.
+fn from_rfc3339(timestamp: &str) -> Result<DateTime<FixedOffset>, String> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map_err(|err| format!("invalid RFC 3339 timestamp `{timestamp}` ({err})"))
+}
+
+/// Check that a strftime-like format string is valid, since chrono only reports invalid
+/// specifiers when actually formatting.
+fn check_format(format: &str) -> Result<(), String> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        Err(format!("invalid date/time format `{format}`"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parse `input` according to the strftime-like `format` and return the corresponding RFC 3339
+/// timestamp. If the format doesn't include an offset, the input is interpreted as UTC, and if it
+/// doesn't include a time, the timestamp is at midnight.
+pub fn parse(format: &str, input: &str) -> Result<String, String> {
+    check_format(format)?;
+
+    let datetime = DateTime::parse_from_str(input, format)
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(input, format).map(|naive| naive.and_utc().fixed_offset())
+        })
+        .or_else(|_| {
+            NaiveDate::parse_from_str(input, format)
+                .map(|date| date.and_time(Default::default()).and_utc().fixed_offset())
+        })
+        .map_err(|err| format!("couldn't parse `{input}` with format `{format}` ({err})"))?;
+
+    Ok(to_rfc3339(datetime))
+}
+
+/// Format an RFC 3339 timestamp according to the strftime-like `format`.
+pub fn format(format: &str, timestamp: &str) -> Result<String, String> {
+    check_format(format)?;
+    let datetime = from_rfc3339(timestamp)?;
+
+    let mut result = String::new();
+    write!(result, "{}", datetime.format(format))
+        .map_err(|_| format!("couldn't format `{timestamp}` with format `{format}`"))?;
+
+    Ok(result)
+}
+
+/// Add a duration, expressed as a (possibly negative or fractional) number of seconds, to an RFC
+/// 3339 timestamp. The duration is rounded to the nearest nanosecond.
+pub fn add(seconds: &Number, timestamp: &str) -> Result<String, String> {
+    let datetime = from_rfc3339(timestamp)?;
+
+    let nanos =
+        Integer::rounding_from(seconds * Number::from(NANOS_PER_SEC), RoundingMode::Nearest).0;
+    let out_of_range = || format!("adding {seconds} seconds to `{timestamp}` is out of range");
+    let nanos = i64::try_from(&nanos).map_err(|_| out_of_range())?;
+
+    datetime
+        .checked_add_signed(Duration::nanoseconds(nanos))
+        .map(to_rfc3339)
+        .ok_or_else(out_of_range)
+}
+
+/// Return the duration between two RFC 3339 timestamps in seconds, that is `timestamp1 -
+/// timestamp2`.
+pub fn diff(timestamp1: &str, timestamp2: &str) -> Result<Number, String> {
+    let datetime1 = from_rfc3339(timestamp1)?;
+    let datetime2 = from_rfc3339(timestamp2)?;
+
+    let duration = datetime1.with_timezone(&Utc) - datetime2.with_timezone(&Utc);
+    let seconds = duration.num_seconds();
+    // The remainder is less than a second, so this can't overflow.
+    let subsec_nanos = (duration - Duration::seconds(seconds))
+        .num_nanoseconds()
+        .unwrap_or_default();
+
+    Ok(Number::from(seconds) + Number::from(subsec_nanos) / Number::from(NANOS_PER_SEC))
+}
@@ -0,0 +1,80 @@
+//! URL support for the `std.url` primitive operations.
+//!
+//! A parsed URL is represented on the Nickel side as a record with a field per component of the
+//! URL. Components which are absent from the original URL are absent from the record as well, so
+//! that `std.url.to_string` can reconstruct the original URL (up to normalization). The functions
+//! of this module return an error message on invalid input, which is then wrapped in an
+//! [crate::error::EvalError] by the caller.
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::{
+    identifier::LocIdent,
+    term::{record::RecordData, Number, RichTerm, Term},
+};
+
+/// The characters which are percent-encoded in a URL component: everything but the unreserved
+/// characters of RFC 3986.
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Parse a URL into a record of its components: `scheme`, `username`, `password`, `host`, `port`,
+/// `path`, `query` and `fragment`. Only `scheme` and `path` are always present.
+///
+/// The URL is normalized on the way, following the WHATWG URL standard: for example, the scheme
+/// and the host of `HTTP://Example.COM` are lowercased, and its empty path becomes `/`.
+pub fn parse(input: &str) -> Result<RecordData, String> {
+    let url = ::url::Url::parse(input).map_err(|err| format!("invalid URL `{input}` ({err})"))?;
+    let str_field = |value: &str| RichTerm::from(Term::Str(value.into()));
+
+    let mut fields = vec![
+        ("scheme", str_field(url.scheme())),
+        ("path", str_field(url.path())),
+    ];
+
+    // URLs such as `file:///tmp` have an authority, but no host. We still record an empty host,
+    // which is what tells them apart from URLs such as `mailto:me@example.com`.
+    if url.has_authority() {
+        fields.push(("host", str_field(url.host_str().unwrap_or_default())));
+    }
+
+    if !url.username().is_empty() {
+        fields.push(("username", str_field(url.username())));
+    }
+
+    fields.extend(
+        url.password()
+            .map(|password| ("password", str_field(password))),
+    );
+    fields.extend(
+        url.port()
+            .map(|port| ("port", RichTerm::from(Term::Num(Number::from(port))))),
+    );
+    fields.extend(url.query().map(|query| ("query", str_field(query))));
+    fields.extend(
+        url.fragment()
+            .map(|fragment| ("fragment", str_field(fragment))),
+    );
+
+    Ok(RecordData::with_field_values(
+        fields
+            .into_iter()
+            .map(|(id, value)| (LocIdent::from(id), value)),
+    ))
+}
+
+/// Percent-encode a string so that it can be used as a single URL component, such as a path
+/// segment or a query parameter.
+pub fn encode(s: &str) -> String {
+    utf8_percent_encode(s, COMPONENT).to_string()
+}
+
+/// Decode a percent-encoded string. Fails if the decoded bytes aren't valid UTF-8.
+pub fn decode(s: &str) -> Result<String, String> {
+    percent_decode_str(s)
+        .decode_utf8()
+        .map(String::from)
+        .map_err(|_| format!("`{s}` doesn't decode to a valid UTF-8 string"))
+}
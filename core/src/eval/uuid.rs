@@ -0,0 +1,130 @@
+//! UUID support for the `std.uuid` primitive operations.
+//!
+//! UUIDs are represented on the Nickel side as strings in the canonical lowercase hyphenated form
+//! (e.g. `886313e1-3b8a-5372-9b90-0c9aee199e5d`). Name-based (version 5) UUIDs are pure, but
+//! random (version 4) UUIDs aren't: they are only available when the host provides a seed or
+//! allows random seeding, see [super::VirtualMachine::set_uuid_seed] and
+//! [super::VirtualMachine::allow_random_uuids]. The functions of this module return an error message on
+//! invalid input, which is then wrapped in an [crate::error::EvalError] by the caller.
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+use sha1::{Digest, Sha1};
+
+/// A deterministic generator of random (version 4) UUIDs, seeded by the host. This is a
+/// SplitMix64 generator: it isn't cryptographically secure, but it's plenty for generating unique
+/// identifiers, and the same seed always yields the same sequence of UUIDs.
+#[derive(Clone, Debug)]
+pub struct UuidGenerator {
+    state: u64,
+}
+
+impl UuidGenerator {
+    pub fn new(seed: u64) -> Self {
+        UuidGenerator { state: seed }
+    }
+
+    /// Create a generator seeded from the entropy of the operating system, which yields different
+    /// UUIDs on each run. The seed is taken from the random keys of the standard library's
+    /// [RandomState], which are initialized by the operating system's random number generator.
+    pub fn from_entropy() -> Self {
+        Self::new(RandomState::new().build_hasher().finish())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Generate the next random UUID.
+    pub fn v4(&mut self) -> String {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_be_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_be_bytes());
+
+        to_string(with_version(bytes, 4))
+    }
+}
+
+/// Set the version and the (RFC 4122) variant bits of a UUID.
+fn with_version(mut bytes: [u8; 16], version: u8) -> [u8; 16] {
+    bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    bytes
+}
+
+/// Render a UUID in the canonical lowercase hyphenated form.
+fn to_string(bytes: [u8; 16]) -> String {
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Parse a UUID. Besides the canonical hyphenated form, the simple form (32 hexadecimal digits),
+/// the braced form (`{...}`) and the URN form (`urn:uuid:...`) are accepted, in any case.
+fn to_bytes(input: &str) -> Result<[u8; 16], String> {
+    let invalid = || format!("invalid UUID `{input}`");
+
+    let uuid = if let Some(rest) = input.strip_prefix("urn:uuid:") {
+        rest
+    } else if let Some(rest) = input.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+        rest
+    } else {
+        input
+    };
+
+    let hex: String = match uuid.len() {
+        32 => uuid.to_owned(),
+        36 if [8, 13, 18, 23]
+            .iter()
+            .all(|&idx| uuid.as_bytes()[idx] == b'-') =>
+        {
+            uuid.replace('-', "")
+        }
+        _ => return Err(invalid()),
+    };
+
+    // `from_str_radix` accepts a leading sign, so we need to check the digits beforehand.
+    if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let mut bytes = [0u8; 16];
+    for (idx, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * idx..2 * idx + 2], 16).map_err(|_| invalid())?;
+    }
+
+    Ok(bytes)
+}
+
+/// Parse a UUID and return its canonical lowercase hyphenated form.
+pub fn parse(input: &str) -> Result<String, String> {
+    to_bytes(input).map(to_string)
+}
+
+/// Generate the name-based (version 5) UUID of `name` in the namespace `namespace`, which must be
+/// a valid UUID itself.
+pub fn v5(namespace: &str, name: &str) -> Result<String, String> {
+    let namespace = to_bytes(namespace)?;
+
+    let mut hasher = Sha1::new();
+    hasher.update(namespace);
+    hasher.update(name.as_bytes());
+
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hasher.finalize()[..16]);
+
+    Ok(to_string(with_version(bytes, 5)))
+}
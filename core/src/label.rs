@@ -342,7 +342,8 @@ impl ReifyAsUnifType for Polarity {
     }
 }
 /// A polarity. See [`Label`]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Polarity {
     Positive,
     Negative,
@@ -380,7 +381,7 @@ impl TryFrom<&Term> for Polarity {
 
 /// Custom reporting diagnostic that can be set by user-code through the `label` API. Used to
 /// customize contract error messages, and provide more context than "a contract has failed".
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize)]
 pub struct ContractDiagnostic {
     /// The main error message tag to be printed together with the error message.
     pub message: Option<String>,
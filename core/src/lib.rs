@@ -7,6 +7,7 @@ pub mod error;
 pub mod eval;
 pub mod identifier;
 pub mod label;
+pub mod lint;
 #[cfg(feature = "nix-experimental")]
 pub mod nix_ffi;
 pub mod parser;
@@ -0,0 +1,288 @@
+//! Checks of parsed terms for code which is valid but most likely wrong. The checks don't modify
+//! the term: they only report [warnings][crate::error::warning::Warning].
+use crate::{
+    error::warning::Warning,
+    identifier::LocIdent,
+    position::TermPos,
+    term::{
+        pattern::{
+            ArrayPattern, EnumPattern, PatternData, RecordPattern, RecordPatternTail, TuplePattern,
+        },
+        MatchData, RichTerm, Term, Traverse, TraverseControl,
+    },
+};
+
+/// Check a parsed term and return the warnings found.
+pub fn lint(rt: &RichTerm) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    rt.traverse_ref(
+        &mut |rt: &RichTerm, _: &()| {
+            if let Term::Match(data) = rt.as_ref() {
+                unreachable_branches(data, &mut warnings);
+                redundant_branches(data, &mut warnings);
+                uncovered_tags(data, rt.pos, &mut warnings);
+            }
+
+            TraverseControl::<(), ()>::Continue
+        },
+        &(),
+    );
+
+    warnings
+}
+
+/// Return the index of the first branch which matches any value, if any.
+fn catch_all_branch(data: &MatchData) -> Option<usize> {
+    data.branches.iter().position(|branch| {
+        branch.guard.is_none()
+            && matches!(
+                branch.pattern.data,
+                PatternData::Wildcard | PatternData::Any(_)
+            )
+    })
+}
+
+/// Warn about the branches following a branch which matches any value, as they can never be
+/// selected.
+fn unreachable_branches(data: &MatchData, warnings: &mut Vec<Warning>) {
+    let catch_all = catch_all_branch(data);
+
+    if let Some(idx) = catch_all {
+        let catch_all_pos = data.branches[idx].pattern.pos;
+
+        warnings.extend(data.branches[idx + 1..].iter().map(|branch| {
+            Warning::UnreachableMatchBranch {
+                pos: branch.pattern.pos,
+                catch_all_pos,
+            }
+        }));
+    }
+}
+
+/// Check if `general` matches all the values matched by `specific`. The check is conservative: it
+/// may return `false` for some patterns which do match the same values.
+fn subsumes(general: &PatternData, specific: &PatternData) -> bool {
+    match (general, specific) {
+        (PatternData::Wildcard | PatternData::Any(_), _) => true,
+        (
+            PatternData::Enum(EnumPattern {
+                tag: tag1,
+                pattern: None,
+                ..
+            }),
+            PatternData::Enum(EnumPattern {
+                tag: tag2,
+                pattern: None,
+                ..
+            }),
+        ) => tag1 == tag2,
+        (
+            PatternData::Enum(EnumPattern {
+                tag: tag1,
+                pattern: Some(arg1),
+                ..
+            }),
+            PatternData::Enum(EnumPattern {
+                tag: tag2,
+                pattern: Some(arg2),
+                ..
+            }),
+        ) => tag1 == tag2 && subsumes(&arg1.data, &arg2.data),
+        (PatternData::Constant(c1), PatternData::Constant(c2)) => c1.data == c2.data,
+        (PatternData::Record(r1), PatternData::Record(r2)) => record_subsumes(r1, r2),
+        (PatternData::Array(a1), PatternData::Array(a2)) => array_subsumes(a1, a2),
+        (PatternData::Tuple(t1), PatternData::Tuple(t2)) => tuple_subsumes(t1, t2),
+        _ => false,
+    }
+}
+
+/// Check if the record pattern `general` matches all the values matched by `specific`. Fields with
+/// a default value are never considered, as they match records which don't have the field.
+fn record_subsumes(general: &RecordPattern, specific: &RecordPattern) -> bool {
+    let has_default =
+        |pat: &RecordPattern| pat.patterns.iter().any(|field| field.default.is_some());
+
+    if has_default(general) || has_default(specific) {
+        return false;
+    }
+
+    // A closed pattern only matches records with exactly the fields of the pattern.
+    let tails_compatible = match general.tail {
+        RecordPatternTail::Empty => {
+            matches!(specific.tail, RecordPatternTail::Empty)
+                && general.patterns.len() == specific.patterns.len()
+        }
+        RecordPatternTail::Open | RecordPatternTail::Capture(_) => true,
+    };
+
+    tails_compatible
+        && general.patterns.iter().all(|field| {
+            specific
+                .patterns
+                .iter()
+                .find(|other| other.matched_id == field.matched_id)
+                .is_some_and(|other| subsumes(&field.pattern.data, &other.pattern.data))
+        })
+}
+
+/// Check if the array pattern `general` matches all the values matched by `specific`.
+fn array_subsumes(general: &ArrayPattern, specific: &ArrayPattern) -> bool {
+    // A closed pattern only matches arrays with exactly as many elements as the pattern, while an
+    // open pattern matches arrays with at least as many elements.
+    let lengths_compatible = if general.is_open() {
+        specific.patterns.len() >= general.patterns.len()
+    } else {
+        !specific.is_open() && general.patterns.len() == specific.patterns.len()
+    };
+
+    lengths_compatible
+        && general
+            .patterns
+            .iter()
+            .zip(specific.patterns.iter())
+            .all(|(pat1, pat2)| subsumes(&pat1.data, &pat2.data))
+}
+
+/// Check if the tuple pattern `general` matches all the values matched by `specific`.
+fn tuple_subsumes(general: &TuplePattern, specific: &TuplePattern) -> bool {
+    general.patterns.len() == specific.patterns.len()
+        && general
+            .patterns
+            .iter()
+            .zip(specific.patterns.iter())
+            .all(|(pat1, pat2)| subsumes(&pat1.data, &pat2.data))
+}
+
+/// Warn about the branches which can't be selected because an earlier branch without guard matches
+/// all the values they match. The branches following a catch-all branch are already reported by
+/// [unreachable_branches].
+fn redundant_branches(data: &MatchData, warnings: &mut Vec<Warning>) {
+    let end = catch_all_branch(data).unwrap_or(data.branches.len());
+
+    for (idx, branch) in data.branches[..end].iter().enumerate() {
+        let shadowing = data.branches[..idx].iter().find(|earlier| {
+            earlier.guard.is_none() && subsumes(&earlier.pattern.data, &branch.pattern.data)
+        });
+
+        if let Some(earlier) = shadowing {
+            warnings.push(Warning::RedundantMatchBranch {
+                pos: branch.pattern.pos,
+                shadowed_by: earlier.pattern.pos,
+            });
+        }
+    }
+}
+
+/// Warn about the enum tags which are only matched by branches with a guard, in a match without a
+/// catch-all branch. When the match is statically typed, its argument is inferred to be an enum
+/// with these tags, but the match fails if the guards are false.
+fn uncovered_tags(data: &MatchData, pos: TermPos, warnings: &mut Vec<Warning>) {
+    if catch_all_branch(data).is_some() {
+        return;
+    }
+
+    let tag_of = |data: &PatternData| match data {
+        PatternData::Enum(EnumPattern { tag, .. }) => Some(*tag),
+        _ => None,
+    };
+
+    let mut tags: Vec<LocIdent> = Vec::new();
+
+    for branch in data.branches.iter() {
+        if let Some(tag) = tag_of(&branch.pattern.data) {
+            if branch.guard.is_some()
+                && !tags.contains(&tag)
+                && !data
+                    .branches
+                    .iter()
+                    .any(|other| other.guard.is_none() && tag_of(&other.pattern.data) == Some(tag))
+            {
+                tags.push(tag);
+            }
+        }
+    }
+
+    if !tags.is_empty() {
+        warnings.push(Warning::UncoveredEnumTags { tags, pos });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{grammar, lexer, ErrorTolerantParser};
+    use codespan::Files;
+
+    fn lint_str(s: &str) -> Vec<Warning> {
+        let id = Files::new().add("<test>", String::from(s));
+        let rt = grammar::TermParser::new()
+            .parse_strict(id, lexer::Lexer::new(s))
+            .unwrap();
+
+        lint(&rt)
+    }
+
+    #[test]
+    fn unreachable_match_branches() {
+        assert!(lint_str("match { 'a => 1, x => 2 }").is_empty());
+        assert!(lint_str("match { x if x > 0 => 1, 'a => 2 }").is_empty());
+        assert_eq!(
+            lint_str("match { 'a => 1, _ => 2, 'b => 3, y => 4 }").len(),
+            2
+        );
+        assert_eq!(
+            lint_str("match { x => match { _ => 1, 'c => 2 } x, 'b => 3 }").len(),
+            2
+        );
+    }
+
+    #[test]
+    fn redundant_match_branches() {
+        let redundant = |s: &str| {
+            lint_str(s)
+                .into_iter()
+                .filter(|w| matches!(w, Warning::RedundantMatchBranch { .. }))
+                .count()
+        };
+
+        assert_eq!(redundant("match { 'a => 1, 'b => 2, 'a => 3 }"), 1);
+        assert_eq!(redundant("match { 'a x => 1, 'a 'b => 2, 'a => 3 }"), 1);
+        assert_eq!(redundant("match { 'a if true => 1, 'a => 2 }"), 0);
+        assert_eq!(
+            redundant("match { {x, ..} => 1, {x, y} => 2, {y} => 3 }"),
+            1
+        );
+        assert_eq!(redundant("match { {x} => 1, {x, y} => 2 }"), 0);
+        assert_eq!(redundant("match { {x ? 1} => 1, {x} => 2 }"), 0);
+        assert_eq!(redundant("match { 1 => 1, \"1\" => 2, 1 => 3 }"), 1);
+        assert_eq!(redundant("match { [x, ..] => 1, [1, y] => 2, [] => 3 }"), 1);
+        assert_eq!(redundant("match { [x, y] => 1, [x, ..rest] => 2 }"), 0);
+        assert_eq!(
+            redundant("match { (x, 1) => 1, (2, 1) => 2, (x, y) => 3 }"),
+            1
+        );
+    }
+
+    #[test]
+    fn uncovered_enum_tags() {
+        let uncovered = |s: &str| {
+            lint_str(s)
+                .into_iter()
+                .find_map(|w| match w {
+                    Warning::UncoveredEnumTags { tags, .. } => {
+                        Some(tags.iter().map(|tag| tag.label().to_owned()).collect())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_else(Vec::<String>::new)
+        };
+
+        assert_eq!(
+            uncovered("match { 'a x if x => 1, 'b if false => 2, 'c => 3 }"),
+            vec!["a", "b"]
+        );
+        assert!(uncovered("match { 'a if false => 1, 'a => 2 }").is_empty());
+        assert!(uncovered("match { 'a if false => 1, _ => 2 }").is_empty());
+    }
+}
@@ -192,6 +192,8 @@ pub enum NormalToken<'input> {
 
     #[token("%apply_contract%")]
     ApplyContract,
+    #[token("%contract_freeze%")]
+    ContractFreeze,
     #[token("%array_lazy_app_ctr%")]
     ArrayLazyAppCtr,
     #[token("%record_lazy_app_ctr%")]
@@ -256,6 +258,14 @@ pub enum NormalToken<'input> {
     ValuesOf,
     #[token("%pow%")]
     Pow,
+    #[token("%bit_and%")]
+    BitAnd,
+    #[token("%bit_or%")]
+    BitOr,
+    #[token("%bit_xor%")]
+    BitXor,
+    #[token("%bit_shift%")]
+    BitShift,
     #[token("%trace%")]
     Trace,
 
@@ -292,9 +302,16 @@ pub enum NormalToken<'input> {
     Force,
     #[token("not_exported")]
     NotExported,
+    // `deprecated` and `replaced_by` are only keywords right after a `|`, and are lexed as
+    // identifiers everywhere else. These tokens are thus never produced by logos directly, but
+    // by [Lexer::handle_normal_token].
+    Deprecated,
+    ReplacedBy,
 
     #[token("%hash%")]
     OpHash,
+    #[token("%structural_diff%")]
+    StructuralDiff,
     #[token("%serialize%")]
     Serialize,
     #[token("%deserialize%")]
@@ -321,16 +338,56 @@ pub enum NormalToken<'input> {
     StrFind,
     #[token("%str_find_all%")]
     StrFindAll,
+    #[token("%str_split_keep%")]
+    StrSplitKeep,
     #[token("%str_length%")]
     StrLength,
     #[token("%str_substr%")]
     StrSubstr,
+    #[token("%time_parse%")]
+    TimeParse,
+    #[token("%time_format%")]
+    TimeFormat,
+    #[token("%time_add%")]
+    TimeAdd,
+    #[token("%time_diff%")]
+    TimeDiff,
+    #[token("%uuid_v5%")]
+    UuidV5,
+    #[token("%semver_compare%")]
+    SemverCompare,
+    #[token("%semver_satisfies%")]
+    SemverSatisfies,
     #[token("%to_str%")]
     ToStr,
     #[token("%num_from_str%")]
     NumFromStr,
     #[token("%enum_from_str%")]
     EnumFromStr,
+    #[token("%url_parse%")]
+    UrlParse,
+    #[token("%url_encode%")]
+    UrlEncode,
+    #[token("%url_decode%")]
+    UrlDecode,
+    #[token("%base64_encode%")]
+    Base64Encode,
+    #[token("%base64_decode%")]
+    Base64Decode,
+    #[token("%semver_parse%")]
+    SemverParse,
+    #[token("%structural_key%")]
+    StructuralKey,
+    #[token("%uuid_v4%")]
+    UuidV4,
+    #[token("%uuid_parse%")]
+    UuidParse,
+    #[token("%env_get%")]
+    EnvGet,
+    #[token("%fs_read_text%")]
+    FsReadText,
+    #[token("%fs_read_dir%")]
+    FsReadDir,
     #[token("%enum_unwrap_variant%")]
     EnumUnwrapVariant,
     #[token("%enum_is_variant%")]
@@ -338,6 +395,19 @@ pub enum NormalToken<'input> {
     #[token("%enum_get_tag%")]
     EnumGetTag,
 
+    #[token("%record_deep_map%")]
+    RecordDeepMap,
+    #[token("%record_deep_filter%")]
+    RecordDeepFilter,
+    #[token("%record_deep_merge_with%")]
+    RecordDeepMergeWith,
+    #[token("%record_sort_fields%")]
+    RecordSortFields,
+    #[token("%record_sort_fields_by%")]
+    RecordSortFieldsBy,
+    #[token("%record_move_first%")]
+    RecordMoveFirst,
+
     #[token("%label_with_message%")]
     LabelWithMessage,
     #[token("%label_with_notes%")]
@@ -348,6 +418,24 @@ pub enum NormalToken<'input> {
     LabelPushDiag,
     #[token("%array_slice%")]
     ArraySlice,
+    #[token("%array_sort_by%")]
+    ArraySortBy,
+    #[token("%array_chunks%")]
+    ArrayChunks,
+    #[token("%array_windows%")]
+    ArrayWindows,
+    #[token("%array_zip_with%")]
+    ArrayZipWith,
+    #[token("%sequence_take%")]
+    SequenceTake,
+    #[token("%csv_parse%")]
+    CsvParse,
+    #[token("%number_round%")]
+    NumberRound,
+    #[token("%hash_with%")]
+    HashWith,
+    #[token("%hmac%")]
+    Hmac,
     #[token("%eval_nix%")]
     EvalNix,
 
@@ -547,6 +635,9 @@ pub struct NormalData {
     /// The current brace counter to determine if a closing brace is the end of
     /// an interpolated expression.
     brace_count: usize,
+    /// Whether the previous token (ignoring comments) was a `|`, in which case `deprecated` and
+    /// `replaced_by` are lexed as metadata keywords.
+    after_pipe: bool,
 }
 
 impl NormalData {
@@ -587,7 +678,7 @@ impl<'input> Lexer<'input> {
     pub fn new(s: &'input str) -> Self {
         Lexer {
             lexer: Some(ModalLexer::Normal {
-                mode_data: NormalData { brace_count: 0 },
+                mode_data: NormalData::new(),
                 logos_lexer: NormalToken::lexer(s),
             }),
             modes: Vec::new(),
@@ -748,6 +839,18 @@ impl<'input> Lexer<'input> {
         span: Range<usize>,
         token: NormalToken<'input>,
     ) -> Option<Result<SpannedToken<'input>, ParseError>> {
+        let after_pipe = self.normal_mode_data_mut().after_pipe;
+
+        let token = match token {
+            NormalToken::Identifier("deprecated") if after_pipe => NormalToken::Deprecated,
+            NormalToken::Identifier("replaced_by") if after_pipe => NormalToken::ReplacedBy,
+            token => token,
+        };
+
+        if !matches!(token, NormalToken::LineComment) {
+            self.normal_mode_data_mut().after_pipe = matches!(token, NormalToken::Pipe);
+        }
+
         match token {
             NormalToken::DoubleQuote | NormalToken::StrEnumTagBegin => self.enter_str(),
             NormalToken::MultiStringStart(delim_size)
@@ -123,12 +123,43 @@ where
         lexer: lexer::Lexer,
     ) -> Result<(T, ParseErrors), ParseError> {
         let mut parse_errors = Vec::new();
+        let mut lexical_errors = Vec::new();
         let mut next_wildcard_id = 0;
+
+        // Localized lexical errors (an unexpected character or an invalid escape sequence) don't
+        // prevent the lexer from going on. We skip the offending token and report the error
+        // alongside the other recoverable errors, so that the rest of the input is still parsed.
+        let tokens = lexer.filter(|token| match token {
+            Err(
+                err @ self::error::ParseError::Lexical(
+                    error::LexicalError::Generic(_)
+                    | error::LexicalError::InvalidEscapeSequence(_)
+                    | error::LexicalError::InvalidAsciiEscapeCode(_),
+                ),
+            ) => {
+                lexical_errors.push(err.clone());
+                false
+            }
+            _ => true,
+        });
+
         let result = self
-            .parse(file_id, &mut parse_errors, &mut next_wildcard_id, lexer)
+            .parse(file_id, &mut parse_errors, &mut next_wildcard_id, tokens)
             .map_err(|err| ParseError::from_lalrpop(err, file_id));
 
-        let parse_errors = ParseErrors::from_recoverable(parse_errors, file_id);
+        // Lexical errors usually cause the syntax errors that follow them, so we report them
+        // first.
+        let parse_errors = ParseErrors::from_recoverable(
+            lexical_errors
+                .into_iter()
+                .map(|error| lalrpop_util::ErrorRecovery {
+                    error: lalrpop_util::ParseError::User { error },
+                    dropped_tokens: Vec::new(),
+                })
+                .chain(parse_errors)
+                .collect(),
+            file_id,
+        );
         match result {
             Ok(t) => Ok((t, parse_errors)),
             Err(e) => Err(e),
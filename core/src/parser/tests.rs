@@ -451,6 +451,21 @@ fn carriage_returns() {
     assert_matches!(parse("foo\rbar"), Err(ParseError::UnexpectedToken(..)))
 }
 
+#[test]
+fn lexical_errors_are_recoverable() {
+    let s = "{ foo = 1\r, bar = \"bad escape \\g\" }";
+    let id = Files::new().add("<test>", String::from(s));
+
+    let (term, errors) = super::grammar::TermParser::new()
+        .parse_tolerant(id, Lexer::new(s))
+        .unwrap();
+
+    assert_eq!(errors.errors.len(), 2);
+    assert_matches!(errors.errors[0], ParseError::UnexpectedToken(..));
+    assert_matches!(errors.errors[1], ParseError::InvalidEscapeSequence(..));
+    assert_matches!(term.as_ref(), RecRecord(data, ..) if data.fields.len() == 2);
+}
+
 #[test]
 fn ascii_escape() {
     assert_matches!(
@@ -570,3 +585,25 @@ fn import() {
         )
     );
 }
+
+#[test]
+fn deprecated_is_not_reserved() {
+    for src in [
+        "let deprecated = 1 in deprecated",
+        "fun deprecated replaced_by => deprecated + replaced_by",
+        "let f = fun { deprecated, .. } => deprecated in f { deprecated = 1 }",
+        "{ deprecated = 1, replaced_by = 2 }",
+    ] {
+        assert_matches!(parse(src), Ok(_), "{}", src);
+    }
+
+    assert_eq!(
+        lex_without_pos("x | # comment\n deprecated deprecated").unwrap(),
+        vec![
+            Token::Normal(NormalToken::Identifier("x")),
+            Token::Normal(NormalToken::Pipe),
+            Token::Normal(NormalToken::Deprecated),
+            Token::Normal(NormalToken::Identifier("deprecated")),
+        ]
+    );
+}
@@ -9,6 +9,7 @@ use crate::{
     identifier::Ident,
     position::{RawSpan, TermPos},
     term::{
+        array::ArrayAttrs,
         record::{Field, FieldMetadata, RecordAttrs},
         LabeledType, MergePriority, RichTerm, Term, TypeAnnotation,
     },
@@ -53,6 +54,8 @@ pub enum UniTermNode {
     Var(LocIdent),
     /// A record. Can refer both to a record literal or a record type.
     Record(UniRecord),
+    /// A tuple. Can refer both to a tuple literal or a tuple type.
+    Tuple(Vec<UniTerm>),
     /// A uniterm that has been determined to be a term.
     Term(RichTerm),
     /// A uniterm that has been determined to be a type.
@@ -92,6 +95,11 @@ impl TryFrom<UniTerm> for Type {
         let ty_without_pos = match ut.node {
             UniTermNode::Var(id) => Type::from(TypeF::Var(id.ident())),
             UniTermNode::Record(r) => Type::try_from(r)?,
+            UniTermNode::Tuple(elts) => Type::from(TypeF::Tuple(
+                elts.into_iter()
+                    .map(|ut| Type::try_from(ut).map(Box::new))
+                    .collect::<Result<_, _>>()?,
+            )),
             UniTermNode::Type(ty) => ty,
             UniTermNode::Term(rt) => {
                 if matches!(
@@ -126,6 +134,15 @@ impl TryFrom<UniTerm> for RichTerm {
         let rt = match node {
             UniTermNode::Var(id) => RichTerm::new(Term::Var(id), pos),
             UniTermNode::Record(r) => RichTerm::try_from(r)?,
+            UniTermNode::Tuple(elts) => RichTerm::new(
+                Term::Array(
+                    elts.into_iter()
+                        .map(RichTerm::try_from)
+                        .collect::<Result<_, _>>()?,
+                    ArrayAttrs::new().tuple(),
+                ),
+                pos,
+            ),
             UniTermNode::Type(mut ty) => {
                 ty.fix_type_vars(pos.unwrap())?;
                 if let TypeF::Flat(rt) = ty.typ {
@@ -327,6 +344,7 @@ impl UniRecord {
                             opt: false,
                             not_exported: false,
                             priority: MergePriority::Neutral,
+                            deprecated: None,
                         },
                     // At this stage, this field should always be empty. It's a run-time thing, and
                     // is only filled during program transformation.
@@ -362,6 +380,7 @@ impl UniRecord {
                             opt: false,
                             not_exported: false,
                             priority: MergePriority::Neutral,
+                            deprecated: None,
                         },
                     // At this stage, this field should always be empty. It's a run-time thing, and
                     // is only filled during program transformation.
@@ -749,6 +768,9 @@ impl FixTypeVars for Type {
             } | TypeF::Array(ref mut ty) => {
                 (*ty).fix_type_vars_env(bound_vars, span)
             }
+            TypeF::Tuple(ref mut tys) => tys
+                .iter_mut()
+                .try_for_each(|ty| ty.fix_type_vars_env(bound_vars.clone(), span)),
             TypeF::Enum(ref mut erows) => erows.fix_type_vars_env(bound_vars, span),
             TypeF::Record(ref mut rrows) => rrows.fix_type_vars_env(bound_vars, span),
         }
@@ -21,7 +21,7 @@ use crate::{
     term::pattern::{Pattern, PatternData},
     term::{
         make as mk_term,
-        record::{Field, FieldMetadata, RecordAttrs, RecordData},
+        record::{Deprecation, Field, FieldMetadata, RecordAttrs, RecordData},
         *,
     },
     typ::Type,
@@ -302,6 +302,7 @@ impl Combine for FieldMetadata {
             // The resulting field will be suppressed from serialization if either of the fields to be merged is.
             not_exported: left.not_exported || right.not_exported,
             priority,
+            deprecated: Deprecation::combine(left.deprecated, right.deprecated),
         }
     }
 }
@@ -583,6 +584,10 @@ fn merge_fields(id_span: RawSpan, field1: Field, field2: Field) -> Field {
             opt: field1.metadata.opt && field2.metadata.opt,
             not_exported: field1.metadata.not_exported || field2.metadata.not_exported,
             priority,
+            deprecated: Deprecation::combine(
+                field1.metadata.deprecated,
+                field2.metadata.deprecated,
+            ),
         },
         pending_contracts: Vec::new(),
     }
@@ -637,6 +642,60 @@ pub fn mk_let(
     }
 }
 
+/// Generate a group of mutually recursive let-bindings `let rec id1 = t1, .., idn = tn in body`.
+/// The bindings are put in a recursive record, so that they can refer to each other and their
+/// dependencies are tracked as for any recursive record. The group is desugared to:
+///
+/// ```text
+/// let %group = { id1 = t1, .., idn = tn } in
+/// let id1 = %group.id1 in
+/// ..
+/// let idn = %group.idn in
+/// body
+/// ```
+pub fn mk_let_rec_group(
+    bindings: Vec<(Pattern, RichTerm)>,
+    body: RichTerm,
+    span: RawSpan,
+) -> Result<RichTerm, ParseError> {
+    let mut fields: IndexMap<LocIdent, Field> = IndexMap::new();
+
+    for (pat, value) in bindings {
+        let PatternData::Any(id) = pat.data else {
+            return Err(ParseError::RecursiveLetPattern(span));
+        };
+
+        match fields.entry(id) {
+            Entry::Occupied(occupied) => {
+                return Err(ParseError::DuplicateIdentInLetRec {
+                    ident: id,
+                    prev_ident: *occupied.key(),
+                })
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(Field::from(value));
+            }
+        }
+    }
+
+    let group_id = LocIdent::fresh();
+    let ids: Vec<LocIdent> = fields.keys().copied().collect();
+    let group = RichTerm::new(
+        Term::RecRecord(
+            RecordData::new(fields, RecordAttrs::default(), None),
+            Vec::new(),
+            None,
+        ),
+        TermPos::Original(span),
+    );
+
+    let body = ids.into_iter().rev().fold(body, |body, id| {
+        mk_term::let_in(id, mk_term::static_access(Term::Var(group_id), [id]), body)
+    });
+
+    Ok(mk_term::let_in(group_id, group, body))
+}
+
 /// Generate a `Fun` (when the pattern is trivial) or a `FunPattern` from the parsing of a function
 /// definition. This function panics if the definition somehow has neither an `Ident` nor a
 /// non-`Empty` `Destruct` pattern.
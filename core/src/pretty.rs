@@ -3,6 +3,7 @@ use std::fmt;
 use crate::identifier::LocIdent;
 use crate::parser::lexer::KEYWORDS;
 use crate::term::{
+    array::Array,
     pattern::*,
     record::{Field, FieldMetadata, RecordData},
     *,
@@ -150,21 +151,194 @@ fn needs_parens_in_type_pos(typ: &Type) -> bool {
     }
 }
 
-pub fn fmt_pretty<'a, T>(value: &T, f: &mut fmt::Formatter) -> fmt::Result
+/// When to break lines when pretty printing.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, clap::ValueEnum)]
+pub enum LineBreakStyle {
+    /// Break lines only when an expression doesn't fit in the maximum width.
+    #[default]
+    Fit,
+    /// Break lines wherever possible, putting each element of a record, an array, etc. on its own
+    /// line.
+    Always,
+    /// Never break lines, rendering each expression on a single line.
+    Never,
+}
+
+impl fmt::Display for LineBreakStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fit => write!(f, "fit"),
+            Self::Always => write!(f, "always"),
+            Self::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// Layout options of the pretty printer.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, clap::Args)]
+pub struct PrettyOptions {
+    /// The maximum width of the pretty-printed output, in columns. Only used when breaking lines
+    /// to fit
+    #[arg(long, default_value_t = 80)]
+    pub width: usize,
+    /// The number of spaces of one level of indentation in the pretty-printed output
+    #[arg(long, default_value_t = 2)]
+    pub indent: usize,
+    /// When to break lines in the pretty-printed output: only when an expression doesn't fit in
+    /// the maximum width, always, or never
+    #[arg(long, value_enum, default_value_t)]
+    pub line_breaks: LineBreakStyle,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        PrettyOptions {
+            width: 80,
+            indent: 2,
+            line_breaks: LineBreakStyle::Fit,
+        }
+    }
+}
+
+impl PrettyOptions {
+    /// The width passed to the renderer, which is what actually controls line breaking.
+    fn render_width(&self) -> usize {
+        match self.line_breaks {
+            LineBreakStyle::Fit => self.width,
+            LineBreakStyle::Always => 0,
+            LineBreakStyle::Never => usize::MAX,
+        }
+    }
+}
+
+/// A document allocator which knows about the layout options which can't be set at rendering
+/// time, that is the indentation size. Documents are allocated as with [pretty::BoxAllocator].
+#[derive(Copy, Clone, Debug)]
+pub struct Allocator {
+    indent: isize,
+    max_elements: Option<usize>,
+}
+
+impl Allocator {
+    pub fn new(options: &PrettyOptions) -> Self {
+        Allocator {
+            indent: isize::try_from(options.indent).unwrap_or(isize::MAX),
+            max_elements: None,
+        }
+    }
+
+    /// Only print the first `max` elements of arrays and the first `max` fields of records, in the
+    /// order they are printed. Elided array elements are replaced by a marker showing how many of
+    /// them there are, as in `[ 1, 2, … 998 more ]`, and records with elided fields are printed as
+    /// open records. The output isn't valid Nickel anymore: this is meant to shorten the terms
+    /// shown to the user, e.g. in error messages.
+    pub fn with_max_elements(mut self, max: usize) -> Self {
+        self.max_elements = Some(max);
+        self
+    }
+}
+
+impl<'a, A: 'a> DocAllocator<'a, A> for Allocator {
+    type Doc = pretty::BoxDoc<'a, A>;
+
+    fn alloc(&'a self, doc: pretty::Doc<'a, Self::Doc, A>) -> Self::Doc {
+        DocAllocator::alloc(&pretty::BoxAllocator, doc)
+    }
+
+    fn alloc_column_fn(
+        &'a self,
+        f: impl Fn(usize) -> Self::Doc + 'a,
+    ) -> <Self::Doc as pretty::DocPtr<'a, A>>::ColumnFn {
+        DocAllocator::alloc_column_fn(&pretty::BoxAllocator, f)
+    }
+
+    fn alloc_width_fn(
+        &'a self,
+        f: impl Fn(isize) -> Self::Doc + 'a,
+    ) -> <Self::Doc as pretty::DocPtr<'a, A>>::WidthFn {
+        DocAllocator::alloc_width_fn(&pretty::BoxAllocator, f)
+    }
+}
+
+pub fn fmt_pretty<T>(value: &T, options: &PrettyOptions, f: &mut fmt::Formatter) -> fmt::Result
+where
+    T: for<'a> Pretty<'a, Allocator, ()> + Clone,
+{
+    let allocator = Allocator::new(options);
+    let doc: DocBuilder<_, ()> = value.clone().pretty(&allocator);
+    doc.render_fmt(options.render_width(), f)
+}
+
+/// Pretty print a value to a string, using the given layout options.
+pub fn to_string_with<T>(value: T, options: &PrettyOptions) -> String
+where
+    T: for<'a> Pretty<'a, Allocator, ()>,
+{
+    let allocator = Allocator::new(options);
+    let doc: DocBuilder<_, ()> = value.pretty(&allocator);
+    let mut out = String::new();
+    // Writing to a string can't fail.
+    let _ = doc.render_fmt(options.render_width(), &mut out);
+    out
+}
+
+/// Pretty print a value to a string, using the default layout options and keeping at most
+/// `max_elements` elements of each array and record. See [Allocator::with_max_elements].
+pub fn to_string_elided<T>(value: T, max_elements: usize) -> String
+where
+    T: for<'a> Pretty<'a, Allocator, ()>,
+{
+    let options = PrettyOptions::default();
+    let allocator = Allocator::new(&options).with_max_elements(max_elements);
+    let doc: DocBuilder<_, ()> = value.pretty(&allocator);
+    let mut out = String::new();
+    // Writing to a string can't fail.
+    let _ = doc.render_fmt(options.render_width(), &mut out);
+    out
+}
+
+/// Pretty print a value to a writer, using the given layout options.
+pub fn render_with<T>(
+    value: T,
+    options: &PrettyOptions,
+    out: &mut impl std::io::Write,
+) -> std::io::Result<()>
 where
-    T: Pretty<'a, pretty::BoxAllocator, ()> + Clone,
+    T: for<'a> Pretty<'a, Allocator, ()>,
 {
-    let doc: DocBuilder<_, ()> = value.clone().pretty(&pretty::BoxAllocator);
-    doc.render_fmt(80, f)
+    let allocator = Allocator::new(options);
+    let doc: DocBuilder<_, ()> = value.pretty(&allocator);
+    doc.render(options.render_width(), out)
 }
 
 impl<'a, A: Clone + 'a> NickelAllocatorExt<'a, A> for pretty::BoxAllocator {}
 
+impl<'a, A: Clone + 'a> NickelAllocatorExt<'a, A> for Allocator {
+    fn indent(&self) -> isize {
+        self.indent
+    }
+
+    fn max_elements(&self) -> Option<usize> {
+        self.max_elements
+    }
+}
+
 trait NickelAllocatorExt<'a, A: 'a>: DocAllocator<'a, A> + Sized
 where
     Self::Doc: Clone,
     A: Clone,
 {
+    /// The number of spaces of one level of indentation.
+    fn indent(&self) -> isize {
+        2
+    }
+
+    /// The maximum number of elements printed for each array and record, if any. See
+    /// [Allocator::with_max_elements].
+    fn max_elements(&self) -> Option<usize> {
+        None
+    }
+
     /// Escape the special characters in a string, including the newline character, so that it can
     /// be enclosed by double quotes a be a valid Nickel string.
     fn escaped_string(&'a self, s: &str) -> DocBuilder<'a, Self, A> {
@@ -245,7 +419,7 @@ where
                     StrChunk::Expr(e, _i) => docs![self, interp.clone(), "{", e, "}"],
                 }
             })))
-            .nest(if multiline { 2 } else { 0 })
+            .nest(if multiline { self.indent() } else { 0 })
             .append(line_maybe)
             .double_quotes()
             .enclose(start_delimiter, end_delimiter)
@@ -286,7 +460,38 @@ where
                 MergePriority::Numeral(p) =>
                     docs![self, self.line(), "| priority ", p.to_sci().to_string()],
                 MergePriority::Top => docs![self, self.line(), "| force"],
-            }
+            },
+            match &metadata.deprecated {
+                Some(deprecation) => docs![
+                    self,
+                    self.line(),
+                    "| deprecated",
+                    match &deprecation.message {
+                        Some(message) => docs![
+                            self,
+                            " ",
+                            self.chunks(
+                                &[StrChunk::Literal(message.clone())],
+                                StringRenderStyle::ForceMonoline
+                            ),
+                        ],
+                        None => self.nil(),
+                    },
+                    match &deprecation.replacement {
+                        Some(replacement) => docs![
+                            self,
+                            self.line(),
+                            "| replaced_by ",
+                            self.chunks(
+                                &[StrChunk::Literal(replacement.clone())],
+                                StringRenderStyle::ForceMonoline
+                            ),
+                        ],
+                        None => self.nil(),
+                    },
+                ],
+                None => self.nil(),
+            },
         ]
     }
 
@@ -322,34 +527,56 @@ where
                     } else {
                         docs![self, " =", self.line()]
                     },
-                    value.pretty(self).nest(2)
+                    value.pretty(self).nest(self.indent())
                 ]
             } else {
                 self.nil()
             },
             ","
         ]
-        .nest(2)
+        .nest(self.indent())
     }
 
-    fn fields(&'a self, fields: &IndexMap<LocIdent, Field>) -> DocBuilder<'a, Self, A> {
+    fn fields(
+        &'a self,
+        fields: &IndexMap<LocIdent, Field>,
+        kept: usize,
+    ) -> DocBuilder<'a, Self, A> {
         self.intersperse(
             sorted_map(fields)
                 .iter()
+                .take(kept)
                 .map(|(id, field)| self.field(id, field)),
             self.line(),
         )
     }
 
-    fn dyn_fields(&'a self, fields: &[(RichTerm, Field)]) -> DocBuilder<'a, Self, A> {
+    fn dyn_fields(&'a self, fields: &[(RichTerm, Field)], kept: usize) -> DocBuilder<'a, Self, A> {
         self.intersperse(
             fields
                 .iter()
+                .take(kept)
                 .map(|(id_term, field)| self.dyn_field(id_term, field)),
             self.line(),
         )
     }
 
+    /// Print the elements of an array, separated by commas. Elements beyond
+    /// [Self::max_elements] are replaced by a marker showing how many of them there are.
+    fn elements(&'a self, elts: &Array) -> DocBuilder<'a, Self, A> {
+        let kept = self.max_elements().unwrap_or(usize::MAX);
+        let marker =
+            (elts.len() > kept).then(|| self.text(format!("\u{2026} {} more", elts.len() - kept)));
+
+        self.intersperse(
+            elts.iter()
+                .take(kept)
+                .map(|rt| rt.pretty(self))
+                .chain(marker),
+            self.text(",").append(self.line()),
+        )
+    }
+
     fn record(
         &'a self,
         record_data: &RecordData,
@@ -360,22 +587,27 @@ where
             return self.text("{}");
         }
 
+        let kept = self.max_elements().unwrap_or(usize::MAX);
+        let kept_dyn = kept.saturating_sub(record_data.fields.len());
+        // Records with elided fields are printed as open records.
+        let elided = record_data.fields.len() + dyn_fields.len() > kept;
+
         docs![
             self,
             self.line(),
-            self.fields(&record_data.fields),
-            if !dyn_fields.is_empty() {
-                docs![self, self.line(), self.dyn_fields(dyn_fields)]
+            self.fields(&record_data.fields, kept),
+            if kept_dyn > 0 && !dyn_fields.is_empty() {
+                docs![self, self.line(), self.dyn_fields(dyn_fields, kept_dyn)]
             } else {
                 self.nil()
             },
-            if record_data.attrs.open {
+            if record_data.attrs.open || elided {
                 docs![self, self.line(), ".."]
             } else {
                 self.nil()
             }
         ]
-        .nest(2)
+        .nest(self.indent())
         .append(self.line())
         .braces()
         .group()
@@ -502,7 +734,7 @@ where
             Embed(id) => docs![
                 allocator,
                 "%embed%",
-                docs![allocator, allocator.line(), id.to_string()].nest(2)
+                docs![allocator, allocator.line(), id.to_string()].nest(allocator.indent())
             ],
             op => allocator.text(format!("%{op}%")).append(allocator.space()),
         }
@@ -588,6 +820,8 @@ where
             PatternData::Wildcard => allocator.text("_"),
             PatternData::Any(id) => allocator.as_string(id),
             PatternData::Record(rp) => rp.pretty(allocator),
+            PatternData::Array(ap) => ap.pretty(allocator),
+            PatternData::Tuple(tp) => tp.pretty(allocator),
             PatternData::Enum(evp) => evp.pretty(allocator),
             PatternData::Constant(cp) => cp.pretty(allocator),
         }
@@ -683,7 +917,7 @@ where
                         },
                         ","
                     ]
-                    .nest(2)
+                    .nest(allocator.indent())
                 }),
                 allocator.line()
             ),
@@ -694,13 +928,72 @@ where
                     docs![allocator, allocator.line(), "..", id.ident().to_string()],
             },
         ]
-        .nest(2)
+        .nest(allocator.indent())
         .append(allocator.line())
         .braces()
         .group()
     }
 }
 
+impl<'a, D, A> Pretty<'a, D, A> for &ArrayPattern
+where
+    D: NickelAllocatorExt<'a, A>,
+    D::Doc: Clone,
+    A: Clone + 'a,
+{
+    fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D, A> {
+        let ArrayPattern { patterns, tail, .. } = self;
+        docs![
+            allocator,
+            allocator.line(),
+            allocator.intersperse(
+                patterns.iter().map(|pat| pat.pretty(allocator)),
+                allocator.text(",").append(allocator.line()),
+            ),
+            match tail {
+                ArrayPatternTail::Empty => allocator.nil(),
+                ArrayPatternTail::Open if patterns.is_empty() => allocator.text(".."),
+                ArrayPatternTail::Capture(id) if patterns.is_empty() =>
+                    docs![allocator, "..", id.ident().to_string()],
+                ArrayPatternTail::Open => docs![allocator, ",", allocator.line(), ".."],
+                ArrayPatternTail::Capture(id) => docs![
+                    allocator,
+                    ",",
+                    allocator.line(),
+                    "..",
+                    id.ident().to_string()
+                ],
+            },
+        ]
+        .nest(allocator.indent())
+        .append(allocator.line())
+        .brackets()
+        .group()
+    }
+}
+
+impl<'a, D, A> Pretty<'a, D, A> for &TuplePattern
+where
+    D: NickelAllocatorExt<'a, A>,
+    D::Doc: Clone,
+    A: Clone + 'a,
+{
+    fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D, A> {
+        docs![
+            allocator,
+            allocator.line_(),
+            allocator.intersperse(
+                self.patterns.iter().map(|pat| pat.pretty(allocator)),
+                allocator.text(",").append(allocator.line()),
+            ),
+        ]
+        .nest(allocator.indent())
+        .append(allocator.line_())
+        .parens()
+        .group()
+    }
+}
+
 impl<'a, D, A> Pretty<'a, D, A> for &RichTerm
 where
     D: NickelAllocatorExt<'a, A>,
@@ -747,7 +1040,7 @@ where
                     allocator.line(),
                     rt
                 ]
-                .nest(2)
+                .nest(allocator.indent())
                 .group()
             }
             FunPattern(..) => {
@@ -767,7 +1060,7 @@ where
                     allocator.line(),
                     rt
                 ]
-                .nest(2)
+                .nest(allocator.indent())
                 .group()
             }
             Lbl(_lbl) => allocator.text("%<label>").append(allocator.line()),
@@ -795,9 +1088,9 @@ where
                 allocator.line(),
                 "in",
             ]
-            .nest(2)
+            .nest(allocator.indent())
             .append(allocator.line())
-            .append(body.pretty(allocator).nest(2))
+            .append(body.pretty(allocator).nest(allocator.indent()))
             .group(),
             LetPattern(pattern, rt, body) => docs![
                 allocator,
@@ -818,9 +1111,9 @@ where
                 allocator.line(),
                 "in",
             ]
-            .nest(2)
+            .nest(allocator.indent())
             .append(allocator.line())
-            .append(body.pretty(allocator).nest(2))
+            .append(body.pretty(allocator).nest(allocator.indent()))
             .group(),
             App(rt1, rt2) => match rt1.as_ref() {
                 App(iop, t) if matches!(iop.as_ref(), Op1(UnaryOp::Ite(), _)) => match iop.as_ref()
@@ -830,10 +1123,10 @@ where
                         "if ",
                         i,
                         " then",
-                        docs![allocator, allocator.line(), t].nest(2),
+                        docs![allocator, allocator.line(), t].nest(allocator.indent()),
                         allocator.line(),
                         "else",
-                        docs![allocator, allocator.line(), rt2].nest(2)
+                        docs![allocator, allocator.line(), rt2].nest(allocator.indent())
                     ]
                     .group(),
                     _ => unreachable!(),
@@ -854,14 +1147,14 @@ where
                     allocator,
                     rt1,
                     docs![allocator, allocator.line(), allocator.atom(rt2)]
-                        .nest(2)
+                        .nest(allocator.indent())
                         .group()
                 ],
                 _ => docs![
                     allocator,
                     allocator.atom(rt1),
                     docs![allocator, allocator.line(), allocator.atom(rt2)]
-                        .nest(2)
+                        .nest(allocator.indent())
                         .group()
                 ],
             },
@@ -872,7 +1165,7 @@ where
                 .append(allocator.text(ident_quoted(tag)))
                 .append(
                     docs![allocator, allocator.line(), allocator.atom(arg)]
-                        .nest(2)
+                        .nest(allocator.indent())
                         .group(),
                 ),
             Record(record_data) => allocator.record(record_data, &[]),
@@ -892,27 +1185,27 @@ where
                         allocator.line(),
                     ),
                 ]
-                .nest(2)
+                .nest(allocator.indent())
                 .append(allocator.line())
                 .braces()
             ]
             .group(),
+            Array(fields, attrs) if attrs.tuple => {
+                docs![allocator, allocator.line_(), allocator.elements(fields)]
+                    .nest(allocator.indent())
+                    .append(allocator.line_())
+                    .parens()
+                    .group()
+            }
             Array(fields, _) =>
-            // NOTE: the Array attributes are ignored here. They contain only
-            // information that has no surface syntax.
+            // NOTE: the Array attributes are ignored here, except for the tuple
+            // flag. They contain only information that has no surface syntax.
             {
-                docs![
-                    allocator,
-                    allocator.line(),
-                    allocator.intersperse(
-                        fields.iter().map(|rt| rt.pretty(allocator)),
-                        allocator.text(",").append(allocator.line()),
-                    ),
-                ]
-                .nest(2)
-                .append(allocator.line())
-                .brackets()
-                .group()
+                docs![allocator, allocator.line(), allocator.elements(fields)]
+                    .nest(allocator.indent())
+                    .append(allocator.line())
+                    .brackets()
+                    .group()
             }
 
             Op1(UnaryOp::StaticAccess(id), rt) => {
@@ -925,7 +1218,7 @@ where
                 OpPos::Prefix => docs![
                     allocator,
                     op,
-                    docs![allocator, allocator.line(), allocator.atom(rt)].nest(2)
+                    docs![allocator, allocator.line(), allocator.atom(rt)].nest(allocator.indent())
                 ]
                 .group(),
                 OpPos::Special | OpPos::Postfix | OpPos::Infix => {
@@ -947,7 +1240,7 @@ where
                             allocator.atom(rtl),
                             allocator.line()
                         ]
-                        .nest(2),
+                        .nest(allocator.indent()),
                     )
                 } else {
                     docs![allocator, allocator.atom(rtl), allocator.line(), op, " "]
@@ -964,15 +1257,17 @@ where
                     allocator
                         .intersperse(rts.iter().map(|rt| allocator.atom(rt)), allocator.line())
                 ]
-                .nest(2)
+                .nest(allocator.indent())
             ]
             .group(),
             SealingKey(sym) => allocator.text(format!("%<sealing key: {sym}>")),
             Sealed(_i, _rt, _lbl) => allocator.text("%<sealed>"),
             Annotated(annot, rt) => allocator.atom(rt).append(annot.pretty(allocator)),
-            Import(f) => allocator
-                .text("import ")
-                .append(allocator.as_string(f.to_string_lossy()).double_quotes()),
+            Import(f) => allocator.text("import ").append(
+                allocator
+                    .escaped_string(&f.to_string_lossy())
+                    .double_quotes(),
+            ),
             ResolvedImport(id) => allocator.text(format!("import <file_id: {id:?}>")),
             // This type is in term position, so we don't need to add parentheses.
             Type(ty) => ty.pretty(allocator),
@@ -1091,17 +1386,29 @@ where
             Bool => allocator.text("Bool"),
             String => allocator.text("String"),
             Array(ty) => if ty.fmt_is_atom() {
-                docs![allocator, "Array", allocator.line(), ty.as_ref()].nest(2)
+                docs![allocator, "Array", allocator.line(), ty.as_ref()].nest(allocator.indent())
             } else {
                 docs![
                     allocator,
                     "Array (",
-                    docs![allocator, allocator.line_(), ty.as_ref()].nest(2),
+                    docs![allocator, allocator.line_(), ty.as_ref()].nest(allocator.indent()),
                     allocator.line_(),
                     ")"
                 ]
             }
             .group(),
+            Tuple(tys) => docs![
+                allocator,
+                allocator.line_(),
+                allocator.intersperse(
+                    tys.iter().map(|ty| ty.as_ref().pretty(allocator)),
+                    allocator.text(",").append(allocator.line()),
+                ),
+            ]
+            .nest(allocator.indent())
+            .append(allocator.line_())
+            .parens()
+            .group(),
             Symbol => allocator.text("Symbol"),
             Flat(t) => t.pretty(allocator),
             Var(var) => allocator.as_string(var),
@@ -1128,16 +1435,16 @@ where
                     allocator.line(),
                     allocator.type_part(curr)
                 ]
-                .nest(2)
+                .nest(allocator.indent())
                 .group()
             }
             Enum(erows) => docs![allocator, allocator.line(), erows]
-                .nest(2)
+                .nest(allocator.indent())
                 .append(allocator.line())
                 .enclose("[|", "|]")
                 .group(),
             Record(rrows) => docs![allocator, allocator.line(), rrows]
-                .nest(2)
+                .nest(allocator.indent())
                 .append(allocator.line())
                 .braces()
                 .group(),
@@ -1155,7 +1462,7 @@ where
                 " ",
                 allocator.type_part(ty.as_ref()),
             ]
-            .nest(2)
+            .nest(allocator.indent())
             .append(allocator.line())
             .braces()
             .group(),
@@ -1164,7 +1471,7 @@ where
                 allocator
                     .type_part(dom)
                     .parens_if(matches!(dom.typ, Arrow(..) | Forall { .. }))
-                    .nest(2),
+                    .nest(allocator.indent()),
                 allocator.line(),
                 "-> ",
                 allocator
@@ -1196,7 +1503,8 @@ where
             guard,
             allocator.space(),
             "=>",
-            docs![allocator, allocator.line(), self.body.pretty(allocator),].nest(2),
+            docs![allocator, allocator.line(), self.body.pretty(allocator),]
+                .nest(allocator.indent()),
         ]
     }
 }
@@ -1207,7 +1515,7 @@ macro_rules! impl_display_from_pretty {
     ($ty:ty) => {
         impl std::fmt::Display for $ty {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                $crate::pretty::fmt_pretty(&self, f)
+                $crate::pretty::fmt_pretty(&self, &Default::default(), f)
             }
         }
     };
@@ -1557,6 +1865,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pretty_options() {
+        let term = parse_term("{ a = b, c = d, }");
+        let render = |width, indent, line_breaks| {
+            to_string_with(
+                &term,
+                &PrettyOptions {
+                    width,
+                    indent,
+                    line_breaks,
+                },
+            )
+        };
+
+        assert_eq!(render(80, 2, LineBreakStyle::Fit), "{ a = b, c = d, }");
+        assert_eq!(
+            render(10, 2, LineBreakStyle::Fit),
+            indoc! {"
+                {
+                  a = b,
+                  c = d,
+                }"
+            }
+        );
+        assert_eq!(render(10, 2, LineBreakStyle::Never), "{ a = b, c = d, }");
+        assert_eq!(
+            render(80, 4, LineBreakStyle::Always),
+            indoc! {"
+                {
+                    a =
+                        b,
+                    c =
+                        d,
+                }"
+            }
+        );
+    }
+
     #[test]
     fn pretty_let() {
         assert_long_short_term(
@@ -1755,6 +2101,10 @@ mod tests {
         assert_format_eq("Array (Number -> Number)");
         assert_format_eq("Array (Array (Array Dyn) -> Number)");
 
+        assert_format_eq("(Number, String)");
+        assert_format_eq("Array (Number, Number -> Bool)");
+        assert_format_eq("(Number, String) -> (String, Number)");
+
         assert_format_eq("_");
         assert_format_eq("_ -> _");
         assert_format_eq("{ x : _, y : Bool }");
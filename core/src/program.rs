@@ -23,16 +23,21 @@
 use crate::{
     cache::*,
     error::{
-        report::{report, ColorOpt, ErrorFormat},
-        Error, EvalError, IOError, IntoDiagnostics, ParseError,
+        report::{report, BlameReport, ColorOpt, ErrorFormat},
+        warning::{DeniedWarning, WarningConfig, WarningLevel},
+        Error, EvalError, ExportError, ExportErrorData, IOError, IntoDiagnostics, ParseError,
     },
-    eval::{cache::Cache as EvalCache, Closure, VirtualMachine},
+    eval::{cache::Cache as EvalCache, cancel::CancellationToken, Closure, VirtualMachine},
     identifier::LocIdent,
     label::Label,
     metrics::increment,
+    pretty::PrettyOptions,
+    serialize::{self, ExportFormat, InexactNumbers, NickelPointer, NickelPointerElem},
     term::{
-        make as mk_term, make::builder, record::Field, BinaryOp, MergePriority, RichTerm, Term,
+        make as mk_term, make::builder, record::Field, BinaryOp, MergePriority, RichTerm,
+        RuntimeContract, Term,
     },
+    transform::Transformation,
 };
 
 use codespan::FileId;
@@ -40,10 +45,13 @@ use codespan_reporting::term::termcolor::Ansi;
 use std::path::PathBuf;
 
 use std::{
+    collections::HashMap,
     ffi::OsString,
     fmt,
     io::{self, Cursor, Read, Write},
     result::Result,
+    sync::Arc,
+    time::Duration,
 };
 
 /// A path of fields, that is a list, locating this field from the root of the configuration.
@@ -179,6 +187,8 @@ pub struct Program<EC: EvalCache> {
     vm: VirtualMachine<Cache, EC>,
     /// The color option to use when reporting errors.
     pub color_opt: ColorOpt,
+    /// The levels of warnings, used when reporting them. See [Self::report_warnings].
+    pub warning_config: WarningConfig,
     /// A list of [`FieldOverride`]s. During [`prepare_eval`], each
     /// override is imported in a separate in-memory source, for complete isolation (this way,
     /// overrides can't accidentally or intentionally capture other fields of the configuration).
@@ -232,6 +242,7 @@ impl<EC: EvalCache> Program<EC> {
             main_id,
             vm,
             color_opt: clap::ColorChoice::Auto.into(),
+            warning_config: WarningConfig::default(),
             overrides: Vec::new(),
             field: FieldPath::new(),
         })
@@ -275,6 +286,48 @@ impl<EC: EvalCache> Program<EC> {
             main_id,
             vm,
             color_opt: clap::ColorChoice::Auto.into(),
+            warning_config: WarningConfig::default(),
+            overrides: Vec::new(),
+            field: FieldPath::new(),
+        })
+    }
+
+    /// Create a program from a set of named in-memory modules, which can import each other
+    /// without ever touching the filesystem. `main` is the name of the module to evaluate.
+    ///
+    /// Module names are synthetic paths, interpreted relative to the current directory: a module
+    /// `lib/foo.ncl` can import `bar.ncl` as `import "../bar.ncl"`, and the module `bar.ncl`
+    /// shadows a file with the same path on disk. Importing a name which isn't a module still
+    /// falls back to reading the corresponding file.
+    pub fn new_from_modules<I, N, S>(
+        main: impl Into<OsString>,
+        modules: I,
+        trace: impl Write + 'static,
+    ) -> std::io::Result<Self>
+    where
+        I: IntoIterator<Item = (N, S)>,
+        N: Into<OsString>,
+        S: Into<String>,
+    {
+        increment!("Program::new");
+        let mut cache = Cache::new(ErrorTolerance::Strict);
+
+        for (name, source) in modules {
+            let path = normalize_path(name.into())?;
+            cache.add_string(SourcePath::Path(path), source.into());
+        }
+
+        let main_term = RichTerm::from(Term::Import(normalize_path(main.into())?.into()));
+        let main_id =
+            cache.add_string(SourcePath::Generated("main".into()), format!("{main_term}"));
+
+        let vm = VirtualMachine::new(cache, trace);
+
+        Ok(Self {
+            main_id,
+            vm,
+            color_opt: clap::ColorChoice::Auto.into(),
+            warning_config: WarningConfig::default(),
             overrides: Vec::new(),
             field: FieldPath::new(),
         })
@@ -333,6 +386,23 @@ impl<EC: EvalCache> Program<EC> {
         Self::new_from_inputs(inputs, trace)
     }
 
+    /// Shadow files on the filesystem with in-memory contents, for example to evaluate unsaved or
+    /// generated versions of files. Each path of `overlay`, interpreted relative to the current
+    /// directory, is mapped to the content used instead of the file at this path, whether the file
+    /// is imported or is the main program. Paths which don't exist on the filesystem can be
+    /// overlaid as well.
+    ///
+    /// The overlay must be set before the program is evaluated, typechecked or queried.
+    pub fn with_overlay(mut self, overlay: HashMap<PathBuf, String>) -> std::io::Result<Self> {
+        let cache = self.vm.import_resolver_mut();
+
+        for (path, content) in overlay {
+            cache.add_overlay(path, content)?;
+        }
+
+        Ok(self)
+    }
+
     /// Parse an assignment of the form `path.to_field=value` as an override, with the provided
     /// merge priority. Assignments are typically provided by the user on the command line, as part
     /// of the customize mode.
@@ -367,20 +437,113 @@ impl<EC: EvalCache> Program<EC> {
         self.vm.import_resolver_mut().add_import_paths(paths);
     }
 
-    /// Only parse the program, don't typecheck or evaluate. returns the [`RichTerm`] AST
-    pub fn parse(&mut self) -> Result<RichTerm, Error> {
+    /// Extend or override the standard library with custom modules. Each module `(name, source)`
+    /// is made available as `std.<name>`, replacing the builtin module of the same name, if any.
+    ///
+    /// This must be called before the program is typechecked or evaluated. See
+    /// [Cache::set_stdlib_extensions].
+    pub fn set_stdlib<N, S>(&mut self, modules: impl IntoIterator<Item = (N, S)>)
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        self.vm.import_resolver_mut().set_stdlib_extensions(modules);
+    }
+
+    /// Enable the generation of random UUIDs by `std.uuid.v4`, using `seed` to initialize the
+    /// generator. See [VirtualMachine::set_uuid_seed].
+    pub fn set_uuid_seed(&mut self, seed: u64) {
+        self.vm.set_uuid_seed(seed);
+    }
+
+    /// Enable the generation of random UUIDs by `std.uuid.v4`, seeded from the entropy of the
+    /// operating system. See [VirtualMachine::allow_random_uuids].
+    pub fn allow_random_uuids(&mut self) {
+        self.vm.allow_random_uuids();
+    }
+
+    /// Allow the program to read the given environment variables through `std.env.get`. See
+    /// [VirtualMachine::allow_env].
+    pub fn allow_env<S: Into<String>>(&mut self, vars: impl IntoIterator<Item = S>) {
+        self.vm.allow_env(vars);
+    }
+
+    /// Allow the program to read files and list directories under the given path prefixes through
+    /// `std.fs`. See [VirtualMachine::allow_read].
+    pub fn allow_read<P: Into<PathBuf>>(&mut self, prefixes: impl IntoIterator<Item = P>) {
+        self.vm.allow_read(prefixes);
+    }
+
+    /// Bound the number of evaluation steps. See [VirtualMachine::set_fuel].
+    pub fn set_fuel(&mut self, fuel: usize) {
+        self.vm.set_fuel(fuel);
+    }
+
+    /// Bound the number of elements allocated in the evaluation cache from now on. As evaluating
+    /// the program builds the initial environment from the standard library, the elements
+    /// allocated to build it are counted as well. See [VirtualMachine::set_max_allocations].
+    pub fn set_max_allocations(&mut self, max: usize) {
+        self.vm.set_max_allocations(max);
+    }
+
+    /// Stop evaluation once `token` has been cancelled. See
+    /// [VirtualMachine::set_cancellation_token].
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.vm.set_cancellation_token(token);
+    }
+
+    /// Stop evaluation if it's still running once `timeout` has elapsed, starting from now. See
+    /// [VirtualMachine::set_timeout].
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.vm.set_timeout(timeout);
+    }
+
+    /// Evaluate the program in sandbox mode: the program can only import files located under one
+    /// of `roots`, and can't read environment variables nor files through the standard library.
+    /// See [Cache::set_sandbox_roots] and [VirtualMachine::enable_sandbox].
+    pub fn enable_sandbox<P: Into<PathBuf>>(&mut self, roots: impl IntoIterator<Item = P>) {
         self.vm
             .import_resolver_mut()
-            .parse(self.main_id, InputFormat::Nickel)
-            .map_err(Error::ParseErrors)?;
-        Ok(self
+            .set_sandbox_roots(roots.into_iter().map(Into::into).collect());
+        self.vm.enable_sandbox();
+    }
+
+    /// Register a custom transformation, applied to the program and to the files it imports
+    /// between import resolution and evaluation, after the transformations already registered. See
+    /// [Transformation].
+    ///
+    /// Transformations must be registered before the program is evaluated, typechecked or
+    /// queried: files which have already been transformed aren't transformed again.
+    pub fn add_transformation(&mut self, transformation: impl Transformation + 'static) {
+        self.vm
+            .import_resolver_mut()
+            .add_transformation(Arc::new(transformation));
+    }
+
+    /// Serve the files imported by the program from a custom source, looked up before the
+    /// filesystem. See [ImportSource].
+    pub fn set_import_source(&mut self, import_source: impl ImportSource + 'static) {
+        self.vm
+            .import_resolver_mut()
+            .set_import_source(Arc::new(import_source));
+    }
+
+    /// Only parse the program, don't typecheck or evaluate. returns the [`RichTerm`] AST
+    ///
+    /// The parser recovers from syntax errors, so that all the syntax errors of the program are
+    /// reported at once.
+    pub fn parse(&mut self) -> Result<RichTerm, Error> {
+        let (term, parse_errs) = self
             .vm
-            .import_resolver()
-            .terms()
-            .get(&self.main_id)
-            .expect("File parsed and then immediately accessed doesn't exist")
-            .term
-            .clone())
+            .import_resolver_mut()
+            .parse_recover(self.main_id, InputFormat::Nickel)
+            .map_err(|err| Error::ParseErrors(err.into()))?;
+
+        if parse_errs.no_errors() {
+            Ok(term)
+        } else {
+            Err(Error::ParseErrors(parse_errs))
+        }
     }
 
     /// Retrieve the parsed term, typecheck it, and generate a fresh initial environment. If
@@ -478,6 +641,161 @@ impl<EC: EvalCache> Program<EC> {
         Ok(self.vm.eval_full_for_export_closure(prepared)?)
     }
 
+    /// Evaluate the program and write it to `writer` as pretty-printed JSON, like
+    /// [Self::eval_full_for_export] followed by [serialize::to_writer], but incrementally: arrays
+    /// and records are written one element at a time, and each element is only evaluated when
+    /// it's about to be written. The fully evaluated program is never built in memory, which
+    /// matters for very large outputs.
+    ///
+    /// Elements are written as soon as they're evaluated: if an error occurs, what has already
+    /// been written to `writer` is incomplete. If `sort_keys` is set, the fields of all the
+    /// records are written in alphabetical order, as with [serialize::sort_keys].
+    pub fn export_json_streaming(
+        &mut self,
+        writer: impl Write,
+        inexact_numbers: InexactNumbers,
+        sort_keys: bool,
+    ) -> Result<(), Error> {
+        let prepared = self.prepare_eval()?;
+        self.vm.reset();
+
+        let mut writer = io::BufWriter::new(writer);
+        self.stream_json(
+            prepared,
+            &mut writer,
+            inexact_numbers,
+            sort_keys,
+            &mut NickelPointer::new(),
+            0,
+        )?;
+
+        Ok(writer.flush().map_err(IOError::from)?)
+    }
+
+    /// Evaluate `closure` to a weak head normal form and write it as JSON, indented at `depth`.
+    /// Arrays and records are written element by element by recursive calls.
+    fn stream_json(
+        &mut self,
+        closure: Closure,
+        writer: &mut dyn Write,
+        inexact_numbers: InexactNumbers,
+        sort_keys: bool,
+        path: &mut NickelPointer,
+        depth: usize,
+    ) -> Result<(), Error> {
+        fn io_err(err: io::Error) -> Error {
+            IOError::from(err).into()
+        }
+
+        let indent = |depth: usize| "  ".repeat(depth);
+        let Closure { body, env } = self.vm.eval_closure(closure)?;
+
+        match body.as_ref() {
+            Term::Record(record) => {
+                let mut fields = Vec::new();
+
+                for (id, field) in record.fields.iter() {
+                    if field.metadata.not_exported {
+                        continue;
+                    }
+
+                    if let Some(value) = record
+                        .get_value_with_ctrs(id)
+                        .map_err(|err| err.into_eval_err(body.pos, body.pos))?
+                    {
+                        fields.push((*id, value));
+                    }
+                }
+
+                if sort_keys || !record.attrs.ordered {
+                    fields.sort_by_key(|(id, _)| *id);
+                }
+
+                if fields.is_empty() {
+                    return write!(writer, "{{}}").map_err(io_err);
+                }
+
+                write!(writer, "{{").map_err(io_err)?;
+
+                for (idx, (id, value)) in fields.into_iter().enumerate() {
+                    let sep = if idx == 0 { "" } else { "," };
+                    write!(writer, "{sep}\n{}", indent(depth + 1)).map_err(io_err)?;
+                    serde_json::to_writer(&mut *writer, id.label()).map_err(|err| {
+                        ExportError::from(ExportErrorData::Other(err.to_string()))
+                    })?;
+                    write!(writer, ": ").map_err(io_err)?;
+
+                    path.0.push(NickelPointerElem::Field(id.ident()));
+                    self.stream_json(
+                        Closure {
+                            body: value,
+                            env: env.clone(),
+                        },
+                        writer,
+                        inexact_numbers,
+                        sort_keys,
+                        path,
+                        depth + 1,
+                    )?;
+                    path.0.pop();
+                }
+
+                write!(writer, "\n{}}}", indent(depth)).map_err(io_err)
+            }
+            Term::Array(ts, attrs) => {
+                if ts.is_empty() {
+                    return write!(writer, "[]").map_err(io_err);
+                }
+
+                write!(writer, "[").map_err(io_err)?;
+
+                for (idx, t) in ts.iter().enumerate() {
+                    let sep = if idx == 0 { "" } else { "," };
+                    write!(writer, "{sep}\n{}", indent(depth + 1)).map_err(io_err)?;
+
+                    path.0.push(NickelPointerElem::Index(idx));
+                    self.stream_json(
+                        Closure {
+                            body: RuntimeContract::apply_all(
+                                t.clone(),
+                                attrs.pending_contracts.iter().cloned(),
+                                t.pos,
+                            ),
+                            env: env.clone(),
+                        },
+                        writer,
+                        inexact_numbers,
+                        sort_keys,
+                        path,
+                        depth + 1,
+                    )?;
+                    path.0.pop();
+                }
+
+                write!(writer, "\n{}]", indent(depth)).map_err(io_err)
+            }
+            _ => {
+                // The errors of `validate` and `handle_inexact_numbers` are located relatively to
+                // the leaf: we prepend the path of the leaf itself.
+                let with_path = |mut err: ExportError| {
+                    let mut full_path = path.clone();
+                    full_path.0.append(&mut err.path.0);
+                    err.path = full_path;
+                    err
+                };
+
+                serialize::validate(ExportFormat::Json, &body).map_err(with_path)?;
+                let leaf =
+                    serialize::handle_inexact_numbers(inexact_numbers, body).map_err(with_path)?;
+
+                serde_json::to_writer(&mut *writer, &leaf)
+                    .map_err(|err| ExportError::from(ExportErrorData::Other(err.to_string())))?;
+
+                Ok(())
+            }
+        }
+    }
+
     /// Same as `eval_full`, but does not substitute all variables.
     pub fn eval_deep(&mut self) -> Result<RichTerm, Error> {
         let prepared = self.prepare_eval()?;
@@ -527,6 +845,44 @@ impl<EC: EvalCache> Program<EC> {
         report(self.vm.import_resolver_mut(), error, format, self.color_opt)
     }
 
+    /// Report the warnings emitted since the last call to this method, according to
+    /// [Self::warning_config]. Return the number of denied warnings, which have been reported as
+    /// errors. Warnings located in the standard library aren't reported, as the user can't do
+    /// anything about them.
+    pub fn report_warnings(&mut self, format: ErrorFormat) -> usize {
+        let mut warnings = self.vm.import_resolver_mut().take_warnings();
+        warnings.extend(self.vm.take_warnings());
+
+        let cache = self.vm.import_resolver();
+        warnings.retain(|warning| {
+            !warning
+                .pos()
+                .into_opt()
+                .is_some_and(|span| cache.is_stdlib_module(span.src_id))
+        });
+
+        let mut denied = 0;
+
+        for warning in warnings {
+            match self.warning_config.level(warning.code()) {
+                WarningLevel::Allow => (),
+                WarningLevel::Warn => self.report(warning, format),
+                WarningLevel::Deny => {
+                    denied += 1;
+                    self.report(DeniedWarning(warning), format);
+                }
+            }
+        }
+
+        denied
+    }
+
+    /// Build a machine-readable report of `error` if it's a contract blame error. See
+    /// [`BlameReport`].
+    pub fn blame_report(&self, error: &Error) -> Option<BlameReport> {
+        BlameReport::from_error(error, self.vm.import_resolver().files())
+    }
+
     /// Build an error report as a string and return it.
     pub fn report_as_str<E>(&mut self, error: E) -> String
     where
@@ -680,13 +1036,17 @@ impl<EC: EvalCache> Program<EC> {
         do_eval(&mut self.vm, prepared.body, prepared.env)
     }
 
-    /// Extract documentation from the program
+    /// Extract documentation from the program. Types and contracts are rendered using the given
+    /// layout options.
     #[cfg(feature = "doc")]
-    pub fn extract_doc(&mut self) -> Result<doc::ExtractedDocumentation, Error> {
+    pub fn extract_doc(
+        &mut self,
+        options: &PrettyOptions,
+    ) -> Result<doc::ExtractedDocumentation, Error> {
         use crate::error::ExportErrorData;
 
         let term = self.eval_record_spine()?;
-        doc::ExtractedDocumentation::extract_from_term(&term).ok_or(Error::ExportError(
+        doc::ExtractedDocumentation::extract_from_term(&term, options).ok_or(Error::ExportError(
             ExportErrorData::NoDocumentation(term.clone()).into(),
         ))
     }
@@ -700,23 +1060,17 @@ impl<EC: EvalCache> Program<EC> {
         &mut self,
         out: &mut impl std::io::Write,
         apply_transforms: bool,
+        options: &PrettyOptions,
     ) -> Result<(), Error> {
-        use crate::{pretty::*, transform::transform};
-        use pretty::BoxAllocator;
+        use crate::{pretty::render_with, transform::transform};
 
-        let Program {
-            ref main_id, vm, ..
-        } = self;
-        let allocator = BoxAllocator;
-
-        let rt = vm.import_resolver().parse_nocache(*main_id)?.0;
+        let rt = self.parse()?;
         let rt = if apply_transforms {
             transform(rt, None).map_err(EvalError::from)?
         } else {
             rt
         };
-        let doc: DocBuilder<_, ()> = rt.pretty(&allocator);
-        doc.render(80, out).map_err(IOError::from)?;
+        render_with(&rt, options, out).map_err(IOError::from)?;
         writeln!(out).map_err(IOError::from)?;
 
         Ok(())
@@ -726,6 +1080,7 @@ impl<EC: EvalCache> Program<EC> {
 #[cfg(feature = "doc")]
 mod doc {
     use crate::error::{Error, ExportErrorData, IOError};
+    use crate::pretty::{to_string_with, PrettyOptions};
     use crate::term::{RichTerm, Term};
     use comrak::arena_tree::NodeEdge;
     use comrak::nodes::{
@@ -756,14 +1111,17 @@ mod doc {
     }
 
     impl ExtractedDocumentation {
-        pub fn extract_from_term(rt: &RichTerm) -> Option<Self> {
+        pub fn extract_from_term(rt: &RichTerm, options: &PrettyOptions) -> Option<Self> {
             match rt.term.as_ref() {
                 Term::Record(record) | Term::RecRecord(record, _, _) => {
                     let fields = record
                         .fields
                         .iter()
                         .map(|(ident, field)| {
-                            let fields = field.value.as_ref().and_then(Self::extract_from_term);
+                            let fields = field
+                                .value
+                                .as_ref()
+                                .and_then(|value| Self::extract_from_term(value, options));
 
                             // We use the original user-written type stored
                             // in the label. Using `lt.typ` instead is often
@@ -774,14 +1132,14 @@ mod doc {
                                 .annotation
                                 .typ
                                 .as_ref()
-                                .map(|lt| lt.label.typ.to_string());
+                                .map(|lt| to_string_with(lt.label.typ.as_ref(), options));
 
                             let contracts = field
                                 .metadata
                                 .annotation
                                 .contracts
                                 .iter()
-                                .map(|lt| lt.label.typ.to_string())
+                                .map(|lt| to_string_with(lt.label.typ.as_ref(), options))
                                 .collect();
 
                             let documentation = field.metadata.doc.clone();
@@ -980,13 +1338,13 @@ mod doc {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::error::EvalError;
+    use crate::error::{EvalError, ImportError};
     use crate::eval::cache::CacheImpl;
     use crate::identifier::LocIdent;
     use crate::position::TermPos;
     use crate::term::array::ArrayAttrs;
     use assert_matches::assert_matches;
-    use std::io::Cursor;
+    use std::{io::Cursor, path::Path};
 
     fn eval_full(s: &str) -> Result<RichTerm, Error> {
         let src = Cursor::new(s);
@@ -1056,4 +1414,375 @@ mod tests {
             Err(Error::ParseErrors(_))
         );
     }
+
+    #[test]
+    fn parse_reports_all_syntax_errors() {
+        let src = Cursor::new("{ foo = 1\r, bar = \"bad escape \\g\" }");
+        let mut p: Program<CacheImpl> =
+            Program::new_from_source(src, "<test>", std::io::sink()).unwrap();
+
+        assert_matches!(
+            p.parse(),
+            Err(Error::ParseErrors(errs)) if errs.errors.len() == 2
+        );
+        assert_matches!(
+            p.pprint_ast(&mut std::io::sink(), false, &Default::default()),
+            Err(Error::ParseErrors(errs)) if errs.errors.len() == 2
+        );
+    }
+
+    #[test]
+    fn stdlib_extensions() {
+        use crate::term::make as mk_term;
+
+        let src = Cursor::new("std.company.port + std.string.length \"abc\"");
+        let mut p: Program<CacheImpl> =
+            Program::new_from_source(src, "<test>", std::io::sink()).unwrap();
+        p.set_stdlib([
+            ("company", "{ port = std.number.max 1 8000 }"),
+            ("string", "{ length = fun _s => 2 }"),
+        ]);
+
+        assert_eq!(
+            p.eval_full().unwrap().without_pos(),
+            mk_term::integer(8002).without_pos()
+        );
+    }
+
+    #[test]
+    fn in_memory_modules() {
+        use crate::term::make as mk_term;
+
+        let modules = [
+            ("main.ncl", "(import \"lib/a.ncl\").value + 1"),
+            ("lib/a.ncl", "{ value = (import \"../b.ncl\").base * 2 }"),
+            ("b.ncl", "{ base = 20 }"),
+        ];
+        let mut p: Program<CacheImpl> =
+            Program::new_from_modules("main.ncl", modules, std::io::sink()).unwrap();
+
+        assert_eq!(
+            p.eval_full().unwrap().without_pos(),
+            mk_term::integer(41).without_pos()
+        );
+    }
+
+    #[test]
+    fn uuid_v4_requires_seed() {
+        let src = "[std.uuid.v4 null, std.uuid.v4 null]";
+        let eval_with_seed = |seed: Option<u64>| {
+            let mut p: Program<CacheImpl> =
+                Program::new_from_source(Cursor::new(src), "<test>", std::io::sink()).unwrap();
+            if let Some(seed) = seed {
+                p.set_uuid_seed(seed);
+            }
+            p.eval_full().map(RichTerm::without_pos)
+        };
+
+        assert_matches!(
+            eval_with_seed(None),
+            Err(Error::EvalError(EvalError::Other(..)))
+        );
+
+        let uuids = eval_with_seed(Some(42)).unwrap();
+        assert_eq!(uuids, eval_with_seed(Some(42)).unwrap());
+        assert_ne!(uuids, eval_with_seed(Some(43)).unwrap());
+
+        let Term::Array(uuids, _) = uuids.as_ref() else {
+            panic!("expected an array of UUIDs, got {uuids:?}");
+        };
+        assert_ne!(uuids.get(0), uuids.get(1));
+    }
+
+    #[test]
+    fn env_get_requires_allowance() {
+        std::env::set_var("NICKEL_TEST_ENV_GET", "value");
+
+        let src = "std.env.get \"NICKEL_TEST_ENV_GET\"";
+        let eval_allowing = |vars: &[&str]| {
+            let mut p: Program<CacheImpl> =
+                Program::new_from_source(Cursor::new(src), "<test>", std::io::sink()).unwrap();
+            p.allow_env(vars.iter().copied());
+            p.eval_full().unwrap()
+        };
+
+        assert_matches!(
+            eval_allowing(&[]).as_ref(),
+            Term::Enum(tag) if tag.label() == "None"
+        );
+        assert_matches!(
+            eval_allowing(&["OTHER", "NICKEL_TEST_ENV_GET"]).as_ref(),
+            Term::EnumVariant { tag, arg, .. }
+                if tag.label() == "Some"
+                    && matches!(arg.as_ref(), Term::Str(value) if value.as_str() == "value")
+        );
+    }
+
+    #[test]
+    fn fs_read_requires_allowance() {
+        let lib = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/integration/inputs/lib");
+        let src = format!(
+            "{{ text = std.fs.read_text \"{lib}/../lib/imported.ncl\", \
+            dir = std.fs.read_dir \"{lib}\" }}"
+        );
+        let mk_program = || -> Program<CacheImpl> {
+            Program::new_from_source(Cursor::new(src.clone()), "<test>", std::io::sink()).unwrap()
+        };
+
+        assert_matches!(
+            mk_program().eval_full(),
+            Err(Error::EvalError(EvalError::Other(..)))
+        );
+
+        let mut p = mk_program();
+        p.allow_read([lib]);
+        let result = p.eval_full().unwrap();
+        let Term::Record(record) = result.as_ref() else {
+            panic!("expected a record, got {result:?}");
+        };
+
+        let expected_text = std::fs::read_to_string(format!("{lib}/imported.ncl")).unwrap();
+        assert_matches!(
+            record.fields[&LocIdent::from("text")].value.as_ref().map(AsRef::as_ref),
+            Some(Term::Str(text)) if text.as_str() == expected_text
+        );
+
+        let Some(Term::Array(entries, _)) = record.fields[&LocIdent::from("dir")]
+            .value
+            .as_ref()
+            .map(AsRef::as_ref)
+        else {
+            panic!("expected an array of directory entries");
+        };
+        assert!(entries.iter().any(
+            |entry| matches!(entry.as_ref(), Term::Str(name) if name.as_str() == "assert.ncl")
+        ));
+
+        // Paths outside of the allowed prefixes can't be read, even through `..`.
+        let mut p: Program<CacheImpl> = Program::new_from_source(
+            Cursor::new(format!("std.fs.read_dir \"{lib}/..\"")),
+            "<test>",
+            std::io::sink(),
+        )
+        .unwrap();
+        p.allow_read([lib]);
+        assert_matches!(p.eval_full(), Err(Error::EvalError(EvalError::Other(..))));
+
+        // Whether a file exists outside of the allowed prefixes can't be observed.
+        let read_error = |path: &str| {
+            let mut p: Program<CacheImpl> = Program::new_from_source(
+                Cursor::new(format!("std.fs.read_text \"{path}\"")),
+                "<test>",
+                std::io::sink(),
+            )
+            .unwrap();
+            p.allow_read([lib]);
+            match p.eval_full() {
+                Err(Error::EvalError(EvalError::Other(msg, _))) => msg,
+                result => panic!("expected an error, got {result:?}"),
+            }
+        };
+
+        let existing = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        let missing = concat!(env!("CARGO_MANIFEST_DIR"), "/missing.ncl");
+        assert_eq!(
+            read_error(existing),
+            format!("fs_read_text: reading `{existing}` isn't allowed")
+        );
+        assert_eq!(
+            read_error(missing),
+            format!("fs_read_text: reading `{missing}` isn't allowed")
+        );
+    }
+
+    #[test]
+    fn custom_transformations() {
+        struct NullToBool;
+
+        impl Transformation for NullToBool {
+            fn name(&self) -> &str {
+                "null_to_bool"
+            }
+
+            fn transform_one(&self, rt: RichTerm) -> RichTerm {
+                match rt.as_ref() {
+                    Term::Null => RichTerm::new(Term::Bool(true), rt.pos),
+                    _ => rt,
+                }
+            }
+        }
+
+        let mut p: Program<CacheImpl> = Program::new_from_source(
+            Cursor::new("{ a = null, b = [null] } |> std.record.values"),
+            "<test>",
+            std::io::sink(),
+        )
+        .unwrap();
+        p.add_transformation(NullToBool);
+
+        assert_eq!(
+            p.eval_full().unwrap().to_string(),
+            eval_full("[true, [true]]").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn streaming_export() {
+        let program = r#"
+            let Port = std.contract.from_predicate (fun x => x > 0) in
+            {
+              zeta = [1, { b | default = "x", a = null }, [], {}],
+              alpha = { ports | Array Port = [80, 443], secret | not_exported = 0 },
+              "with space" = 1/3,
+              opt | optional,
+            }
+        "#;
+
+        let new_program = || -> Program<CacheImpl> {
+            Program::new_from_source(Cursor::new(program), "<test>", std::io::sink()).unwrap()
+        };
+
+        let mut streamed = Vec::new();
+        new_program()
+            .export_json_streaming(&mut streamed, InexactNumbers::Approximate, false)
+            .unwrap();
+
+        let expected = serialize::to_string(
+            ExportFormat::Json,
+            &new_program().eval_full_for_export().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(String::from_utf8(streamed).unwrap(), expected);
+
+        let mut streamed = Vec::new();
+        let result = Program::<CacheImpl>::new_from_source(
+            Cursor::new("{ a = [1, { b = fun x => x }] }"),
+            "<test>",
+            std::io::sink(),
+        )
+        .unwrap()
+        .export_json_streaming(&mut streamed, InexactNumbers::Approximate, false);
+
+        assert_matches!(
+            result,
+            Err(Error::ExportError(ExportError { path, data: ExportErrorData::NonSerializable(_) }))
+                if path.to_string() == "a[1].b"
+        );
+    }
+
+    #[test]
+    fn custom_import_source() {
+        struct Assets(HashMap<PathBuf, String>);
+
+        impl ImportSource for Assets {
+            fn read(&self, path: &Path) -> io::Result<Option<String>> {
+                Ok(self.0.get(path).cloned())
+            }
+        }
+
+        let assets = Assets(HashMap::from([
+            (
+                normalize_path("assets/main.ncl").unwrap(),
+                String::from("(import \"lib.ncl\").value + 1"),
+            ),
+            (
+                normalize_path("assets/lib.ncl").unwrap(),
+                String::from("{ value = 41 }"),
+            ),
+        ]));
+
+        let mut p: Program<CacheImpl> = Program::new_from_source(
+            Cursor::new("import \"assets/main.ncl\""),
+            "<test>",
+            std::io::sink(),
+        )
+        .unwrap();
+        p.set_import_source(assets);
+
+        assert_eq!(p.eval_full().unwrap().without_pos(), mk_term::integer(42));
+
+        let mut p: Program<CacheImpl> = Program::new_from_source(
+            Cursor::new("import \"assets/missing.ncl\""),
+            "<test>",
+            std::io::sink(),
+        )
+        .unwrap();
+        p.set_import_source(Assets(HashMap::new()));
+
+        assert_matches!(
+            p.eval_full(),
+            Err(Error::ImportError(ImportError::IOError(..)))
+        );
+    }
+
+    #[test]
+    fn overlay() {
+        let p: Program<CacheImpl> = Program::new_from_source(
+            Cursor::new("(import \"generated/config.ncl\").port"),
+            "<test>",
+            std::io::sink(),
+        )
+        .unwrap();
+        let mut p = p
+            .with_overlay(HashMap::from([(
+                PathBuf::from("generated/config.ncl"),
+                String::from("{ port = 80 }"),
+            )]))
+            .unwrap();
+
+        assert_eq!(p.eval_full().unwrap().without_pos(), mk_term::integer(80));
+
+        // The file on disk evaluates to 2.
+        let path = "tests/integration/inputs/imports/imported/two.ncl";
+        let p: Program<CacheImpl> = Program::new_from_file(path, std::io::sink()).unwrap();
+        let mut p = p
+            .with_overlay(HashMap::from([(
+                PathBuf::from(path),
+                String::from("40 + 2"),
+            )]))
+            .unwrap();
+
+        assert_eq!(p.eval_full().unwrap().without_pos(), mk_term::integer(42));
+    }
+
+    #[test]
+    fn sandbox() {
+        let imported = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/integration/inputs/imports/imported"
+        );
+        let eval_sandboxed = |src: String| {
+            let mut p: Program<CacheImpl> =
+                Program::new_from_source(Cursor::new(src), "<test>", std::io::sink()).unwrap();
+            p.allow_env(["HOME"]);
+            p.allow_read([imported]);
+            p.enable_sandbox([imported]);
+            p.eval_full()
+        };
+
+        assert_eq!(
+            eval_sandboxed(format!("import \"{imported}/two.ncl\""))
+                .unwrap()
+                .without_pos(),
+            mk_term::integer(2)
+        );
+
+        // `import_parent.ncl` imports a file located in the parent directory.
+        assert_matches!(
+            eval_sandboxed(format!("import \"{imported}/import_parent.ncl\"")),
+            Err(Error::ImportError(ImportError::SandboxViolation(..)))
+        );
+
+        // The environment and the filesystem are off limits, even when allowed.
+        for src in [
+            String::from("std.env.get \"HOME\""),
+            format!("std.fs.read_text \"{imported}/two.ncl\""),
+        ] {
+            assert_matches!(
+                eval_sandboxed(src),
+                Err(Error::EvalError(EvalError::SandboxViolation { .. }))
+            );
+        }
+    }
 }
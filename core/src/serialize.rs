@@ -2,6 +2,7 @@
 use crate::{
     error::{ExportError, ExportErrorData},
     identifier::{Ident, LocIdent},
+    parser::utils::parse_number_sci,
     term::{
         array::{Array, ArrayAttrs},
         record::RecordData,
@@ -15,7 +16,10 @@ use serde::{
 };
 
 use malachite::{
-    num::conversion::traits::{IsInteger, RoundingFrom},
+    num::conversion::{
+        string::options::ToSciOptions,
+        traits::{IsInteger, RoundingFrom, ToSci},
+    },
     rounding_modes::RoundingMode,
 };
 use once_cell::sync::Lazy;
@@ -31,6 +35,30 @@ pub enum ExportFormat {
     Json,
     Yaml,
     Toml,
+    Cbor,
+    Msgpack,
+    /// `KEY=value` lines, as read by shells and `.env` loaders. See [to_env_writer].
+    Env,
+}
+
+impl ExportFormat {
+    /// Whether the format is a binary format, whose output isn't valid UTF-8 in general.
+    pub fn is_binary(&self) -> bool {
+        matches!(self, Self::Cbor | Self::Msgpack)
+    }
+
+    /// The usual extension of the files of this format, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Raw => "txt",
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Cbor => "cbor",
+            Self::Msgpack => "msgpack",
+            Self::Env => "env",
+        }
+    }
 }
 
 impl fmt::Display for ExportFormat {
@@ -40,6 +68,9 @@ impl fmt::Display for ExportFormat {
             Self::Json => write!(f, "json"),
             Self::Yaml => write!(f, "yaml"),
             Self::Toml => write!(f, "toml"),
+            Self::Cbor => write!(f, "cbor"),
+            Self::Msgpack => write!(f, "msgpack"),
+            Self::Env => write!(f, "env"),
         }
     }
 }
@@ -62,7 +93,8 @@ impl fmt::Display for ParseFormatError {
 ///
 /// If the number doesn't fit into an `i64` or `u64`, we approximate it by the nearest `f64` and
 /// serialize this value. This may incur a loss of precision, but this is expected: we can't
-/// represent something like e.g. `1/3` exactly in JSON anyway.
+/// represent something like e.g. `1/3` exactly in JSON anyway. Use [handle_inexact_numbers] before
+/// serializing to fail or to write such numbers as strings instead.
 pub fn serialize_num<S>(n: &Number, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -107,7 +139,8 @@ where
     t.serialize(serializer)
 }
 
-/// Serializer for a record. Serialize fields in alphabetical order to get a deterministic output
+/// Serializer for a record. Serialize fields in alphabetical order to get a deterministic output,
+/// unless the record is [ordered](crate::term::record::RecordAttrs::ordered).
 pub fn serialize_record<S>(record: &RecordData, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -122,7 +155,9 @@ where
             ))
         })?;
 
-    entries.sort_by_key(|(k, _)| *k);
+    if !record.attrs.ordered {
+        entries.sort_by_key(|(k, _)| *k);
+    }
 
     let mut map_ser = serializer.serialize_map(Some(entries.len()))?;
     for (id, t) in entries.iter() {
@@ -293,8 +328,8 @@ pub fn validate(format: ExportFormat, t: &RichTerm) -> Result<(), ExportError> {
     // reversed before finally returning from validate.
     fn do_validate(format: ExportFormat, t: &RichTerm) -> Result<(), ExportError> {
         match t.as_ref() {
-            // TOML doesn't support null values
-            Null if format == ExportFormat::Json || format == ExportFormat::Yaml => Ok(()),
+            // TOML and env don't support null values
+            Null if format != ExportFormat::Toml && format != ExportFormat::Env => Ok(()),
             Null => Err(ExportErrorData::UnsupportedNull(format, t.clone()).into()),
             Bool(_) | Str(_) | Enum(_) => Ok(()),
             Num(n) => {
@@ -325,6 +360,9 @@ pub fn validate(format: ExportFormat, t: &RichTerm) -> Result<(), ExportError> {
                 })?;
                 Ok(())
             }
+            Array(..) if format == ExportFormat::Env => {
+                Err(ExportErrorData::NotFlattenable(t.clone()).into())
+            }
             Array(array, _) => {
                 array.iter().enumerate().try_for_each(|(index, t)| {
                     do_validate(format, t)
@@ -342,6 +380,8 @@ pub fn validate(format: ExportFormat, t: &RichTerm) -> Result<(), ExportError> {
         } else {
             Err(ExportErrorData::NotAString(t.clone()).into())
         }
+    } else if format == ExportFormat::Env && !matches!(t.term.as_ref(), Term::Record(_)) {
+        Err(ExportErrorData::NotFlattenable(t.clone()).into())
     } else {
         let mut result = do_validate(format, t);
 
@@ -353,6 +393,147 @@ pub fn validate(format: ExportFormat, t: &RichTerm) -> Result<(), ExportError> {
     }
 }
 
+/// What to do with numbers which can't be serialized exactly, see [serialize_num].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, clap::ValueEnum)]
+pub enum InexactNumbers {
+    /// Serialize the nearest 64 bits float.
+    #[default]
+    Approximate,
+    /// Fail with an error.
+    Fail,
+    /// Serialize a string holding the exact value, either as a decimal number or as a fraction.
+    String,
+}
+
+impl fmt::Display for InexactNumbers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Approximate => write!(f, "approximate"),
+            Self::Fail => write!(f, "fail"),
+            Self::String => write!(f, "string"),
+        }
+    }
+}
+
+/// Check if a number is serialized exactly by [serialize_num]. This is the case of integers which
+/// fit in an `i64` or an `u64`, and of numbers whose nearest 64 bits float is written with the
+/// very same value: serializers write floats using the shortest decimal representation which
+/// converts back to the same float, so that e.g. `0.1` is exact, but `1/3` isn't.
+fn is_serialized_exactly(n: &Number) -> bool {
+    if n.is_integer() && (i64::try_from(n).is_ok() || u64::try_from(n).is_ok()) {
+        return true;
+    }
+
+    let as_f64 = f64::rounding_from(n, RoundingMode::Nearest).0;
+
+    parse_number_sci(&as_f64.to_string()).is_ok_and(|written| written == *n)
+}
+
+/// Return the exact value of a number as a string: a decimal number if its decimal expansion is
+/// finite, or a fraction otherwise.
+fn to_exact_string(n: &Number) -> String {
+    let mut options = ToSciOptions::default();
+    options.set_size_complete();
+
+    if n.fmt_sci_valid(options) {
+        n.to_sci_with_options(options).to_string()
+    } else {
+        n.to_string()
+    }
+}
+
+/// Handle the numbers of a term which can't be serialized exactly, as specified by `mode`: either
+/// leave them as they are, fail, or replace them with strings holding their exact value. The term
+/// must have been fully evaluated and [validated](validate) beforehand.
+pub fn handle_inexact_numbers(mode: InexactNumbers, rt: RichTerm) -> Result<RichTerm, ExportError> {
+    // As for `validate`, the path to the error is built bottom-up, and reversed at the end.
+    fn do_handle(mode: InexactNumbers, rt: RichTerm) -> Result<RichTerm, ExportError> {
+        match rt.as_ref() {
+            Term::Num(n) if !is_serialized_exactly(n) => match mode {
+                InexactNumbers::Approximate => Ok(rt),
+                InexactNumbers::Fail => Err(ExportErrorData::InexactNumber {
+                    term: rt.clone(),
+                    value: n.clone(),
+                }
+                .into()),
+                InexactNumbers::String => {
+                    Ok(RichTerm::new(Term::Str(to_exact_string(n).into()), rt.pos))
+                }
+            },
+            Term::Record(record) => {
+                let mut record = record.clone();
+
+                for (id, field) in record.fields.iter_mut() {
+                    if field.metadata.not_exported {
+                        continue;
+                    }
+
+                    if let Some(value) = field.value.take() {
+                        let value = do_handle(mode, value).map_err(|mut err| {
+                            err.path.0.push(NickelPointerElem::Field(id.ident()));
+                            err
+                        })?;
+
+                        field.value = Some(value);
+                    }
+                }
+
+                Ok(RichTerm::new(Term::Record(record), rt.pos))
+            }
+            Term::Array(array, attrs) => {
+                let array = array
+                    .iter()
+                    .enumerate()
+                    .map(|(index, t)| {
+                        do_handle(mode, t.clone()).map_err(|mut err| {
+                            err.path.0.push(NickelPointerElem::Index(index));
+                            err
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(RichTerm::new(Term::Array(array, attrs.clone()), rt.pos))
+            }
+            _ => Ok(rt),
+        }
+    }
+
+    if mode == InexactNumbers::Approximate {
+        return Ok(rt);
+    }
+
+    let mut result = do_handle(mode, rt);
+
+    if let Err(ExportError { path, .. }) = &mut result {
+        path.0.reverse();
+    }
+
+    result
+}
+
+/// Make the fields of all the records of a term be serialized in alphabetical order, including the
+/// records which have been explicitly [ordered](crate::term::record::RecordAttrs::ordered), for
+/// example by `std.record.sort_fields`. The term must have been fully evaluated beforehand.
+pub fn sort_keys(rt: RichTerm) -> RichTerm {
+    match rt.as_ref() {
+        Term::Record(record) => {
+            let mut record = record.clone();
+            record.attrs.ordered = false;
+
+            for field in record.fields.values_mut() {
+                field.value = field.value.take().map(sort_keys);
+            }
+
+            RichTerm::new(Term::Record(record), rt.pos)
+        }
+        Term::Array(array, attrs) => {
+            let array = array.iter().cloned().map(sort_keys).collect();
+            RichTerm::new(Term::Array(array, attrs.clone()), rt.pos)
+        }
+        _ => rt,
+    }
+}
+
 pub fn to_writer<W>(mut writer: W, format: ExportFormat, rt: &RichTerm) -> Result<(), ExportError>
 where
     W: io::Write,
@@ -369,6 +550,14 @@ where
                     .write_all(s.as_bytes())
                     .map_err(|err| ExportErrorData::Other(err.to_string()))
             }),
+        // Records are serialized with their fields sorted (see [serialize_record]), so that the
+        // binary output is deterministic as well.
+        ExportFormat::Cbor => {
+            ciborium::into_writer(rt, writer).map_err(|err| ExportErrorData::Other(err.to_string()))
+        }
+        ExportFormat::Msgpack => rmp_serde::encode::write(&mut writer, rt)
+            .map_err(|err| ExportErrorData::Other(err.to_string())),
+        ExportFormat::Env => return to_env_writer(writer, rt, &EnvOptions::default()),
         ExportFormat::Raw => match rt.as_ref() {
             Term::Str(s) => writer
                 .write_all(s.as_bytes())
@@ -385,13 +574,439 @@ where
     Ok(())
 }
 
+/// How to quote values in the env format.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, clap::ValueEnum)]
+pub enum EnvQuoting {
+    /// Quote values which contain characters with a special meaning for shells, such as spaces.
+    #[default]
+    Auto,
+    /// Quote all values.
+    Always,
+    /// Never quote values. Values spanning several lines are rejected.
+    Never,
+}
+
+impl fmt::Display for EnvQuoting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Always => write!(f, "always"),
+            Self::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// Options of the env format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvOptions {
+    /// The separator put between the fields of a path to build the name of a variable. For
+    /// example, with the default separator `_`, `{db = {host = "localhost"}}` is written
+    /// `db_host=localhost`.
+    pub separator: String,
+    /// How to quote values.
+    pub quoting: EnvQuoting,
+}
+
+impl Default for EnvOptions {
+    fn default() -> Self {
+        EnvOptions {
+            separator: String::from("_"),
+            quoting: EnvQuoting::default(),
+        }
+    }
+}
+
+/// Quote a value of the env format with double quotes, if required by `quoting`.
+fn quote_env_value(value: &str, quoting: EnvQuoting) -> Result<String, ExportErrorData> {
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || "_-+.,:/@%".contains(c);
+
+    match quoting {
+        EnvQuoting::Never if value.contains('\n') => Err(ExportErrorData::Other(String::from(
+            "a value spanning several lines must be quoted in the env format",
+        ))),
+        EnvQuoting::Never => Ok(value.to_owned()),
+        EnvQuoting::Auto if !value.is_empty() && value.chars().all(is_plain) => {
+            Ok(value.to_owned())
+        }
+        EnvQuoting::Auto | EnvQuoting::Always => {
+            let mut quoted = String::with_capacity(value.len() + 2);
+            quoted.push('"');
+
+            for c in value.chars() {
+                match c {
+                    '"' | '\\' | '$' | '`' => {
+                        quoted.push('\\');
+                        quoted.push(c);
+                    }
+                    '\n' => quoted.push_str("\\n"),
+                    c => quoted.push(c),
+                }
+            }
+
+            quoted.push('"');
+            Ok(quoted)
+        }
+    }
+}
+
+/// Serialize a record as `KEY=value` lines. Nested records are flattened: the name of each
+/// variable is the path of the corresponding field, joined with [EnvOptions::separator]. Fields
+/// are sorted as for the other formats. The term must have been [validated](validate) for
+/// [ExportFormat::Env] beforehand.
+pub fn to_env_writer<W>(
+    mut writer: W,
+    rt: &RichTerm,
+    options: &EnvOptions,
+) -> Result<(), ExportError>
+where
+    W: io::Write,
+{
+    fn collect(
+        prefix: &str,
+        rt: &RichTerm,
+        options: &EnvOptions,
+        out: &mut Vec<(String, String)>,
+    ) -> Result<(), ExportErrorData> {
+        let value = match rt.as_ref() {
+            Term::Record(record) => {
+                let mut entries = record
+                    .iter_serializable()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| {
+                        ExportErrorData::Other(format!("missing field definition for `{}`", err.id))
+                    })?;
+
+                if !record.attrs.ordered {
+                    entries.sort_by_key(|(k, _)| *k);
+                }
+
+                for (id, value) in entries {
+                    let name = if prefix.is_empty() {
+                        id.to_string()
+                    } else {
+                        format!("{prefix}{}{id}", options.separator)
+                    };
+
+                    collect(&name, value, options, out)?;
+                }
+
+                return Ok(());
+            }
+            Term::Str(s) => s.to_string(),
+            Term::Enum(tag) => tag.to_string(),
+            Term::Bool(_) | Term::Num(_) => {
+                serde_json::to_string(rt).map_err(|err| ExportErrorData::Other(err.to_string()))?
+            }
+            _ => return Err(ExportErrorData::NotFlattenable(rt.clone())),
+        };
+
+        out.push((prefix.to_owned(), quote_env_value(&value, options.quoting)?));
+        Ok(())
+    }
+
+    let mut variables = Vec::new();
+    collect("", rt, options, &mut variables)?;
+
+    for (name, value) in variables {
+        writeln!(writer, "{name}={value}")
+            .map_err(|err| ExportErrorData::Other(err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Serialize a term to a string. Binary formats aren't supported, use [to_writer] instead.
 pub fn to_string(format: ExportFormat, rt: &RichTerm) -> Result<String, ExportError> {
+    if format.is_binary() {
+        return Err(ExportErrorData::Other(format!(
+            "{format} is a binary format, which can't be serialized to a string"
+        ))
+        .into());
+    }
+
     let mut buffer: Vec<u8> = Vec::new();
     to_writer(&mut buffer, format, rt)?;
 
     Ok(String::from_utf8_lossy(&buffer).into_owned())
 }
 
+/// Serialize an array as a stream of YAML documents, one per element.
+pub fn to_yaml_documents(rt: &RichTerm) -> Result<String, ExportError> {
+    let Term::Array(array, _) = rt.as_ref() else {
+        return Err(ExportErrorData::Other(format!(
+            "a stream of YAML documents must be serialized from an `Array`, got {}",
+            // unwrap(): terms must be fully evaluated before serialization,
+            // and fully evaluated terms have a definite type.
+            rt.as_ref().type_of().unwrap()
+        ))
+        .into());
+    };
+
+    let mut result = String::new();
+
+    for (index, doc) in array.iter().enumerate() {
+        validate(ExportFormat::Yaml, doc).map_err(|mut err| {
+            err.path.0.insert(0, NickelPointerElem::Index(index));
+            err
+        })?;
+
+        result.push_str("---\n");
+        result.push_str(&to_string(ExportFormat::Yaml, doc)?);
+    }
+
+    Ok(result)
+}
+
+/// An error occurring when converting a deserialized value to a term. As for [validate], the path
+/// to the offending value is reconstructed bottom-up, and must be reversed at the end.
+struct ConversionError {
+    path: NickelPointer,
+    message: String,
+}
+
+impl ConversionError {
+    fn new(message: String) -> Self {
+        ConversionError {
+            path: NickelPointer::new(),
+            message,
+        }
+    }
+
+    fn with_elem(mut self, elem: NickelPointerElem) -> Self {
+        self.path.0.push(elem);
+        self
+    }
+
+    /// Render the error. The input of the deserialization is usually a string computed at
+    /// runtime, so there's no meaningful source position to report: we locate the offending value
+    /// by its path in the document instead.
+    fn into_message(mut self) -> String {
+        self.path.0.reverse();
+
+        if self.path.0.is_empty() {
+            self.message
+        } else {
+            format!("at `{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+fn num_from_float(f: f64) -> Result<Number, ConversionError> {
+    Number::try_from_float_simplest(f).map_err(|_| {
+        ConversionError::new(format!(
+            "couldn't convert {f} to a Nickel number: Nickel doesn't support NaN nor infinity"
+        ))
+    })
+}
+
+fn array_from_values<V>(
+    values: impl IntoIterator<Item = V>,
+    convert: impl Fn(V) -> Result<RichTerm, ConversionError>,
+) -> Result<Term, ConversionError> {
+    let terms = values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            convert(value).map_err(|err| err.with_elem(NickelPointerElem::Index(index)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Term::Array(
+        Array::new(Rc::from(terms)),
+        ArrayAttrs::default(),
+    ))
+}
+
+fn record_from_entries<V>(
+    entries: impl IntoIterator<Item = (String, V)>,
+    convert: impl Fn(V) -> Result<RichTerm, ConversionError>,
+) -> Result<Term, ConversionError> {
+    let fields = entries
+        .into_iter()
+        .map(|(key, value)| {
+            let id = LocIdent::from(key);
+            convert(value)
+                .map(|rt| (id, rt))
+                .map_err(|err| err.with_elem(NickelPointerElem::Field(id.ident())))
+        })
+        .collect::<Result<IndexMap<_, _>, _>>()?;
+
+    Ok(Term::Record(RecordData::with_field_values(fields)))
+}
+
+/// Convert a YAML value to a term. Besides strings, numbers and booleans are accepted as mapping
+/// keys and converted to strings. Tagged values aren't supported.
+fn yaml_to_term(value: serde_yaml::Value) -> Result<RichTerm, ConversionError> {
+    use serde_yaml::Value;
+
+    let term = match value {
+        Value::Null => Term::Null,
+        Value::Bool(b) => Term::Bool(b),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Term::Num(Number::from(i))
+            } else if let Some(u) = n.as_u64() {
+                Term::Num(Number::from(u))
+            } else {
+                // unwrap(): a YAML number which isn't an integer is a float.
+                Term::Num(num_from_float(n.as_f64().unwrap())?)
+            }
+        }
+        Value::String(s) => Term::Str(s.into()),
+        Value::Sequence(values) => array_from_values(values, yaml_to_term)?,
+        Value::Mapping(mapping) => {
+            let entries = mapping
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = match key {
+                        Value::String(s) => s,
+                        Value::Bool(b) => b.to_string(),
+                        Value::Number(n) => n.to_string(),
+                        Value::Null => {
+                            return Err(ConversionError::new(String::from(
+                                "mapping keys must be strings, got a null key",
+                            )))
+                        }
+                        _ => {
+                            return Err(ConversionError::new(String::from(
+                                "mapping keys must be strings, got a composite key",
+                            )))
+                        }
+                    };
+
+                    Ok((key, value))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            record_from_entries(entries, yaml_to_term)?
+        }
+        Value::Tagged(tagged) => {
+            return Err(ConversionError::new(format!(
+                "unsupported YAML tag `{}`",
+                tagged.tag
+            )))
+        }
+    };
+
+    Ok(RichTerm::from(term))
+}
+
+/// Convert a TOML value to a term. Dates and times are converted to their RFC 3339
+/// representation, which is the one used by `std.time`.
+fn toml_to_term(value: toml::Value) -> Result<RichTerm, ConversionError> {
+    use toml::Value;
+
+    let term = match value {
+        Value::String(s) => Term::Str(s.into()),
+        Value::Integer(i) => Term::Num(Number::from(i)),
+        Value::Float(f) => Term::Num(num_from_float(f)?),
+        Value::Boolean(b) => Term::Bool(b),
+        Value::Datetime(datetime) => Term::Str(datetime.to_string().into()),
+        Value::Array(values) => array_from_values(values, toml_to_term)?,
+        Value::Table(table) => record_from_entries(table, toml_to_term)?,
+    };
+
+    Ok(RichTerm::from(term))
+}
+
+/// Deserialize a single YAML document.
+pub fn from_yaml(s: &str) -> Result<RichTerm, String> {
+    let value: serde_yaml::Value = serde_yaml::from_str(s).map_err(|err| err.to_string())?;
+    yaml_to_term(value).map_err(ConversionError::into_message)
+}
+
+/// Deserialize a stream of YAML documents, separated by `---`, into an array of documents.
+pub fn from_yaml_documents(s: &str) -> Result<RichTerm, String> {
+    let values = serde_yaml::Deserializer::from_str(s)
+        .map(serde_yaml::Value::deserialize)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    array_from_values(values, yaml_to_term)
+        .map(RichTerm::from)
+        .map_err(ConversionError::into_message)
+}
+
+/// Deserialize a TOML document.
+pub fn from_toml(s: &str) -> Result<RichTerm, String> {
+    let value: toml::Value = toml::from_str(s).map_err(|err| err.to_string())?;
+    from_toml_value(value)
+}
+
+/// Convert a parsed TOML document to a term. Dates and times are converted to their RFC 3339
+/// representation.
+pub fn from_toml_value(value: toml::Value) -> Result<RichTerm, String> {
+    toml_to_term(value).map_err(ConversionError::into_message)
+}
+
+/// Options of the deserialization of CSV documents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The character separating the fields of a row.
+    pub delimiter: u8,
+    /// The character used to quote fields.
+    pub quote: u8,
+    /// If set, fields which are valid numbers or booleans (`true` and `false`) are converted to
+    /// numbers and booleans. Otherwise, all the fields are strings.
+    pub infer_types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            quote: b'"',
+            infer_types: true,
+        }
+    }
+}
+
+/// Convert a field of a CSV document to a term, inferring its type if `infer_types` is set.
+fn csv_field_to_term(field: &str, infer_types: bool) -> Term {
+    match field {
+        "true" if infer_types => Term::Bool(true),
+        "false" if infer_types => Term::Bool(false),
+        _ if infer_types => parse_number_sci(field)
+            .map(Term::Num)
+            .unwrap_or_else(|_| Term::Str(field.into())),
+        _ => Term::Str(field.into()),
+    }
+}
+
+/// Deserialize a CSV document into an array of records, one per row. The first row is the header:
+/// it gives the names of the fields of the records.
+pub fn from_csv(s: &str, options: &CsvOptions) -> Result<RichTerm, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .from_reader(s.as_bytes());
+
+    let headers = reader.headers().map_err(|err| err.to_string())?.clone();
+    let mut rows = Vec::new();
+
+    for row in reader.records() {
+        let row = row.map_err(|err| err.to_string())?;
+        let mut fields = IndexMap::new();
+
+        for (name, field) in headers.iter().zip(row.iter()) {
+            let value = RichTerm::from(csv_field_to_term(field, options.infer_types));
+
+            if fields.insert(LocIdent::from(name), value).is_some() {
+                return Err(format!("duplicate column `{name}`"));
+            }
+        }
+
+        rows.push(RichTerm::from(Term::Record(RecordData::with_field_values(
+            fields,
+        ))));
+    }
+
+    Ok(RichTerm::from(Term::Array(
+        Array::new(Rc::from(rows)),
+        ArrayAttrs::default(),
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -452,6 +1067,55 @@ mod tests {
         assert_nickel_eq(from_toml, evaluated);
     }
 
+    #[test]
+    fn env_format() {
+        let evaluated = eval(
+            "{b = {port = 8080, debug = false}, a = \"hello world\", c = 'foo, d = \"$HOME\"}",
+        );
+        validate(ExportFormat::Env, &evaluated).unwrap();
+
+        let mut output = Vec::new();
+        to_env_writer(&mut output, &evaluated, &EnvOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "a=\"hello world\"\nb_debug=false\nb_port=8080\nc=foo\nd=\"\\$HOME\"\n"
+        );
+
+        let options = EnvOptions {
+            separator: String::from("__"),
+            quoting: EnvQuoting::Always,
+        };
+        let mut output = Vec::new();
+        to_env_writer(&mut output, &eval("{a = {b = 1}}"), &options).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a__b=\"1\"\n");
+
+        assert_fail_validation(ExportFormat::Env, "{a = [1, 2]}");
+        assert_fail_validation(ExportFormat::Env, "{a = null}");
+        assert_fail_validation(ExportFormat::Env, "1");
+    }
+
+    #[test]
+    fn binary_formats() {
+        let evaluated = eval("{a = [1, -2, 0.5], b = {c = null, d = \"foo\"}, e = 'bar}");
+
+        let mut cbor = Vec::new();
+        to_writer(&mut cbor, ExportFormat::Cbor, &evaluated).unwrap();
+        let from_cbor: RichTerm = ciborium::from_reader(cbor.as_slice()).unwrap();
+
+        let mut msgpack = Vec::new();
+        to_writer(&mut msgpack, ExportFormat::Msgpack, &evaluated).unwrap();
+        let from_msgpack: RichTerm = rmp_serde::from_slice(&msgpack).unwrap();
+
+        assert_nickel_eq(from_cbor, evaluated.clone());
+        assert_nickel_eq(from_msgpack, evaluated.clone());
+
+        // The output doesn't depend on the definition order of fields.
+        let mut reordered = Vec::new();
+        let reordered_term = eval("{e = 'bar, b = {d = \"foo\", c = null}, a = [1, -2, 0.5]}");
+        to_writer(&mut reordered, ExportFormat::Cbor, &reordered_term).unwrap();
+        assert_eq!(cbor, reordered);
+    }
+
     #[test]
     fn basic() {
         assert_json_eq("1 + 1", 2);
@@ -533,6 +1197,47 @@ mod tests {
         assert_fail_validation(ExportFormat::Toml, "{foo = null}");
     }
 
+    #[test]
+    fn inexact_numbers() {
+        let handle = |mode, term| {
+            handle_inexact_numbers(mode, eval(term)).map(|rt| serde_json::to_string(&rt).unwrap())
+        };
+
+        assert_eq!(
+            handle(InexactNumbers::Approximate, "{a = 1/3}").unwrap(),
+            r#"{"a":0.3333333333333333}"#
+        );
+        assert_eq!(
+            handle(
+                InexactNumbers::String,
+                "{a = 1/3, b = [0.1, 2, -1/4], c = 18446744073709551617}"
+            )
+            .unwrap(),
+            r#"{"a":"1/3","b":[0.1,2,-0.25],"c":"18446744073709551617"}"#
+        );
+
+        let err = handle(InexactNumbers::Fail, "{a = [0.5, 1/3]}").unwrap_err();
+        assert!(matches!(err.data, ExportErrorData::InexactNumber { .. }));
+        assert_eq!(err.path.to_string(), "a[1]");
+    }
+
+    #[test]
+    fn sorted_keys() {
+        let term = eval(
+            "{ b = std.record.move_first [\"z\"] { a = 1, z = 2 }, \
+               a = [std.record.move_first [\"d\"] { c = 1, d = 2 }] }",
+        );
+
+        assert_eq!(
+            serde_json::to_string(&term).unwrap(),
+            r#"{"a":[{"d":2,"c":1}],"b":{"z":2,"a":1}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&sort_keys(term)).unwrap(),
+            r#"{"a":[{"c":1,"d":2}],"b":{"a":1,"z":2}}"#
+        );
+    }
+
     #[test]
     fn involution() {
         assert_involutory("{val = 1 + 1}");
@@ -540,4 +1245,20 @@ mod tests {
         assert_involutory("{val = [\"a\", 3, []]}");
         assert_involutory("{a.foo.bar = \"2\", b = false, c = [{d = \"e\"}, {d = \"f\"}]}");
     }
+
+    #[test]
+    fn deserialization_error_paths() {
+        assert_eq!(
+            from_yaml("servers:\n  - name: main\n    image: !ref base").unwrap_err(),
+            "at `servers[0].image`: unsupported YAML tag `!ref`"
+        );
+        assert_eq!(
+            from_yaml_documents("a: 1\n---\nb:\n  ? [1, 2]\n  : pair").unwrap_err(),
+            "at `[1].b`: mapping keys must be strings, got a composite key"
+        );
+        assert_eq!(
+            from_yaml("!ref base").unwrap_err(),
+            "unsupported YAML tag `!ref`"
+        );
+    }
 }
@@ -76,6 +76,7 @@ pub mod internals {
 
     generate_accessor!(array);
     generate_accessor!(array_dyn);
+    generate_accessor!(tuple);
 
     generate_accessor!(func);
     generate_accessor!(func_dom);
@@ -18,6 +18,10 @@ pub struct ArrayAttrs {
     /// List of lazily-applied contracts.
     /// These are only observed when data enters or leaves the array.
     pub pending_contracts: Vec<RuntimeContract>,
+    /// The array has been written as a tuple literal, as in `(1, "a")`. Tuples are arrays at
+    /// runtime: this flag is only used to give the literal a tuple type during typechecking and
+    /// to pretty print it back as a tuple.
+    pub tuple: bool,
 }
 
 impl ArrayAttrs {
@@ -34,6 +38,12 @@ impl ArrayAttrs {
         self
     }
 
+    /// Set the `tuple` flag to `true`.
+    pub fn tuple(mut self) -> Self {
+        self.tuple = true;
+        self
+    }
+
     /// Drop the pending contracts.
     pub fn contracts_cleared(mut self) -> Self {
         self.pending_contracts.clear();
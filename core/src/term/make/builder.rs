@@ -639,6 +639,7 @@ mod tests {
                         opt: true,
                         priority: MergePriority::Bottom,
                         not_exported: true,
+                        deprecated: None,
                         annotation: TypeAnnotation {
                             typ: Some(LabeledType {
                                 typ: Type {
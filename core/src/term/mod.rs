@@ -44,6 +44,8 @@ pub use malachite::{
 };
 
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 // Because we use `IndexMap` for recors, consumer of Nickel (as a library) might have to
 // manipulate values of this type, so we re-export this type.
@@ -55,7 +57,7 @@ use std::{
     ffi::OsString,
     fmt,
     ops::Deref,
-    rc::Rc,
+    rc::{Rc, Weak},
 };
 
 /// The AST of a Nickel expression.
@@ -1108,6 +1110,41 @@ impl SharedTerm {
     pub fn ptr_eq(this: &SharedTerm, that: &SharedTerm) -> bool {
         Rc::ptr_eq(&this.shared, &that.shared)
     }
+
+    /// Return the address of the term. See [WeakTerm::addr].
+    pub fn addr(this: &SharedTerm) -> usize {
+        Rc::as_ptr(&this.shared) as usize
+    }
+
+    /// Create a weak reference to this term. See [WeakTerm].
+    pub fn downgrade(this: &SharedTerm) -> WeakTerm {
+        WeakTerm {
+            shared: Rc::downgrade(&this.shared),
+        }
+    }
+}
+
+/// A weak reference to a [SharedTerm], which doesn't keep the term alive.
+///
+/// As long as a weak reference exists, the memory of the term isn't reused, even once the term has
+/// been dropped. Besides, [SharedTerm::make_mut] doesn't modify a term in place when there are
+/// weak references to it. A weak reference thus identifies one term for as long as it exists.
+#[derive(Debug, Clone)]
+pub struct WeakTerm {
+    shared: Weak<Term>,
+}
+
+impl WeakTerm {
+    /// Check if the term has been dropped.
+    pub fn is_dropped(&self) -> bool {
+        self.shared.strong_count() == 0
+    }
+
+    /// Return the address of the term, which identifies it for as long as this weak reference
+    /// exists.
+    pub fn addr(&self) -> usize {
+        self.shared.as_ptr() as usize
+    }
 }
 
 impl AsRef<Term> for SharedTerm {
@@ -1180,6 +1217,14 @@ pub enum UnaryOp {
     /// tags to the corresponding branches, and the default case when `has_default` is `true`.
     TagsOnlyMatch { has_default: bool },
 
+    /// A primop for match which selects the branches to try from the head constructor of the
+    /// matched value, such as its type, its enum tag or its set of fields (see
+    /// [crate::term::pattern::compile]).
+    /// [Self::HeadMatch] takes two additional lazy arguments: a record mapping the keys of head
+    /// constructors to the corresponding branches, and the branches to try for other head
+    /// constructors.
+    HeadMatch(),
+
     /// Static access to a record field.
     ///
     /// Static means that the field identifier is a statically known string inside the source.
@@ -1264,6 +1309,10 @@ pub enum UnaryOp {
     /// Return the values of the fields of a record as an array.
     ValuesOf(),
 
+    /// Sort the fields of a record alphabetically. The resulting record is marked as
+    /// [ordered](crate::term::record::RecordAttrs::ordered).
+    RecordSortFields(),
+
     /// Remove heading and trailing spaces from a string.
     StrTrim(),
 
@@ -1288,14 +1337,54 @@ pub enum UnaryOp {
     /// Transform a string to an enum.
     EnumFromStr(),
 
+    /// Parse a URL into a record of its components. See [crate::eval::url].
+    UrlParse(),
+
+    /// Percent-encode a string to be used as a URL component.
+    UrlEncode(),
+
+    /// Decode a percent-encoded string.
+    UrlDecode(),
+
+    /// Encode the UTF-8 bytes of a string in standard base64 with padding.
+    Base64Encode(),
+
+    /// Decode a string encoded in standard base64. Fails if the input isn't valid base64, or if
+    /// the decoded bytes aren't valid UTF-8.
+    Base64Decode(),
+
+    /// Parse a semantic version into a record of its components. See [crate::eval::semver].
+    SemverParse(),
+
+    /// Compute the structural key of a value, which is the same for structurally equal values. The
+    /// argument must have been fully evaluated beforehand. See [crate::eval::structural_key].
+    StructuralKey(),
+
+    /// Generate a random (version 4) UUID. The argument is ignored. See [crate::eval::uuid].
+    UuidV4(),
+
+    /// Parse a UUID and return its canonical form.
+    UuidParse(),
+
+    /// Read an environment variable, returning `'Some value`, or `'None` if the variable isn't
+    /// set or if the host didn't allow the program to read it.
+    EnvGet(),
+
+    /// Read the content of a text file, if the host allowed the program to read it. See
+    /// [crate::eval::fs].
+    FsReadText(),
+
+    /// List the entries of a directory, if the host allowed the program to read it.
+    FsReadDir(),
+
     /// Test if a regex matches a string.
     /// Like [`UnaryOp::StrFind`], this is a unary operator because we would like a way to share the
     /// same "compiled regex" for many matching calls. This is done by returning functions
     /// wrapping [`UnaryOp::StrIsMatchCompiled`] and [`UnaryOp::StrFindCompiled`]
     StrIsMatch(),
 
-    /// Match a regex on a string, and returns the captured groups together, the index of the
-    /// match, etc.
+    /// Match a regex on a string, and returns the captured groups together, the named captured
+    /// groups, the index of the match, etc.
     StrFind(),
 
     /// Returns all matches of a regex on a string, as an array of matches. Each
@@ -1303,6 +1392,9 @@ pub enum UnaryOp {
     /// matched string.
     StrFindAll(),
 
+    /// Split a string on the matches of a regex, keeping the matches in the result.
+    StrSplitKeep(),
+
     /// Version of [`UnaryOp::StrIsMatch`] which remembers the compiled regex.
     StrIsMatchCompiled(CompiledRegex),
 
@@ -1312,6 +1404,9 @@ pub enum UnaryOp {
     /// Version of [`UnaryOp::StrFindAll`] which remembers the compiled regex.
     StrFindAllCompiled(CompiledRegex),
 
+    /// Version of [`UnaryOp::StrSplitKeep`] which remembers the compiled regex.
+    StrSplitKeepCompiled(CompiledRegex),
+
     /// Force full evaluation of a term and return it.
     ///
     /// This was added in the context of [`BinaryOp::ArrayLazyAppCtr`], in particular to make
@@ -1435,6 +1530,7 @@ impl fmt::Display for UnaryOp {
             Blame() => write!(f, "blame"),
             Embed(_) => write!(f, "embed"),
             TagsOnlyMatch { .. } => write!(f, "match"),
+            HeadMatch() => write!(f, "match"),
             StaticAccess(_) => write!(f, "static_access"),
             ArrayMap() => write!(f, "map"),
             RecordMap() => write!(f, "record_map"),
@@ -1452,6 +1548,7 @@ impl fmt::Display for UnaryOp {
             FieldsOf(RecordOpKind::IgnoreEmptyOpt) => write!(f, "fields"),
             FieldsOf(RecordOpKind::ConsiderAllFields) => write!(f, "fields_with_opts"),
             ValuesOf() => write!(f, "values"),
+            RecordSortFields() => write!(f, "record_sort_fields"),
             StrTrim() => write!(f, "str_trim"),
             StrChars() => write!(f, "str_chars"),
             StrUppercase() => write!(f, "str_uppercase"),
@@ -1460,12 +1557,26 @@ impl fmt::Display for UnaryOp {
             ToStr() => write!(f, "to_str"),
             NumFromStr() => write!(f, "num_from_str"),
             EnumFromStr() => write!(f, "enum_from_str"),
+            UrlParse() => write!(f, "url_parse"),
+            UrlEncode() => write!(f, "url_encode"),
+            UrlDecode() => write!(f, "url_decode"),
+            Base64Encode() => write!(f, "base64_encode"),
+            Base64Decode() => write!(f, "base64_decode"),
+            SemverParse() => write!(f, "semver_parse"),
+            StructuralKey() => write!(f, "structural_key"),
+            UuidV4() => write!(f, "uuid_v4"),
+            UuidParse() => write!(f, "uuid_parse"),
+            EnvGet() => write!(f, "env_get"),
+            FsReadText() => write!(f, "fs_read_text"),
+            FsReadDir() => write!(f, "fs_read_dir"),
             StrIsMatch() => write!(f, "str_is_match"),
             StrFind() => write!(f, "str_find"),
             StrFindAll() => write!(f, "str_find_all"),
+            StrSplitKeep() => write!(f, "str_split_keep"),
             StrIsMatchCompiled(_) => write!(f, "str_is_match_compiled"),
             StrFindCompiled(_) => write!(f, "str_find_compiled"),
             StrFindAllCompiled(_) => write!(f, "str_find_all_compiled"),
+            StrSplitKeepCompiled(_) => write!(f, "str_split_keep_compiled"),
             Force { .. } => write!(f, "force"),
             RecDefault() => write!(f, "rec_default"),
             RecForce() => write!(f, "rec_force"),
@@ -1574,6 +1685,19 @@ pub enum BinaryOp {
     /// Raise a number to a power.
     Pow(),
 
+    /// Bitwise and of integers, in two's complement for negative integers.
+    BitAnd(),
+
+    /// Bitwise or of integers, in two's complement for negative integers.
+    BitOr(),
+
+    /// Bitwise exclusive or of integers, in two's complement for negative integers.
+    BitXor(),
+
+    /// Shift the bits of an integer to the left by a given amount, or to the right if the amount
+    /// is negative.
+    BitShift(),
+
     /// Concatenation of strings.
     StrConcat(),
 
@@ -1599,6 +1723,13 @@ pub enum BinaryOp {
     /// argument on the stack for better error reporting.
     ApplyContract(),
 
+    /// Mark an evaluated record or array as validated by a contract, which is recorded by the
+    /// virtual machine (see [crate::eval::freeze]). Subsequent applications of an equal contract
+    /// to this very value are then no-ops.
+    /// Other values are returned unchanged. This operator doesn't check the contract itself: it
+    /// must be used on a value which has already been fully checked, see `std.contract.freeze`.
+    ContractFreeze(),
+
     /// Unseal a sealed term.
     ///
     /// See [`BinaryOp::Seal`].
@@ -1646,6 +1777,19 @@ pub enum BinaryOp {
     /// Access the n-th element of an array.
     ArrayElemAt(),
 
+    /// Sort an array using a comparison function, which is called back from within the
+    /// primop. The sort is stable.
+    ArraySortBy(),
+
+    /// Split an array into consecutive sub-arrays of a given size. The last chunk is shorter if
+    /// the size doesn't divide the length of the array. The chunks are slices of the original
+    /// array, and don't copy its elements.
+    ArrayChunks(),
+
+    /// Return all the contiguous sub-arrays of a given size of an array, in order. As for
+    /// [BinaryOp::ArrayChunks], the windows are slices of the original array.
+    ArrayWindows(),
+
     /// The merge operator (see [crate::eval::merge]). `Merge` is parametrized by a
     /// [crate::label::MergeLabel], which carries additional information for error-reporting
     /// purpose.
@@ -1654,6 +1798,10 @@ pub enum BinaryOp {
     /// Hash a string.
     Hash(),
 
+    /// List the differences between two values, as an array of human-readable lines. The
+    /// arguments must have been fully evaluated beforehand. See [crate::eval::structural_diff].
+    StructuralDiff(),
+
     /// Serialize a value to a string.
     Serialize(),
 
@@ -1666,6 +1814,28 @@ pub enum BinaryOp {
     /// Determine if a string is a substring of another one.
     StrContains(),
 
+    /// Parse a date and time according to a format string, returning an RFC 3339 timestamp. See
+    /// [crate::eval::time].
+    TimeParse(),
+
+    /// Format an RFC 3339 timestamp according to a format string.
+    TimeFormat(),
+
+    /// Add a duration in seconds to an RFC 3339 timestamp.
+    TimeAdd(),
+
+    /// Compute the duration in seconds between two RFC 3339 timestamps.
+    TimeDiff(),
+
+    /// Generate the name-based (version 5) UUID of a name in a namespace. See [crate::eval::uuid].
+    UuidV5(),
+
+    /// Compare two semantic versions by precedence. See [crate::eval::semver].
+    SemverCompare(),
+
+    /// Check if a semantic version satisfies a version requirement.
+    SemverSatisfies(),
+
     /// Seal a term with a sealing key (see [`Term::Sealed`]).
     Seal(),
 
@@ -1677,6 +1847,24 @@ pub enum BinaryOp {
     /// the name of the field as a parameter and returns the corresponding contract.
     RecordLazyAppCtr(),
 
+    /// Map a function over the leaves of a tree of nested records. The function takes the path
+    /// of the leaf, as an array of strings, and its value.
+    RecordDeepMap(),
+
+    /// Filter the leaves of a tree of nested records using a predicate, which takes the path of
+    /// the leaf, as an array of strings, and its value.
+    RecordDeepFilter(),
+
+    /// Sort the fields of a record using a comparison function on their names, which is called
+    /// back from within the primop. The sort is stable. As for [UnaryOp::RecordSortFields], the
+    /// resulting record is marked as ordered.
+    RecordSortFieldsBy(),
+
+    /// Move the fields of a record whose names are given as an array of strings first, in this
+    /// order, followed by the other fields in their original order. Names which aren't fields of
+    /// the record are ignored. The resulting record is marked as ordered.
+    RecordMoveFirst(),
+
     /// Set the message of the current diagnostic of a label.
     LabelWithMessage(),
 
@@ -1712,6 +1900,10 @@ impl fmt::Display for BinaryOp {
             Div() => write!(f, "div"),
             Modulo() => write!(f, "modulo"),
             Pow() => write!(f, "pow"),
+            BitAnd() => write!(f, "bit_and"),
+            BitOr() => write!(f, "bit_or"),
+            BitXor() => write!(f, "bit_xor"),
+            BitShift() => write!(f, "bit_shift"),
             StrConcat() => write!(f, "str_concat"),
             Eq() => write!(f, "eq"),
             LessThan() => write!(f, "less_than"),
@@ -1719,6 +1911,7 @@ impl fmt::Display for BinaryOp {
             GreaterThan() => write!(f, "greater_than"),
             GreaterOrEq() => write!(f, "greater_or_eq"),
             ApplyContract() => write!(f, "apply_contract"),
+            ContractFreeze() => write!(f, "contract_freeze"),
             Unseal() => write!(f, "unseal"),
             GoField() => write!(f, "go_field"),
             DynExtend {
@@ -1740,15 +1933,30 @@ impl fmt::Display for BinaryOp {
             }
             ArrayConcat() => write!(f, "array_concat"),
             ArrayElemAt() => write!(f, "elem_at"),
+            ArraySortBy() => write!(f, "array_sort_by"),
+            ArrayChunks() => write!(f, "array_chunks"),
+            ArrayWindows() => write!(f, "array_windows"),
             Merge(_) => write!(f, "merge"),
             Hash() => write!(f, "hash"),
+            StructuralDiff() => write!(f, "structural_diff"),
             Serialize() => write!(f, "serialize"),
             Deserialize() => write!(f, "deserialize"),
             StrSplit() => write!(f, "str_split"),
             StrContains() => write!(f, "str_contains"),
+            TimeParse() => write!(f, "time_parse"),
+            TimeFormat() => write!(f, "time_format"),
+            TimeAdd() => write!(f, "time_add"),
+            TimeDiff() => write!(f, "time_diff"),
+            UuidV5() => write!(f, "uuid_v5"),
+            SemverCompare() => write!(f, "semver_compare"),
+            SemverSatisfies() => write!(f, "semver_satisfies"),
             Seal() => write!(f, "seal"),
             ArrayLazyAppCtr() => write!(f, "array_lazy_app_ctr"),
             RecordLazyAppCtr() => write!(f, "record_lazy_app_ctr"),
+            RecordDeepMap() => write!(f, "record_deep_map"),
+            RecordDeepFilter() => write!(f, "record_deep_filter"),
+            RecordSortFieldsBy() => write!(f, "record_sort_fields_by"),
+            RecordMoveFirst() => write!(f, "record_move_first"),
             LabelWithMessage() => write!(f, "label_with_message"),
             LabelWithNotes() => write!(f, "label_with_notes"),
             LabelAppendNote() => write!(f, "label_append_note"),
@@ -1807,6 +2015,42 @@ pub enum NAryOp {
     /// hood, as long as the array isn't modified later, this operation is constant in time and
     /// memory.
     ArraySlice(),
+
+    /// Recursively merge two trees of nested records. Fields defined on both sides are merged
+    /// recursively if they are both records, or combined using a function otherwise. The
+    /// arguments are in order the function, which takes the path of the field as an array of
+    /// strings and the two values, and the two records.
+    RecordDeepMergeWith(),
+
+    /// Combine two arrays element-wise using a function. The length of the result is the smaller
+    /// of the lengths of the two arrays. The arguments are in order the function and the two
+    /// arrays.
+    ArrayZipWith(),
+
+    /// Materialize the first elements of a lazy sequence into an array. The arguments are in
+    /// order the maximum number of elements, the step function and the initial state. The step
+    /// function is called on the current state and returns either `'None`, which ends the
+    /// sequence, or `'Some { value, state }`. It's only called as many times as needed.
+    SequenceTake(),
+
+    /// Parse a CSV document into an array of records, one per row, whose field names are given by
+    /// the header row. The arguments are in order the delimiter, the quote character, whether to
+    /// infer the types of the fields, and the document. See [crate::serialize::from_csv].
+    CsvParse(),
+
+    /// Round a number to a given number of decimal digits. The arguments are in order the rounding
+    /// mode, the number of digits after the decimal point, which can be negative to round to tens,
+    /// hundreds and so on, and the number. The rounding is exact, as it doesn't go through
+    /// floating point numbers.
+    NumberRound(),
+
+    /// Hash a string. The arguments are in order the hashing algorithm, the encoding of the
+    /// digest, either hexadecimal or base64, and the string. See [crate::eval::digest].
+    HashWith(),
+
+    /// Compute the HMAC of a string. The arguments are in order the hashing algorithm, the
+    /// encoding of the digest, the secret key and the string. See [crate::eval::digest].
+    Hmac(),
 }
 
 impl NAryOp {
@@ -1818,8 +2062,13 @@ impl NAryOp {
             | NAryOp::MergeContract()
             | NAryOp::RecordUnsealTail()
             | NAryOp::InsertTypeVar()
-            | NAryOp::ArraySlice() => 3,
-            NAryOp::RecordSealTail() => 4,
+            | NAryOp::ArraySlice()
+            | NAryOp::RecordDeepMergeWith()
+            | NAryOp::ArrayZipWith()
+            | NAryOp::SequenceTake()
+            | NAryOp::NumberRound()
+            | NAryOp::HashWith() => 3,
+            NAryOp::RecordSealTail() | NAryOp::CsvParse() | NAryOp::Hmac() => 4,
         }
     }
 }
@@ -1836,6 +2085,13 @@ impl fmt::Display for NAryOp {
             RecordUnsealTail() => write!(f, "record_unseal_tail"),
             InsertTypeVar() => write!(f, "insert_type_variable"),
             ArraySlice() => write!(f, "array_slice"),
+            RecordDeepMergeWith() => write!(f, "record_deep_merge_with"),
+            ArrayZipWith() => write!(f, "array_zip_with"),
+            SequenceTake() => write!(f, "sequence_take"),
+            CsvParse() => write!(f, "csv_parse"),
+            NumberRound() => write!(f, "number_round"),
+            HashWith() => write!(f, "hash_with"),
+            Hmac() => write!(f, "hmac"),
         }
     }
 }
@@ -1900,27 +2156,63 @@ impl RichTerm {
         self
     }
 
-    /// Pretty print a term capped to a given max length (in characters). Useful to limit the size
-    /// of terms reported e.g. in typechecking errors. If the output of pretty printing is greater
-    /// than the bound, the string is truncated to `max_width` and the last character after
-    /// truncate is replaced by the ellipsis unicode character U+2026.
+    /// Pretty print a term capped to a given max width (in columns, as displayed in a terminal).
+    /// Useful to limit the size of terms reported e.g. in typechecking errors.
+    ///
+    /// If the output of pretty printing is wider than the bound, the elements of large arrays and
+    /// records are elided, keeping fewer and fewer of them, as in `[ 1, 2, … 998 more ]`. If this
+    /// isn't enough, the output is truncated on a grapheme boundary and the last grapheme is
+    /// replaced by the ellipsis unicode character U+2026.
     pub fn pretty_print_cap(&self, max_width: usize) -> String {
-        let output = self.to_string();
+        // The successive numbers of elements kept in arrays and records when eliding.
+        const KEPT_ELEMENTS: [usize; 5] = [8, 4, 2, 1, 0];
+
+        let mut output = self.to_string();
+
+        for kept in KEPT_ELEMENTS {
+            if output.width() <= max_width {
+                return output;
+            }
+
+            output = crate::pretty::to_string_elided(self, kept);
+        }
 
-        if output.len() <= max_width {
+        if output.width() <= max_width {
             output
         } else {
-            let (end, _) = output.char_indices().nth(max_width).unwrap();
-            let mut truncated = String::from(&output[..end]);
+            truncate_to_width(&output, max_width)
+        }
+    }
+}
 
-            if max_width >= 2 {
-                truncated.pop();
-                truncated.push('\u{2026}');
-            }
+/// Truncate a string to a given max width (in columns), on a grapheme boundary. If the string is
+/// truncated and `max_width` is at least 2, the last grapheme is replaced by the ellipsis unicode
+/// character U+2026.
+fn truncate_to_width(s: &str, max_width: usize) -> String {
+    let budget = if max_width >= 2 {
+        max_width - 1
+    } else {
+        max_width
+    };
 
-            truncated
+    let mut truncated = String::new();
+    let mut width = 0;
+
+    for grapheme in s.graphemes(true) {
+        width += grapheme.width();
+
+        if width > budget {
+            break;
         }
+
+        truncated.push_str(grapheme);
     }
+
+    if max_width >= 2 {
+        truncated.push('\u{2026}');
+    }
+
+    truncated
 }
 
 /// Flow control for tree traverals.
@@ -2655,4 +2947,42 @@ mod tests {
             t
         );
     }
+
+    #[test]
+    fn pretty_print_cap_elides_elements() {
+        let array: RichTerm =
+            Term::Array((0..1000).map(make::integer).collect(), ArrayAttrs::new()).into();
+        assert_eq!(
+            array.pretty_print_cap(30),
+            "[ 0, 1, 2, 3, \u{2026} 996 more ]"
+        );
+
+        let record: RichTerm = Term::Record(RecordData::with_field_values(
+            ('a'..='z').map(|c| (LocIdent::from(c.to_string()), make::integer(1))),
+        ))
+        .into();
+        assert_eq!(record.pretty_print_cap(20), "{ a = 1, b = 1, .. }");
+
+        let small: RichTerm = Term::Array(
+            [1, 2].map(make::integer).into_iter().collect(),
+            ArrayAttrs::new(),
+        )
+        .into();
+        assert_eq!(small.pretty_print_cap(80), "[ 1, 2 ]");
+    }
+
+    #[test]
+    fn truncate_on_grapheme_boundaries() {
+        assert_eq!(truncate_to_width("abcdef", 4), "abc\u{2026}");
+        // Wide characters take two columns.
+        assert_eq!(
+            truncate_to_width("\u{65e5}\u{672c}\u{8a9e}", 5),
+            "\u{65e5}\u{672c}\u{2026}"
+        );
+        // A combining accent isn't separated from its base character.
+        assert_eq!(
+            truncate_to_width("e\u{301}e\u{301}e\u{301}", 3),
+            "e\u{301}e\u{301}\u{2026}"
+        );
+    }
 }
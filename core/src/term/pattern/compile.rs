@@ -11,11 +11,25 @@
 //! resource on this topic is the paper [_Compiling Pattern Matching to Good Decision
 //! Trees_](https://dl.acm.org/doi/10.1145/1411304.1411311) by Luc Maranget.
 //!
-//! The current version of pattern compilation in Nickel is naive: it simply compiles each pattern
-//! to a checking expression and tries them all until one works. We don't expect pattern matching
-//! to be relevant for performance anytime soon (allegedly, there are much more impacting aspects
-//! to handle before that). We might revisit this in the future if pattern matching turns out to be
-//! a bottleneck.
+//! The default version of pattern compilation in Nickel is naive: it simply compiles each pattern
+//! to a checking expression and tries them all until one works (see
+//! [MatchCompilation::Sequential]). When the branches have different head constructors - the
+//! outermost shape of their pattern, such as a specific enum tag or a record with a given set of
+//! fields - matching can do better: the experimental [MatchCompilation::DecisionTree] strategy
+//! builds the first level of a decision tree in the style of Maranget's. The head constructor of
+//! the matched value is computed once by the [crate::term::UnaryOp::HeadMatch] primop, which then
+//! selects the branches which can match a value with this head constructor, and only those are
+//! tried in order.
+//!
+//! For records, the head constructor is the set of the fields of the record. A closed record
+//! pattern without default values, such as `{x, y}`, only matches records with exactly these
+//! fields, so its branch is only tried for such records. Other record patterns, which are open or
+//! have default values, are tried for any record.
+//!
+//! The deeper levels of the tree aren't built: the selected patterns are still checked from
+//! scratch. Besides, a match expression is compiled each time it's applied, and building the
+//! dispatch costs a pass over the branches for each head constructor. Until the whole tree is
+//! built and compiled matches are reused, this strategy isn't the default.
 //!
 //! Most building blocks are generated programmatically rather than written out as e.g. members of
 //! the [crate::stdlib::internals] module. While clunkier, this makes it easier to change
@@ -26,8 +40,8 @@ use super::*;
 use crate::{
     mk_app,
     term::{
-        make, record::FieldMetadata, BinaryOp, MatchBranch, MatchData, RecordExtKind, RecordOpKind,
-        RichTerm, Term, UnaryOp,
+        make, record::FieldMetadata, BinaryOp, MatchBranch, MatchData, NAryOp, RecordExtKind,
+        RecordOpKind, RichTerm, Term, UnaryOp,
     },
 };
 
@@ -258,6 +272,8 @@ impl CompilePart for PatternData {
                 insert_binding(*id, value_id, bindings_id)
             }
             PatternData::Record(pat) => pat.compile_part(value_id, bindings_id),
+            PatternData::Array(pat) => pat.compile_part(value_id, bindings_id),
+            PatternData::Tuple(pat) => pat.compile_part(value_id, bindings_id),
             PatternData::Enum(pat) => pat.compile_part(value_id, bindings_id),
             PatternData::Constant(pat) => pat.compile_part(value_id, bindings_id),
         }
@@ -579,6 +595,177 @@ impl CompilePart for RecordPattern {
     }
 }
 
+/// Compile the element patterns and the tail of an array pattern. This is shared by array patterns
+/// and tuple patterns, which are compiled as closed array patterns.
+//
+// if %typeof% value_id == 'Array && %length% value_id <op> <n> then
+//   # <op> is `==` if the pattern is closed, and `>=` if it's open
+//
+//   let final_bindings_id =
+//     <fold (idx, elem_pat) in patterns
+//      - cont is the accumulator
+//      - initial accumulator is `bindings_id`
+//      >
+//
+//      let local_bindings_id = cont in
+//
+//      if local_bindings_id == null then
+//        null
+//      else
+//        let local_value_id = %elem_at% value_id <idx> in
+//        <elem_pat.compile_part(local_value_id, local_bindings_id)>
+//     <end fold>
+//   in
+//
+//   <if tail is capture(rest)>
+//     if final_bindings_id == null then
+//       null
+//     else
+//       %record_insert% <rest>
+//         final_bindings_id
+//         (%array_slice% <n> (%length% value_id) value_id)
+//   <else>
+//     final_bindings_id
+//   <end if>
+// else
+//   null
+fn compile_array_pattern(
+    patterns: &[Pattern],
+    tail: &ArrayPatternTail,
+    value_id: LocIdent,
+    bindings_id: LocIdent,
+) -> RichTerm {
+    let len = patterns.len();
+
+    // The fold block:
+    //
+    // <fold (idx, elem_pat) in patterns
+    //  - cont is the accumulator
+    //  - initial accumulator is `bindings_id`
+    // >
+    //
+    // let local_bindings_id = cont in
+    //
+    // if local_bindings_id == null then
+    //   null
+    // else
+    //   let local_value_id = %elem_at% value_id <idx> in
+    //   <elem_pat.compile_part(local_value_id, local_bindings_id)>
+    let fold_block: RichTerm =
+        patterns
+            .iter()
+            .enumerate()
+            .fold(Term::Var(bindings_id).into(), |cont, (idx, elem_pat)| {
+                let local_bindings_id = LocIdent::fresh();
+                let local_value_id = LocIdent::fresh();
+
+                // %elem_at% value_id <idx>
+                let extracted_value = make::op2(
+                    BinaryOp::ArrayElemAt(),
+                    Term::Var(value_id),
+                    Term::Num(idx.into()),
+                );
+
+                // let local_value_id = <extracted_value> in
+                // <elem_pat.compile_part(local_value_id, local_bindings_id)>
+                let inner_else_block = make::let_in(
+                    local_value_id,
+                    extracted_value,
+                    elem_pat.compile_part(local_value_id, local_bindings_id),
+                );
+
+                // if local_bindings_id == null then
+                //   null
+                // else
+                //   <inner_else_block>
+                let inner_if = make::if_then_else(
+                    make::op2(BinaryOp::Eq(), Term::Var(local_bindings_id), Term::Null),
+                    Term::Null,
+                    inner_else_block,
+                );
+
+                // let local_bindings_id = cont in <inner_if>
+                make::let_in(local_bindings_id, cont, inner_if)
+            });
+
+    let final_bindings_id = LocIdent::fresh();
+
+    // the block which depends on the tail of the array pattern
+    let tail_block = match tail {
+        // if final_bindings_id == null then
+        //   null
+        // else
+        //   %record_insert% <rest>
+        //     final_bindings_id
+        //     (%array_slice% <n> (%length% value_id) value_id)
+        ArrayPatternTail::Capture(rest) => make::if_then_else(
+            make::op2(BinaryOp::Eq(), Term::Var(final_bindings_id), Term::Null),
+            Term::Null,
+            mk_app!(
+                make::op2(
+                    record_insert(),
+                    Term::Str(rest.label().into()),
+                    Term::Var(final_bindings_id),
+                ),
+                make::opn(
+                    NAryOp::ArraySlice(),
+                    vec![
+                        Term::Num(len.into()).into(),
+                        make::op1(UnaryOp::ArrayLength(), Term::Var(value_id)),
+                        Term::Var(value_id).into(),
+                    ]
+                )
+            ),
+        ),
+        // final_bindings_id
+        ArrayPatternTail::Empty | ArrayPatternTail::Open => Term::Var(final_bindings_id).into(),
+    };
+
+    // let final_bindings_id = <fold_block> in <tail_block>
+    let outer_let = make::let_in(final_bindings_id, fold_block, tail_block);
+
+    // %typeof% value_id == 'Array
+    let is_array: RichTerm = make::op2(
+        BinaryOp::Eq(),
+        make::op1(UnaryOp::Typeof(), Term::Var(value_id)),
+        Term::Enum("Array".into()),
+    );
+
+    // %length% value_id <op> <n>
+    let length_matches = make::op2(
+        if !matches!(tail, ArrayPatternTail::Empty) {
+            BinaryOp::GreaterOrEq()
+        } else {
+            BinaryOp::Eq()
+        },
+        make::op1(UnaryOp::ArrayLength(), Term::Var(value_id)),
+        Term::Num(len.into()),
+    );
+
+    // <is_array> && <length_matches>
+    let if_condition = mk_app!(make::op1(UnaryOp::BoolAnd(), is_array), length_matches);
+
+    // if <if_condition> then <outer_let> else null
+    make::if_then_else(if_condition, outer_let, Term::Null)
+}
+
+impl CompilePart for ArrayPattern {
+    fn compile_part(&self, value_id: LocIdent, bindings_id: LocIdent) -> RichTerm {
+        compile_array_pattern(&self.patterns, &self.tail, value_id, bindings_id)
+    }
+}
+
+impl CompilePart for TuplePattern {
+    fn compile_part(&self, value_id: LocIdent, bindings_id: LocIdent) -> RichTerm {
+        compile_array_pattern(
+            &self.patterns,
+            &ArrayPatternTail::Empty,
+            value_id,
+            bindings_id,
+        )
+    }
+}
+
 impl CompilePart for EnumPattern {
     fn compile_part(&self, value_id: LocIdent, bindings_id: LocIdent) -> RichTerm {
         // %enum_get_tag% value_id == '<self.tag>
@@ -643,6 +830,252 @@ impl CompilePart for EnumPattern {
     }
 }
 
+/// The strategy used to compile match expressions. See the [module documentation][self].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatchCompilation {
+    /// Try each branch in order, checking the matched value from scratch each time.
+    #[default]
+    Sequential,
+    /// First dispatch on the head constructor of the matched value, and then only try the branches
+    /// which can match a value with this head constructor, in order. This strategy is
+    /// experimental.
+    DecisionTree,
+}
+
+/// The key of the head constructor of any record, used for the record patterns which can match
+/// records with different sets of fields. See [record_shape_head].
+const ANY_RECORD_HEAD: &str = "type:Record";
+
+/// Return the key of the head constructor of the records with exactly the given fields. The field
+/// names are sorted, and quoted so that the key is unambiguous whatever the names.
+fn record_shape_head<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+    let mut fields: Vec<_> = fields.collect();
+    fields.sort_unstable();
+    format!("record:{fields:?}")
+}
+
+/// Return the key of the head constructor of the values matched by a pattern, or `None` if the
+/// pattern matches values with any head constructor. See [value_heads].
+fn pattern_head(data: &PatternData) -> Option<String> {
+    match data {
+        PatternData::Wildcard | PatternData::Any(_) => None,
+        // A closed pattern without default values only matches the records which define exactly
+        // the fields of the pattern.
+        PatternData::Record(record_pat)
+            if !record_pat.is_open()
+                && record_pat
+                    .patterns
+                    .iter()
+                    .all(|field| field.default.is_none()) =>
+        {
+            Some(record_shape_head(
+                record_pat
+                    .patterns
+                    .iter()
+                    .map(|field| field.matched_id.label()),
+            ))
+        }
+        PatternData::Record(_) => Some(String::from(ANY_RECORD_HEAD)),
+        PatternData::Array(_) | PatternData::Tuple(_) => Some(String::from("type:Array")),
+        PatternData::Enum(EnumPattern {
+            tag, pattern: None, ..
+        }) => Some(format!("tag:{tag}")),
+        PatternData::Enum(EnumPattern { tag, .. }) => Some(format!("variant:{tag}")),
+        PatternData::Constant(ConstantPattern { data, .. }) => Some(String::from(match data {
+            ConstantPatternData::Bool(_) => "type:Bool",
+            ConstantPatternData::Number(_) => "type:Number",
+            ConstantPatternData::String(_) => "type:String",
+            ConstantPatternData::Null => "type:Null",
+        })),
+    }
+}
+
+/// Return the keys of the head constructors of an evaluated value, from the most specific to the
+/// least specific, as used by the [crate::term::UnaryOp::HeadMatch] primop to select the branches
+/// of a match. The prefixes of the keys make them distinct, whatever the enum tags and field names.
+pub(crate) fn value_heads(t: &Term) -> Vec<String> {
+    let head = match t {
+        Term::Enum(tag) => format!("tag:{tag}"),
+        Term::EnumVariant { tag, .. } => format!("variant:{tag}"),
+        // Optional fields without a value are ignored by record patterns, as they are by other
+        // record operations.
+        Term::Record(record) => {
+            return vec![
+                record_shape_head(
+                    record
+                        .fields
+                        .iter()
+                        .filter(|(_, field)| !field.is_empty_optional())
+                        .map(|(id, _)| id.label()),
+                ),
+                String::from(ANY_RECORD_HEAD),
+            ]
+        }
+        Term::RecRecord(..) => String::from(ANY_RECORD_HEAD),
+        Term::Array(..) => String::from("type:Array"),
+        Term::Bool(_) => String::from("type:Bool"),
+        Term::Num(_) => String::from("type:Number"),
+        Term::Str(_) => String::from("type:String"),
+        Term::Null => String::from("type:Null"),
+        _ => String::from("type:Other"),
+    };
+
+    vec![head]
+}
+
+/// Check if a branch whose pattern has the head constructor `pattern_head` can match a value
+/// whose most specific head constructor is `value_head`.
+fn head_compatible(pattern_head: &str, value_head: &str) -> bool {
+    pattern_head == value_head
+        || (pattern_head == ANY_RECORD_HEAD && value_head.starts_with("record:"))
+}
+
+/// Compile branches to a sequence of checks, tried in order until one matches (see
+/// [MatchCompilation::Sequential]). The branches refer to the matched value as `value_id`, and
+/// `error_case` is evaluated when no branch matches.
+//
+// <fold (pattern, body) in branches.rev()
+//  - cont is the accumulator
+//  - initial accumulator is `error_case`
+// >
+//    let init_bindings_id = {} in
+//    let bindings_id = <pattern.compile_part(value_id, init_bindings)> in
+//
+//    if bindings_id == null || !<guard> then
+//      cont
+//    else
+//      # this primop evaluates body with an environment extended with bindings_id
+//      %pattern_branch% body bindings_id
+fn compile_sequential(
+    branches: Vec<MatchBranch>,
+    value_id: LocIdent,
+    error_case: RichTerm,
+) -> RichTerm {
+    branches.into_iter().rev().fold(error_case, |cont, branch| {
+        let init_bindings_id = LocIdent::fresh();
+        let bindings_id = LocIdent::fresh();
+
+        // inner if condition:
+        // bindings_id == null || !<guard>
+        let inner_if_cond = make::op2(BinaryOp::Eq(), Term::Var(bindings_id), Term::Null);
+        let inner_if_cond = if let Some(guard) = branch.guard {
+            // the guard must be evaluated in the same environment as the body of the
+            // branch, as it might use bindings introduced by the pattern. Since `||` is
+            // lazy in Nickel, we know that `bindings_id` is not null if the guard
+            // condition is ever evaluated.
+            let guard_cond = mk_app!(
+                make::op1(UnaryOp::PatternBranch(), Term::Var(bindings_id)),
+                guard
+            );
+
+            mk_app!(
+                make::op1(UnaryOp::BoolOr(), inner_if_cond),
+                make::op1(UnaryOp::BoolNot(), guard_cond)
+            )
+        } else {
+            inner_if_cond
+        };
+
+        // inner if block:
+        //
+        // if bindings_id == null then
+        //   cont
+        // else
+        //   # this primop evaluates body with an environment extended with bindings_id
+        //   %pattern_branch% bindings_id body
+        let inner = make::if_then_else(
+            inner_if_cond,
+            cont,
+            mk_app!(
+                make::op1(UnaryOp::PatternBranch(), Term::Var(bindings_id),),
+                branch.body
+            ),
+        );
+
+        // The two initial chained let-bindings:
+        //
+        // let init_bindings_id = {} in
+        // let bindings_id = <pattern.compile_part(value_id, init_bindings)> in
+        // <inner>
+        make::let_in(
+            init_bindings_id,
+            Term::Record(RecordData::empty()),
+            make::let_in(
+                bindings_id,
+                branch.pattern.compile_part(value_id, init_bindings_id),
+                inner,
+            ),
+        )
+    })
+}
+
+/// Compile branches to a dispatch on the head constructor of the matched value (see
+/// [MatchCompilation::DecisionTree]), or return `None` if there's nothing to gain, that is if the
+/// branches don't have at least two different head constructors.
+///
+/// The dispatch forces the matched value. To preserve the semantics of the sequential strategy,
+/// which doesn't force the value if the first branch matches anything, the dispatch is only used
+/// when the pattern of the first branch has a head constructor.
+//
+// %head_match% value_id
+//   {
+//     <for head in heads>
+//       "<head>" = <compile_sequential(branches compatible with head)>,
+//     <end for>
+//     # For records, the primop first looks for the key of the exact set of fields of the
+//     # matched value, and falls back to the key of any record.
+//   }
+//   <compile_sequential(branches matching any head constructor)>
+fn compile_head_dispatch(
+    branches: &[MatchBranch],
+    value_id: LocIdent,
+    error_case: &RichTerm,
+) -> Option<RichTerm> {
+    let first_head = pattern_head(&branches.first()?.pattern.data)?;
+    let mut heads = vec![first_head];
+
+    for branch in branches {
+        if let Some(head) = pattern_head(&branch.pattern.data) {
+            if !heads.contains(&head) {
+                heads.push(head);
+            }
+        }
+    }
+
+    if heads.len() < 2 {
+        return None;
+    }
+
+    // The branches which can match a value whose head constructor is `head`, or any head
+    // constructor not mentioned by the patterns if `head` is `None`.
+    let compatible = |head: Option<&str>| {
+        branches
+            .iter()
+            .filter(|branch| match (pattern_head(&branch.pattern.data), head) {
+                (Some(branch_head), Some(head)) => head_compatible(&branch_head, head),
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let cases = heads.iter().map(|head| {
+        (
+            LocIdent::from(head.as_str()),
+            compile_sequential(compatible(Some(head)), value_id, error_case.clone()),
+        )
+    });
+    let cases = Term::Record(RecordData::with_field_values(cases));
+    let default = compile_sequential(compatible(None), value_id, error_case.clone());
+
+    Some(mk_app!(
+        make::op1(UnaryOp::HeadMatch(), Term::Var(value_id)),
+        cases,
+        default
+    ))
+}
+
 pub trait Compile {
     /// Compile a match expression to a Nickel expression with the provided `value_id` as a
     /// free variable (representing a placeholder for the matched expression).
@@ -650,24 +1083,26 @@ pub trait Compile {
 }
 
 impl Compile for MatchData {
+    fn compile(self, value: RichTerm, pos: TermPos) -> RichTerm {
+        self.compile_with(value, pos, MatchCompilation::default())
+    }
+}
+
+impl MatchData {
+    /// Compile a match expression with the given strategy. Matches whose patterns are all enum
+    /// tags are always compiled to the specialized [crate::term::UnaryOp::TagsOnlyMatch] primop.
+    //
     // Compilation of a full match expression (code between < and > is Rust code, think of it as a
     // kind of templating). Note that some special cases compile differently as optimizations.
     //
     // let value_id = value in
-    //
-    // <fold (pattern, body) in branches.rev()
-    //  - cont is the accumulator
-    //  - initial accumulator is the default branch (or error if not default branch)
-    // >
-    //    let init_bindings_id = {} in
-    //    let bindings_id = <pattern.compile()> value_id init_bindings_id in
-    //
-    //    if bindings_id == null then
-    //      cont
-    //    else
-    //      # this primop evaluates body with an environment extended with bindings_id
-    //      %pattern_branch% body bindings_id
-    fn compile(mut self, value: RichTerm, pos: TermPos) -> RichTerm {
+    // <compile_head_dispatch(branches) or compile_sequential(branches)>
+    pub fn compile_with(
+        mut self,
+        value: RichTerm,
+        pos: TermPos,
+        strategy: MatchCompilation,
+    ) -> RichTerm {
         if self.branches.iter().all(|branch| {
             // While we could get something working even with a guard, it's a bit more work and
             // there's no current incentive to do so (a guard on a tags-only match is arguably less
@@ -739,83 +1174,16 @@ impl Compile for MatchData {
 
         let value_id = LocIdent::fresh();
 
-        // The fold block:
-        //
-        // <for branch in branches.rev()
-        //  - cont is the accumulator
-        //  - initial accumulator is the default branch (or error if not default branch)
-        // >
-        //    let init_bindings_id = {} in
-        //    let bindings_id = <pattern.compile_part(value_id, init_bindings)> in
-        //
-        //    if bindings_id == null || !<guard> then
-        //      cont
-        //    else
-        //      # this primop evaluates body with an environment extended with bindings_id
-        //      %pattern_branch% body bindings_id
-        let fold_block = self
-            .branches
-            .into_iter()
-            .rev()
-            .fold(error_case, |cont, branch| {
-                let init_bindings_id = LocIdent::fresh();
-                let bindings_id = LocIdent::fresh();
-
-                // inner if condition:
-                // bindings_id == null || !<guard>
-                let inner_if_cond = make::op2(BinaryOp::Eq(), Term::Var(bindings_id), Term::Null);
-                let inner_if_cond = if let Some(guard) = branch.guard {
-                    // the guard must be evaluated in the same environment as the body of the
-                    // branch, as it might use bindings introduced by the pattern. Since `||` is
-                    // lazy in Nickel, we know that `bindings_id` is not null if the guard
-                    // condition is ever evaluated.
-                    let guard_cond = mk_app!(
-                        make::op1(UnaryOp::PatternBranch(), Term::Var(bindings_id)),
-                        guard
-                    );
-
-                    mk_app!(
-                        make::op1(UnaryOp::BoolOr(), inner_if_cond),
-                        make::op1(UnaryOp::BoolNot(), guard_cond)
-                    )
-                } else {
-                    inner_if_cond
-                };
-
-                // inner if block:
-                //
-                // if bindings_id == null then
-                //   cont
-                // else
-                //   # this primop evaluates body with an environment extended with bindings_id
-                //   %pattern_branch% bindings_id body
-                let inner = make::if_then_else(
-                    inner_if_cond,
-                    cont,
-                    mk_app!(
-                        make::op1(UnaryOp::PatternBranch(), Term::Var(bindings_id),),
-                        branch.body
-                    ),
-                );
-
-                // The two initial chained let-bindings:
-                //
-                // let init_bindings_id = {} in
-                // let bindings_id = <pattern.compile_part(value_id, init_bindings)> in
-                // <inner>
-                make::let_in(
-                    init_bindings_id,
-                    Term::Record(RecordData::empty()),
-                    make::let_in(
-                        bindings_id,
-                        branch.pattern.compile_part(value_id, init_bindings_id),
-                        inner,
-                    ),
-                )
-            });
+        let branches_block = match strategy {
+            MatchCompilation::DecisionTree => {
+                compile_head_dispatch(&self.branches, value_id, &error_case)
+            }
+            MatchCompilation::Sequential => None,
+        }
+        .unwrap_or_else(|| compile_sequential(self.branches, value_id, error_case));
 
-        // let value_id = value in <fold_block>
-        make::let_in(value_id, value, fold_block)
+        // let value_id = value in <branches_block>
+        make::let_in(value_id, value, branches_block)
     }
 }
 
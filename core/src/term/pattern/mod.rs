@@ -25,6 +25,10 @@ pub enum PatternData {
     Any(LocIdent),
     /// A record pattern as in `{ a = { b, c } }`
     Record(RecordPattern),
+    /// An array pattern as in `[a, b, ..rest]`
+    Array(ArrayPattern),
+    /// A tuple pattern as in `(a, b)`
+    Tuple(TuplePattern),
     /// An enum pattern as in `'Foo x` or `'Foo`
     Enum(EnumPattern),
     /// A constant pattern as in `42` or `true`.
@@ -72,8 +76,8 @@ pub struct FieldPattern {
 
 /// The last match in a data structure pattern. This can either be a normal match, or an ellipsis
 /// which can capture the rest of the data structure. The type parameter `P` is the type of the
-/// pattern of the data structure: currently, ellipsis matches are only supported for record, but
-/// we'll probably support them for arrays as well.
+/// pattern of the data structure, either a field pattern for records or an element pattern for
+/// arrays.
 ///
 /// This enum is mostly used during parsing.
 ///
@@ -82,6 +86,7 @@ pub struct FieldPattern {
 /// - In `{foo={}, bar}`, the last match is an normal match.
 /// - In `{foo={}, bar, ..}`, the last match is a non-capturing ellipsis.
 /// - In `{foo={}, bar, ..rest}`, the last match is a capturing ellipsis.
+/// - In `[x, ..rest]`, the last match is a capturing ellipsis as well.
 #[derive(Debug, PartialEq, Clone)]
 pub enum LastPattern<P> {
     /// The last field is a normal match. In this case the pattern is "closed" so every record
@@ -103,6 +108,26 @@ pub struct RecordPattern {
     pub pos: TermPos,
 }
 
+/// An array pattern.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArrayPattern {
+    /// The patterns of the first elements of the array.
+    pub patterns: Vec<Pattern>,
+    /// The tail of the pattern, indicating if the pattern is open, i.e. if it ended with an
+    /// ellipsis, capturing the remaining elements or not.
+    pub tail: ArrayPatternTail,
+    pub pos: TermPos,
+}
+
+/// A tuple pattern. Tuples are arrays at runtime, so a tuple pattern matches the arrays with
+/// exactly as many elements as there are patterns, like a closed array pattern.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TuplePattern {
+    /// The patterns of the elements of the tuple.
+    pub patterns: Vec<Pattern>,
+    pub pos: TermPos,
+}
+
 /// A constant pattern, matching a constant value.
 #[derive(Debug, PartialEq, Clone)]
 pub struct ConstantPattern {
@@ -130,6 +155,19 @@ pub enum RecordPatternTail {
     Capture(LocIdent),
 }
 
+/// The tail of an array pattern which might capture the remaining elements of the array.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ArrayPatternTail {
+    /// The pattern is closed, i.e. it only matches arrays with exactly as many elements as there
+    /// are patterns. For example, `[x, y]`.
+    Empty,
+    /// The pattern ends with an ellipsis, making it open. For example, `[x, y, ..]`.
+    Open,
+    /// The pattern ends with an ellipsis and a variable capturing the remaining elements as an
+    /// array. For example, `[x, y, ..rest]`.
+    Capture(LocIdent),
+}
+
 impl RecordPattern {
     /// Check the matches for duplication, and raise an error if any occur.
     ///
@@ -183,9 +221,22 @@ impl RecordPattern {
     }
 }
 
+impl ArrayPattern {
+    /// Check if this array pattern is open, meaning that it accepts additional elements to be
+    /// present, whether they are captured or not.
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.tail,
+            ArrayPatternTail::Open | ArrayPatternTail::Capture(_)
+        )
+    }
+}
+
 impl_display_from_pretty!(PatternData);
 impl_display_from_pretty!(Pattern);
 impl_display_from_pretty!(ConstantPatternData);
 impl_display_from_pretty!(ConstantPattern);
 impl_display_from_pretty!(RecordPattern);
+impl_display_from_pretty!(ArrayPattern);
+impl_display_from_pretty!(TuplePattern);
 impl_display_from_pretty!(EnumPattern);
@@ -23,6 +23,10 @@ pub struct RecordAttrs {
     /// be closurized by construction. In the meantime, while we need to cope with a unique AST
     /// across the whole pipeline, we use this flag.
     pub closurized: bool,
+    /// If the fields of the record have been explicitly reordered, for example by
+    /// `std.record.sort_fields`. Records are serialized with their fields sorted alphabetically,
+    /// unless they are ordered, in which case the order of the underlying map is followed.
+    pub ordered: bool,
 }
 
 impl RecordAttrs {
@@ -35,6 +39,12 @@ impl RecordAttrs {
         self.closurized = true;
         self
     }
+
+    /// Set the `ordered` flag to true and return the updated attributes.
+    pub fn ordered(mut self) -> Self {
+        self.ordered = true;
+        self
+    }
 }
 
 impl Combine for RecordAttrs {
@@ -42,6 +52,8 @@ impl Combine for RecordAttrs {
         RecordAttrs {
             open: left.open || right.open,
             closurized: left.closurized && right.closurized,
+            // The order of the fields of a merged record doesn't follow the one of the operands.
+            ordered: false,
         }
     }
 }
@@ -103,6 +115,30 @@ pub struct RecordDeps {
     pub dyn_fields: Vec<FieldDeps>,
 }
 
+/// The deprecation of a record field, set by the `deprecated` and `replaced_by` metadata.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct Deprecation {
+    /// An explanation of the deprecation.
+    pub message: Option<String>,
+    /// What should be used instead of the deprecated field.
+    pub replacement: Option<String>,
+}
+
+impl Deprecation {
+    /// Combine the deprecations of two definitions of a field. The field is deprecated if one of
+    /// the definitions is. If both are, the message and the replacement of the left one take
+    /// precedence.
+    pub fn combine(left: Option<Self>, right: Option<Self>) -> Option<Self> {
+        match (left, right) {
+            (Some(left), Some(right)) => Some(Deprecation {
+                message: left.message.or(right.message),
+                replacement: left.replacement.or(right.replacement),
+            }),
+            (deprecation, None) | (None, deprecation) => deprecation,
+        }
+    }
+}
+
 /// The metadata attached to record fields.
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct FieldMetadata {
@@ -113,6 +149,8 @@ pub struct FieldMetadata {
     /// If the field is serialized.
     pub not_exported: bool,
     pub priority: MergePriority,
+    /// If the field is deprecated. Accessing a deprecated field emits a warning.
+    pub deprecated: Option<Deprecation>,
 }
 
 impl FieldMetadata {
@@ -126,6 +164,7 @@ impl FieldMetadata {
             && !self.opt
             && !self.not_exported
             && matches!(self.priority, MergePriority::Neutral)
+            && self.deprecated.is_none()
     }
 }
 
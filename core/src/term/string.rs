@@ -283,9 +283,31 @@ impl NickelString {
         result.into()
     }
 
+    /// Split `self` on the matches of `regex`, keeping the matches in the
+    /// result. The result alternates between the parts of `self` between two
+    /// matches and the matches themselves: it always starts and ends with a
+    /// (possibly empty) part.
+    ///
+    /// Note that matches will be ignored if they begin or end in the middle
+    /// of a Unicode extended grapheme cluster.
+    pub fn split_keep_regex(&self, regex: &CompiledRegex) -> Array {
+        use grapheme_cluster_preservation::regex;
+
+        let mut result = Vec::new();
+        let mut prev_match_end = 0;
+        for m in regex::find_iter(self, regex) {
+            result.push(Term::Str(self[prev_match_end..m.start()].into()).into());
+            result.push(Term::Str(m.as_str().into()).into());
+            prev_match_end = m.end();
+        }
+        result.push(Term::Str(self[prev_match_end..].into()).into());
+
+        Array::from_iter(result)
+    }
+
     /// Find the first match in `self` for a given `regex`, and return the
     /// match itself, the index in `self` where it appears, and any capture
-    /// groups specified.
+    /// groups specified, positional and named.
     ///
     /// Note that matches will be ignored if either the match itself or any
     /// of its capture groups begin or end in the middle of a Unicode extended
@@ -298,7 +320,7 @@ impl NickelString {
 
     /// Find all matches in `self` for a given `regex`, returning an iterator
     /// over the match itself, the index in `self` where it appears, and any
-    /// capture groups specified.
+    /// capture groups specified, positional and named.
     ///
     /// Note that matches will be ignored if either the match itself or any of
     /// its capture groups begin or end in the middle of a Unicode extended
@@ -318,6 +340,13 @@ impl NickelString {
                 .skip(1)
                 .filter_map(|s_opt| s_opt.map(|s| s.as_str().into()))
                 .collect();
+            // Named groups are also part of the positional groups above, but
+            // we additionally provide them by name.
+            let named_groups = regex
+                .capture_names()
+                .flatten()
+                .filter_map(|name| Some((name.to_owned(), capt.name(name)?.as_str().into())))
+                .collect();
 
             // The indices returned by the `regex` crate are byte offsets into
             // the string, but we need to return the index into the Nickel string,
@@ -338,6 +367,7 @@ impl NickelString {
                 matched: first_match.as_str().into(),
                 index: adjusted_index,
                 groups,
+                named_groups,
             }
         })
     }
@@ -358,6 +388,7 @@ pub struct RegexFindResult {
     pub matched: NickelString,
     pub index: Number,
     pub groups: Vec<NickelString>,
+    pub named_groups: Vec<(String, NickelString)>,
 }
 
 /// Errors returned by `NickelString`'s `substring` method.
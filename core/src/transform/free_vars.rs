@@ -203,6 +203,11 @@ impl CollectFreeVars for Type {
                 type_fields: ty, ..
             }
             | TypeF::Array(ty) => ty.as_mut().collect_free_vars(set),
+            TypeF::Tuple(tys) => {
+                for ty in tys.iter_mut() {
+                    ty.as_mut().collect_free_vars(set);
+                }
+            }
             // No term can appear anywhere in a enum row type, hence we can stop here.
             TypeF::Enum(_) => (),
             TypeF::Record(rrows) => rrows.collect_free_vars(set),
@@ -257,6 +262,12 @@ impl RemoveBindings for PatternData {
             PatternData::Record(record_pat) => {
                 record_pat.remove_bindings(working_set);
             }
+            PatternData::Array(array_pat) => {
+                array_pat.remove_bindings(working_set);
+            }
+            PatternData::Tuple(tuple_pat) => {
+                tuple_pat.remove_bindings(working_set);
+            }
             PatternData::Enum(enum_variant_pat) => {
                 enum_variant_pat.remove_bindings(working_set);
             }
@@ -294,6 +305,26 @@ impl RemoveBindings for RecordPattern {
     }
 }
 
+impl RemoveBindings for ArrayPattern {
+    fn remove_bindings(&self, working_set: &mut HashSet<Ident>) {
+        for m in &self.patterns {
+            m.remove_bindings(working_set);
+        }
+
+        if let ArrayPatternTail::Capture(rest) = self.tail {
+            working_set.remove(&rest.ident());
+        }
+    }
+}
+
+impl RemoveBindings for TuplePattern {
+    fn remove_bindings(&self, working_set: &mut HashSet<Ident>) {
+        for m in &self.patterns {
+            m.remove_bindings(working_set);
+        }
+    }
+}
+
 impl RemoveBindings for EnumPattern {
     fn remove_bindings(&self, working_set: &mut HashSet<Ident>) {
         if let Some(ref arg_pat) = self.pattern {
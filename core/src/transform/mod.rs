@@ -1,4 +1,6 @@
 //! Program transformations.
+use std::{convert::Infallible, sync::Arc};
+
 use crate::{
     cache::ImportResolver,
     term::{RichTerm, Traverse, TraverseOrder},
@@ -26,6 +28,46 @@ pub fn transform(
     transform_no_free_vars(rt, wildcards)
 }
 
+/// A custom program transformation, registered by an embedder (see
+/// [crate::program::Program::add_transformation]). Custom transformations are applied to the
+/// program and to the files it imports, but not to the standard library, after import resolution
+/// and typechecking, and before the builtin transformations. They can be used e.g. to inject
+/// default values, rewrite references to secrets, or strip fields.
+///
+/// A transformation is applied on each node of a term with [Traverse::traverse], in the order
+/// given by [Self::order].
+pub trait Transformation: Send + Sync {
+    /// The name of the transformation, for debugging purposes.
+    fn name(&self) -> &str;
+
+    /// Transform one node of the term.
+    fn transform_one(&self, rt: RichTerm) -> RichTerm;
+
+    /// The order in which the nodes are visited. Defaults to top-down.
+    fn order(&self) -> TraverseOrder {
+        TraverseOrder::TopDown
+    }
+}
+
+impl std::fmt::Debug for dyn Transformation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Transformation({})", self.name())
+    }
+}
+
+/// Apply custom transformations to a term, in order.
+pub fn apply_custom(rt: RichTerm, transformations: &[Arc<dyn Transformation>]) -> RichTerm {
+    transformations.iter().fold(rt, |rt, transformation| {
+        rt.traverse(
+            &mut |rt: RichTerm| -> Result<RichTerm, Infallible> {
+                Ok(transformation.transform_one(rt))
+            },
+            transformation.order(),
+        )
+        .unwrap()
+    })
+}
+
 /// Same as [`transform`], but doesn't apply the free vars transformation.
 pub fn transform_no_free_vars(
     rt: RichTerm,
@@ -293,6 +293,8 @@ pub enum TypeF<Ty, RRows, ERows> {
     },
     /// A parametrized array.
     Array(Ty),
+    /// A tuple, that is an array of a fixed length whose elements may have different types.
+    Tuple(Vec<Ty>),
     /// A type wildcard, wrapping an ID unique within a given file.
     Wildcard(usize),
 }
@@ -566,6 +568,11 @@ impl<Ty, RRows, ERows> TypeF<Ty, RRows, ERows> {
                 flavour: attrs,
             }),
             TypeF::Array(t) => Ok(TypeF::Array(f(t, state)?)),
+            TypeF::Tuple(tys) => Ok(TypeF::Tuple(
+                tys.into_iter()
+                    .map(|ty| f(ty, state))
+                    .collect::<Result<_, _>>()?,
+            )),
             TypeF::Wildcard(i) => Ok(TypeF::Wildcard(i)),
         }
     }
@@ -821,6 +828,17 @@ impl Subcontract for Type {
             // Array Dyn is specialized to array_dyn, which is constant time
             TypeF::Array(ref ty) if matches!(ty.typ, TypeF::Dyn) => internals::array_dyn(),
             TypeF::Array(ref ty) => mk_app!(internals::array(), ty.subcontract(vars, pol, sy)?),
+            TypeF::Tuple(ref tys) => {
+                let elts_contracts = tys
+                    .iter()
+                    .map(|ty| ty.subcontract(vars.clone(), pol, sy))
+                    .collect::<Result<Array, _>>()?;
+
+                mk_app!(
+                    internals::tuple(),
+                    Term::Array(elts_contracts, Default::default())
+                )
+            }
             TypeF::Symbol => panic!("unexpected Symbol type during contract elaboration"),
             // Similarly, any variant of `A -> B` where either `A` or `B` is `Dyn` get specialized
             // to the corresponding builtin contract.
@@ -1273,6 +1291,11 @@ impl Type {
                         flavour,
                     },
                     TypeF::Array(t) => TypeF::Array(Box::new(t.optimize(vars_elide, polarity))),
+                    TypeF::Tuple(tys) => TypeF::Tuple(
+                        tys.into_iter()
+                            .map(|ty| Box::new(ty.optimize(vars_elide.clone(), polarity)))
+                            .collect(),
+                    ),
                     // All other types don't contain subtypes, it's a base case
                     t => t,
                 };
@@ -1342,7 +1365,8 @@ impl Type {
             | TypeF::String
             | TypeF::Var(_)
             | TypeF::Record(_)
-            | TypeF::Enum(_) => true,
+            | TypeF::Enum(_)
+            | TypeF::Tuple(_) => true,
             TypeF::Flat(rt) if rt.as_ref().is_atom() => true,
             _ => false,
         }
@@ -1418,6 +1442,7 @@ impl Traverse<Type> for Type {
             TypeF::Forall { body: t, .. }
             | TypeF::Dict { type_fields: t, .. }
             | TypeF::Array(t) => t.traverse_ref(f, state),
+            TypeF::Tuple(tys) => tys.iter().find_map(|ty| ty.traverse_ref(f, state)),
             TypeF::Record(rrows) => rrows.traverse_ref(f, state),
         }
     }
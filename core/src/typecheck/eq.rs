@@ -579,6 +579,13 @@ fn type_eq_bounded<E: TermEnvironment>(
                 (TypeF::Array(uty1), TypeF::Array(uty2)) => {
                     type_eq_bounded(state, uty1, env1, uty2, env2)
                 }
+                (TypeF::Tuple(utys1), TypeF::Tuple(utys2)) => {
+                    utys1.len() == utys2.len()
+                        && utys1
+                            .iter()
+                            .zip(utys2.iter())
+                            .all(|(uty1, uty2)| type_eq_bounded(state, uty1, env1, uty2, env2))
+                }
                 (TypeF::Arrow(s1, t1), TypeF::Arrow(s2, t2)) => {
                     type_eq_bounded(state, s1, env1, s2, env2)
                         && type_eq_bounded(state, t1, env1, t2, env2)
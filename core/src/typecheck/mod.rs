@@ -236,6 +236,11 @@ impl<E: TermEnvironment> VarLevelUpperBound for GenericUnifTypeUnrolling<E> {
             TypeF::Record(rrows) => rrows.var_level_upper_bound(),
             TypeF::Dict { type_fields, .. } => type_fields.var_level_upper_bound(),
             TypeF::Array(ty_elts) => ty_elts.var_level_upper_bound(),
+            TypeF::Tuple(tys) => tys
+                .iter()
+                .map(|ty| ty.var_level_upper_bound())
+                .max()
+                .unwrap_or(VarLevel::NO_VAR),
             TypeF::Wildcard(_) | TypeF::Var(_) => VarLevel::NO_VAR,
             // This should be unreachable, but let's not panic in release mode nonetheless
             TypeF::Flat(_) => {
@@ -1644,6 +1649,9 @@ fn walk_type<V: TypecheckVisitor>(
            walk_type(state, ctxt, visitor, ty2.as_ref())
        }
        TypeF::Record(rrows) => walk_rrows(state, ctxt, visitor, rrows),
+       TypeF::Tuple(tys) => tys
+           .iter()
+           .try_for_each(|ty| walk_type(state, ctxt.clone(), visitor, ty.as_ref())),
        TypeF::Flat(t) => walk(state, ctxt, visitor, t),
        TypeF::Dict { type_fields: ty2, .. }
        | TypeF::Array(ty2)
@@ -1899,6 +1907,45 @@ fn check<V: TypecheckVisitor>(
                 .map_err(|err| err.into_typecheck_err(state, rt.pos))?;
             check(state, ctxt, visitor, t, trg)
         }
+        // A tuple literal has a tuple type, with one type per element. If the literal is checked
+        // against an array type instead, it is checked as an array, following the subsumption of
+        // tuple types by array types (see [subsumption]).
+        Term::Array(terms, attrs) if attrs.tuple => {
+            let ty = ty.into_root(state.table);
+
+            if let UnifType::Concrete {
+                typ: TypeF::Array(ty_elts),
+                ..
+            } = &ty
+            {
+                return terms
+                    .iter()
+                    .try_for_each(|t| -> Result<(), TypecheckError> {
+                        check(state, ctxt.clone(), visitor, t, ty_elts.as_ref().clone())
+                    });
+            }
+
+            let tys_elts: Vec<UnifType> = terms
+                .iter()
+                .map(|_| state.table.fresh_type_uvar(ctxt.var_level))
+                .collect();
+
+            ty.unify(
+                UnifType::concrete(TypeF::Tuple(
+                    tys_elts.iter().cloned().map(Box::new).collect(),
+                )),
+                state,
+                &ctxt,
+            )
+            .map_err(|err| err.into_typecheck_err(state, rt.pos))?;
+
+            terms
+                .iter()
+                .zip(tys_elts)
+                .try_for_each(|(t, ty_elt)| -> Result<(), TypecheckError> {
+                    check(state, ctxt.clone(), visitor, t, ty_elt)
+                })
+        }
         Term::Array(terms, _) => {
             let ty_elts = state.table.fresh_type_uvar(ctxt.var_level);
 
@@ -2330,10 +2377,11 @@ fn check<V: TypecheckVisitor>(
 
 /// Change from inference mode to checking mode, and apply a potential subsumption rule.
 ///
-/// Currently, there is no subtyping (until RFC004 is implemented), hence this function performs
-/// polymorphic type instantiation with unification variable on the left (on the inferred type),
-/// and then simply performs unification (put differently, the subtyping relation is the equality
-/// relation).
+/// Currently, there is almost no subtyping (until RFC004 is implemented), hence this function
+/// performs polymorphic type instantiation with unification variable on the left (on the inferred
+/// type), and then simply performs unification (put differently, the subtyping relation is the
+/// equality relation). The only exception is that a tuple type is a subtype of an array type
+/// `Array T` if each of its element types is `T`, as tuples are arrays at runtime.
 ///
 /// The type instantiation corresponds to the zero-ary case of application in the current
 /// specification (which is based on [A Quick Look at Impredicativity][quick-look], although we
@@ -2349,7 +2397,25 @@ pub fn subsumption(
     checked: UnifType,
 ) -> Result<(), UnifError> {
     let inferred_inst = instantiate_foralls(state, &mut ctxt, inferred, ForallInst::UnifVar);
-    checked.unify(inferred_inst, state, &ctxt)
+
+    match (
+        inferred_inst.into_root(state.table),
+        checked.into_root(state.table),
+    ) {
+        (
+            UnifType::Concrete {
+                typ: TypeF::Tuple(tys),
+                ..
+            },
+            UnifType::Concrete {
+                typ: TypeF::Array(ty_elts),
+                ..
+            },
+        ) => tys
+            .into_iter()
+            .try_for_each(|ty| ty_elts.as_ref().clone().unify(*ty, state, &ctxt)),
+        (inferred_inst, checked) => checked.unify(inferred_inst, state, &ctxt),
+    }
 }
 
 fn check_field<V: TypecheckVisitor>(
@@ -2795,6 +2861,14 @@ pub fn apparent_type(
             Term::Bool(_) => ApparentType::Inferred(Type::from(TypeF::Bool)),
             Term::SealingKey(_) => ApparentType::Inferred(Type::from(TypeF::Symbol)),
             Term::Str(_) | Term::StrChunks(_) => ApparentType::Inferred(Type::from(TypeF::String)),
+            Term::Array(terms, attrs) if attrs.tuple => {
+                ApparentType::Approximated(Type::from(TypeF::Tuple(
+                    terms
+                        .iter()
+                        .map(|_| Box::new(Type::from(TypeF::Dyn)))
+                        .collect(),
+                )))
+            }
             Term::Array(..) => ApparentType::Approximated(Type::from(TypeF::Array(Box::new(
                 Type::from(TypeF::Dyn),
             )))),
@@ -59,7 +59,9 @@ pub fn get_uop_type(
             (domain, codomain)
         }
         // This should not happen, as a match primop is only produced during evaluation.
-        UnaryOp::TagsOnlyMatch { .. } => panic!("cannot typecheck match primop"),
+        UnaryOp::TagsOnlyMatch { .. } | UnaryOp::HeadMatch() => {
+            panic!("cannot typecheck match primop")
+        }
         // Morally, Label -> Label
         // Dyn -> Dyn
         UnaryOp::ChangePolarity()
@@ -138,6 +140,11 @@ pub fn get_uop_type(
 
             (mk_uniftype::dict(ty_a.clone()), mk_uniftype::array(ty_a))
         }
+        // forall a. { _: a } -> { _: a }
+        UnaryOp::RecordSortFields() => {
+            let ty_dict = mk_uniftype::dict(state.table.fresh_type_uvar(var_level));
+            (ty_dict.clone(), ty_dict)
+        }
         // Str -> Str
         UnaryOp::StrTrim() => (mk_uniftype::str(), mk_uniftype::str()),
         // Str -> Array Str
@@ -152,6 +159,31 @@ pub fn get_uop_type(
         UnaryOp::ToStr() => (mk_uniftype::dynamic(), mk_uniftype::str()),
         // Str -> Num
         UnaryOp::NumFromStr() => (mk_uniftype::str(), mk_uniftype::num()),
+        // Str -> Dyn
+        UnaryOp::UrlParse() => (mk_uniftype::str(), mk_uniftype::dynamic()),
+        // Str -> Str
+        UnaryOp::UrlEncode() | UnaryOp::UrlDecode() => (mk_uniftype::str(), mk_uniftype::str()),
+        // Str -> Str
+        UnaryOp::Base64Encode() | UnaryOp::Base64Decode() => {
+            (mk_uniftype::str(), mk_uniftype::str())
+        }
+        // Str -> Dyn
+        UnaryOp::SemverParse() => (mk_uniftype::str(), mk_uniftype::dynamic()),
+        // Dyn -> Str
+        UnaryOp::StructuralKey() => (mk_uniftype::dynamic(), mk_uniftype::str()),
+        // Dyn -> Str
+        UnaryOp::UuidV4() => (mk_uniftype::dynamic(), mk_uniftype::str()),
+        // Str -> Str
+        UnaryOp::UuidParse() => (mk_uniftype::str(), mk_uniftype::str()),
+        // Str -> [| 'Some Str, 'None |]
+        UnaryOp::EnvGet() => (
+            mk_uniftype::str(),
+            mk_uty_enum!(("Some", mk_uniftype::str()), "None"),
+        ),
+        // Str -> Str
+        UnaryOp::FsReadText() => (mk_uniftype::str(), mk_uniftype::str()),
+        // Str -> Array Str
+        UnaryOp::FsReadDir() => (mk_uniftype::str(), mk_uniftype::array(mk_uniftype::str())),
         // Str -> < | a> for a rigid type variable a
         UnaryOp::EnumFromStr() => (
             mk_uniftype::str(),
@@ -162,7 +194,7 @@ pub fn get_uop_type(
             mk_uniftype::str(),
             mk_uty_arrow!(mk_uniftype::str(), mk_uniftype::bool()),
         ),
-        // Str -> Str -> {matched: Str, index: Num, groups: Array Str}
+        // Str -> Str -> {matched: Str, index: Num, groups: Array Str, named_groups: {_: Str}}
         UnaryOp::StrFind() => (
             mk_uniftype::str(),
             mk_uty_arrow!(
@@ -170,11 +202,17 @@ pub fn get_uop_type(
                 mk_uty_record!(
                     ("matched", TypeF::String),
                     ("index", TypeF::Number),
-                    ("groups", mk_uniftype::array(TypeF::String))
+                    ("groups", mk_uniftype::array(TypeF::String)),
+                    ("named_groups", mk_uniftype::dict(TypeF::String))
                 )
             ),
         ),
-        // String -> String -> Array { matched: String, index: Number, groups: Array String }
+        // String -> String -> Array {
+        //   matched: String,
+        //   index: Number,
+        //   groups: Array String,
+        //   named_groups: { _ : String },
+        // }
         UnaryOp::StrFindAll() => (
             mk_uniftype::str(),
             mk_uty_arrow!(
@@ -182,19 +220,21 @@ pub fn get_uop_type(
                 mk_uniftype::array(mk_uty_record!(
                     ("matched", TypeF::String),
                     ("index", TypeF::Number),
-                    ("groups", mk_uniftype::array(TypeF::String))
+                    ("groups", mk_uniftype::array(TypeF::String)),
+                    ("named_groups", mk_uniftype::dict(TypeF::String))
                 ))
             ),
         ),
         // Str -> Bool
         UnaryOp::StrIsMatchCompiled(_) => (mk_uniftype::str(), mk_uniftype::bool()),
-        // Str -> {matched: Str, index: Num, groups: Array Str}
+        // Str -> {matched: Str, index: Num, groups: Array Str, named_groups: {_: Str}}
         UnaryOp::StrFindCompiled(_) => (
             mk_uniftype::str(),
             mk_uty_record!(
                 ("matched", TypeF::String),
                 ("index", TypeF::Number),
-                ("groups", mk_uniftype::array(TypeF::String))
+                ("groups", mk_uniftype::array(TypeF::String)),
+                ("named_groups", mk_uniftype::dict(TypeF::String))
             ),
         ),
         UnaryOp::StrFindAllCompiled(_) => (
@@ -202,9 +242,17 @@ pub fn get_uop_type(
             mk_uniftype::array(mk_uty_record!(
                 ("matched", TypeF::String),
                 ("index", TypeF::Number),
-                ("groups", mk_uniftype::array(TypeF::String))
+                ("groups", mk_uniftype::array(TypeF::String)),
+                ("named_groups", mk_uniftype::dict(TypeF::String))
             )),
         ),
+        // Str -> Str -> Array Str
+        UnaryOp::StrSplitKeep() => (
+            mk_uniftype::str(),
+            mk_uty_arrow!(mk_uniftype::str(), mk_uniftype::array(TypeF::String)),
+        ),
+        // Str -> Array Str
+        UnaryOp::StrSplitKeepCompiled(_) => (mk_uniftype::str(), mk_uniftype::array(TypeF::String)),
         // Dyn -> Dyn
         UnaryOp::Force { .. } => (mk_uniftype::dynamic(), mk_uniftype::dynamic()),
         // forall a. a -> a
@@ -282,6 +330,13 @@ pub fn get_bop_type(
             mk_uniftype::dynamic(),
             mk_uty_arrow!(mk_uniftype::dynamic(), mk_uniftype::dynamic()),
         ),
+        // Ideally: Contract -> a -> a
+        // Currently: Dyn -> Dyn -> Dyn
+        BinaryOp::ContractFreeze() => (
+            mk_uniftype::dynamic(),
+            mk_uniftype::dynamic(),
+            mk_uniftype::dynamic(),
+        ),
         // Sym -> Dyn -> Dyn -> Dyn
         BinaryOp::Unseal() => (
             mk_uniftype::sym(),
@@ -377,37 +432,90 @@ pub fn get_bop_type(
                 ty_elt,
             )
         }
+        // forall a. (a -> a -> [| 'Lesser, 'Equal, 'Greater |]) -> Array a -> Array a
+        BinaryOp::ArraySortBy() => {
+            let ty_elt = state.table.fresh_type_uvar(var_level);
+            let ty_array = mk_uniftype::array(ty_elt.clone());
+            (
+                mk_uty_arrow!(
+                    ty_elt.clone(),
+                    ty_elt,
+                    mk_uty_enum!("Lesser", "Equal", "Greater")
+                ),
+                ty_array.clone(),
+                ty_array,
+            )
+        }
+        // forall a. Num -> Array a -> Array (Array a)
+        BinaryOp::ArrayChunks() | BinaryOp::ArrayWindows() => {
+            let ty_array = mk_uniftype::array(state.table.fresh_type_uvar(var_level));
+            (
+                mk_uniftype::num(),
+                ty_array.clone(),
+                mk_uniftype::array(ty_array),
+            )
+        }
         // Dyn -> Dyn -> Dyn
         BinaryOp::Merge(_) => (
             mk_uniftype::dynamic(),
             mk_uniftype::dynamic(),
             mk_uniftype::dynamic(),
         ),
-        // <Md5, Sha1, Sha256, Sha512> -> Str -> Str
+        // <Md5, Sha1, Sha256, Sha512, Blake3> -> Str -> Str
         BinaryOp::Hash() => (
-            mk_uty_enum!("Md5", "Sha1", "Sha256", "Sha512"),
+            mk_uty_enum!("Md5", "Sha1", "Sha256", "Sha512", "Blake3"),
             mk_uniftype::str(),
             mk_uniftype::str(),
         ),
-        // forall a. <Json, Yaml, Toml> -> a -> Str
+        // Dyn -> Dyn -> Array Str
+        BinaryOp::StructuralDiff() => (
+            mk_uniftype::dynamic(),
+            mk_uniftype::dynamic(),
+            mk_uniftype::array(mk_uniftype::str()),
+        ),
+        // forall a. <Json, Yaml, YamlDocuments, Toml> -> a -> Str
         BinaryOp::Serialize() => {
             let ty_input = state.table.fresh_type_uvar(var_level);
             (
-                mk_uty_enum!("Json", "Yaml", "Toml"),
+                mk_uty_enum!("Json", "Yaml", "YamlDocuments", "Toml"),
                 ty_input,
                 mk_uniftype::str(),
             )
         }
-        // <Json, Yaml, Toml> -> Str -> Dyn
+        // <Json, Yaml, YamlDocuments, Toml, Csv> -> Str -> Dyn
         BinaryOp::Deserialize() => (
-            mk_uty_enum!("Json", "Yaml", "Toml"),
+            mk_uty_enum!("Json", "Yaml", "YamlDocuments", "Toml", "Csv"),
             mk_uniftype::str(),
             mk_uniftype::dynamic(),
         ),
         // Num -> Num -> Num
         BinaryOp::Pow() => (mk_uniftype::num(), mk_uniftype::num(), mk_uniftype::num()),
+        // Num -> Num -> Num
+        BinaryOp::BitAnd() | BinaryOp::BitOr() | BinaryOp::BitXor() | BinaryOp::BitShift() => {
+            (mk_uniftype::num(), mk_uniftype::num(), mk_uniftype::num())
+        }
         // Str -> Str -> Bool
         BinaryOp::StrContains() => (mk_uniftype::str(), mk_uniftype::str(), mk_uniftype::bool()),
+        // Str -> Str -> Str
+        BinaryOp::TimeParse() | BinaryOp::TimeFormat() => {
+            (mk_uniftype::str(), mk_uniftype::str(), mk_uniftype::str())
+        }
+        // Num -> Str -> Str
+        BinaryOp::TimeAdd() => (mk_uniftype::num(), mk_uniftype::str(), mk_uniftype::str()),
+        // Str -> Str -> Num
+        BinaryOp::TimeDiff() => (mk_uniftype::str(), mk_uniftype::str(), mk_uniftype::num()),
+        // Str -> Str -> Str
+        BinaryOp::UuidV5() => (mk_uniftype::str(), mk_uniftype::str(), mk_uniftype::str()),
+        // Str -> Str -> [| 'Lesser, 'Equal, 'Greater |]
+        BinaryOp::SemverCompare() => (
+            mk_uniftype::str(),
+            mk_uniftype::str(),
+            mk_uty_enum!("Lesser", "Equal", "Greater"),
+        ),
+        // Str -> Str -> Bool
+        BinaryOp::SemverSatisfies() => {
+            (mk_uniftype::str(), mk_uniftype::str(), mk_uniftype::bool())
+        }
         // Str -> Str -> Array Str
         BinaryOp::StrSplit() => (
             mk_uniftype::str(),
@@ -436,6 +544,48 @@ pub fn get_bop_type(
                 mk_uty_arrow!(mk_uniftype::dynamic(), ty_dict),
             )
         }
+        // (Array Str -> Dyn -> Dyn) -> {_: Dyn} -> {_: Dyn}
+        BinaryOp::RecordDeepMap() => (
+            mk_uty_arrow!(
+                mk_uniftype::array(mk_uniftype::str()),
+                mk_uniftype::dynamic(),
+                mk_uniftype::dynamic()
+            ),
+            mk_uniftype::dict(mk_uniftype::dynamic()),
+            mk_uniftype::dict(mk_uniftype::dynamic()),
+        ),
+        // (Array Str -> Dyn -> Bool) -> {_: Dyn} -> {_: Dyn}
+        BinaryOp::RecordDeepFilter() => (
+            mk_uty_arrow!(
+                mk_uniftype::array(mk_uniftype::str()),
+                mk_uniftype::dynamic(),
+                mk_uniftype::bool()
+            ),
+            mk_uniftype::dict(mk_uniftype::dynamic()),
+            mk_uniftype::dict(mk_uniftype::dynamic()),
+        ),
+        // forall a. (Str -> Str -> [| 'Lesser, 'Equal, 'Greater |]) -> { _: a } -> { _: a }
+        BinaryOp::RecordSortFieldsBy() => {
+            let ty_dict = mk_uniftype::dict(state.table.fresh_type_uvar(var_level));
+            (
+                mk_uty_arrow!(
+                    mk_uniftype::str(),
+                    mk_uniftype::str(),
+                    mk_uty_enum!("Lesser", "Equal", "Greater")
+                ),
+                ty_dict.clone(),
+                ty_dict,
+            )
+        }
+        // forall a. Array Str -> { _: a } -> { _: a }
+        BinaryOp::RecordMoveFirst() => {
+            let ty_dict = mk_uniftype::dict(state.table.fresh_type_uvar(var_level));
+            (
+                mk_uniftype::array(mk_uniftype::str()),
+                ty_dict.clone(),
+                ty_dict,
+            )
+        }
         // Morally: Str -> Lbl -> Lbl
         // Actual: Str -> Dyn -> Dyn
         BinaryOp::LabelWithMessage() => (
@@ -515,6 +665,82 @@ pub fn get_nop_type(
                 mk_uniftype::array(element_type),
             )
         }
+        // (Array Str -> Dyn -> Dyn -> Dyn) -> {_: Dyn} -> {_: Dyn} -> {_: Dyn}
+        NAryOp::RecordDeepMergeWith() => (
+            vec![
+                mk_uty_arrow!(
+                    mk_uniftype::array(mk_uniftype::str()),
+                    mk_uniftype::dynamic(),
+                    mk_uniftype::dynamic(),
+                    mk_uniftype::dynamic()
+                ),
+                mk_uniftype::dict(mk_uniftype::dynamic()),
+                mk_uniftype::dict(mk_uniftype::dynamic()),
+            ],
+            mk_uniftype::dict(mk_uniftype::dynamic()),
+        ),
+        // forall a b c. (a -> b -> c) -> Array a -> Array b -> Array c
+        NAryOp::ArrayZipWith() => {
+            let ty_a = state.table.fresh_type_uvar(var_level);
+            let ty_b = state.table.fresh_type_uvar(var_level);
+            let ty_c = state.table.fresh_type_uvar(var_level);
+
+            (
+                vec![
+                    mk_uty_arrow!(ty_a.clone(), ty_b.clone(), ty_c.clone()),
+                    mk_uniftype::array(ty_a),
+                    mk_uniftype::array(ty_b),
+                ],
+                mk_uniftype::array(ty_c),
+            )
+        }
+        // Num -> (Dyn -> Dyn) -> Dyn -> Array Dyn
+        NAryOp::SequenceTake() => (
+            vec![
+                mk_uniftype::num(),
+                mk_uty_arrow!(mk_uniftype::dynamic(), mk_uniftype::dynamic()),
+                mk_uniftype::dynamic(),
+            ],
+            mk_uniftype::array(mk_uniftype::dynamic()),
+        ),
+        // Str -> Str -> Bool -> Str -> Array {_: Dyn}
+        NAryOp::CsvParse() => (
+            vec![
+                mk_uniftype::str(),
+                mk_uniftype::str(),
+                mk_uniftype::bool(),
+                mk_uniftype::str(),
+            ],
+            mk_uniftype::array(mk_uniftype::dict(mk_uniftype::dynamic())),
+        ),
+        // <Floor, Ceil, Truncate, HalfUp, HalfEven> -> Num -> Num -> Num
+        NAryOp::NumberRound() => (
+            vec![
+                mk_uty_enum!("Floor", "Ceil", "Truncate", "HalfUp", "HalfEven"),
+                mk_uniftype::num(),
+                mk_uniftype::num(),
+            ],
+            mk_uniftype::num(),
+        ),
+        // <Md5, Sha1, Sha256, Sha512, Blake3> -> <Hex, Base64> -> Str -> Str
+        NAryOp::HashWith() => (
+            vec![
+                mk_uty_enum!("Md5", "Sha1", "Sha256", "Sha512", "Blake3"),
+                mk_uty_enum!("Hex", "Base64"),
+                mk_uniftype::str(),
+            ],
+            mk_uniftype::str(),
+        ),
+        // <Md5, Sha1, Sha256, Sha512, Blake3> -> <Hex, Base64> -> Str -> Str -> Str
+        NAryOp::Hmac() => (
+            vec![
+                mk_uty_enum!("Md5", "Sha1", "Sha256", "Sha512", "Blake3"),
+                mk_uty_enum!("Hex", "Base64"),
+                mk_uniftype::str(),
+                mk_uniftype::str(),
+            ],
+            mk_uniftype::str(),
+        ),
         // This should not happen, as MergeContract() is only produced during evaluation.
         NAryOp::MergeContract() => panic!("cannot typecheck MergeContract()"),
         // Morally: Sym -> Polarity -> Lbl -> Lbl
@@ -24,10 +24,15 @@ pub type TypeBindings = Vec<(LocIdent, UnifType)>;
 ///
 /// - The path of the full pattern within itself is the empty path.
 /// - The path of the `arg` pattern is `[Field("foo"), Field("bar"), Variant]`.
+///
+/// All the element patterns of an array pattern share the same path, as the elements of an array
+/// all have the same type.
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Hash)]
 pub enum PatternPathElem {
     Field(Ident),
     Variant,
+    ArrayElem,
+    TupleElem(usize),
 }
 
 pub type PatternPath = Vec<PatternPathElem>;
@@ -330,6 +335,12 @@ impl PatternTypes for PatternData {
             PatternData::Record(record_pat) => Ok(UnifType::concrete(TypeF::Record(
                 record_pat.pattern_types_inj(pt_state, path, state, ctxt, mode)?,
             ))),
+            PatternData::Array(array_pat) => {
+                array_pat.pattern_types_inj(pt_state, path, state, ctxt, mode)
+            }
+            PatternData::Tuple(tuple_pat) => {
+                tuple_pat.pattern_types_inj(pt_state, path, state, ctxt, mode)
+            }
             PatternData::Enum(enum_pat) => {
                 let row = enum_pat.pattern_types_inj(pt_state, path.clone(), state, ctxt, mode)?;
                 // We elaborate the type `[| row; a |]` where `a` is a fresh enum rows unification
@@ -351,6 +362,76 @@ impl PatternTypes for PatternData {
     }
 }
 
+/// Builds the type associated to an array pattern, which is `Array a` where `a` is the common
+/// type of all the element patterns. A capture of the remaining elements has the same type as the
+/// whole array.
+impl PatternTypes for ArrayPattern {
+    type PatType = UnifType;
+
+    fn pattern_types_inj(
+        &self,
+        pt_state: &mut PatTypeState,
+        mut path: PatternPath,
+        state: &mut State,
+        ctxt: &Context,
+        mode: TypecheckMode,
+    ) -> Result<Self::PatType, TypecheckError> {
+        path.push(PatternPathElem::ArrayElem);
+
+        let elem_type = any_type(mode, state, ctxt);
+
+        for elem_pat in self.patterns.iter() {
+            let elem_pat_type =
+                elem_pat.pattern_types_inj(pt_state, path.clone(), state, ctxt, mode)?;
+
+            // In walk mode, the elements have type `Dyn`, but the element patterns might still
+            // have a more precise type (for example constant patterns), so we don't unify them.
+            if let TypecheckMode::Enforce = mode {
+                elem_type
+                    .clone()
+                    .unify(elem_pat_type, state, ctxt)
+                    .map_err(|e| e.into_typecheck_err(state, elem_pat.pos))?;
+            }
+        }
+
+        let typ = mk_uniftype::array(elem_type);
+
+        if let ArrayPatternTail::Capture(rest) = self.tail {
+            pt_state.bindings.push((rest, typ.clone()));
+        }
+
+        Ok(typ)
+    }
+}
+
+/// Builds the type associated to a tuple pattern, which is the tuple of the types of the element
+/// patterns.
+impl PatternTypes for TuplePattern {
+    type PatType = UnifType;
+
+    fn pattern_types_inj(
+        &self,
+        pt_state: &mut PatTypeState,
+        path: PatternPath,
+        state: &mut State,
+        ctxt: &Context,
+        mode: TypecheckMode,
+    ) -> Result<Self::PatType, TypecheckError> {
+        let elem_types = self
+            .patterns
+            .iter()
+            .enumerate()
+            .map(|(idx, elem_pat)| {
+                let mut path = path.clone();
+                path.push(PatternPathElem::TupleElem(idx));
+                elem_pat.pattern_types_inj(pt_state, path, state, ctxt, mode)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(UnifType::concrete(TypeF::Tuple(elem_types)))
+    }
+}
+
 impl PatternTypes for ConstantPattern {
     type PatType = UnifType;
 
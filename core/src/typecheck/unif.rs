@@ -1214,6 +1214,10 @@ impl Unify for UnifType {
                 | (TypeF::String, TypeF::String)
                 | (TypeF::Symbol, TypeF::Symbol) => Ok(()),
                 (TypeF::Array(uty1), TypeF::Array(uty2)) => uty1.unify(*uty2, state, ctxt),
+                (TypeF::Tuple(utys1), TypeF::Tuple(utys2)) if utys1.len() == utys2.len() => utys1
+                    .into_iter()
+                    .zip(utys2)
+                    .try_for_each(|(uty1, uty2)| uty1.unify(*uty2, state, ctxt)),
                 (TypeF::Arrow(s1s, s1t), TypeF::Arrow(s2s, s2t)) => {
                     s1s.clone()
                         .unify((*s2s).clone(), state, ctxt)
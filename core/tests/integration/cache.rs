@@ -0,0 +1,223 @@
+use std::{collections::HashSet, fs, path::Path, time::Duration};
+
+use assert_matches::assert_matches;
+use codespan::FileId;
+use nickel_lang_core::{
+    cache::{Cache, CacheOp, EntryState, ErrorTolerance, EvictionPolicy, SourcePath},
+    typecheck::Context,
+};
+use tempfile::{tempdir, TempDir};
+
+/// Create a temporary directory holding the given files.
+fn dir_with(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempdir().unwrap();
+
+    for (name, content) in files {
+        fs::write(dir.path().join(name), content).unwrap();
+    }
+
+    dir
+}
+
+/// Write `content` to the file at `path`, making sure that its timestamp changes even on
+/// filesystems with a coarse timestamp resolution.
+fn rewrite(path: &Path, content: &str) {
+    let modified = fs::metadata(path).unwrap().modified().unwrap();
+    fs::write(path, content).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(modified + Duration::from_secs(1))
+        .unwrap();
+}
+
+/// Load a file and prepare it for evaluation, returning its id.
+fn load(cache: &mut Cache, path: &Path) -> FileId {
+    let file_id = cache.get_or_add_file(path).unwrap().inner();
+    cache.prepare(file_id, &Context::new()).unwrap();
+    file_id
+}
+
+#[test]
+fn eviction_bounds_entries() {
+    let dir = dir_with(&[("a.ncl", "1"), ("b.ncl", "2"), ("c.ncl", "3")]);
+    let mut cache = Cache::new(ErrorTolerance::Strict);
+    cache.set_eviction_policy(EvictionPolicy {
+        max_entries: Some(2),
+        max_bytes: None,
+    });
+
+    let a = load(&mut cache, &dir.path().join("a.ncl"));
+    load(&mut cache, &dir.path().join("b.ncl"));
+    load(&mut cache, &dir.path().join("c.ncl"));
+
+    // The least recently used entry is evicted, along with its source.
+    assert_eq!(cache.evict(), vec![a]);
+    assert_eq!(cache.terms().len(), 2);
+    assert_eq!(cache.source(a), "");
+
+    // Importing the file again reuses its id instead of storing a new copy of the source.
+    let op = cache.get_or_add_file(dir.path().join("a.ncl")).unwrap();
+    assert_eq!(op, CacheOp::Done(a));
+    assert_eq!(cache.source(a), "1");
+    cache.prepare(a, &Context::new()).unwrap();
+    cache.evict();
+    assert_eq!(cache.terms().len(), 2);
+    assert!(cache.terms().contains_key(&a));
+}
+
+#[test]
+fn eviction_bounds_bytes() {
+    let dir = dir_with(&[("a.ncl", "[1, 2, 3]"), ("b.ncl", "[4, 5, 6]")]);
+    let mut cache = Cache::new(ErrorTolerance::Strict);
+    cache.set_eviction_policy(EvictionPolicy {
+        max_entries: None,
+        max_bytes: Some(10),
+    });
+
+    let a = load(&mut cache, &dir.path().join("a.ncl"));
+    let b = load(&mut cache, &dir.path().join("b.ncl"));
+
+    assert_eq!(cache.evict(), vec![a]);
+    assert_eq!(cache.terms().keys().copied().collect::<Vec<_>>(), vec![b]);
+
+    // Loading the evicted file again brings the cache above the limit, evicting the other one.
+    assert_eq!(load(&mut cache, &dir.path().join("a.ncl")), a);
+    assert_eq!(cache.evict(), vec![b]);
+    assert_eq!(cache.terms().keys().copied().collect::<Vec<_>>(), vec![a]);
+}
+
+#[test]
+fn glob_imports_pick_up_new_files() {
+    let dir = dir_with(&[("main.ncl", "import \"parts/*.ncl\"")]);
+    fs::create_dir(dir.path().join("parts")).unwrap();
+    fs::write(dir.path().join("parts/a.ncl"), "1").unwrap();
+
+    let mut cache = Cache::new(ErrorTolerance::Strict);
+    let main = load(&mut cache, &dir.path().join("main.ncl"));
+
+    let pattern = SourcePath::Path(dir.path().join("parts").join("*.ncl"));
+    let glob = cache.id_of(&pattern).unwrap();
+    assert!(cache.source(glob).contains("a.ncl"));
+    assert!(!cache.source(glob).contains("b.ncl"));
+
+    fs::write(dir.path().join("parts/b.ncl"), "2").unwrap();
+
+    let changes = cache.poll_changes();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].old_id, glob);
+    assert!(changes[0].invalidated.contains(&main));
+
+    let new_glob = changes[0].new_id.unwrap();
+    assert_eq!(cache.id_of(&pattern), Some(new_glob));
+    assert!(cache.source(new_glob).contains("b.ncl"));
+}
+
+/// A directory holding a chain of imports: `main.ncl` imports `mid.ncl`, which imports
+/// `leaf.ncl`.
+fn import_chain() -> TempDir {
+    dir_with(&[
+        ("main.ncl", "import \"mid.ncl\""),
+        ("mid.ncl", "import \"leaf.ncl\""),
+        ("leaf.ncl", "1"),
+    ])
+}
+
+fn id_of(cache: &Cache, path: &Path) -> FileId {
+    cache.id_of(&SourcePath::Path(path.to_owned())).unwrap()
+}
+
+#[test]
+fn poll_changes_invalidates_importers() {
+    let dir = import_chain();
+    let mut cache = Cache::new(ErrorTolerance::Strict);
+    let main = load(&mut cache, &dir.path().join("main.ncl"));
+    let mid = id_of(&cache, &dir.path().join("mid.ncl"));
+    let leaf = id_of(&cache, &dir.path().join("leaf.ncl"));
+
+    rewrite(&dir.path().join("leaf.ncl"), "2");
+
+    let changes = cache.poll_changes();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, dir.path().join("leaf.ncl"));
+    assert_eq!(changes[0].old_id, leaf);
+    assert_eq!(changes[0].invalidated, HashSet::from([main, mid]));
+
+    let new_leaf = changes[0].new_id.unwrap();
+    assert_ne!(new_leaf, leaf);
+    assert_eq!(cache.source(new_leaf), "2");
+    assert!(!cache.terms().contains_key(&main));
+    assert!(!cache.terms().contains_key(&mid));
+
+    // Nothing changed since the last poll.
+    assert!(cache.poll_changes().is_empty());
+}
+
+#[test]
+fn poll_changes_ignores_identical_content() {
+    let dir = import_chain();
+    let mut cache = Cache::new(ErrorTolerance::Strict);
+    let main = load(&mut cache, &dir.path().join("main.ncl"));
+
+    rewrite(&dir.path().join("leaf.ncl"), "1");
+
+    assert!(cache.poll_changes().is_empty());
+    assert!(cache.terms().contains_key(&main));
+}
+
+#[test]
+fn touched_files_are_reused() {
+    let dir = dir_with(&[("a.ncl", "{ foo = 1 }")]);
+    let path = dir.path().join("a.ncl");
+    let mut cache = Cache::new(ErrorTolerance::Strict);
+    let a = load(&mut cache, &path);
+
+    // Only the timestamp changes: the entry is reused as is, and isn't processed again.
+    rewrite(&path, "{ foo = 1 }");
+
+    assert_eq!(cache.get_or_add_file(&path).unwrap(), CacheOp::Cached(a));
+    assert_eq!(cache.entry_state(a), Some(EntryState::Transformed));
+    assert_eq!(cache.id_of(&SourcePath::Path(path.clone())), Some(a));
+
+    // Changing the content does load a new version.
+    rewrite(&path, "{ foo = 2 }");
+
+    assert_matches!(cache.get_or_add_file(&path).unwrap(), CacheOp::Done(id) if id != a);
+}
+
+#[test]
+fn transitive_imports() {
+    let dir = import_chain();
+    let mut cache = Cache::new(ErrorTolerance::Strict);
+    let main = load(&mut cache, &dir.path().join("main.ncl"));
+    let mid = id_of(&cache, &dir.path().join("mid.ncl"));
+    let leaf = id_of(&cache, &dir.path().join("leaf.ncl"));
+
+    assert_eq!(
+        cache.get_imports_transitive(main),
+        HashSet::from([mid, leaf])
+    );
+    assert_eq!(cache.get_imports_transitive(leaf), HashSet::new());
+    assert_eq!(
+        cache.get_rev_imports_transitive(leaf),
+        HashSet::from([main, mid])
+    );
+    assert_eq!(cache.get_rev_imports_transitive(main), HashSet::new());
+}
+
+#[test]
+fn transitive_imports_of_cycles() {
+    let dir = dir_with(&[
+        ("a.ncl", "{ a = 1, b = (import \"b.ncl\").b }"),
+        ("b.ncl", "{ b = 2, a = (import \"a.ncl\").a }"),
+    ]);
+    let mut cache = Cache::new(ErrorTolerance::Strict);
+    let a = load(&mut cache, &dir.path().join("a.ncl"));
+    let b = id_of(&cache, &dir.path().join("b.ncl"));
+
+    // Files which belong to a cycle are part of their own transitive imports.
+    assert_eq!(cache.get_imports_transitive(a), HashSet::from([a, b]));
+    assert_eq!(cache.get_rev_imports_transitive(a), HashSet::from([a, b]));
+    assert_eq!(cache.get_imports_transitive(b), HashSet::from([a, b]));
+}
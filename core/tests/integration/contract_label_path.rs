@@ -60,3 +60,19 @@ fn dictionary_contracts_label_path_is_set_correctly() {
         err => panic!("expected blame error, got {err:#?}"),
     }
 }
+
+#[test]
+fn blame_report_points_to_the_offending_field() {
+    use nickel_lang_core::{error::report::BlameReport, label::Polarity};
+
+    let err =
+        eval("%force% ({server = {port = \"80\"}} | {server | {port: Number}}) false").unwrap_err();
+    // Without the file database of the program, locations are unknown.
+    let report = BlameReport::from_error(&err, &Files::new()).expect("expected a blame error");
+
+    assert_eq!(report.code, "N0201");
+    assert_eq!(report.field_path, vec!["server", "port"]);
+    assert_eq!(report.contract, "{ port : Number }");
+    assert_eq!(report.value.as_deref(), Some("\"80\""));
+    assert_eq!(report.polarity, Polarity::Positive);
+}
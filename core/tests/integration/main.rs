@@ -14,6 +14,7 @@ use nickel_lang_utils::{
 use serde::Deserialize;
 use test_generator::test_resources;
 
+mod cache;
 mod contract_label_path;
 mod free_vars;
 mod pretty;
@@ -171,6 +172,8 @@ enum ErrorExpectation {
     EvalNonExhaustiveMatch,
     #[serde(rename = "EvalError::NonExhaustiveEnumMatch")]
     EvalNonExhaustiveEnumMatch,
+    #[serde(rename = "EvalError::DeserializationError")]
+    EvalDeserializationError,
     #[serde(rename = "TypecheckError::UnboundIdentifier")]
     TypecheckUnboundIdentifier { identifier: String },
     #[serde(rename = "TypecheckError::UnboundTypeVariable")]
@@ -245,6 +248,7 @@ impl PartialEq<Error> for ErrorExpectation {
                 EvalNonExhaustiveEnumMatch,
                 Error::EvalError(EvalError::NonExhaustiveEnumMatch { .. }),
             )
+            | (EvalDeserializationError, Error::EvalError(EvalError::DeserializationError(..)))
             | (
                 TypecheckRecordRowMismatch,
                 Error::TypecheckError(TypecheckError::RecordRowMismatch { .. }),
@@ -404,6 +408,7 @@ impl std::fmt::Display for ErrorExpectation {
             }
             EvalNonExhaustiveMatch => "EvalError::NonExhaustiveMatch".to_owned(),
             EvalNonExhaustiveEnumMatch => "EvalError::NonExhaustiveEnumMatch".to_owned(),
+            EvalDeserializationError => "EvalError::DeserializationError".to_owned(),
             TypecheckUnboundIdentifier { identifier } => {
                 format!("TypecheckError::UnboundIdentifier({identifier})")
             }
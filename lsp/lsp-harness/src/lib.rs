@@ -9,11 +9,13 @@ use log::error;
 use lsp_types::{
     notification::{Notification, PublishDiagnostics},
     request::{
-        Completion, DocumentSymbolRequest, Formatting, GotoDefinition, HoverRequest, References,
-        Rename, Request as LspRequest,
+        CodeLensRequest, Completion, DocumentSymbolRequest, ExecuteCommand, Formatting,
+        GotoDefinition, HoverRequest, InlayHintRequest, RangeFormatting, References, Rename,
+        Request as LspRequest, SignatureHelpRequest,
     },
-    CompletionParams, DocumentFormattingParams, DocumentSymbolParams, GotoDefinitionParams,
-    HoverParams, PublishDiagnosticsParams, ReferenceParams, RenameParams, Url,
+    CodeLensParams, CompletionParams, DocumentFormattingParams, DocumentRangeFormattingParams,
+    DocumentSymbolParams, ExecuteCommandParams, GotoDefinitionParams, HoverParams, InlayHintParams,
+    PublishDiagnosticsParams, ReferenceParams, RenameParams, SignatureHelpParams, Url,
 };
 pub use output::LspDebug;
 use serde::Deserialize;
@@ -39,12 +41,17 @@ pub struct TestFile {
 #[derive(Clone, Deserialize, Debug)]
 #[serde(tag = "type")]
 pub enum Request {
+    CodeLens(CodeLensParams),
+    ExecuteCommand(ExecuteCommandParams),
     GotoDefinition(GotoDefinitionParams),
     References(ReferenceParams),
     Completion(CompletionParams),
     Formatting(DocumentFormattingParams),
+    RangeFormatting(DocumentRangeFormattingParams),
     Hover(HoverParams),
+    InlayHint(InlayHintParams),
     Rename(RenameParams),
+    SignatureHelp(SignatureHelpParams),
     Symbols(DocumentSymbolParams),
 }
 
@@ -146,12 +153,17 @@ impl TestHarness {
 
     pub fn request_dyn(&mut self, req: Request) {
         match req {
+            Request::CodeLens(c) => self.request::<CodeLensRequest>(c),
+            Request::ExecuteCommand(c) => self.request::<ExecuteCommand>(c),
             Request::GotoDefinition(d) => self.request::<GotoDefinition>(d),
             Request::Completion(c) => self.request::<Completion>(c),
             Request::Formatting(f) => self.request::<Formatting>(f),
+            Request::RangeFormatting(f) => self.request::<RangeFormatting>(f),
             Request::Hover(h) => self.request::<HoverRequest>(h),
+            Request::InlayHint(h) => self.request::<InlayHintRequest>(h),
             Request::References(r) => self.request::<References>(r),
             Request::Rename(r) => self.request::<Rename>(r),
+            Request::SignatureHelp(s) => self.request::<SignatureHelpRequest>(s),
             Request::Symbols(s) => self.request::<DocumentSymbolRequest>(s),
         }
     }
@@ -170,6 +170,59 @@ impl LspDebug for lsp_types::MarkupContent {
     }
 }
 
+impl LspDebug for lsp_types::SignatureHelp {
+    fn debug(&self, mut w: impl Write) -> std::io::Result<()> {
+        for sig in &self.signatures {
+            write!(w, "{}", sig.label)?;
+
+            let active = sig.active_parameter.or(self.active_parameter);
+            let param = active.and_then(|i| sig.parameters.as_ref()?.get(i as usize));
+            if let Some(param) = param {
+                let label = match &param.label {
+                    lsp_types::ParameterLabel::Simple(s) => s.clone(),
+                    lsp_types::ParameterLabel::LabelOffsets([start, end]) => {
+                        let utf16: Vec<u16> = sig.label.encode_utf16().collect();
+                        String::from_utf16_lossy(&utf16[*start as usize..*end as usize])
+                    }
+                };
+                write!(w, " @ {label}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LspDebug for lsp_types::CodeLens {
+    fn debug(&self, mut w: impl Write) -> std::io::Result<()> {
+        write!(w, "<{}>", self.range.debug_str())?;
+        if let Some(command) = &self.command {
+            write!(w, " {}", command.title)?;
+        }
+        Ok(())
+    }
+}
+
+impl LspDebug for lsp_types::InlayHint {
+    fn debug(&self, mut w: impl Write) -> std::io::Result<()> {
+        write!(w, "<{}:{}>", self.position.line, self.position.character)?;
+        match &self.label {
+            lsp_types::InlayHintLabel::String(s) => write!(w, "{s}"),
+            lsp_types::InlayHintLabel::LabelParts(parts) => {
+                for part in parts {
+                    write!(w, "{}", part.value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl LspDebug for serde_json::Value {
+    fn debug(&self, mut w: impl Write) -> std::io::Result<()> {
+        write!(w, "{self}")
+    }
+}
+
 impl LspDebug for lsp_types::SymbolInformation {
     fn debug(&self, mut w: impl Write) -> std::io::Result<()> {
         let name = &self.name;
@@ -1,7 +1,412 @@
+use std::collections::HashMap;
+
+use codespan::FileId;
 use lsp_server::{RequestId, Response, ResponseError};
-use lsp_types::{CodeActionOrCommand, CodeActionParams};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic,
+    DiagnosticSeverity, NumberOrString, Position, Range, TextEdit, WorkspaceEdit,
+};
+use nickel_lang_core::{
+    error::ErrorCode,
+    identifier::Ident,
+    pretty::ident_quoted,
+    term::{
+        pattern::{EnumPattern, PatternData},
+        RichTerm, Term, Traverse, TraverseControl,
+    },
+};
+
+use crate::{
+    cache::CacheExt,
+    codespan_lsp::{byte_index_to_position, position_to_byte_index},
+    diagnostic::UNUSED_VARIABLE,
+    field_walker::{FieldResolver, Record},
+    server::Server,
+    world::World,
+};
+
+/// The placeholder used as a value when we insert a new record field or a new match branch. It
+/// fails loudly if it's ever evaluated. Its type is `Dyn`, so it must be replaced (or annotated)
+/// in statically typed code.
+const PLACEHOLDER: &str = "std.fail_with \"TODO\"";
+
+/// Extract the first backquoted name of the first line of a diagnostic message, such as `foo` in
+/// "unbound identifier `foo`". Translated messages keep the names they mention between backquotes,
+/// so this doesn't depend on the language of the message.
+fn quoted_name(message: &str) -> Option<&str> {
+    let mut parts = message.lines().next()?.split('`');
+    parts.next()?;
+    let name = parts.next()?;
+    // The name must be followed by a closing backquote.
+    parts.next().map(|_| name)
+}
+
+/// Find the innermost term enclosing the byte index `idx` that satisfies `pred`.
+fn innermost_enclosing(
+    rt: &RichTerm,
+    idx: usize,
+    mut pred: impl FnMut(&RichTerm) -> bool,
+) -> Option<RichTerm> {
+    let mut found = None;
+
+    rt.traverse_ref(
+        &mut |rt: &RichTerm, _: &()| {
+            let Some(span) = rt.pos.into_opt() else {
+                return TraverseControl::Continue;
+            };
+
+            if span.start.to_usize() > idx || span.end.to_usize() <= idx {
+                TraverseControl::SkipBranch
+            } else {
+                if pred(rt) {
+                    found = Some(rt.clone());
+                }
+                TraverseControl::<(), ()>::Continue
+            }
+        },
+        &(),
+    );
+
+    found
+}
+
+/// Compute the text to insert right before the closing delimiter of a record or a match
+/// expression spanning `source[start..end]`, adding a separating comma if needed. Returns the
+/// byte index of the insertion and the text to insert.
+fn insert_before_closing(
+    source: &str,
+    start: usize,
+    end: usize,
+    item: &str,
+) -> Option<(usize, String)> {
+    let closing = source.get(start..end)?.rfind('}')? + start;
+    let before = source.get(start..closing)?.trim_end();
+    let separator = if before.ends_with(',') || before.ends_with('{') {
+        " "
+    } else {
+        ", "
+    };
+
+    Some((closing, format!("{separator}{item} ")))
+}
+
+struct QuickFixes<'a> {
+    world: &'a World,
+    file_id: FileId,
+    uri: &'a lsp_types::Url,
+    actions: Vec<CodeActionOrCommand>,
+}
+
+impl<'a> QuickFixes<'a> {
+    fn source(&self) -> &'a str {
+        self.world.cache.files().source(self.file_id)
+    }
+
+    fn position(&self, idx: usize) -> Option<Position> {
+        byte_index_to_position(self.world.cache.files(), self.file_id, idx).ok()
+    }
 
-use crate::{cache::CacheExt, server::Server};
+    fn byte_index(&self, pos: Position) -> Option<usize> {
+        position_to_byte_index(self.world.cache.files(), self.file_id, &pos).ok()
+    }
+
+    fn push(&mut self, title: String, diagnostic: &Diagnostic, range: Range, new_text: String) {
+        let changes = HashMap::from([(self.uri.clone(), vec![TextEdit { range, new_text }])]);
+
+        self.actions
+            .push(CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }));
+    }
+
+    fn push_insertion(&mut self, title: String, diagnostic: &Diagnostic, idx: usize, text: String) {
+        if let Some(pos) = self.position(idx) {
+            self.push(title, diagnostic, Range::new(pos, pos), text);
+        }
+    }
+
+    /// Qualify an unbound identifier with the stdlib paths defining a field of the same name,
+    /// e.g. `map` to `std.array.map` or `std.record.map`.
+    fn qualify_with_stdlib(&mut self, diagnostic: &Diagnostic, name: &str) {
+        let Some(std_term) = self
+            .world
+            .initial_term_env
+            .get(&Ident::new("std"))
+            .and_then(|def| def.value().cloned())
+        else {
+            return;
+        };
+
+        let id = Ident::new(name);
+        let resolver = FieldResolver::new(self.world);
+        let mut paths = Vec::new();
+
+        for record in resolver.resolve_record(&std_term) {
+            let Record::RecordTerm(data) = record else {
+                continue;
+            };
+
+            if data.fields.contains_key(&id) {
+                paths.push(format!("std.{}", ident_quoted(&id.into())));
+            }
+
+            for (module, field) in data.fields.iter() {
+                let Some(value) = &field.value else {
+                    continue;
+                };
+
+                if resolver
+                    .resolve_record(value)
+                    .iter()
+                    .any(|r| r.field_loc(id).is_some())
+                {
+                    paths.push(format!(
+                        "std.{}.{}",
+                        ident_quoted(module),
+                        ident_quoted(&id.into())
+                    ));
+                }
+            }
+        }
+
+        paths.sort();
+        paths.dedup();
+
+        for path in paths {
+            self.push(
+                format!("Replace with `{path}`"),
+                diagnostic,
+                diagnostic.range,
+                path,
+            );
+        }
+    }
+
+    /// Turn the `:` of a typed field without definition into a `|`, as suggested by the
+    /// diagnostic.
+    fn colon_to_pipe(&mut self, diagnostic: &Diagnostic) {
+        let Some(field_end) = self.byte_index(diagnostic.range.end) else {
+            return;
+        };
+        let source = self.source();
+        let Some(colon) = source
+            .get(field_end..)
+            .and_then(|rest| rest.find(':'))
+            .map(|offset| field_end + offset)
+        else {
+            return;
+        };
+
+        if let (Some(start), Some(end)) = (self.position(colon), self.position(colon + 1)) {
+            self.push(
+                "Use a contract annotation `|` instead of `:`".to_owned(),
+                diagnostic,
+                Range::new(start, end),
+                "|".to_owned(),
+            );
+        }
+    }
+
+    /// Add the definition of a field required by a contract to the innermost record literal
+    /// enclosing `at`.
+    fn insert_missing_field(&mut self, diagnostic: &Diagnostic, at: Position, name: &str) {
+        let Some(rt) = self.world.cache.get_ref(self.file_id) else {
+            return;
+        };
+        let Some(idx) = self.byte_index(at) else {
+            return;
+        };
+        let Some(record) = innermost_enclosing(rt, idx, |rt| {
+            matches!(rt.as_ref(), Term::Record(_) | Term::RecRecord(..))
+        }) else {
+            return;
+        };
+        let Some(span) = record.pos.into_opt() else {
+            return;
+        };
+
+        let id = Ident::new(name);
+        let field = format!("{} = {PLACEHOLDER}", ident_quoted(&id.into()));
+
+        if let Some((idx, text)) = insert_before_closing(
+            self.source(),
+            span.start.to_usize(),
+            span.end.to_usize(),
+            &field,
+        ) {
+            self.push_insertion(format!("Add missing field `{name}`"), diagnostic, idx, text);
+        }
+    }
+
+    /// Prefix an unused binding with an underscore, or remove it altogether if it's a `let`
+    /// binding.
+    ///
+    /// Pattern bindings are left alone: the bound variable is often the name of the matched field
+    /// as well, as in `let { foo } = ...`, and renaming it would change the meaning of the pattern.
+    fn unused_binding(&mut self, diagnostic: &Diagnostic, name: &str) {
+        let Some(rt) = self.world.cache.get_ref(self.file_id) else {
+            return;
+        };
+        let Some(idx) = self.byte_index(diagnostic.range.start) else {
+            return;
+        };
+        let Some(binder) = innermost_enclosing(rt, idx, |rt| match rt.as_ref() {
+            Term::Let(id, ..) | Term::Fun(id, _) => {
+                matches!(id.pos.into_opt(), Some(span) if span.start.to_usize() == idx)
+            }
+            _ => false,
+        }) else {
+            return;
+        };
+
+        self.push(
+            format!("Prefix `{name}` with an underscore"),
+            diagnostic,
+            Range::new(diagnostic.range.start, diagnostic.range.start),
+            "_".to_owned(),
+        );
+
+        if let Term::Let(_, _, body, _) = binder.as_ref() {
+            let (Some(let_span), Some(body_span)) = (binder.pos.into_opt(), body.pos.into_opt())
+            else {
+                return;
+            };
+            let Some(body_text) = self
+                .source()
+                .get(body_span.start.to_usize()..body_span.end.to_usize())
+            else {
+                return;
+            };
+
+            if let (Some(start), Some(end)) = (
+                self.position(let_span.start.to_usize()),
+                self.position(let_span.end.to_usize()),
+            ) {
+                self.push(
+                    format!("Remove unused binding `{name}`"),
+                    diagnostic,
+                    Range::new(start, end),
+                    body_text.to_owned(),
+                );
+            }
+        }
+    }
+
+    /// Add a branch for an enum tag which isn't handled by the innermost match expression
+    /// enclosing the diagnostic.
+    fn insert_missing_branch(&mut self, diagnostic: &Diagnostic, name: &str) {
+        let Some(rt) = self.world.cache.get_ref(self.file_id) else {
+            return;
+        };
+        let Some(idx) = self.byte_index(diagnostic.range.start) else {
+            return;
+        };
+        let Some(match_term) =
+            innermost_enclosing(rt, idx, |rt| matches!(rt.as_ref(), Term::Match(_)))
+        else {
+            return;
+        };
+        let Term::Match(data) = match_term.as_ref() else {
+            return;
+        };
+        let Some(span) = match_term.pos.into_opt() else {
+            return;
+        };
+
+        let id = Ident::new(name);
+        let already_handled = data.branches.iter().any(|branch| {
+            matches!(
+                &branch.pattern.data,
+                PatternData::Enum(EnumPattern { tag, .. }) if tag.ident() == id
+            )
+        });
+
+        if already_handled {
+            return;
+        }
+
+        let branch = format!("'{} => {PLACEHOLDER}", ident_quoted(&id.into()));
+
+        if let Some((idx, text)) = insert_before_closing(
+            self.source(),
+            span.start.to_usize(),
+            span.end.to_usize(),
+            &branch,
+        ) {
+            self.push_insertion(
+                format!("Add missing match branch `'{name}`"),
+                diagnostic,
+                idx,
+                text,
+            );
+        }
+    }
+}
+
+/// Return the code of a diagnostic reported by Nickel, such as `N0101`.
+fn diagnostic_code(diagnostic: &Diagnostic) -> Option<ErrorCode> {
+    match diagnostic.code.as_ref()? {
+        NumberOrString::String(code) => code.parse().ok(),
+        NumberOrString::Number(_) => None,
+    }
+}
+
+/// Compute the quick fixes applying to the diagnostics of a code action request.
+fn quick_fixes(
+    world: &World,
+    file_id: FileId,
+    params: &CodeActionParams,
+) -> Vec<CodeActionOrCommand> {
+    let mut fixes = QuickFixes {
+        world,
+        file_id,
+        uri: &params.text_document.uri,
+        actions: Vec::new(),
+    };
+
+    for diagnostic in &params.context.diagnostics {
+        let name = quoted_name(&diagnostic.message);
+
+        let Some(code) = diagnostic_code(diagnostic) else {
+            continue;
+        };
+
+        // Each label of an error is also reported as a hint with the same code, located at the
+        // label (see [crate::diagnostic::SerializableDiagnostic]). Fixes are only computed from
+        // the main diagnostic.
+        if code != UNUSED_VARIABLE && diagnostic.severity == Some(DiagnosticSeverity::HINT) {
+            continue;
+        }
+
+        match (code, name) {
+            // Unbound identifier, reported by the typechecker or during evaluation.
+            (ErrorCode(101) | ErrorCode(211), Some(name)) => {
+                fixes.qualify_with_stdlib(diagnostic, name)
+            }
+            // Statically typed field without a definition.
+            (ErrorCode(13), _) => fixes.colon_to_pipe(diagnostic),
+            // Missing definition of a field required by a contract.
+            (ErrorCode(202), Some(name)) => {
+                fixes.insert_missing_field(diagnostic, params.range.start, name)
+            }
+            // Missing or extra row, for example when a match doesn't handle an enum tag.
+            (ErrorCode(102) | ErrorCode(104), Some(name)) => {
+                fixes.insert_missing_branch(diagnostic, name)
+            }
+            (UNUSED_VARIABLE, Some(name)) => fixes.unused_binding(diagnostic, name),
+            _ => (),
+        }
+    }
+
+    fixes.actions
+}
 
 pub fn handle_code_action(
     params: CodeActionParams,
@@ -10,12 +415,9 @@ pub fn handle_code_action(
 ) -> Result<(), ResponseError> {
     let mut actions = Vec::new();
 
-    if server
-        .world
-        .cache
-        .file_id(&params.text_document.uri)?
-        .is_some()
-    {
+    if let Some(file_id) = server.world.cache.file_id(&params.text_document.uri)? {
+        actions.extend(quick_fixes(&server.world, file_id, &params));
+
         actions.push(CodeActionOrCommand::Command(lsp_types::Command {
             title: "evaluate term".to_owned(),
             command: "eval".to_owned(),
@@ -26,3 +428,41 @@ pub fn handle_code_action(
     server.reply(Response::new_ok(req, Some(actions)));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{insert_before_closing, quoted_name};
+
+    #[test]
+    fn extract_quoted_name() {
+        assert_eq!(quoted_name("unbound identifier `foo`"), Some("foo"));
+        assert_eq!(quoted_name("identifiant `foo` non lié"), Some("foo"));
+        assert_eq!(
+            quoted_name("type error: missing row `bar`\nsome note `baz`"),
+            Some("bar")
+        );
+        assert_eq!(quoted_name("incompatible types"), None);
+        assert_eq!(quoted_name("unterminated `quote\n`"), None);
+    }
+
+    #[test]
+    fn insert_separator() {
+        let src = "{ foo = 1 }";
+        assert_eq!(
+            insert_before_closing(src, 0, src.len(), "bar = 2"),
+            Some((10, ", bar = 2 ".to_owned()))
+        );
+
+        let src = "{ foo = 1, }";
+        assert_eq!(
+            insert_before_closing(src, 0, src.len(), "bar = 2"),
+            Some((11, " bar = 2 ".to_owned()))
+        );
+
+        let src = "{}";
+        assert_eq!(
+            insert_before_closing(src, 0, src.len(), "bar = 2"),
+            Some((1, " bar = 2 ".to_owned()))
+        );
+    }
+}
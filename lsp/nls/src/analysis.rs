@@ -350,6 +350,9 @@ pub struct TypeCollector {
 pub struct CollectedTypes<Ty> {
     pub terms: HashMap<RichTermPtr, Ty>,
     pub idents: HashMap<LocIdent, Ty>,
+    /// The types inferred for the wildcards of the file, indexed by wildcard id. This is only
+    /// filled once typechecking is over, by [`TypeCollector::complete`].
+    pub wildcards: Vec<Type>,
 }
 
 impl<Ty> Default for CollectedTypes<Ty> {
@@ -357,6 +360,7 @@ impl<Ty> Default for CollectedTypes<Ty> {
         Self {
             terms: Default::default(),
             idents: Default::default(),
+            wildcards: Default::default(),
         }
     }
 }
@@ -395,7 +399,11 @@ impl TypeCollector {
             .into_iter()
             .map(|(id, uty)| (id, transform_type(uty)))
             .collect();
-        CollectedTypes { terms, idents }
+        CollectedTypes {
+            terms,
+            idents,
+            wildcards: type_tables.wildcards,
+        }
     }
 }
 
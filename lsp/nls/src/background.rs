@@ -112,6 +112,10 @@ pub fn worker_main() -> anyhow::Result<()> {
             );
         }
 
+        // The diagnostics we send replace the ones issued synchronously by the main process, so
+        // we need to include the hints about unused bindings as well.
+        diagnostics.extend(world.unused_binding_diagnostics(file_id));
+
         diagnostics.sort();
         diagnostics.dedup();
         let diagnostics = Diagnostics { path, diagnostics };
@@ -1,8 +1,22 @@
 use lsp_server::{RequestId, Response, ResponseError};
 use lsp_types::{ExecuteCommandParams, TextDocumentIdentifier, Url};
-use nickel_lang_core::eval::{cache::CacheImpl, VirtualMachine};
+use nickel_lang_core::{
+    eval::{cache::CacheImpl, VirtualMachine},
+    identifier::Ident,
+    term::{make as mk_term, RichTerm},
+};
 
-use crate::{cache::CacheExt, error::Error, server::Server};
+use crate::{
+    cache::CacheExt, error::Error, requests::code_lens::EVAL_FIELD_COMMAND, server::Server,
+};
+
+/// The number of evaluation steps allowed when evaluating a single field. Fields are evaluated
+/// while the user is editing the configuration, so we'd rather give up than hang on a program
+/// that doesn't terminate.
+const EVAL_FIELD_FUEL: usize = 1_000_000;
+
+/// Evaluated values are shown inline, so we cut them after this many characters.
+const MAX_SUMMARY_LEN: usize = 80;
 
 pub fn handle_command(
     params: ExecuteCommandParams,
@@ -18,6 +32,14 @@ pub fn handle_command(
             eval(server, &doc.uri)?;
             Ok(())
         }
+        EVAL_FIELD_COMMAND => {
+            let doc: TextDocumentIdentifier =
+                serde_json::from_value(params.arguments[0].clone()).unwrap();
+            let field: String = serde_json::from_value(params.arguments[1].clone()).unwrap();
+            let summary = eval_field(server, &doc.uri, Ident::new(field))?;
+            server.reply(Response::new_ok(req, summary));
+            Ok(())
+        }
         _ => Err(Error::CommandNotFound(params.command).into()),
     }
 }
@@ -35,3 +57,55 @@ fn eval(server: &mut Server, uri: &Url) -> Result<(), Error> {
     }
     Ok(())
 }
+
+/// Render a value on a single line, truncating it if it's too long.
+fn summarize(rt: &RichTerm) -> String {
+    let rendered = rt.to_string();
+    let mut summary = rendered.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if let Some((idx, _)) = summary.char_indices().nth(MAX_SUMMARY_LEN) {
+        summary.truncate(idx);
+        summary.push('…');
+    }
+
+    summary
+}
+
+/// Evaluate the top-level field `field` of the file at `uri`, with a bounded amount of fuel, and
+/// record a summary of the result (or of the error) to be shown by code lenses and hovers.
+fn eval_field(server: &mut Server, uri: &Url, field: Ident) -> Result<Option<String>, Error> {
+    let Some(file_id) = server.world.cache.file_id(uri)? else {
+        return Ok(None);
+    };
+
+    let mut vm = VirtualMachine::<_, CacheImpl>::new(server.world.cache.clone(), std::io::sink())
+        .with_fuel(EVAL_FIELD_FUEL);
+
+    let result = vm
+        .prepare_eval(file_id)
+        .map_err(|e| server.world.lsp_diagnostics(file_id, e))
+        .and_then(|rt| {
+            vm.eval_full(mk_term::static_access(rt, [field]))
+                .map_err(|e| server.world.lsp_diagnostics(file_id, e))
+        });
+
+    let summary = match result {
+        Ok(value) => summarize(&value),
+        Err(diags) => {
+            let msg = diags
+                .first()
+                .map(|d| d.message.as_str())
+                .unwrap_or("evaluation failed");
+            format!("error: {}", msg.lines().next().unwrap_or_default())
+        }
+    };
+
+    server
+        .world
+        .field_evals
+        .entry(file_id)
+        .or_default()
+        .insert(field, summary.clone());
+
+    Ok(Some(summary))
+}
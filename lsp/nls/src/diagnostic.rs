@@ -3,11 +3,17 @@ use std::ops::Range;
 use codespan::{FileId, Files};
 use codespan_reporting::diagnostic::{self, Diagnostic, LabelStyle};
 use lsp_types::{DiagnosticRelatedInformation, NumberOrString};
-use nickel_lang_core::{error::UNKNOWN_SOURCE_NAME, position::RawSpan};
+use nickel_lang_core::{
+    error::{ErrorCode, UNKNOWN_SOURCE_NAME},
+    position::RawSpan,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::codespan_lsp::byte_span_to_range;
 
+/// The code of the hints reported for unused variables.
+pub const UNUSED_VARIABLE: ErrorCode = ErrorCode(501);
+
 /// A more serializable alternative to lsp_types::Diagnostic
 ///
 /// lsp_types::Diagnostic is not serializable to bincode (and therefore not
@@ -24,6 +30,7 @@ pub struct SerializableDiagnostic {
     pub code: Option<String>,
     pub message: String,
     pub related_information: Option<Vec<OrdDiagnosticRelatedInformation>>,
+    pub tags: Option<Vec<lsp_types::DiagnosticTag>>,
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Default, Deserialize, Serialize)]
@@ -77,6 +84,7 @@ impl From<SerializableDiagnostic> for lsp_types::Diagnostic {
             related_information: d
                 .related_information
                 .map(|xs| xs.into_iter().map(|x| x.0).collect()),
+            tags: d.tags,
             ..Default::default()
         }
     }
@@ -198,6 +206,7 @@ impl DiagnosticCompat for SerializableDiagnostic {
                             })
                             .collect(),
                     ),
+                    tags: None,
                 });
             }
         }
@@ -211,6 +220,7 @@ impl DiagnosticCompat for SerializableDiagnostic {
                 severity: Some(lsp_types::DiagnosticSeverity::HINT),
                 code: code.clone(),
                 related_information: None,
+                tags: None,
             }
         }));
         diagnostics
@@ -455,6 +455,7 @@ impl<'a> FieldResolver<'a> {
                 defs.chain(self.resolve_container(term)).collect()
             }
             Term::Type(typ) => self.resolve_type(typ),
+            Term::App(..) => self.resolve_app(rt),
             _ => Default::default(),
         };
 
@@ -468,6 +469,74 @@ impl<'a> FieldResolver<'a> {
         combine(term_fields, typ_fields)
     }
 
+    /// Find all the containers that a function application resolves to.
+    ///
+    /// This is a very lightweight form of partial evaluation: we don't substitute the arguments,
+    /// but we look into the body of the applied function (when it can be found) for e.g. record
+    /// literals. Conditionals and match expressions resolve to the union of their branches.
+    fn resolve_app(&self, rt: &RichTerm) -> Vec<Container> {
+        let mut head = rt;
+        let mut args = Vec::new();
+
+        while let Term::App(fun, arg) = head.as_ref() {
+            head = fun;
+            args.push(arg);
+        }
+        args.reverse();
+
+        match (head.as_ref(), args.as_slice()) {
+            (Term::Op1(UnaryOp::Ite(), _), [if_true, if_false]) => combine(
+                self.resolve_container(if_true),
+                self.resolve_container(if_false),
+            ),
+            _ => self
+                .fun_bodies(head, args.len())
+                .iter()
+                .flat_map(|body| self.resolve_container(body))
+                .collect(),
+        }
+    }
+
+    /// Find the possible bodies of the function `rt` once applied to `arity` arguments, going
+    /// through variables, annotations and `let` bindings. A match expression has one body per
+    /// branch.
+    fn fun_bodies(&self, rt: &RichTerm, arity: usize) -> Vec<RichTerm> {
+        if arity == 0 {
+            return vec![rt.clone()];
+        }
+
+        match rt.as_ref() {
+            Term::Fun(_, body) | Term::FunPattern(_, body) => self.fun_bodies(body, arity - 1),
+            Term::Match(data) => data
+                .branches
+                .iter()
+                .flat_map(|branch| self.fun_bodies(&branch.body, arity - 1))
+                .collect(),
+            Term::Annotated(_, t) | Term::Let(_, _, t, _) | Term::LetPattern(_, _, t) => {
+                self.fun_bodies(t, arity)
+            }
+            Term::Var(id) => {
+                let id = LocIdent::from(*id);
+                if !self.blackholed_ids.borrow_mut().insert(id) {
+                    log::warn!("detected recursion when resolving {id:?}");
+                    return Vec::new();
+                }
+
+                let ret = self
+                    .world
+                    .analysis
+                    .get_def(&id)
+                    .filter(|def| def.path().is_empty())
+                    .and_then(|def| def.value())
+                    .map(|val| self.fun_bodies(val, arity))
+                    .unwrap_or_default();
+                self.blackholed_ids.borrow_mut().remove(&id);
+                ret
+            }
+            _ => Vec::new(),
+        }
+    }
+
     fn resolve_type(&self, typ: &Type) -> Vec<Container> {
         match &typ.typ {
             TypeF::Record(rows) => vec![Container::RecordType(rows.clone())],
@@ -1,13 +1,16 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
+use codespan_reporting::files::SimpleFile;
 use lsp_server::RequestId;
 use lsp_types::{
     notification::{DidOpenTextDocument, Notification},
-    DidChangeTextDocumentParams, DidOpenTextDocumentParams, Url,
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, TextDocumentContentChangeEvent, Url,
 };
 
 use crate::{
+    cache::CacheExt as _,
+    codespan_lsp::position_to_byte_index,
     error::Error,
     trace::{param::FileUpdate, Enrich, Trace},
 };
@@ -23,6 +26,34 @@ pub(crate) fn uri_to_path(uri: &Url) -> std::result::Result<PathBuf, Error> {
     }
 }
 
+/// Apply the changes of a `didChange` notification to the previous content of a file, in order.
+/// A change without a range replaces the whole content.
+fn apply_changes(
+    text: &mut String,
+    changes: Vec<TextDocumentContentChangeEvent>,
+    uri: &Url,
+) -> std::result::Result<(), Error> {
+    for change in changes {
+        let Some(range) = change.range else {
+            *text = change.text;
+            continue;
+        };
+
+        let file = SimpleFile::new("", text.as_str());
+        let byte_index = |pos| {
+            position_to_byte_index(&file, (), &pos).map_err(|_| Error::InvalidPosition {
+                pos,
+                file: uri.clone(),
+            })
+        };
+        let range = byte_index(range.start)?..byte_index(range.end)?;
+
+        text.replace_range(range, &change.text);
+    }
+
+    Ok(())
+}
+
 pub fn handle_open(server: &mut Server, params: DidOpenTextDocumentParams) -> Result<()> {
     let id: RequestId = format!(
         "{}#{}",
@@ -41,7 +72,8 @@ pub fn handle_open(server: &mut Server, params: DidOpenTextDocumentParams) -> Re
         .world
         .add_file(params.text_document.uri.clone(), params.text_document.text)?;
 
-    let diags = server.world.parse_and_typecheck(file_id);
+    let mut diags = server.world.parse_and_typecheck(file_id);
+    diags.extend(server.world.unused_binding_diagnostics(file_id));
     server.issue_diagnostics(file_id, diags);
 
     for rev_dep in &invalid {
@@ -59,20 +91,20 @@ pub fn handle_save(server: &mut Server, params: DidChangeTextDocumentParams) ->
     )
     .into();
 
+    let uri = params.text_document.uri;
+    let mut contents = match server.world.cache.file_id(&uri)? {
+        Some(file_id) => server.world.cache.files().source(file_id).clone(),
+        None => String::new(),
+    };
+    apply_changes(&mut contents, params.content_changes, &uri)?;
+
     Trace::receive(id.clone(), DidOpenTextDocument::METHOD);
-    Trace::enrich(
-        &id,
-        FileUpdate {
-            content: &params.content_changes[0].text,
-        },
-    );
+    Trace::enrich(&id, FileUpdate { content: &contents });
 
-    let (file_id, invalid) = server.world.update_file(
-        params.text_document.uri.clone(),
-        params.content_changes[0].text.clone(),
-    )?;
+    let (file_id, invalid) = server.world.update_file(uri, contents)?;
 
-    let diags = server.world.parse_and_typecheck(file_id);
+    let mut diags = server.world.parse_and_typecheck(file_id);
+    diags.extend(server.world.unused_binding_diagnostics(file_id));
     server.issue_diagnostics(file_id, diags);
 
     for f in &invalid {
@@ -0,0 +1,295 @@
+//! Incremental reparsing of open files.
+//!
+//! Each edit to a file triggers a new parse, which gets expensive for large files. Most Nickel
+//! configurations are a big record, possibly preceded by some `let` bindings, and an edit usually
+//! touches only one of its fields. The fields defined before the edit are unaffected: neither their
+//! text nor their position changed, so we reuse their previously parsed form and only parse the
+//! rest of the file.
+//!
+//! To get correct positions without having to shift them, we don't parse the rest of the file in
+//! isolation but a copy of the whole new source where the reused fields are blanked out, which has
+//! the same length as the original.
+
+use codespan::{ByteIndex, FileId};
+use nickel_lang_core::{
+    error::ParseErrors,
+    identifier::{Ident, LocIdent},
+    parser::{
+        grammar,
+        lexer::{Lexer, NormalToken, Token},
+        ErrorTolerantParser,
+    },
+    position::{RawSpan, TermPos},
+    term::{
+        record::{Field, RecordData},
+        RichTerm, Term,
+    },
+};
+
+/// The text of a source together with the term it parsed to, before any further processing (such
+/// as import resolution) happened.
+#[derive(Clone, Debug)]
+pub struct ParsedSource {
+    pub text: String,
+    pub term: RichTerm,
+}
+
+/// Parse `new_text`, the new content of the source `file_id`, reusing the parts of the previous
+/// parse `old` that come before the edit.
+///
+/// `old` must have parsed without errors. Returns `None` if the shape of the source or of the edit
+/// doesn't allow reusing anything, in which case the source must be parsed from scratch.
+pub fn reparse(
+    file_id: FileId,
+    old: &ParsedSource,
+    new_text: &str,
+) -> Option<(RichTerm, ParseErrors)> {
+    let edit_start = common_prefix_len(&old.text, new_text);
+
+    let old_data = record_data(toplevel_record(&old.term)?)?;
+    let (kept, blank) = reusable_fields(old_data, edit_start)?;
+
+    // Type wildcards are numbered during parsing. The numbering restarts when reparsing, so the
+    // wildcards of the reused fields could collide with the new ones.
+    if has_wildcards(&old.text[blank.clone()]) {
+        return None;
+    }
+
+    let mut blanked = new_text.as_bytes().to_vec();
+    blanked[blank].fill(b' ');
+    // The blanked range starts and ends on token boundaries, so we can't have cut a multibyte
+    // character in half.
+    let blanked = String::from_utf8(blanked).ok()?;
+
+    let (term, errors) = grammar::TermParser::new()
+        .parse_tolerant(file_id, Lexer::new(&blanked))
+        .ok()?;
+    let term = splice(&term, old_data, &kept)?;
+
+    let pos = TermPos::Original(RawSpan {
+        src_id: file_id,
+        start: ByteIndex(0),
+        end: ByteIndex(new_text.len() as u32),
+    });
+
+    Some((term.with_pos(pos), errors))
+}
+
+/// The length of the longest common prefix of `left` and `right`, rounded down to a character
+/// boundary.
+fn common_prefix_len(left: &str, right: &str) -> usize {
+    let mut len = left
+        .bytes()
+        .zip(right.bytes())
+        .take_while(|(l, r)| l == r)
+        .count();
+
+    while !left.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    len
+}
+
+/// The record making up the body of the file, under its leading `let` bindings.
+fn toplevel_record(rt: &RichTerm) -> Option<&RichTerm> {
+    match rt.as_ref() {
+        Term::Let(_, _, body, _) | Term::LetPattern(_, _, body) => toplevel_record(body),
+        Term::Record(_) | Term::RecRecord(..) => Some(rt),
+        _ => None,
+    }
+}
+
+/// The static fields of a record. Records with dynamic fields are not supported.
+fn record_data(rt: &RichTerm) -> Option<&RecordData> {
+    match rt.as_ref() {
+        Term::Record(data) => Some(data),
+        Term::RecRecord(data, dyn_fields, _) if dyn_fields.is_empty() => Some(data),
+        _ => None,
+    }
+}
+
+/// Determine the fields of `data` that are entirely defined before `edit_start`, together with the
+/// byte range spanned by their definitions.
+///
+/// We only know the position of the name of a field and of its value. A field defined piecewise
+/// (`foo | Number, bar = 1, foo = 2`) can't be told apart from a field defined in one go just by
+/// looking at the position of its name, so we require each value to be located between the name
+/// of its field and the name of the next field. Merged values don't have a position at all.
+fn reusable_fields(
+    data: &RecordData,
+    edit_start: usize,
+) -> Option<(Vec<Ident>, std::ops::Range<usize>)> {
+    let mut fields = data
+        .fields
+        .iter()
+        .map(|(id, field)| Some((id.pos.into_opt()?.start.to_usize(), id, field)))
+        .collect::<Option<Vec<(usize, &LocIdent, &Field)>>>()?;
+    fields.sort_by_key(|(start, _, _)| *start);
+
+    let mut kept = Vec::new();
+    let mut blank_end = None;
+
+    for (i, (start, id, field)) in fields.iter().enumerate() {
+        // The definition of the last field ends at the closing brace, whose position we don't
+        // know. We never reuse it.
+        let end = fields.get(i + 1).map_or(usize::MAX, |(next, _, _)| *next);
+
+        if let Some(value) = &field.value {
+            match value.pos {
+                TermPos::Original(span)
+                    if *start <= span.start.to_usize() && span.end.to_usize() <= end => {}
+                _ => return None,
+            }
+        }
+
+        if blank_end.is_none() {
+            if end <= edit_start {
+                kept.push(id.ident());
+            } else {
+                blank_end = Some(*start);
+            }
+        }
+    }
+
+    let (blank_start, _, _) = fields.first()?;
+    let blank_end = blank_end?;
+
+    (!kept.is_empty()).then_some((kept, *blank_start..blank_end))
+}
+
+/// Does `text` contain type wildcards `_`? We err on the side of caution: the underscore is also
+/// used as a catch-all pattern, and we answer `true` if `text` can't be lexed.
+fn has_wildcards(text: &str) -> bool {
+    Lexer::new(text).any(|token| {
+        matches!(
+            token,
+            Ok((_, Token::Normal(NormalToken::Underscore), _)) | Err(_)
+        )
+    })
+}
+
+/// Replace the toplevel record of the freshly parsed term `rt` with a record containing both the
+/// fields `kept` of the previous record `old_data` and the fields of the new record.
+fn splice(rt: &RichTerm, old_data: &RecordData, kept: &[Ident]) -> Option<RichTerm> {
+    let with_kept_fields = |data: &RecordData| {
+        if data.fields.keys().any(|id| kept.contains(&id.ident())) {
+            return None;
+        }
+
+        // Starting from the old map keeps the reused fields in their definition order.
+        let mut fields = old_data.fields.clone();
+        fields.retain(|id, _| kept.contains(&id.ident()));
+        fields.extend(data.fields.iter().map(|(id, field)| (*id, field.clone())));
+
+        Some(RecordData::new(
+            fields,
+            data.attrs,
+            data.sealed_tail.clone(),
+        ))
+    };
+
+    let term = match rt.as_ref() {
+        Term::Let(id, val, body, attrs) => Term::Let(
+            *id,
+            val.clone(),
+            splice(body, old_data, kept)?,
+            attrs.clone(),
+        ),
+        Term::LetPattern(pat, val, body) => {
+            Term::LetPattern(pat.clone(), val.clone(), splice(body, old_data, kept)?)
+        }
+        Term::Record(data) => Term::Record(with_kept_fields(data)?),
+        Term::RecRecord(data, dyn_fields, deps) => {
+            Term::RecRecord(with_kept_fields(data)?, dyn_fields.clone(), deps.clone())
+        }
+        _ => return None,
+    };
+
+    Some(RichTerm::new(term, rt.pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use codespan::{ByteIndex, Files};
+    use nickel_lang_core::{
+        parser::{grammar, lexer::Lexer, ErrorTolerantParser},
+        position::{RawSpan, TermPos},
+        term::{RichTerm, Term},
+    };
+
+    use super::{reparse, ParsedSource};
+
+    fn parse(files: &mut Files<String>, s: &str) -> RichTerm {
+        let id = files.add("<test>", s.to_owned());
+        let pos = TermPos::Original(RawSpan {
+            src_id: id,
+            start: ByteIndex(0),
+            end: ByteIndex(s.len() as u32),
+        });
+
+        grammar::TermParser::new()
+            .parse_strict(id, Lexer::new(s))
+            .unwrap()
+            .with_pos(pos)
+    }
+
+    fn field_value_pos(rt: &RichTerm, name: &str) -> TermPos {
+        match rt.as_ref() {
+            Term::Let(_, _, body, _) => field_value_pos(body, name),
+            Term::RecRecord(data, ..) => {
+                data.fields
+                    .iter()
+                    .find(|(id, _)| id.label() == name)
+                    .and_then(|(_, field)| field.value.as_ref())
+                    .unwrap()
+                    .pos
+            }
+            _ => panic!("expected a record"),
+        }
+    }
+
+    #[test]
+    fn reuse_fields_before_edit() {
+        let mut files = Files::new();
+        let old_text = "let x = 1 in {\n  foo = x,\n  bar.baz = [1, 2],\n  qux = \"old\",\n}";
+        let new_text = "let x = 1 in {\n  foo = x,\n  bar.baz = [1, 2],\n  qux = \"newer\",\n}";
+
+        let old = ParsedSource {
+            text: old_text.to_owned(),
+            term: parse(&mut files, old_text),
+        };
+        let expected = parse(&mut files, new_text);
+        let file_id = files.add("<test>", new_text.to_owned());
+
+        let (term, errors) = reparse(file_id, &old, new_text).unwrap();
+        assert!(errors.no_errors());
+        assert_eq!(term.clone().without_pos(), expected.clone().without_pos());
+
+        for name in ["foo", "bar", "qux"] {
+            let (pos, expected_pos) = (
+                field_value_pos(&term, name),
+                field_value_pos(&expected, name),
+            );
+            assert_eq!(
+                pos.into_opt().map(|span| (span.start, span.end)),
+                expected_pos.into_opt().map(|span| (span.start, span.end)),
+            );
+        }
+    }
+
+    #[test]
+    fn piecewise_definitions_are_not_reused() {
+        let mut files = Files::new();
+        let old_text = "{ a.b = 1, x = 2, a.c = 3, y = 4 }";
+        let new_text = "{ a.b = 1, x = 2, a.c = 3, y = 5 }";
+
+        let old = ParsedSource {
+            text: old_text.to_owned(),
+            term: parse(&mut files, old_text),
+        };
+        let file_id = files.add("<test>", new_text.to_owned());
+
+        assert!(reparse(file_id, &old, new_text).is_none());
+    }
+}
@@ -17,6 +17,7 @@ mod field_walker;
 mod files;
 mod identifier;
 mod incomplete;
+mod incremental;
 mod position;
 mod requests;
 mod server;
@@ -86,6 +86,8 @@ impl InjectBindings for PatternData {
             PatternData::Record(record_pat) => {
                 record_pat.inject_bindings(bindings, path, parent_deco)
             }
+            PatternData::Array(array_pat) => array_pat.inject_bindings(bindings, path, parent_deco),
+            PatternData::Tuple(tuple_pat) => tuple_pat.inject_bindings(bindings, path, parent_deco),
             PatternData::Enum(evariant_pat) => {
                 evariant_pat.inject_bindings(bindings, path, parent_deco)
             }
@@ -118,6 +120,39 @@ impl InjectBindings for RecordPattern {
     }
 }
 
+impl InjectBindings for ArrayPattern {
+    fn inject_bindings(
+        &self,
+        bindings: &mut Vec<(Vec<LocIdent>, LocIdent, Field)>,
+        path: Vec<LocIdent>,
+        parent_extra: Option<&Field>,
+    ) {
+        // Array elements aren't reachable through a field path, so the element patterns share the
+        // path of the array pattern.
+        for elem_pat in self.patterns.iter() {
+            elem_pat.inject_bindings(bindings, path.clone(), None);
+        }
+
+        if let ArrayPatternTail::Capture(rest) = self.tail {
+            bindings.push((path, rest, parent_extra.cloned().unwrap_or_default()));
+        }
+    }
+}
+
+impl InjectBindings for TuplePattern {
+    fn inject_bindings(
+        &self,
+        bindings: &mut Vec<(Vec<LocIdent>, LocIdent, Field)>,
+        path: Vec<LocIdent>,
+        _parent_extra: Option<&Field>,
+    ) {
+        // As for array patterns, the element patterns share the path of the tuple pattern.
+        for elem_pat in self.patterns.iter() {
+            elem_pat.inject_bindings(bindings, path.clone(), None);
+        }
+    }
+}
+
 impl InjectBindings for FieldPattern {
     fn inject_bindings(
         &self,
@@ -0,0 +1,97 @@
+use lsp_server::{RequestId, Response, ResponseError};
+use lsp_types::{CodeLens, CodeLensParams, Command, Range, TextDocumentIdentifier};
+use nickel_lang_core::{
+    identifier::LocIdent,
+    term::{RichTerm, Term},
+};
+
+use crate::{cache::CacheExt as _, diagnostic::LocationCompat, server::Server};
+
+/// The command run by the code lenses, evaluating a single top-level field.
+pub const EVAL_FIELD_COMMAND: &str = "eval-field";
+
+/// The record making up the body of a file, under its leading `let` bindings and annotations.
+fn toplevel_record(rt: &RichTerm) -> Option<&RichTerm> {
+    match rt.as_ref() {
+        Term::Let(_, _, body, _) | Term::LetPattern(_, _, body) | Term::Annotated(_, body) => {
+            toplevel_record(body)
+        }
+        Term::Record(_) | Term::RecRecord(..) => Some(rt),
+        _ => None,
+    }
+}
+
+/// The names of the static fields of the top-level record of `rt`, in order of definition. A field
+/// defined piecewise is only reported once, at its first definition.
+pub fn toplevel_fields(rt: &RichTerm) -> Vec<LocIdent> {
+    let data = match toplevel_record(rt).map(AsRef::as_ref) {
+        Some(Term::Record(data) | Term::RecRecord(data, ..)) => data,
+        _ => return Vec::new(),
+    };
+
+    let mut fields: Vec<_> = data
+        .fields
+        .keys()
+        .filter(|id| id.pos.into_opt().is_some())
+        .copied()
+        .collect();
+    fields.sort_by_key(|id| id.pos.into_opt().map(|span| span.start));
+
+    let mut seen = Vec::new();
+    fields.retain(|id| {
+        let fresh = !seen.contains(&id.ident());
+        seen.push(id.ident());
+        fresh
+    });
+
+    fields
+}
+
+pub fn handle_code_lens(
+    params: CodeLensParams,
+    id: RequestId,
+    server: &mut Server,
+) -> Result<(), ResponseError> {
+    let Some(file_id) = server.world.cache.file_id(&params.text_document.uri)? else {
+        server.reply(Response::new_ok(id, Vec::<CodeLens>::new()));
+        return Ok(());
+    };
+
+    let fields = server
+        .world
+        .cache
+        .get_ref(file_id)
+        .map(toplevel_fields)
+        .unwrap_or_default();
+    let evaluated = server.world.field_evals.get(&file_id);
+
+    let lenses: Vec<_> = fields
+        .into_iter()
+        .filter_map(|field| {
+            let span = field.pos.into_opt()?;
+            let title = match evaluated.and_then(|evals| evals.get(&field.ident())) {
+                Some(value) => format!("▶ {value}"),
+                None => "▶ evaluate".to_owned(),
+            };
+
+            Some(CodeLens {
+                range: Range::from_span(&span, server.world.cache.files()),
+                command: Some(Command {
+                    title,
+                    command: EVAL_FIELD_COMMAND.to_owned(),
+                    arguments: Some(vec![
+                        serde_json::to_value(TextDocumentIdentifier {
+                            uri: params.text_document.uri.clone(),
+                        })
+                        .unwrap(),
+                        serde_json::Value::String(field.label().to_owned()),
+                    ]),
+                }),
+                data: None,
+            })
+        })
+        .collect();
+
+    server.reply(Response::new_ok(id, lenses));
+    Ok(())
+}
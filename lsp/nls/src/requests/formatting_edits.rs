@@ -0,0 +1,116 @@
+use lsp_types::{Position, Range, TextEdit};
+use similar::{DiffOp, TextDiff};
+
+/// Compute the text edits turning `text` into its formatted version `formatted`.
+///
+/// Instead of sending one huge edit replacing the whole document, we diff the two versions line by
+/// line and send one edit per changed hunk, so that the editor can preserve e.g. the cursor
+/// position or the marks in untouched parts of the document. If `range` is provided, only the
+/// hunks overlapping the lines of `range` are kept, which is how we implement range formatting:
+/// Topiary can only format whole documents.
+pub fn formatting_edits(text: &str, formatted: &str, range: Option<Range>) -> Vec<TextEdit> {
+    let diff = TextDiff::from_lines(text, formatted);
+    let new_lines: Vec<&str> = diff.new_slices().to_vec();
+
+    let overlaps = |start: usize, end: usize| match range {
+        None => true,
+        Some(range) => {
+            let (first, last) = (range.start.line as usize, range.end.line as usize);
+            // An insertion (`start == end`) overlaps if it's inserted inside the range.
+            start <= last && (end > first || (start == end && start >= first))
+        }
+    };
+
+    diff.ops()
+        .iter()
+        .filter_map(|op| {
+            let (old, new) = match *op {
+                DiffOp::Equal { .. } => return None,
+                DiffOp::Delete {
+                    old_index,
+                    old_len,
+                    new_index,
+                } => (old_index..old_index + old_len, new_index..new_index),
+                DiffOp::Insert {
+                    old_index,
+                    new_index,
+                    new_len,
+                } => (old_index..old_index, new_index..new_index + new_len),
+                DiffOp::Replace {
+                    old_index,
+                    old_len,
+                    new_index,
+                    new_len,
+                } => (
+                    old_index..old_index + old_len,
+                    new_index..new_index + new_len,
+                ),
+            };
+
+            overlaps(old.start, old.end).then(|| TextEdit {
+                range: Range {
+                    start: Position {
+                        line: old.start as u32,
+                        character: 0,
+                    },
+                    // The end position is exclusive: we replace whole lines by specifying the
+                    // beginning of the line following the last replaced line.
+                    end: Position {
+                        line: old.end as u32,
+                        character: 0,
+                    },
+                },
+                new_text: new_lines[new].concat(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range};
+
+    use super::formatting_edits;
+
+    fn lines(start: u32, end: u32) -> Range {
+        Range {
+            start: Position {
+                line: start,
+                character: 0,
+            },
+            end: Position {
+                line: end,
+                character: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn only_changed_hunks() {
+        let text = "{\n  foo=1,\n  bar = 2,\n  baz=3,\n}\n";
+        let formatted = "{\n  foo = 1,\n  bar = 2,\n  baz = 3,\n}\n";
+
+        let edits = formatting_edits(text, formatted, None);
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].range, lines(1, 2));
+        assert_eq!(edits[0].new_text, "  foo = 1,\n");
+        assert_eq!(edits[1].range, lines(3, 4));
+        assert_eq!(edits[1].new_text, "  baz = 3,\n");
+    }
+
+    #[test]
+    fn restricted_to_range() {
+        let text = "{\n  foo=1,\n  bar = 2,\n  baz=3,\n}\n";
+        let formatted = "{\n  foo = 1,\n  bar = 2,\n  baz = 3,\n}\n";
+
+        let edits = formatting_edits(text, formatted, Some(lines(3, 3)));
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, "  baz = 3,\n");
+    }
+
+    #[test]
+    fn already_formatted() {
+        let text = "{ foo = 1 }\n";
+        assert!(formatting_edits(text, text, None).is_empty());
+    }
+}
@@ -1,10 +1,10 @@
 use lsp_server::{RequestId, Response, ResponseError};
-use lsp_types::{Hover, HoverContents, HoverParams, LanguageString, MarkedString, Range};
+use lsp_types::{Hover, HoverContents, HoverParams, LanguageString, MarkedString, Range, Url};
 use nickel_lang_core::{
     combine::Combine,
     identifier::Ident,
     position::RawSpan,
-    term::{record::FieldMetadata, LabeledType, RichTerm, Term, UnaryOp},
+    term::{record::FieldMetadata, LabeledType, MergePriority, RichTerm, Term, UnaryOp},
     typ::Type,
 };
 use serde_json::Value;
@@ -14,6 +14,7 @@ use crate::{
     diagnostic::LocationCompat,
     field_walker::{FieldResolver, Record},
     identifier::LocIdent,
+    requests::code_lens::toplevel_fields,
     server::Server,
     world::World,
 };
@@ -24,12 +25,16 @@ struct HoverData {
     metadata: Vec<FieldMetadata>,
     span: Option<RawSpan>,
     ty: Option<Type>,
+    /// The definition sites of all the pieces contributing to the hovered field, when it's
+    /// defined piecewise across several merged records.
+    defs: Vec<RawSpan>,
 }
 
 impl Combine for HoverData {
     fn combine(mut left: Self, mut right: Self) -> Self {
         left.values.append(&mut right.values);
         left.metadata.append(&mut right.metadata);
+        left.defs.append(&mut right.defs);
         left.ty = left.ty.or(right.ty);
         left.span = left.span.or(right.span);
         left
@@ -53,16 +58,33 @@ fn nickel_string(s: String) -> MarkedString {
 fn values_and_metadata_from_field(
     parents: Vec<Record>,
     ident: Ident,
-) -> (Vec<RichTerm>, Vec<FieldMetadata>) {
+) -> (Vec<RichTerm>, Vec<FieldMetadata>, Vec<RawSpan>) {
     let mut values = Vec::new();
     let mut metadata = Vec::new();
+    let mut defs = Vec::new();
     for parent in parents {
-        if let Some(field) = parent.field(ident) {
-            values.extend(field.value.iter().cloned());
-            metadata.push(field.metadata.clone());
+        if let Some((loc, field)) = parent.field_and_loc(ident) {
+            defs.extend(loc.pos.into_opt());
+
+            if let Some(field) = field {
+                values.extend(field.value.iter().cloned());
+                metadata.push(field.metadata.clone());
+            }
         }
     }
-    (values, metadata)
+    (values, metadata, defs)
+}
+
+/// Render a definition site as a markdown link `file:line` pointing to it. Returns `None` for
+/// definitions which don't live in a file on disk (e.g. in the stdlib).
+fn def_link(span: &RawSpan, world: &World) -> Option<String> {
+    let files = world.cache.files();
+    let name = files.name(span.src_id);
+    let uri = Url::from_file_path(name).ok()?;
+    let line = Range::from_span(span, files).start.line + 1;
+    let file_name = std::path::Path::new(name).file_name()?.to_string_lossy();
+
+    Some(format!("[{file_name}:{line}]({uri}#L{line})"))
 }
 
 fn ident_hover(ident: LocIdent, world: &World) -> Option<HoverData> {
@@ -73,21 +95,24 @@ fn ident_hover(ident: LocIdent, world: &World) -> Option<HoverData> {
         metadata: Vec::new(),
         span: Some(span),
         ty,
+        defs: Vec::new(),
     };
 
     if let Some(def) = world.analysis.get_def(&ident) {
         let resolver = FieldResolver::new(world);
         if let Some(((last, path), val)) = def.path().split_last().zip(def.value()) {
             let parents = resolver.resolve_path(val, path.iter().copied());
-            let (values, metadata) = values_and_metadata_from_field(parents, *last);
+            let (values, metadata, defs) = values_and_metadata_from_field(parents, *last);
             ret.values = values;
             ret.metadata = metadata;
+            ret.defs = defs;
         } else if def.path().is_empty() {
             let cousins = resolver.cousin_defs(def);
             if cousins.is_empty() {
                 ret.values.extend(def.value().into_iter().cloned());
             } else {
-                for (_, cousin) in cousins {
+                for (loc, cousin) in cousins {
+                    ret.defs.extend(loc.pos.into_opt());
                     if let Some(val) = cousin.value {
                         ret.values.push(val);
                     }
@@ -108,12 +133,13 @@ fn term_hover(rt: &RichTerm, world: &World) -> Option<HoverData> {
         Term::Op1(UnaryOp::StaticAccess(id), parent) => {
             let resolver = FieldResolver::new(world);
             let parents = resolver.resolve_record(parent);
-            let (values, metadata) = values_and_metadata_from_field(parents, id.ident());
+            let (values, metadata, defs) = values_and_metadata_from_field(parents, id.ident());
             Some(HoverData {
                 values,
                 metadata,
                 span,
                 ty,
+                defs,
             })
         }
         _ => Some(HoverData {
@@ -121,6 +147,7 @@ fn term_hover(rt: &RichTerm, world: &World) -> Option<HoverData> {
             metadata: vec![],
             span,
             ty,
+            defs: vec![],
         }),
     }
 }
@@ -208,10 +235,76 @@ pub fn handle(
 
         contents.extend(contracts.into_iter().map(nickel_string));
 
-        // Not sure how to do documentation merging yet, so pick the first non-empty one.
-        let doc = hover.metadata.iter().find_map(|m| m.doc.as_ref());
-        if let Some(doc) = doc {
-            contents.push(MarkedString::String(doc.to_owned()));
+        // The effective priority of a field defined piecewise is the highest priority among its
+        // pieces. We only report it when it isn't the default neutral one.
+        let priority = hover.metadata.iter().map(|m| &m.priority).fold(
+            None,
+            |acc: Option<&MergePriority>, prio| match acc {
+                Some(acc) if acc >= prio => Some(acc),
+                _ => Some(prio),
+            },
+        );
+
+        if let Some(priority) = priority.filter(|p| !matches!(p, MergePriority::Neutral)) {
+            contents.push(MarkedString::String(format!("priority: `{priority}`")));
+        }
+
+        // Each piece of a merged field can come with its own documentation. We show all of them,
+        // in order, skipping duplicates (which happen e.g. when the same definition is reached
+        // through several paths).
+        let mut docs: Vec<&String> = Vec::new();
+        for doc in hover.metadata.iter().filter_map(|m| m.doc.as_ref()) {
+            if !docs.contains(&doc) {
+                docs.push(doc);
+            }
+        }
+        contents.extend(docs.into_iter().cloned().map(MarkedString::String));
+
+        // Only link to the definition sites if there are several of them, since go-to-definition
+        // already covers the simple case.
+        let mut defs = hover.defs;
+        defs.sort_by_key(|span| (span.src_id, span.start));
+        defs.dedup();
+
+        if defs.len() > 1 {
+            let links: Vec<_> = defs
+                .iter()
+                .filter_map(|span| def_link(span, &server.world))
+                .collect();
+
+            if !links.is_empty() {
+                contents.push(MarkedString::String(format!(
+                    "Defined at: {}",
+                    links.join(", ")
+                )));
+            }
+        }
+
+        // Top-level fields which were evaluated through their code lens also show their value.
+        let evaluated = server
+            .world
+            .lookup_ident_by_position(pos)?
+            .filter(|ident| {
+                server
+                    .world
+                    .cache
+                    .get_ref(pos.src_id)
+                    .map(toplevel_fields)
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|field| field.pos == ident.pos)
+            })
+            .and_then(|ident| {
+                server
+                    .world
+                    .field_evals
+                    .get(&pos.src_id)?
+                    .get(&ident.ident)
+                    .cloned()
+            });
+
+        if let Some(value) = evaluated {
+            contents.push(MarkedString::String(format!("Evaluates to: `{value}`")));
         }
 
         server.reply(Response::new_ok(
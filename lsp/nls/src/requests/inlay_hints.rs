@@ -0,0 +1,175 @@
+use lsp_server::{RequestId, Response, ResponseError};
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, Range};
+use nickel_lang_core::{
+    identifier::LocIdent,
+    position::RawSpan,
+    term::{RichTerm, Term, Traverse, TraverseControl, TypeAnnotation},
+    typ::{Type, TypeF},
+};
+
+use crate::{analysis::CollectedTypes, cache::CacheExt as _, server::Server, world::World};
+
+/// Build an inlay hint displaying `label` right after the end of `span`, or `None` if `span`
+/// doesn't fall inside `range`.
+fn hint_after(world: &World, span: RawSpan, range: &Range, label: String) -> Option<InlayHint> {
+    let position = crate::codespan_lsp::byte_index_to_position(
+        world.cache.files(),
+        span.src_id,
+        span.end.to_usize(),
+    )
+    .ok()?;
+
+    (range.start <= position && position <= range.end).then(|| InlayHint {
+        position,
+        label: InlayHintLabel::String(label),
+        kind: Some(InlayHintKind::TYPE),
+        text_edits: None,
+        tooltip: None,
+        padding_left: None,
+        padding_right: None,
+        data: None,
+    })
+}
+
+/// Is this type worth showing to the user? We don't show `Dyn`, nor types that the typechecker
+/// didn't manage to solve.
+fn is_informative(ty: &Type) -> bool {
+    !matches!(ty.typ, TypeF::Dyn | TypeF::Wildcard(_))
+}
+
+/// Collect the wildcards (and their position) appearing in the static type of an annotation.
+fn annotation_wildcards(annot: &TypeAnnotation, acc: &mut Vec<(RawSpan, usize)>) {
+    if let Some(labeled_ty) = &annot.typ {
+        labeled_ty.typ.traverse_ref(
+            &mut |ty: &Type, _: &()| {
+                if let (TypeF::Wildcard(id), Some(span)) = (&ty.typ, ty.pos.into_opt()) {
+                    acc.push((span, *id));
+                }
+                TraverseControl::<(), ()>::Continue
+            },
+            &(),
+        );
+    }
+}
+
+/// Is the value of a field with this annotation in a statically typed block, given whether the
+/// record itself is? A type annotation starts a statically typed block, while a contract
+/// annotation alone ends it.
+fn is_field_typed(annot: &TypeAnnotation, record_typed: bool) -> bool {
+    annot.typ.is_some() || (record_typed && annot.contracts.is_empty())
+}
+
+/// Collect the let-bound identifiers whose type should be hinted at, and the wildcards of type
+/// annotations, in `rt`. `typed` tells whether `rt` is in a statically typed block.
+///
+/// Only let-bindings inside statically typed blocks get a hint: outside of them, the type of a
+/// let-binding is at best the apparent type of its value (for example `Number` for a literal),
+/// which isn't inferred and is obvious from the code anyway.
+fn collect_hinted(
+    rt: &RichTerm,
+    typed: bool,
+    let_idents: &mut Vec<LocIdent>,
+    wildcards: &mut Vec<(RawSpan, usize)>,
+) {
+    rt.traverse_ref(
+        &mut |rt: &RichTerm, typed: &bool| -> TraverseControl<bool, ()> {
+            match rt.as_ref() {
+                Term::Let(id, val, _, _) => {
+                    if *typed && !matches!(val.as_ref(), Term::Annotated(..)) {
+                        let_idents.push(*id);
+                    }
+                    TraverseControl::Continue
+                }
+                Term::Annotated(annot, _) => {
+                    annotation_wildcards(annot, wildcards);
+                    TraverseControl::ContinueWithScope(annot.typ.is_some())
+                }
+                // The fields of a record don't all have the same annotations, so we recurse into
+                // them ourselves.
+                Term::Record(data) | Term::RecRecord(data, ..) => {
+                    let dyn_fields = match rt.as_ref() {
+                        Term::RecRecord(_, dyn_fields, _) => dyn_fields.as_slice(),
+                        _ => &[],
+                    };
+
+                    for (name, _) in dyn_fields {
+                        collect_hinted(name, *typed, let_idents, wildcards);
+                    }
+
+                    for field in data
+                        .fields
+                        .values()
+                        .chain(dyn_fields.iter().map(|(_, field)| field))
+                    {
+                        let annot = &field.metadata.annotation;
+                        annotation_wildcards(annot, wildcards);
+
+                        if let Some(value) = &field.value {
+                            let field_typed = is_field_typed(annot, *typed);
+                            collect_hinted(value, field_typed, let_idents, wildcards);
+                        }
+                    }
+
+                    TraverseControl::SkipBranch
+                }
+                _ => TraverseControl::Continue,
+            }
+        },
+        &typed,
+    );
+}
+
+fn inlay_hints(
+    world: &World,
+    type_lookup: &CollectedTypes<Type>,
+    rt: &RichTerm,
+    range: &Range,
+) -> Vec<InlayHint> {
+    let mut let_idents = Vec::new();
+    let mut wildcards = Vec::new();
+
+    collect_hinted(rt, false, &mut let_idents, &mut wildcards);
+
+    let let_hints = let_idents.into_iter().filter_map(|id| {
+        let ty = type_lookup.idents.get(&id.into())?;
+        let span = id.pos.into_opt()?;
+        is_informative(ty)
+            .then(|| hint_after(world, span, range, format!(": {ty}")))
+            .flatten()
+    });
+
+    let wildcard_hints = wildcards.into_iter().filter_map(|(span, id)| {
+        let ty = type_lookup.wildcards.get(id)?;
+        is_informative(ty)
+            .then(|| hint_after(world, span, range, format!(" = {ty}")))
+            .flatten()
+    });
+
+    let mut hints: Vec<_> = let_hints.chain(wildcard_hints).collect();
+    // Sort so the response is deterministic.
+    hints.sort_by_key(|hint| hint.position);
+    hints
+}
+
+pub fn handle_inlay_hints(
+    params: InlayHintParams,
+    id: RequestId,
+    server: &mut Server,
+) -> Result<(), ResponseError> {
+    let file_id = server
+        .world
+        .cache
+        .file_id(&params.text_document.uri)?
+        .ok_or_else(|| crate::error::Error::FileNotFound(params.text_document.uri.clone()))?;
+
+    let type_lookup = &server.world.file_analysis(file_id)?.type_lookup;
+    let hints = server
+        .world
+        .cache
+        .get_ref(file_id)
+        .map(|rt| inlay_hints(&server.world, type_lookup, rt, &params.range))
+        .unwrap_or_default();
+
+    server.reply(Response::new_ok(id, hints));
+    Ok(())
+}
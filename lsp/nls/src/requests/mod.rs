@@ -1,9 +1,14 @@
+pub mod code_lens;
 pub mod completion;
 pub mod goto;
 pub mod hover;
+pub mod inlay_hints;
 pub mod rename;
+pub mod signature_help;
 pub mod symbols;
 
+mod formatting_edits;
+
 #[cfg(feature = "format")]
 pub mod formatting;
 
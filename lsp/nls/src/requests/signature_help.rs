@@ -0,0 +1,319 @@
+use codespan::ByteIndex;
+use lsp_server::{RequestId, Response, ResponseError};
+use lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureHelpParams, SignatureInformation,
+};
+use nickel_lang_core::{
+    position::{RawPos, RawSpan},
+    term::{
+        record::FieldMetadata, LabeledType, RichTerm, Term, Traverse, TraverseControl, UnaryOp,
+    },
+    typ::{Type, TypeF},
+};
+use serde_json::Value;
+
+use crate::{
+    cache::CacheExt as _, field_walker::FieldResolver, identifier::LocIdent, server::Server,
+    world::World,
+};
+
+/// Find the innermost application whose span contains `idx`. Contrary to most position lookups,
+/// the end of the span is inclusive: when the cursor is right after the last argument, the user is
+/// still typing it.
+fn enclosing_app(rt: &RichTerm, idx: usize) -> Option<RichTerm> {
+    let mut found = None;
+
+    rt.traverse_ref(
+        &mut |rt: &RichTerm, _: &()| {
+            let Some(span) = rt.pos.into_opt() else {
+                return TraverseControl::Continue;
+            };
+
+            if span.start.to_usize() > idx || span.end.to_usize() < idx {
+                TraverseControl::SkipBranch
+            } else {
+                if matches!(rt.as_ref(), Term::App(..)) {
+                    found = Some(rt.clone());
+                }
+                TraverseControl::<(), ()>::Continue
+            }
+        },
+        &(),
+    );
+
+    found
+}
+
+/// Split an application `f x1 .. xn` into `f` and `[x1, .., xn]`.
+fn split_app(rt: &RichTerm) -> (&RichTerm, Vec<&RichTerm>) {
+    let mut head = rt;
+    let mut args = Vec::new();
+
+    while let Term::App(fun, arg) = head.as_ref() {
+        head = fun;
+        args.push(arg);
+    }
+    args.reverse();
+
+    (head, args)
+}
+
+/// The values and metadata of the definition of the function `head`, together with its name.
+fn head_definition(
+    head: &RichTerm,
+    world: &World,
+) -> (Option<String>, Vec<RichTerm>, Vec<FieldMetadata>) {
+    let resolver = FieldResolver::new(world);
+    let mut values = Vec::new();
+    let mut metadata = Vec::new();
+
+    let (name, parents, field) = match head.as_ref() {
+        Term::Var(id) => {
+            let id = LocIdent::from(*id);
+            let Some(def) = world.analysis.get_def(&id) else {
+                return (Some(id.ident.to_string()), values, metadata);
+            };
+
+            match (def.path().split_last(), def.value()) {
+                (Some((last, path)), Some(val)) => (
+                    id.ident.to_string(),
+                    resolver.resolve_path(val, path.iter().copied()),
+                    *last,
+                ),
+                (_, val) => {
+                    values.extend(val.cloned());
+                    return (Some(id.ident.to_string()), values, metadata);
+                }
+            }
+        }
+        Term::Op1(UnaryOp::StaticAccess(id), parent) => {
+            (id.to_string(), resolver.resolve_record(parent), id.ident())
+        }
+        _ => {
+            values.push(head.clone());
+            return (None, values, metadata);
+        }
+    };
+
+    for parent in parents {
+        if let Some(field) = parent.field(field) {
+            values.extend(field.value.iter().cloned());
+            metadata.push(field.metadata.clone());
+        }
+    }
+
+    (Some(name), values, metadata)
+}
+
+/// The names of the parameters of a function definition, going through annotations. Parameters
+/// bound by a destructuring pattern are rendered as the pattern.
+fn param_names(mut rt: &RichTerm) -> Vec<String> {
+    let mut names = Vec::new();
+
+    loop {
+        match rt.as_ref() {
+            Term::Fun(id, body) => {
+                names.push(id.to_string());
+                rt = body;
+            }
+            Term::FunPattern(pat, body) => {
+                names.push(pat.to_string());
+                rt = body;
+            }
+            Term::Annotated(_, inner) if names.is_empty() => rt = inner,
+            _ => return names,
+        }
+    }
+}
+
+fn is_arrow(ty: &Type) -> bool {
+    match &ty.typ {
+        TypeF::Arrow(..) => true,
+        TypeF::Forall { body, .. } => is_arrow(body),
+        _ => false,
+    }
+}
+
+/// Split a function type into the types of its `arity` first parameters and its return type.
+/// Polymorphic types are instantiated naively, by just dropping the `forall` binders.
+fn split_arrows(mut ty: &Type, arity: usize) -> (Vec<&Type>, Option<&Type>) {
+    let mut doms = Vec::new();
+
+    loop {
+        match &ty.typ {
+            TypeF::Forall { body, .. } => ty = body,
+            TypeF::Arrow(dom, codom) if doms.len() < arity => {
+                doms.push(dom.as_ref());
+                ty = codom;
+            }
+            _ => break,
+        }
+    }
+
+    let ret = (doms.len() == arity).then_some(ty);
+    (doms, ret)
+}
+
+/// The type of the function being applied: an explicit annotation if there is one, or the type
+/// inferred by the typechecker otherwise.
+fn head_type(
+    head: &RichTerm,
+    values: &[RichTerm],
+    metadata: &[FieldMetadata],
+    world: &World,
+) -> Option<Type> {
+    let annotated = values.iter().filter_map(|val| match val.as_ref() {
+        Term::Annotated(annot, _) => Some(annot),
+        _ => None,
+    });
+
+    metadata
+        .iter()
+        .map(|m| &m.annotation)
+        .chain(annotated)
+        .flat_map(|annot| annot.typ.iter().chain(annot.contracts.iter()))
+        .map(|labeled_ty: &LabeledType| &labeled_ty.typ)
+        .find(|ty| is_arrow(ty))
+        .or_else(|| world.analysis.get_type(head).filter(|ty| is_arrow(ty)))
+        .cloned()
+}
+
+/// Compute the signature of `head` as a label of the form `f (x : Number) y -> String`, together
+/// with the offsets of each parameter in the label.
+fn signature(head: &RichTerm, world: &World) -> Option<SignatureInformation> {
+    let (name, values, metadata) = head_definition(head, world);
+    let names = values.first().map(param_names).unwrap_or_default();
+    let ty = head_type(head, &values, &metadata, world);
+
+    let arity = match &ty {
+        Some(ty) => names.len().max(split_arrows(ty, usize::MAX).0.len()),
+        None => names.len(),
+    };
+
+    if arity == 0 {
+        return None;
+    }
+
+    let (doms, ret) = ty
+        .as_ref()
+        .map(|ty| split_arrows(ty, arity))
+        .unwrap_or_default();
+
+    let mut label = name.unwrap_or_else(|| "fun".to_owned());
+    let mut parameters = Vec::with_capacity(arity);
+
+    for i in 0..arity {
+        let param = match (names.get(i), doms.get(i)) {
+            (Some(name), Some(dom)) => format!("({name} : {dom})"),
+            (Some(name), None) => name.clone(),
+            (None, Some(dom)) => format!("(_ : {dom})"),
+            (None, None) => "_".to_owned(),
+        };
+
+        label.push(' ');
+        // Offsets are expressed in UTF-16 code units, as positions in LSP.
+        let start = label.encode_utf16().count() as u32;
+        label.push_str(&param);
+        let end = label.encode_utf16().count() as u32;
+
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, end]),
+            documentation: None,
+        });
+    }
+
+    if let Some(ret) = ret {
+        label.push_str(&format!(" -> {ret}"));
+    }
+
+    let docs: Vec<_> = metadata.iter().filter_map(|m| m.doc.clone()).collect();
+
+    Some(SignatureInformation {
+        label,
+        documentation: (!docs.is_empty()).then(|| {
+            Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: docs.join("\n\n"),
+            })
+        }),
+        parameters: Some(parameters),
+        active_parameter: None,
+    })
+}
+
+/// The index of the argument being typed at `idx`: the argument containing the cursor, or the
+/// next one if the cursor is after the last argument.
+fn active_parameter(head_span: Option<RawSpan>, args: &[&RichTerm], idx: usize) -> u32 {
+    // Arguments appearing before the function (e.g. with `x |> f`) aren't being typed.
+    let head_start = head_span.map_or(0, |span| span.start.to_usize());
+
+    args.iter()
+        .filter_map(|arg| arg.pos.into_opt())
+        .filter(|span| span.start.to_usize() >= head_start && span.end.to_usize() < idx)
+        .count() as u32
+}
+
+pub fn handle_signature_help(
+    params: SignatureHelpParams,
+    id: RequestId,
+    server: &mut Server,
+) -> Result<(), ResponseError> {
+    let pos = server
+        .world
+        .cache
+        .position(&params.text_document_position_params)?;
+    let idx = pos.index.to_usize();
+
+    // While typing `f x |`, the cursor is outside of any term: we look for the application or the
+    // function ending before the whitespace instead.
+    let source = server.world.cache.files().source(pos.src_id);
+    let lookup_idx = source[..idx.min(source.len())].trim_end().len();
+
+    let app = server
+        .world
+        .cache
+        .get_ref(pos.src_id)
+        .and_then(|rt| enclosing_app(rt, lookup_idx));
+
+    let (head, args) = match &app {
+        Some(app) => split_app(app),
+        // The first argument hasn't been typed yet, so there is no application in the AST. The
+        // term right before the cursor might be a function, though.
+        None if lookup_idx < idx && lookup_idx > 0 => {
+            let head = server.world.lookup_term_by_position(RawPos::new(
+                pos.src_id,
+                ByteIndex((lookup_idx - 1) as u32),
+            ))?;
+
+            match head {
+                Some(head) => (head, Vec::new()),
+                None => {
+                    server.reply(Response::new_ok(id, Value::Null));
+                    return Ok(());
+                }
+            }
+        }
+        None => {
+            server.reply(Response::new_ok(id, Value::Null));
+            return Ok(());
+        }
+    };
+
+    let Some(signature) = signature(head, &server.world) else {
+        server.reply(Response::new_ok(id, Value::Null));
+        return Ok(());
+    };
+
+    let active_parameter = active_parameter(head.pos.into_opt(), &args, idx);
+
+    server.reply(Response::new_ok(
+        id,
+        SignatureHelp {
+            signatures: vec![signature],
+            active_signature: Some(0),
+            active_parameter: Some(active_parameter),
+        },
+    ));
+    Ok(())
+}
@@ -7,24 +7,31 @@ use lsp_types::{
     notification::Notification as _,
     notification::{DidChangeTextDocument, DidOpenTextDocument},
     request::{Request as RequestTrait, *},
-    CodeActionParams, CompletionOptions, CompletionParams, DidChangeTextDocumentParams,
-    DidOpenTextDocumentParams, DocumentFormattingParams, DocumentSymbolParams,
-    ExecuteCommandParams, GotoDefinitionParams, HoverOptions, HoverParams, HoverProviderCapability,
+    CodeActionParams, CodeLensOptions, CodeLensParams, CompletionOptions, CompletionParams,
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, DocumentFormattingParams,
+    DocumentRangeFormattingParams, DocumentSymbolParams, ExecuteCommandParams,
+    GotoDefinitionParams, HoverOptions, HoverParams, HoverProviderCapability, InlayHintParams,
     OneOf, PublishDiagnosticsParams, ReferenceParams, RenameParams, ServerCapabilities,
-    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, Url,
-    WorkDoneProgressOptions,
+    SignatureHelpOptions, SignatureHelpParams, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, Url, WorkDoneProgressOptions,
 };
 
 use crate::{
     actions,
     background::BackgroundJobs,
+    cache::CacheExt as _,
     command,
-    requests::{completion, formatting, goto, hover, rename, symbols},
+    requests::{
+        code_lens, completion, formatting, goto, hover, inlay_hints, rename, signature_help,
+        symbols,
+    },
     trace::Trace,
     world::World,
 };
 
 pub const COMPLETIONS_TRIGGERS: &[&str] = &[".", "\"", "/"];
+/// Arguments are separated by spaces in Nickel, so a space is what starts a new argument.
+pub const SIGNATURE_HELP_TRIGGERS: &[&str] = &[" "];
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum Shutdown {
@@ -44,7 +51,7 @@ impl Server {
             text_document_sync: Some(TextDocumentSyncCapability::Options(
                 TextDocumentSyncOptions {
                     open_close: Some(true),
-                    change: Some(TextDocumentSyncKind::FULL),
+                    change: Some(TextDocumentSyncKind::INCREMENTAL),
                     ..TextDocumentSyncOptions::default()
                 },
             )),
@@ -63,12 +70,26 @@ impl Server {
             }),
             document_symbol_provider: Some(OneOf::Left(true)),
             document_formatting_provider: Some(OneOf::Left(true)),
+            document_range_formatting_provider: Some(OneOf::Left(true)),
             code_action_provider: Some(lsp_types::CodeActionProviderCapability::Simple(true)),
             execute_command_provider: Some(lsp_types::ExecuteCommandOptions {
-                commands: vec!["eval".to_owned()],
+                commands: vec!["eval".to_owned(), code_lens::EVAL_FIELD_COMMAND.to_owned()],
                 ..Default::default()
             }),
+            code_lens_provider: Some(CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
             rename_provider: Some(OneOf::Left(true)),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(
+                    SIGNATURE_HELP_TRIGGERS
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                ),
+                ..Default::default()
+            }),
             ..ServerCapabilities::default()
         }
     }
@@ -194,10 +215,13 @@ impl Server {
                 let params =
                     serde_json::from_value::<DidChangeTextDocumentParams>(notification.params)?;
                 let uri = params.text_document.uri.clone();
-                let contents = params.content_changes[0].text.clone();
                 crate::files::handle_save(self, params)?;
-                self.background_jobs
-                    .update_file(uri.clone(), contents, &self.world);
+                // The changes are incremental: the background jobs need the whole new content.
+                if let Some(file_id) = self.world.cache.file_id(&uri)? {
+                    let contents = self.world.cache.files().source(file_id).clone();
+                    self.background_jobs
+                        .update_file(uri.clone(), contents, &self.world);
+                }
                 self.background_jobs.eval_file(uri);
                 Ok(())
             }
@@ -244,12 +268,25 @@ impl Server {
                 formatting::handle_format_document(params, req.id.clone(), self)
             }
 
+            RangeFormatting::METHOD => {
+                debug!("handle range formatting");
+                let params: DocumentRangeFormattingParams =
+                    serde_json::from_value(req.params).unwrap();
+                formatting::handle_format_range(params, req.id.clone(), self)
+            }
+
             CodeActionRequest::METHOD => {
                 debug!("code action");
                 let params: CodeActionParams = serde_json::from_value(req.params).unwrap();
                 actions::handle_code_action(params, req.id.clone(), self)
             }
 
+            CodeLensRequest::METHOD => {
+                debug!("code lens");
+                let params: CodeLensParams = serde_json::from_value(req.params).unwrap();
+                code_lens::handle_code_lens(params, req.id.clone(), self)
+            }
+
             ExecuteCommand::METHOD => {
                 debug!("command");
                 let params: ExecuteCommandParams = serde_json::from_value(req.params).unwrap();
@@ -262,6 +299,18 @@ impl Server {
                 rename::handle_rename(params, req.id.clone(), self)
             }
 
+            InlayHintRequest::METHOD => {
+                debug!("inlay hints");
+                let params: InlayHintParams = serde_json::from_value(req.params).unwrap();
+                inlay_hints::handle_inlay_hints(params, req.id.clone(), self)
+            }
+
+            SignatureHelpRequest::METHOD => {
+                debug!("signature help");
+                let params: SignatureHelpParams = serde_json::from_value(req.params).unwrap();
+                signature_help::handle_signature_help(params, req.id.clone(), self)
+            }
+
             _ => Ok(()),
         };
 
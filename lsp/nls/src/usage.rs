@@ -43,6 +43,9 @@ pub struct UsageLookup {
     //
     // Currently, variables bound in `let` bindings and record fields count as symbols.
     syms: HashMap<LocIdent, Def>,
+    // The list of all the local bindings in the document, that is variables bound by `let`
+    // bindings, function parameters and patterns (but not record fields).
+    bindings: Vec<LocIdent>,
 }
 
 impl UsageLookup {
@@ -86,6 +89,16 @@ impl UsageLookup {
             .and_then(|span| self.def_table.get(span))
     }
 
+    /// Return the local bindings (`let`-bound variables, function parameters and pattern
+    /// bindings) that are never used. Bindings whose name starts with an underscore are
+    /// considered to be unused on purpose, and aren't reported.
+    pub fn unused_bindings(&self) -> impl Iterator<Item = &LocIdent> {
+        self.bindings.iter().filter(|id| {
+            !id.ident.label().starts_with('_')
+                && matches!(id.pos.as_opt_ref(), Some(span) if !self.usage_table.contains_key(span))
+        })
+    }
+
     fn add_sym(&mut self, def: Def) {
         self.syms.insert(def.ident(), def);
     }
@@ -102,6 +115,7 @@ impl UsageLookup {
                         let mut new_env = env.clone();
                         let ident = LocIdent::from(*id);
                         new_env.insert_def(Def::Fn { ident });
+                        self.bindings.push(ident);
                         TraverseControl::ContinueWithScope(new_env)
                     }
                     Term::FunPattern(pat, _body) => {
@@ -109,6 +123,7 @@ impl UsageLookup {
 
                         for (_path, id, _field) in pat.bindings() {
                             new_env.insert_def(Def::Fn { ident: id.into() });
+                            self.bindings.push(id.into());
                         }
 
                         TraverseControl::ContinueWithScope(new_env)
@@ -122,6 +137,7 @@ impl UsageLookup {
                         };
                         new_env.insert_def(def.clone());
                         self.add_sym(def);
+                        self.bindings.push(LocIdent::from(*id));
 
                         self.fill(val, if attrs.rec { &new_env } else { env });
                         self.fill(body, &new_env);
@@ -140,6 +156,7 @@ impl UsageLookup {
                             };
                             new_env.insert_def(def.clone());
                             self.add_sym(def);
+                            self.bindings.push(LocIdent::from(id));
                         }
 
                         TraverseControl::ContinueWithScope(new_env)
@@ -268,6 +285,21 @@ pub(crate) mod tests {
         assert_eq!(baz_def.path(), vec!["foo".into(), "bar".into()]);
     }
 
+    #[test]
+    fn unused_bindings() {
+        let (file, rt) = parse("let x = 1 in let _y = 2 in let f = fun a b => a in f x");
+        let y = locced("_y", file, 17..19);
+        let b = locced("b", file, 41..42);
+        let table = UsageLookup::new(&rt, &Environment::new());
+
+        // `_y` is unused as well, but it's prefixed with an underscore.
+        assert!(!table.unused_bindings().any(|id| id == &y));
+        assert_eq!(
+            table.unused_bindings().cloned().collect::<Vec<_>>(),
+            vec![b]
+        );
+    }
+
     #[test]
     fn record_bindings() {
         let (file, rt) =
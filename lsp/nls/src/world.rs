@@ -2,14 +2,16 @@ use std::{
     collections::{HashMap, HashSet},
     ffi::OsString,
     path::PathBuf,
+    time::{Duration, Instant},
 };
 
 use codespan::FileId;
 use lsp_server::{ErrorCode, ResponseError};
 use lsp_types::Url;
 use nickel_lang_core::{
-    cache::{Cache, CacheError, ErrorTolerance, InputFormat, SourcePath},
+    cache::{Cache, CacheError, EntryState, ErrorTolerance, InputFormat, SourcePath},
     error::{ImportError, IntoDiagnostics},
+    identifier::Ident,
     position::{RawPos, RawSpan},
     term::{record::FieldMetadata, RichTerm, Term, UnaryOp},
     typecheck::Context,
@@ -18,10 +20,13 @@ use nickel_lang_core::{
 use crate::{
     analysis::{Analysis, AnalysisRegistry},
     cache::CacheExt as _,
-    diagnostic::{DiagnosticCompat, SerializableDiagnostic},
+    diagnostic::{
+        DiagnosticCompat, LocationCompat, OrdRange, SerializableDiagnostic, UNUSED_VARIABLE,
+    },
     field_walker::{Def, FieldResolver},
     files::uri_to_path,
     identifier::LocIdent,
+    incremental::{self, ParsedSource},
     pattern::Bindings as _,
 };
 
@@ -43,8 +48,23 @@ pub struct World {
     /// files that failed to import, and the values in this map are the file ids that tried
     /// to import it.
     pub failed_imports: HashMap<OsString, HashSet<FileId>>,
+
+    /// The last version of each open file that parsed without errors, together with the
+    /// corresponding term. This is used to reparse files incrementally.
+    pub parsed: HashMap<FileId, ParsedSource>,
+
+    /// The summarized values of the top-level fields evaluated through code lenses, for each
+    /// file. They are discarded as soon as the file or one of its imports changes.
+    pub field_evals: HashMap<FileId, HashMap<Ident, String>>,
+
+    /// When the files on disk were last checked for changes, see [Self::poll_disk_changes].
+    last_disk_poll: Option<Instant>,
 }
 
+/// The minimal delay between two checks for changes of the files on disk. Checking reads the
+/// timestamp of every cached file, which we don't want to do on each keystroke.
+const DISK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 impl Default for World {
     fn default() -> Self {
         let mut cache = Cache::new(ErrorTolerance::Tolerant);
@@ -65,6 +85,9 @@ impl Default for World {
             initial_term_env,
             file_uris: HashMap::default(),
             failed_imports: HashMap::default(),
+            parsed: HashMap::default(),
+            field_evals: HashMap::default(),
+            last_disk_poll: None,
         }
     }
 }
@@ -97,8 +120,10 @@ impl World {
         // cross-file references are updated.
         invalid.extend(self.cache.get_rev_imports_transitive(file_id));
 
+        self.field_evals.remove(&file_id);
         for rev_dep in &invalid {
             self.analysis.remove(*rev_dep);
+            self.field_evals.remove(rev_dep);
             // Reset the cached state (Parsed is the earliest one) so that it will
             // re-resolve its imports.
             self.cache
@@ -106,6 +131,7 @@ impl World {
         }
 
         self.file_uris.insert(file_id, uri);
+        invalid.extend(self.poll_disk_changes());
 
         Ok((file_id, invalid))
     }
@@ -121,13 +147,49 @@ impl World {
         let path = uri_to_path(&uri)?;
         let file_id = self.cache.replace_string(SourcePath::Path(path), contents);
 
-        let invalid = self.cache.get_rev_imports_transitive(file_id);
+        let mut invalid = self.cache.get_rev_imports_transitive(file_id);
+        self.field_evals.remove(&file_id);
         for f in &invalid {
             self.analysis.remove(*f);
+            self.field_evals.remove(f);
         }
+        invalid.extend(self.poll_disk_changes());
+
         Ok((file_id, invalid))
     }
 
+    /// Pick up the changes made on disk to the files that aren't open in the editor (open files
+    /// are loaded from memory and only change through the editor), for example when switching
+    /// branches. Returns the open files that were invalidated by those changes.
+    ///
+    /// The disk is checked at most once every [DISK_POLL_INTERVAL]: changes made in between are
+    /// picked up by the next check.
+    fn poll_disk_changes(&mut self) -> HashSet<FileId> {
+        let now = Instant::now();
+        if self
+            .last_disk_poll
+            .is_some_and(|last| now.duration_since(last) < DISK_POLL_INTERVAL)
+        {
+            return HashSet::new();
+        }
+        self.last_disk_poll = Some(now);
+
+        let mut invalid: HashSet<FileId> = self
+            .cache
+            .poll_changes()
+            .into_iter()
+            .flat_map(|change| change.invalidated)
+            .collect();
+
+        for f in &invalid {
+            self.analysis.remove(*f);
+            self.field_evals.remove(f);
+        }
+
+        invalid.retain(|f| self.file_uris.contains_key(f));
+        invalid
+    }
+
     pub fn lsp_diagnostics(
         &mut self,
         file_id: FileId,
@@ -154,17 +216,87 @@ impl World {
         }
     }
 
+    /// Returns hints for the local bindings of a file that are never used. Those are computed from
+    /// the analysis of the file, so nothing is reported if the file failed to typecheck.
+    pub fn unused_binding_diagnostics(&self, file_id: FileId) -> Vec<SerializableDiagnostic> {
+        let Some(analysis) = self.analysis.analysis.get(&file_id) else {
+            return Vec::new();
+        };
+
+        analysis
+            .usage_lookup
+            .unused_bindings()
+            .filter_map(|id| {
+                let span = id.pos.into_opt()?;
+                Some(SerializableDiagnostic {
+                    range: OrdRange(lsp_types::Range::from_span(&span, self.cache.files())),
+                    severity: Some(lsp_types::DiagnosticSeverity::HINT),
+                    code: Some(UNUSED_VARIABLE.to_string()),
+                    message: format!("unused variable `{}`", id.ident),
+                    related_information: None,
+                    tags: Some(vec![lsp_types::DiagnosticTag::UNNECESSARY]),
+                })
+            })
+            .collect()
+    }
+
     /// Returns `Ok` for recoverable (or no) errors, or `Err` for fatal errors.
     pub fn parse(
         &mut self,
         file_id: FileId,
     ) -> Result<Vec<SerializableDiagnostic>, Vec<SerializableDiagnostic>> {
+        self.reparse_incrementally(file_id);
+
         self.cache
             .parse(file_id, InputFormat::Nickel)
-            .map(|nonfatal| self.lsp_diagnostics(file_id, nonfatal.inner()))
+            .map(|nonfatal| {
+                let nonfatal = nonfatal.inner();
+                if nonfatal.no_errors() {
+                    self.remember_parse(file_id);
+                }
+
+                let mut diags = self.lsp_diagnostics(file_id, nonfatal);
+                for warning in self.cache.take_warnings() {
+                    diags.extend(self.lsp_diagnostics(file_id, warning));
+                }
+                diags
+            })
             .map_err(|fatal| self.lsp_diagnostics(file_id, fatal))
     }
 
+    /// If a file hasn't been parsed yet but a previous version of it has, try to parse it by
+    /// reusing the unchanged parts of the previous version.
+    fn reparse_incrementally(&mut self, file_id: FileId) {
+        if self.cache.terms().contains_key(&file_id) {
+            return;
+        }
+
+        let Some(old) = self.parsed.get(&file_id) else {
+            return;
+        };
+
+        let new_text = self.cache.files().source(file_id);
+        if let Some((term, errors)) = incremental::reparse(file_id, old, new_text) {
+            self.cache.insert_parsed(file_id, term, errors);
+        }
+    }
+
+    /// Remember the freshly parsed term of an open file, so that its next version can be
+    /// reparsed incrementally.
+    fn remember_parse(&mut self, file_id: FileId) {
+        // Once imports have been resolved, the term doesn't correspond to the source anymore.
+        if !self.file_uris.contains_key(&file_id)
+            || self.cache.entry_state(file_id) != Some(EntryState::Parsed)
+        {
+            return;
+        }
+
+        if let Some(term) = self.cache.get_owned(file_id) {
+            let text = self.cache.files().source(file_id).clone();
+            self.parsed.insert(file_id, ParsedSource { text, term });
+        }
+    }
+
     /// Typechecks a file, returning diagnostics on error.
     ///
     /// Panics if the file has not yet been parsed. (Use [`World::parse_and_typecheck`] if you